@@ -21,6 +21,24 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     });
     let minimal_quorums = find_minimal_quorums(&fbas);
 
+    c.bench_function("is_quorum", |b| {
+        b.iter(|| {
+            minimal_quorums
+                .iter()
+                .filter(|&quorum| fbas.is_quorum(black_box(quorum)))
+                .count()
+        })
+    });
+    let flat_fbas = FlatFbas::new(&fbas);
+    c.bench_function("is_quorum_flat", |b| {
+        b.iter(|| {
+            minimal_quorums
+                .iter()
+                .filter(|&quorum| flat_fbas.is_quorum(black_box(quorum)))
+                .count()
+        })
+    });
+
     // c.bench_function("all_intersect", |b| {
     //     b.iter(|| all_intersect(black_box(&minimal_quorums)))
     // });
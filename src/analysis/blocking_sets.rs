@@ -1,21 +1,146 @@
 use super::*;
+use std::cmp;
 use std::ops::Index;
 
+/// Panics if the search is stopped early (see `request_stop`), as the result would be incomplete;
+/// use `find_minimal_blocking_sets_up_to_count` for searches that may be stopped.
 pub fn find_minimal_blocking_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
+    let (minimal_blocking_sets, truncated) = find_minimal_blocking_sets_until_stopped(node_sets);
+    assert_search_complete(truncated, "minimal blocking sets");
+    minimal_blocking_sets
+}
+
+/// Like `find_minimal_blocking_sets`, but returns what was found so far if the search is stopped
+/// early, plus whether that happened.
+pub(crate) fn find_minimal_blocking_sets_until_stopped(
+    node_sets: &[NodeIdSet],
+) -> (Vec<NodeIdSet>, bool) {
     debug!("Getting blocking sets...");
-    let blocking_sets = find_blocking_sets(node_sets);
+    let (blocking_sets, complete) = find_blocking_sets(node_sets, None);
+    info!("Found {} blocking sets.", blocking_sets.len());
+
+    debug!("Reducing to minimal blocking sets...");
+    let minimal_blocking_sets = if complete {
+        remove_non_minimal_blocking_sets(blocking_sets)
+    } else {
+        warn!("Search was stopped early.");
+        remove_non_minimal_blocking_sets_of_incomplete_search(blocking_sets, node_sets)
+    };
+    info!(
+        "Reduced to {} minimal blocking sets.",
+        minimal_blocking_sets.len()
+    );
+    (minimal_blocking_sets, !complete)
+}
+
+/// Like `find_minimal_blocking_sets`, but stops searching once more than `max_count` blocking
+/// sets have been found. Returns (up to) `max_count` minimal blocking sets and whether the result
+/// was truncated, i.e., whether there are (or might be) more minimal blocking sets.
+pub fn find_minimal_blocking_sets_up_to_count(
+    node_sets: &[NodeIdSet],
+    max_count: usize,
+) -> (Vec<NodeIdSet>, bool) {
+    debug!("Getting up to {} blocking sets...", max_count);
+    let (blocking_sets, complete) = find_blocking_sets(node_sets, Some(max_count));
     info!("Found {} blocking sets.", blocking_sets.len());
 
     debug!("Reducing to minimal blocking sets...");
-    let minimal_blocking_sets = remove_non_minimal_blocking_sets(blocking_sets);
+    let mut minimal_blocking_sets = if complete {
+        remove_non_minimal_blocking_sets(blocking_sets)
+    } else {
+        warn!("Search was truncated after {} blocking sets.", max_count);
+        let mut minimal_blocking_sets =
+            remove_non_minimal_blocking_sets_of_incomplete_search(blocking_sets, node_sets);
+        minimal_blocking_sets.truncate(max_count);
+        minimal_blocking_sets
+    };
+    minimal_blocking_sets.dedup();
     info!(
         "Reduced to {} minimal blocking sets.",
         minimal_blocking_sets.len()
     );
+    (minimal_blocking_sets, !complete)
+}
+
+// Subsets of found blocking sets might not have been found, so check each one directly.
+fn remove_non_minimal_blocking_sets_of_incomplete_search(
+    blocking_sets: Vec<NodeIdSet>,
+    node_sets: &[NodeIdSet],
+) -> Vec<NodeIdSet> {
+    let mut minimal_blocking_sets: Vec<NodeIdSet> = blocking_sets
+        .into_iter()
+        .filter(|blocking_set| is_minimal_blocking_set(blocking_set, node_sets))
+        .collect();
+    minimal_blocking_sets.sort();
+    minimal_blocking_sets.sort_by_key(|x| x.len());
     minimal_blocking_sets
 }
 
-fn find_blocking_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
+/// Is `node_set` a blocking set for `node_sets` (i.e., does it intersect each of them), such that
+/// no smaller subset is?
+fn is_minimal_blocking_set(node_set: &NodeIdSet, node_sets: &[NodeIdSet]) -> bool {
+    let is_blocking = |candidate: &NodeIdSet| node_sets.iter().all(|x| !x.is_disjoint(candidate));
+    let mut tester = node_set.clone();
+    is_blocking(node_set)
+        && node_set.iter().all(|node_id| {
+            tester.remove(node_id);
+            let is_minimal_without_node = !is_blocking(&tester);
+            tester.insert(node_id);
+            is_minimal_without_node
+        })
+}
+
+/// Checks whether the FBAS stays live despite any `k` failing nodes, i.e., that there is no
+/// blocking set with `k` or fewer members. Uses a bounded search instead of enumerating all
+/// minimal quorums and minimal blocking sets, which is much cheaper for small `k`.
+pub fn is_k_resilient(fbas: &Fbas, k: usize) -> bool {
+    find_blocking_set_up_to_size(fbas, k).is_none()
+}
+
+/// Finds some (not necessarily minimal) blocking set with at most `max_size` members, if there
+/// is any. Each blocking set contains at least one member of each quorum, so we only need to
+/// branch over the members of one minimal quorum of the remaining nodes at a time.
+pub fn find_blocking_set_up_to_size(fbas: &Fbas, max_size: usize) -> Option<NodeIdSet> {
+    fn step(fbas: &Fbas, failed_nodes: &mut NodeIdSet, max_size: usize) -> Option<NodeIdSet> {
+        let mut remaining_nodes = fbas.all_nodes();
+        remaining_nodes.difference_with(failed_nodes);
+        let maximal_quorum = find_unsatisfiable_nodes(&remaining_nodes, fbas).0;
+        if maximal_quorum.is_empty() {
+            Some(failed_nodes.clone())
+        } else if failed_nodes.len() >= max_size {
+            None
+        } else {
+            for node_id in reduce_to_minimal_quorum(maximal_quorum, fbas).iter() {
+                failed_nodes.insert(node_id);
+                if let Some(blocking_set) = step(fbas, failed_nodes, max_size) {
+                    return Some(blocking_set);
+                }
+                failed_nodes.remove(node_id);
+            }
+            None
+        }
+    }
+    step(fbas, &mut NodeIdSet::new(), max_size)
+}
+
+fn reduce_to_minimal_quorum(quorum: NodeIdSet, fbas: &Fbas) -> NodeIdSet {
+    let mut minimal_quorum = quorum.clone();
+    for node_id in quorum.iter() {
+        if minimal_quorum.contains(node_id) {
+            let mut tester = minimal_quorum.clone();
+            tester.remove(node_id);
+            let remaining_quorum = find_unsatisfiable_nodes(&tester, fbas).0;
+            if !remaining_quorum.is_empty() {
+                minimal_quorum = remaining_quorum;
+            }
+        }
+    }
+    minimal_quorum
+}
+
+/// Also returns whether the search was complete, i.e., not stopped after finding more than
+/// `max_count` blocking sets, by exceeding the memory budget or by `request_stop`.
+fn find_blocking_sets(node_sets: &[NodeIdSet], max_count: Option<usize>) -> (Vec<NodeIdSet>, bool) {
     let (mut unprocessed, memberships) = extract_nodes_and_node_set_memberships(node_sets);
 
     debug!("Sorting nodes by number of memberships...");
@@ -29,6 +154,8 @@ fn find_blocking_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
     let mut missing_node_sets_buffer: Vec<BitSet> = vec![BitSet::new(); unprocessed.len()];
 
     debug!("Collecting blocking sets...");
+    // returns `false` once more than `max_count` blocking sets have been found
+    #[allow(clippy::too_many_arguments)]
     fn step(
         unprocessed: &mut NodeIdDeque,
         selection: &mut NodeIdSet,
@@ -36,13 +163,22 @@ fn find_blocking_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
         missing_node_sets: &BitSet,
         missing_node_sets_buffer: &mut [BitSet],
         memberships: &MembershipsMap,
+        max_count: usize,
         selection_changed: bool,
-    ) {
-        if selection_changed && missing_node_sets.is_empty() {
+        progress: &mut ProgressTracker,
+    ) -> bool {
+        count(|s| s.branches_explored += 1);
+        if stop_requested() {
+            false
+        } else if selection_changed && missing_node_sets.is_empty() {
             found_blocking_sets.push(selection.clone());
+            count_result_set_size(found_blocking_sets.len());
             if found_blocking_sets.len() % 100_000 == 0 {
                 debug!("...{} blocking sets found", found_blocking_sets.len());
             }
+            progress.finish_branch(unprocessed.len());
+            found_blocking_sets.len() <= max_count
+                && check_memory_budget(found_blocking_sets.len(), selection)
         } else if let Some(current_candidate) = unprocessed.pop_front() {
             let useful = !missing_node_sets.is_disjoint(&memberships[current_candidate]);
             if useful {
@@ -51,39 +187,58 @@ fn find_blocking_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
                     missing_node_sets_buffer.split_first_mut().unwrap();
                 missing_node_sets_next.clone_from(&missing_node_sets);
                 missing_node_sets_next.difference_with(&memberships[current_candidate]);
-                step(
+                let keep_going = step(
                     unprocessed,
                     selection,
                     found_blocking_sets,
                     missing_node_sets_next,
                     missing_node_sets_buffer,
                     memberships,
+                    max_count,
                     true,
+                    progress,
                 );
                 selection.remove(current_candidate);
+                if !keep_going {
+                    unprocessed.push_front(current_candidate);
+                    return false;
+                }
+            } else {
+                count(|s| s.branches_pruned += 1);
+                progress.finish_branch(unprocessed.len());
             }
-            step(
+            let keep_going = step(
                 unprocessed,
                 selection,
                 found_blocking_sets,
                 missing_node_sets,
                 missing_node_sets_buffer,
                 memberships,
+                max_count,
                 false,
+                progress,
             );
             unprocessed.push_front(current_candidate);
+            keep_going
+        } else {
+            progress.finish_branch(0);
+            true
         }
     }
-    step(
+    let mut progress = ProgressTracker::new("minimal blocking sets", unprocessed.len());
+    let complete = step(
         &mut unprocessed,
         &mut selection,
         &mut found_blocking_sets,
         &missing_node_sets,
         &mut missing_node_sets_buffer,
         &memberships,
+        max_count.unwrap_or(usize::MAX),
         true,
+        &mut progress,
     );
-    found_blocking_sets
+    progress.finish();
+    (found_blocking_sets, complete)
 }
 
 // Warning: this is correct only if `blocking_sets` == the output of `find_blocking_sets`.
@@ -144,10 +299,131 @@ impl MembershipsMap {
     }
 }
 
+/// For each node, the size of a smallest set of other nodes whose failure blocks that node,
+/// i.e., that intersects each of its quorum slices. This is calculated from the structure of the
+/// node's quorum set, without enumerating slices or blocking sets. `None` means that no set of
+/// other nodes can block the node (e.g., because it only trusts itself).
+pub fn find_liveness_buffers(fbas: &Fbas) -> Vec<Option<usize>> {
+    fbas.nodes
+        .iter()
+        .enumerate()
+        .map(|(node_id, node)| liveness_buffer(node_id, &node.quorum_set))
+        .collect()
+}
+
+/// For each node, the maximum number of pairwise disjoint quorum slices its quorum set admits,
+/// not counting the node itself (which is part of all of its slices). A node with only one
+/// slice can be blocked by any member of it, even if the global analyses look fine. `None`
+/// means that there is no limit (e.g., because the node only trusts itself). If some node
+/// appears more than once in a quorum set, the result is an upper bound.
+pub fn find_slice_redundancies(fbas: &Fbas) -> Vec<Option<usize>> {
+    fbas.nodes
+        .iter()
+        .enumerate()
+        .map(|(node_id, node)| number_of_disjoint_slices(node_id, &node.quorum_set))
+        .collect()
+}
+
+fn number_of_disjoint_slices(node_id: NodeId, quorum_set: &QuorumSet) -> Option<usize> {
+    if quorum_set.threshold == 0 {
+        return Some(0); // badly configured quorum sets have no slices
+    }
+    // how many disjoint slices each entry can contribute to
+    let capacities: Vec<Option<usize>> = quorum_set
+        .validators
+        .iter()
+        .map(|&validator| if validator == node_id { None } else { Some(1) })
+        .chain(
+            quorum_set
+                .inner_quorum_sets
+                .iter()
+                .map(|inner| number_of_disjoint_slices(node_id, inner)),
+        )
+        .collect();
+    let unlimited = capacities.iter().filter(|c| c.is_none()).count();
+    if unlimited >= quorum_set.threshold {
+        return None;
+    }
+    // k disjoint slices (of `threshold` distinct entries each) are possible iff the entries can
+    // fill k * threshold places, with each entry used at most once per slice
+    let is_possible = |k: usize| {
+        capacities
+            .iter()
+            .map(|c| c.map_or(k, |c| cmp::min(c, k)))
+            .sum::<usize>()
+            >= k * quorum_set.threshold
+    };
+    Some((1..).find(|&k| !is_possible(k)).unwrap() - 1)
+}
+
+fn liveness_buffer(node_id: NodeId, quorum_set: &QuorumSet) -> Option<usize> {
+    let upper_bound = blocking_set_size_upper_bound(node_id, quorum_set)?;
+    let all_nodes = quorum_set.contained_nodes();
+    if all_nodes.len() == number_of_validator_entries(quorum_set) {
+        // no node appears more than once, so the upper bound is exact
+        Some(upper_bound)
+    } else {
+        // nodes that appear several times can block several entries at once; search for
+        // smaller blocking sets
+        let mut candidates = all_nodes.clone();
+        candidates.remove(node_id);
+        (0..upper_bound)
+            .find(|&size| {
+                combinatorics::subsets_of_size(&candidates, size).any(|failed_nodes| {
+                    !quorum_set
+                        .is_quorum_slice(&combinatorics::complement(&failed_nodes, &all_nodes))
+                })
+            })
+            .or(Some(upper_bound))
+    }
+}
+
+fn number_of_validator_entries(quorum_set: &QuorumSet) -> usize {
+    quorum_set.validators.len()
+        + quorum_set
+            .inner_quorum_sets
+            .iter()
+            .map(number_of_validator_entries)
+            .sum::<usize>()
+}
+
+/// Exact if no node appears more than once in `quorum_set`.
+fn blocking_set_size_upper_bound(node_id: NodeId, quorum_set: &QuorumSet) -> Option<usize> {
+    if quorum_set.threshold == 0 {
+        return Some(0); // badly configured quorum sets are always blocked
+    }
+    let mut entry_costs: Vec<usize> = quorum_set
+        .validators
+        .iter()
+        .filter(|&&validator| validator != node_id)
+        .map(|_| 1)
+        .chain(
+            quorum_set
+                .inner_quorum_sets
+                .iter()
+                .filter_map(|inner| blocking_set_size_upper_bound(node_id, inner)),
+        )
+        .collect();
+    let number_of_entries = quorum_set.validators.len() + quorum_set.inner_quorum_sets.len();
+    if quorum_set.threshold > number_of_entries {
+        return Some(0);
+    }
+    // we need to block all but threshold - 1 entries
+    let entries_to_block = number_of_entries - quorum_set.threshold + 1;
+    if entry_costs.len() < entries_to_block {
+        None
+    } else {
+        entry_costs.sort_unstable();
+        Some(entry_costs.into_iter().take(entries_to_block).sum())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::quorum_set_configurators::IdealQsc;
     use std::path::Path;
+    use std::rc::Rc;
 
     #[test]
     fn find_minimal_blocking_sets_simple() {
@@ -187,6 +463,28 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn find_minimal_blocking_sets_up_to_count_truncates() {
+        let node_sets = vec![
+            bitset![0, 2, 7],
+            bitset![1, 3, 8],
+            bitset![0, 1, 4, 9],
+            bitset![0, 1, 2, 5],
+        ];
+        let all_minimal_blocking_sets = find_minimal_blocking_sets(&node_sets);
+
+        let (actual, truncated) = find_minimal_blocking_sets_up_to_count(&node_sets, 3);
+        assert!(truncated);
+        assert!(actual.len() <= 3);
+        assert!(actual
+            .iter()
+            .all(|blocking_set| all_minimal_blocking_sets.contains(blocking_set)));
+
+        let (actual, truncated) = find_minimal_blocking_sets_up_to_count(&node_sets, 1000);
+        assert!(!truncated);
+        assert_eq!(all_minimal_blocking_sets, actual);
+    }
+
     #[test]
     fn find_minimal_blocking_sets_nontrivial() {
         let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
@@ -213,4 +511,117 @@ mod tests {
         );
         assert_eq!(minimal_blocking_sets, minimal_all);
     }
+
+    #[test]
+    fn liveness_buffers_in_correct_trivial() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+
+        let expected = vec![Some(2), Some(2), Some(2)];
+        let actual = find_liveness_buffers(&fbas);
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn liveness_buffers_of_special_quorum_sets() {
+        let mut fbas = Fbas::new_generic_unconfigured(4);
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![1],
+            inner_quorum_sets: vec![],
+        };
+        fbas.nodes[2].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![2],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 2,
+                validators: vec![0, 1, 3],
+                inner_quorum_sets: vec![],
+            }],
+        };
+
+        let expected = vec![Some(0), None, Some(2), Some(0)];
+        let actual = find_liveness_buffers(&fbas);
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn liveness_buffer_with_overlapping_inner_quorum_sets() {
+        let inner_quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![1, 2],
+            inner_quorum_sets: vec![],
+        };
+        let quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![],
+            inner_quorum_sets: vec![inner_quorum_set.clone(), inner_quorum_set],
+        };
+        assert_eq!(Some(4), blocking_set_size_upper_bound(0, &quorum_set));
+        assert_eq!(Some(2), liveness_buffer(0, &quorum_set));
+    }
+
+    #[test]
+    fn k_resilience_of_correct_fbas() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+
+        assert!(is_k_resilient(&fbas, 0));
+        assert!(is_k_resilient(&fbas, 1));
+        assert!(!is_k_resilient(&fbas, 2));
+    }
+
+    #[test]
+    fn found_small_blocking_set_is_blocking() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let blocking_set = find_blocking_set_up_to_size(&fbas, 3).unwrap();
+
+        let minimal_blocking_sets = find_minimal_blocking_sets(&find_minimal_quorums(&fbas));
+        assert!(blocking_set.len() <= 3);
+        assert!(minimal_blocking_sets
+            .iter()
+            .any(|minimal_blocking_set| minimal_blocking_set.is_subset(&blocking_set)));
+    }
+
+    #[test]
+    fn slice_redundancies_of_flat_quorum_sets() {
+        let fbas = Fbas::new_configured(7, Rc::new(IdealQsc::new()));
+        assert_eq!(vec![Some(1); 7], find_slice_redundancies(&fbas));
+
+        let quorum_set = QuorumSet {
+            threshold: 3,
+            validators: (0..7).collect(),
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(Some(3), number_of_disjoint_slices(0, &quorum_set));
+        assert_eq!(Some(2), number_of_disjoint_slices(7, &quorum_set));
+    }
+
+    #[test]
+    fn slice_redundancies_of_special_quorum_sets() {
+        let mut fbas = Fbas::new_generic_unconfigured(3);
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![1, 2],
+            inner_quorum_sets: vec![],
+        };
+        fbas.nodes[2].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![0, 1],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![0, 1, 2],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        // upper bound for node 2, as nodes 0 and 1 appear twice in its quorum set
+        assert_eq!(vec![Some(0), None, Some(2)], find_slice_redundancies(&fbas));
+    }
 }
@@ -0,0 +1,84 @@
+//! Helpers for enumerating subsets of `NodeIdSet`s, as used by many of the analyses in this
+//! crate. All functions work with both `NodeIdSet` backends.
+//!
+//! ```
+//! use fbas_analyzer::{bitset, combinatorics::*};
+//!
+//! let nodes = bitset![1, 2, 3];
+//!
+//! assert_eq!(8, subsets(&nodes).count());
+//! assert_eq!(
+//!     vec![bitset![1, 2], bitset![1, 3], bitset![2, 3]],
+//!     subsets_of_size(&nodes, 2).collect::<Vec<_>>()
+//! );
+//! assert_eq!(bitset![0, 4], complement(&nodes, &bitset![0, 1, 2, 3, 4]));
+//! ```
+
+use crate::{NodeId, NodeIdSet};
+use itertools::Itertools;
+
+/// All subsets of `node_set`, smallest first (and in lexicographic order for each size). There
+/// are 2^n of them, so use with care.
+pub fn subsets(node_set: &NodeIdSet) -> impl Iterator<Item = NodeIdSet> {
+    subsets_up_to_size(node_set, node_set.len())
+}
+
+/// All subsets of `node_set` with exactly `k` nodes (i.e., all `k`-combinations), in
+/// lexicographic order.
+pub fn subsets_of_size(node_set: &NodeIdSet, k: usize) -> impl Iterator<Item = NodeIdSet> {
+    to_vec(node_set)
+        .into_iter()
+        .combinations(k)
+        .map(|nodes| nodes.into_iter().collect())
+}
+
+/// All subsets of `node_set` with at most `max_size` nodes, smallest first.
+pub fn subsets_up_to_size(
+    node_set: &NodeIdSet,
+    max_size: usize,
+) -> impl Iterator<Item = NodeIdSet> {
+    let nodes = to_vec(node_set);
+    (0..=max_size.min(nodes.len())).flat_map(move |k| {
+        nodes
+            .clone()
+            .into_iter()
+            .combinations(k)
+            .map(|nodes| nodes.into_iter().collect())
+    })
+}
+
+/// All nodes from `universe` that are not in `node_set`.
+pub fn complement(node_set: &NodeIdSet, universe: &NodeIdSet) -> NodeIdSet {
+    let mut complement = universe.clone();
+    complement.difference_with(node_set);
+    complement
+}
+
+fn to_vec(node_set: &NodeIdSet) -> Vec<NodeId> {
+    node_set.iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitset, bitsetvec};
+
+    #[test]
+    fn subsets_are_ordered_by_size() {
+        let actual: Vec<NodeIdSet> = subsets(&bitset![3, 5]).collect();
+        let expected = bitsetvec![[], [3], [5], [3, 5]];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn subsets_up_to_size_are_capped_by_set_size() {
+        let nodes = bitset![0, 1, 2];
+        assert_eq!(
+            bitsetvec![[], [0], [1], [2]],
+            subsets_up_to_size(&nodes, 1).collect::<Vec<_>>()
+        );
+        assert_eq!(8, subsets_up_to_size(&nodes, 10).count());
+        assert_eq!(0, subsets_of_size(&nodes, 4).count());
+        assert_eq!(1, subsets_of_size(&bitset![], 0).count());
+    }
+}
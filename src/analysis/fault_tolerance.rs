@@ -0,0 +1,277 @@
+use super::*;
+use std::cmp;
+
+/// Checks whether all quorums still intersect in some correct node if `faulty_nodes` behave
+/// arbitrarily. Faulty nodes are deleted as in `with_byzantine_nodes_deleted` and the remaining
+/// FBAS is searched for non-intersecting quorums. An FBAS without any quorums passes this check,
+/// as it can't fork (but see `find_minimal_blocking_sets` for liveness).
+pub fn has_quorum_intersection_despite(fbas: &Fbas, faulty_nodes: &NodeIdSet) -> bool {
+    let mut correct_nodes = fbas.all_nodes();
+    correct_nodes.difference_with(faulty_nodes);
+    let (remaining_fbas, _, _) = Fbas::shrunken(
+        &with_byzantine_nodes_deleted(fbas, faulty_nodes),
+        correct_nodes,
+    );
+    find_nonintersecting_quorums(&remaining_fbas).is_none()
+}
+
+/// Searches for a smallest set of at most `max_size` faulty nodes from `candidates` that breaks
+/// quorum intersection (see `has_quorum_intersection_despite`). Returns `None` if safety
+/// survives any such set and the empty set if the FBAS lacks quorum intersection to begin with.
+///
+/// Candidates that are interchangeable (i.e., swapping them doesn't change the FBAS) are grouped
+/// into classes, so that only one faulty set is checked per combination of class counts.
+pub fn find_smallest_splitting_faulty_set(
+    fbas: &Fbas,
+    candidates: &NodeIdSet,
+    max_size: usize,
+) -> Option<NodeIdSet> {
+    let classes = find_interchangeable_node_classes(fbas, candidates);
+    debug!(
+        "Grouped {} candidates into {} classes of interchangeable nodes.",
+        candidates.len(),
+        classes.len()
+    );
+    for size in 0..=cmp::min(max_size, candidates.len()) {
+        info!("Checking faulty sets of size {}...", size);
+        for counts in class_count_combinations(&classes, size) {
+            let faulty_nodes: NodeIdSet = classes
+                .iter()
+                .zip(counts)
+                .flat_map(|(class, count)| class.iter().copied().take(count))
+                .collect();
+            if !has_quorum_intersection_despite(fbas, &faulty_nodes) {
+                info!("Found splitting faulty set of size {}.", size);
+                return Some(faulty_nodes);
+            }
+        }
+    }
+    None
+}
+
+/// Partitions `candidates` into classes of nodes that can be pairwise swapped without changing
+/// the FBAS. Interchangeability is transitive, so comparing with one member per class suffices.
+fn find_interchangeable_node_classes(fbas: &Fbas, candidates: &NodeIdSet) -> Vec<Vec<NodeId>> {
+    let mut classes: Vec<Vec<NodeId>> = vec![];
+    for node_id in candidates.iter() {
+        if let Some(class) = classes
+            .iter_mut()
+            .find(|class| are_interchangeable(fbas, class[0], node_id))
+        {
+            class.push(node_id);
+        } else {
+            classes.push(vec![node_id]);
+        }
+    }
+    classes
+}
+
+fn are_interchangeable(fbas: &Fbas, a: NodeId, b: NodeId) -> bool {
+    let swap = |node_id: NodeId| {
+        if node_id == a {
+            b
+        } else if node_id == b {
+            a
+        } else {
+            node_id
+        }
+    };
+    fbas.nodes.iter().enumerate().all(|(node_id, node)| {
+        canonical_quorum_set(&node.quorum_set, &swap)
+            == canonical_quorum_set(&fbas.nodes[swap(node_id)].quorum_set, &|x| x)
+    })
+}
+
+/// Renames all contained nodes using `rename` and sorts validators and inner quorum sets.
+fn canonical_quorum_set(quorum_set: &QuorumSet, rename: &dyn Fn(NodeId) -> NodeId) -> QuorumSet {
+    let mut validators: Vec<NodeId> = quorum_set.validators.iter().map(|&v| rename(v)).collect();
+    let mut inner_quorum_sets: Vec<QuorumSet> = quorum_set
+        .inner_quorum_sets
+        .iter()
+        .map(|inner| canonical_quorum_set(inner, rename))
+        .collect();
+    validators.sort();
+    inner_quorum_sets.sort();
+    QuorumSet {
+        threshold: quorum_set.threshold,
+        validators,
+        inner_quorum_sets,
+    }
+}
+
+/// All ways of picking `size` nodes from `classes`, as numbers of nodes picked per class.
+fn class_count_combinations(classes: &[Vec<NodeId>], size: usize) -> Vec<Vec<usize>> {
+    if let Some((first, rest)) = classes.split_first() {
+        (0..=cmp::min(size, first.len()))
+            .flat_map(|count| {
+                class_count_combinations(rest, size - count)
+                    .into_iter()
+                    .map(move |mut counts| {
+                        counts.insert(0, count);
+                        counts
+                    })
+            })
+            .collect()
+    } else if size == 0 {
+        vec![vec![]]
+    } else {
+        vec![]
+    }
+}
+
+/// Copy of `fbas` in which `byzantine_nodes` are "deleted" in the sense of Mazieres: each
+/// quorum set is treated as if all byzantine nodes in it were already satisfied, i.e., thresholds
+/// are lowered by the number of removed entries. Nodes whose quorum sets are fully satisfied this
+/// way end up trusting only themselves.
+pub(crate) fn with_byzantine_nodes_deleted(fbas: &Fbas, byzantine_nodes: &NodeIdSet) -> Fbas {
+    let mut fbas = fbas.clone();
+    for (node_id, node) in fbas.nodes.iter_mut().enumerate() {
+        node.quorum_set = match quorum_set_with_nodes_deleted(&node.quorum_set, byzantine_nodes) {
+            Some(quorum_set) => quorum_set,
+            None => QuorumSet {
+                threshold: 1,
+                validators: vec![node_id],
+                inner_quorum_sets: vec![],
+            },
+        };
+    }
+    fbas
+}
+
+/// Returns `None` if the quorum set is satisfied by the deleted nodes alone.
+fn quorum_set_with_nodes_deleted(
+    quorum_set: &QuorumSet,
+    deleted_nodes: &NodeIdSet,
+) -> Option<QuorumSet> {
+    if quorum_set.threshold == 0 {
+        return Some(quorum_set.clone()); // badly configured quorum sets stay unsatisfiable
+    }
+    let validators: Vec<NodeId> = quorum_set
+        .validators
+        .iter()
+        .copied()
+        .filter(|&node_id| !deleted_nodes.contains(node_id))
+        .collect();
+    let inner_quorum_sets: Vec<QuorumSet> = quorum_set
+        .inner_quorum_sets
+        .iter()
+        .filter_map(|inner| quorum_set_with_nodes_deleted(inner, deleted_nodes))
+        .collect();
+    let number_of_deleted_entries = quorum_set.validators.len()
+        + quorum_set.inner_quorum_sets.len()
+        - validators.len()
+        - inner_quorum_sets.len();
+    if number_of_deleted_entries >= quorum_set.threshold {
+        None
+    } else {
+        Some(QuorumSet {
+            threshold: quorum_set.threshold - number_of_deleted_entries,
+            validators,
+            inner_quorum_sets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quorum_set_configurators::IdealQsc;
+    use std::path::Path;
+    use std::rc::Rc;
+
+    #[test]
+    fn deleting_byzantine_nodes_lowers_thresholds() {
+        let mut fbas = Fbas::new_generic_unconfigured(4);
+        fbas.nodes[0].quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1, 2, 3],
+            inner_quorum_sets: vec![],
+        };
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![2, 3],
+            inner_quorum_sets: vec![],
+        };
+        let actual = with_byzantine_nodes_deleted(&fbas, &bitset![2, 3]);
+        let expected_0 = QuorumSet {
+            threshold: 1,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![],
+        };
+        let expected_1 = QuorumSet {
+            threshold: 1,
+            validators: vec![1],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected_0, actual.nodes[0].quorum_set);
+        assert_eq!(expected_1, actual.nodes[1].quorum_set);
+        assert_eq!(QuorumSet::new(), actual.nodes[2].quorum_set);
+    }
+
+    #[test]
+    fn ideal_fbas_tolerates_f_faulty_nodes() {
+        let fbas = Fbas::new_configured(4, Rc::new(IdealQsc::new()));
+        assert!(has_quorum_intersection_despite(&fbas, &bitset![]));
+        assert!(has_quorum_intersection_despite(&fbas, &bitset![2]));
+        assert!(!has_quorum_intersection_despite(&fbas, &bitset![2, 3]));
+    }
+
+    #[test]
+    fn smallest_splitting_faulty_set_in_ideal_fbas() {
+        let fbas = Fbas::new_configured(7, Rc::new(IdealQsc::new()));
+        let all_nodes = fbas.all_nodes();
+        assert_eq!(
+            None,
+            find_smallest_splitting_faulty_set(&fbas, &all_nodes, 2)
+        );
+        assert_eq!(
+            Some(bitset![0, 1, 2]),
+            find_smallest_splitting_faulty_set(&fbas, &all_nodes, 4)
+        );
+    }
+
+    #[test]
+    fn smallest_splitting_faulty_set_is_empty_if_broken() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+        let all_nodes = fbas.all_nodes();
+        assert_eq!(
+            Some(bitset![]),
+            find_smallest_splitting_faulty_set(&fbas, &all_nodes, 2)
+        );
+    }
+
+    #[test]
+    fn interchangeable_nodes_in_nested_quorum_sets() {
+        let mut fbas = Fbas::new_generic_unconfigured(5);
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![4],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![0, 1],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![2, 3],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        for node in fbas.nodes.iter_mut() {
+            node.quorum_set = quorum_set.clone();
+        }
+        let expected = vec![vec![0, 1], vec![2, 3], vec![4]];
+        let actual = find_interchangeable_node_classes(&fbas, &fbas.all_nodes());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn class_count_combinations_respect_class_sizes() {
+        let classes = vec![vec![0, 1], vec![2]];
+        let expected = vec![vec![1, 1], vec![2, 0]];
+        let actual = class_count_combinations(&classes, 2);
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,449 @@
+use super::*;
+use std::cell::{RefCell, RefMut};
+use std::collections::BTreeMap;
+
+/// Quorum set "compiled" for fast repeated `is_quorum_slice` checks. Validators are stored as
+/// bit sets, so that matching validators can be counted block-wise instead of looking up each
+/// validator in the checked node set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlatQuorumSet {
+    threshold: usize,
+    validators: NodeIdSet,
+    // repeated occurrences of validators (beyond the first); they count once per occurrence, as
+    // in `QuorumSet::is_quorum_slice`
+    duplicate_validators: Vec<NodeId>,
+    inner_quorum_sets: Vec<FlatQuorumSet>,
+}
+impl FlatQuorumSet {
+    pub fn new(quorum_set: &QuorumSet) -> Self {
+        let mut validators = NodeIdSet::new();
+        let mut duplicate_validators = vec![];
+        for &validator in quorum_set.validators.iter() {
+            if !validators.insert(validator) {
+                duplicate_validators.push(validator);
+            }
+        }
+        FlatQuorumSet {
+            threshold: quorum_set.threshold,
+            validators,
+            duplicate_validators,
+            inner_quorum_sets: quorum_set
+                .inner_quorum_sets
+                .iter()
+                .map(FlatQuorumSet::new)
+                .collect(),
+        }
+    }
+    /// Same semantics as `QuorumSet::is_quorum_slice`.
+    pub fn is_quorum_slice(&self, node_set: &NodeIdSet) -> bool {
+        if self.threshold == 0 {
+            return false; // badly configured quorum set
+        }
+        let found_validator_matches = count_common_nodes(&self.validators, node_set)
+            + self
+                .duplicate_validators
+                .iter()
+                .filter(|&&validator| node_set.contains(validator))
+                .count();
+        if found_validator_matches >= self.threshold {
+            return true;
+        }
+        let mut missing_matches = self.threshold - found_validator_matches;
+        for inner_quorum_set in self.inner_quorum_sets.iter() {
+            if inner_quorum_set.is_quorum_slice(node_set) {
+                missing_matches -= 1;
+                if missing_matches == 0 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// All quorum sets of an FBAS, compiled once into `FlatQuorumSet`s and reused for many
+/// quorum checks (e.g., during quorum enumeration). Many nodes tend to share identical quorum
+/// sets, so each distinct quorum set is stored and evaluated only once; nodes with the same quorum
+/// set form an equivalence class. Quorum sets that only differ in the order of their validators or
+/// inner quorum sets count as identical.
+#[derive(Clone, Debug)]
+pub struct FlatFbas {
+    // one entry per distinct quorum set
+    quorum_sets: Vec<FlatQuorumSet>,
+    // quorum_set_ids[i]: index of node i's quorum set in `quorum_sets`
+    quorum_set_ids: Vec<usize>,
+    // members[q]: nodes whose quorum set is quorum_sets[q]
+    members: Vec<NodeIdSet>,
+    // trusting_quorum_sets[i]: (indices of) quorum sets that have node i somewhere in them
+    trusting_quorum_sets: Vec<Vec<usize>>,
+    // evaluated[q]: whether quorum_sets[q] was already checked; reused across calls to avoid
+    // allocating in hot loops
+    evaluated: RefCell<Vec<bool>>,
+}
+impl FlatFbas {
+    pub fn new(fbas: &Fbas) -> Self {
+        let mut known_quorum_sets: BTreeMap<QuorumSet, usize> = BTreeMap::new();
+        let mut quorum_sets = vec![];
+        let mut members: Vec<NodeIdSet> = vec![];
+        let mut trusting_quorum_sets = vec![vec![]; fbas.nodes.len()];
+        let quorum_set_ids = fbas
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(node_id, node)| {
+                let quorum_set = normalized_quorum_set(&node.quorum_set);
+                let quorum_set_id =
+                    *known_quorum_sets
+                        .entry(quorum_set)
+                        .or_insert_with_key(|quorum_set| {
+                            let quorum_set_id = quorum_sets.len();
+                            quorum_sets.push(FlatQuorumSet::new(quorum_set));
+                            members.push(NodeIdSet::new());
+                            for trusted_node in quorum_set.contained_nodes().into_iter() {
+                                if trusted_node < trusting_quorum_sets.len() {
+                                    trusting_quorum_sets[trusted_node].push(quorum_set_id);
+                                }
+                            }
+                            quorum_set_id
+                        });
+                members[quorum_set_id].insert(node_id);
+                quorum_set_id
+            })
+            .collect();
+        debug!(
+            "Compiled {} distinct quorum sets for {} nodes.",
+            quorum_sets.len(),
+            fbas.nodes.len()
+        );
+        let evaluated = RefCell::new(vec![false; quorum_sets.len()]);
+        FlatFbas {
+            quorum_sets,
+            quorum_set_ids,
+            members,
+            trusting_quorum_sets,
+            evaluated,
+        }
+    }
+    /// Number of distinct quorum sets.
+    pub fn number_of_quorum_sets(&self) -> usize {
+        self.quorum_sets.len()
+    }
+    pub fn is_quorum_slice(&self, node_id: NodeId, node_set: &NodeIdSet) -> bool {
+        self.quorum_sets[self.quorum_set_ids[node_id]].is_quorum_slice(node_set)
+    }
+    /// Same semantics as `Fbas::is_quorum`. Each distinct quorum set is evaluated at most once.
+    pub fn is_quorum(&self, node_set: &NodeIdSet) -> bool {
+        let mut evaluated = self.cleared_evaluated_buffer();
+        !node_set.is_empty()
+            && node_set.iter().all(|node_id| {
+                let quorum_set_id = self.quorum_set_ids[node_id];
+                if evaluated[quorum_set_id] {
+                    true
+                } else {
+                    evaluated[quorum_set_id] = true;
+                    self.quorum_sets[quorum_set_id].is_quorum_slice(node_set)
+                }
+            })
+    }
+    /// Members of `node_set` that don't have a quorum slice in `node_set`.
+    pub fn unsatisfied_nodes(&self, node_set: &NodeIdSet) -> NodeIdSet {
+        let mut unsatisfied = NodeIdSet::new();
+        let mut evaluated = self.cleared_evaluated_buffer();
+        for node_id in node_set.iter() {
+            let quorum_set_id = self.quorum_set_ids[node_id];
+            if !evaluated[quorum_set_id] {
+                evaluated[quorum_set_id] = true;
+                if !self.quorum_sets[quorum_set_id].is_quorum_slice(node_set) {
+                    let mut unsatisfied_members = self.members[quorum_set_id].clone();
+                    unsatisfied_members.intersect_with(node_set);
+                    unsatisfied.union_with(&unsatisfied_members);
+                }
+            }
+        }
+        unsatisfied
+    }
+    fn cleared_evaluated_buffer(&self) -> RefMut<'_, Vec<bool>> {
+        let mut evaluated = self.evaluated.borrow_mut();
+        evaluated.fill(false);
+        evaluated
+    }
+}
+
+/// Sorts validators and inner quorum sets, so that quorum sets that only differ in the order of
+/// their entries become equal. Duplicate validators are kept, as they count once per occurrence.
+fn normalized_quorum_set(quorum_set: &QuorumSet) -> QuorumSet {
+    let mut validators = quorum_set.validators.clone();
+    let mut inner_quorum_sets: Vec<QuorumSet> = quorum_set
+        .inner_quorum_sets
+        .iter()
+        .map(normalized_quorum_set)
+        .collect();
+    validators.sort_unstable();
+    inner_quorum_sets.sort();
+    QuorumSet {
+        threshold: quorum_set.threshold,
+        validators,
+        inner_quorum_sets,
+    }
+}
+
+/// A node set that keeps track of which of its members are (not) satisfied by it, i.e., of its
+/// members that don't have a quorum slice in it. When a node is inserted or removed, only the
+/// nodes that have it in their quorum sets are re-evaluated, instead of the whole set.
+pub(crate) struct SatisfactionTracker<'a> {
+    nodes: NodeIdSet,
+    unsatisfied: NodeIdSet,
+    fbas: &'a FlatFbas,
+}
+impl<'a> SatisfactionTracker<'a> {
+    pub(crate) fn new(nodes: NodeIdSet, fbas: &'a FlatFbas) -> Self {
+        let unsatisfied = fbas.unsatisfied_nodes(&nodes);
+        SatisfactionTracker {
+            nodes,
+            unsatisfied,
+            fbas,
+        }
+    }
+    pub(crate) fn nodes(&self) -> &NodeIdSet {
+        &self.nodes
+    }
+    /// Members that don't have a quorum slice in `self.nodes()`.
+    pub(crate) fn unsatisfied(&self) -> &NodeIdSet {
+        &self.unsatisfied
+    }
+    pub(crate) fn is_quorum(&self) -> bool {
+        !self.nodes.is_empty() && self.unsatisfied.is_empty()
+    }
+    pub(crate) fn insert(&mut self, node_id: NodeId) {
+        if self.nodes.insert(node_id) {
+            self.update(node_id);
+            self.update_trusting_nodes(node_id);
+        }
+    }
+    pub(crate) fn remove(&mut self, node_id: NodeId) {
+        if self.nodes.remove(node_id) {
+            self.unsatisfied.remove(node_id);
+            self.update_trusting_nodes(node_id);
+        }
+    }
+    fn update_trusting_nodes(&mut self, node_id: NodeId) {
+        let fbas = self.fbas;
+        for &quorum_set_id in fbas.trusting_quorum_sets[node_id].iter() {
+            let members = &fbas.members[quorum_set_id];
+            if members.is_disjoint(&self.nodes) {
+                continue;
+            }
+            if fbas.quorum_sets[quorum_set_id].is_quorum_slice(&self.nodes) {
+                self.unsatisfied.difference_with(members);
+            } else {
+                for member in members.iter() {
+                    if self.nodes.contains(member) {
+                        self.unsatisfied.insert(member);
+                    }
+                }
+            }
+        }
+    }
+    fn update(&mut self, node_id: NodeId) {
+        if self.fbas.is_quorum_slice(node_id, &self.nodes) {
+            self.unsatisfied.remove(node_id);
+        } else {
+            self.unsatisfied.insert(node_id);
+        }
+    }
+}
+
+#[cfg(not(feature = "roaring"))]
+fn count_common_nodes(a: &NodeIdSet, b: &NodeIdSet) -> usize {
+    a.get_ref()
+        .blocks()
+        .zip(b.get_ref().blocks())
+        .map(|(x, y)| (x & y).count_ones() as usize)
+        .sum()
+}
+#[cfg(feature = "roaring")]
+fn count_common_nodes(a: &NodeIdSet, b: &NodeIdSet) -> usize {
+    a.intersection_len(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn flat_quorum_set_evaluates_like_quorum_set() {
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 70],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 2,
+                validators: vec![1, 2, 3],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        let flat_quorum_set = FlatQuorumSet::new(&quorum_set);
+        for node_set in &[
+            bitset![],
+            bitset![0],
+            bitset![0, 70],
+            bitset![0, 1],
+            bitset![0, 1, 2],
+            bitset![70, 2, 3, 100],
+            bitset![1, 2, 3],
+        ] {
+            assert_eq!(
+                quorum_set.is_quorum_slice(node_set),
+                flat_quorum_set.is_quorum_slice(node_set)
+            );
+        }
+    }
+
+    #[test]
+    fn flat_quorum_set_counts_duplicate_validators_like_quorum_set() {
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 0, 1],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 2,
+                validators: vec![2, 2],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        assert!(quorum_set.contains_duplicates());
+        let flat_quorum_set = FlatQuorumSet::new(&quorum_set);
+        for node_set in &[bitset![], bitset![0], bitset![1], bitset![2], bitset![1, 2]] {
+            assert_eq!(
+                quorum_set.is_quorum_slice(node_set),
+                flat_quorum_set.is_quorum_slice(node_set)
+            );
+        }
+        assert!(flat_quorum_set.is_quorum_slice(&bitset![0]));
+    }
+
+    #[test]
+    fn flat_fbas_finds_same_quorums() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let flat_fbas = FlatFbas::new(&fbas);
+        for quorum in find_minimal_quorums(&fbas).iter() {
+            assert!(flat_fbas.is_quorum(quorum));
+            for node_id in quorum.iter() {
+                let mut non_quorum = quorum.clone();
+                non_quorum.remove(node_id);
+                assert_eq!(
+                    fbas.is_quorum(&non_quorum),
+                    flat_fbas.is_quorum(&non_quorum)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn satisfaction_tracker_agrees_with_flat_fbas() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let flat_fbas = FlatFbas::new(&fbas);
+        let mut tracker = SatisfactionTracker::new(bitset![], &flat_fbas);
+        assert!(!tracker.is_quorum());
+
+        let quorum = find_minimal_quorums(&fbas).into_iter().next().unwrap();
+        for node_id in quorum.iter() {
+            tracker.insert(node_id);
+            assert_eq!(flat_fbas.is_quorum(tracker.nodes()), tracker.is_quorum());
+        }
+        assert!(tracker.is_quorum());
+        for node_id in quorum.iter() {
+            tracker.remove(node_id);
+            let expected: NodeIdSet = tracker
+                .nodes()
+                .iter()
+                .filter(|&x| !flat_fbas.is_quorum_slice(x, tracker.nodes()))
+                .collect();
+            assert_eq!(&expected, tracker.unsatisfied());
+        }
+        assert!(tracker.nodes().is_empty());
+    }
+
+    #[test]
+    fn flat_fbas_deduplicates_identical_quorum_sets() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let flat_fbas = FlatFbas::new(&fbas);
+        assert_eq!(1, flat_fbas.number_of_quorum_sets());
+        assert!(flat_fbas.is_quorum(&bitset![0, 1]));
+        assert!(!flat_fbas.is_quorum(&bitset![0]));
+        assert_eq!(bitset![2], flat_fbas.unsatisfied_nodes(&bitset![2]));
+    }
+
+    #[test]
+    fn flat_fbas_deduplicates_quorum_sets_after_normalization() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": {
+                    "threshold": 2,
+                    "validators": ["n0", "n1"],
+                    "innerQuorumSets": [
+                        { "threshold": 1, "validators": ["n2"] },
+                        { "threshold": 1, "validators": ["n1"] }
+                    ]
+                }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": {
+                    "threshold": 2,
+                    "validators": ["n1", "n0"],
+                    "innerQuorumSets": [
+                        { "threshold": 1, "validators": ["n1"] },
+                        { "threshold": 1, "validators": ["n2"] }
+                    ]
+                }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 1, "validators": ["n2"] }
+            }
+        ]"#,
+        );
+        let flat_fbas = FlatFbas::new(&fbas);
+        assert_eq!(2, flat_fbas.number_of_quorum_sets());
+        assert!(flat_fbas.is_quorum(&bitset![0, 1]));
+        assert!(flat_fbas.is_quorum(&bitset![2]));
+        assert_eq!(bitset![0], flat_fbas.unsatisfied_nodes(&bitset![0]));
+    }
+
+    #[test]
+    fn flat_fbas_keeps_quorum_sets_with_duplicate_validators_apart() {
+        let mut fbas = Fbas::new_generic_unconfigured(3);
+        fbas.nodes[0].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 0, 1],
+            inner_quorum_sets: vec![],
+        };
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![],
+        };
+        fbas.nodes[2].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![1, 0, 0],
+            inner_quorum_sets: vec![],
+        };
+        let flat_fbas = FlatFbas::new(&fbas);
+        assert_eq!(2, flat_fbas.number_of_quorum_sets());
+        assert!(flat_fbas.is_quorum_slice(0, &bitset![0]));
+        assert!(!flat_fbas.is_quorum_slice(1, &bitset![0]));
+        assert!(flat_fbas.is_quorum_slice(2, &bitset![0]));
+        assert_eq!(
+            fbas.is_quorum(&bitset![0]),
+            flat_fbas.is_quorum(&bitset![0])
+        );
+    }
+
+    #[test]
+    fn badly_configured_flat_quorum_set_is_never_satisfied() {
+        let flat_quorum_set = FlatQuorumSet::new(&QuorumSet::new());
+        assert!(!flat_quorum_set.is_quorum_slice(&bitset![0, 1, 2]));
+    }
+}
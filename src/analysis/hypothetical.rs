@@ -0,0 +1,187 @@
+use super::*;
+
+/// Key results of an analysis, for comparing configurations (see
+/// `Analysis::with_hypothetical_qset`). If merging by organization, node IDs and set sizes refer
+/// to organizations.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisSummary {
+    pub has_quorum_intersection: bool,
+    pub minimal_quorums: usize,
+    pub minimal_blocking_sets: usize,
+    pub minimal_splitting_sets: usize,
+    pub top_tier: Vec<NodeId>,
+    /// Size of a smallest blocking set (0 if there are none)
+    pub smallest_blocking_set_size: usize,
+    /// Size of a smallest splitting set (0 if there are none)
+    pub smallest_splitting_set_size: usize,
+}
+
+/// Analysis results before and after a hypothetical quorum set change.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HypotheticalChange {
+    pub node_id: NodeId,
+    pub before: AnalysisSummary,
+    pub after: AnalysisSummary,
+}
+impl HypotheticalChange {
+    pub fn changes_anything(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+impl<'a> Analysis<'a> {
+    /// Summarizes the main results (see `AnalysisSummary`). Triggers the search for minimal
+    /// quorums, blocking sets and splitting sets, if not already cached.
+    pub fn summary(&mut self) -> AnalysisSummary {
+        let smallest_size = |node_sets: Vec<NodeIdSet>| {
+            node_sets
+                .iter()
+                .map(|node_set| node_set.len())
+                .min()
+                .unwrap_or(0)
+        };
+        AnalysisSummary {
+            has_quorum_intersection: self.has_quorum_intersection(),
+            minimal_quorums: self.minimal_quorums().len(),
+            minimal_blocking_sets: self.minimal_blocking_sets().len(),
+            minimal_splitting_sets: self.minimal_splitting_sets().len(),
+            top_tier: self.top_tier().into_vec(),
+            smallest_blocking_set_size: smallest_size(self.minimal_blocking_sets().unwrap()),
+            smallest_splitting_set_size: smallest_size(self.minimal_splitting_sets().unwrap()),
+        }
+    }
+    /// Compares the current results with what they would be if the physical node `node_id`
+    /// used `quorum_set` instead of its current quorum set, e.g., for evaluating a configuration
+    /// change before deploying it. Results for the current configuration are taken from the
+    /// cache where possible.
+    ///
+    /// Changes that can't affect any results (a node that nobody else trusts, and that doesn't
+    /// form a quorum on its own, switching to another such quorum set) are detected and not
+    /// analyzed again; all other changes trigger a full analysis of the changed FBAS.
+    ///
+    /// ```
+    /// use fbas_analyzer::{Analysis, Fbas, QuorumSet};
+    ///
+    /// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+    /// let mut analysis = Analysis::new(&fbas, None);
+    ///
+    /// // node 0 decides to trust only itself
+    /// let change = analysis.with_hypothetical_qset(0, QuorumSet::from_entries(1, vec![0], vec![]));
+    /// assert!(change.before.has_quorum_intersection);
+    /// assert!(!change.after.has_quorum_intersection);
+    /// ```
+    pub fn with_hypothetical_qset(
+        &mut self,
+        node_id: NodeId,
+        quorum_set: QuorumSet,
+    ) -> HypotheticalChange {
+        let fbas_original = self.fbas_original;
+        assert!(
+            node_id < fbas_original.number_of_nodes(),
+            "Node ID {} out of range!",
+            node_id
+        );
+        let before = self.summary();
+
+        let old_quorum_set = &fbas_original.nodes[node_id].quorum_set;
+        let is_irrelevant = |quorum_set: &QuorumSet| !quorum_set.is_quorum_slice(&bitset![node_id]);
+        let trusted_by_others = fbas_original
+            .nodes
+            .iter()
+            .enumerate()
+            .any(|(id, node)| id != node_id && node.quorum_set.contains_node(node_id));
+
+        let after = if *old_quorum_set == quorum_set
+            || (!trusted_by_others && is_irrelevant(old_quorum_set) && is_irrelevant(&quorum_set))
+        {
+            debug!("Hypothetical quorum set change can't affect results; skipping analysis.");
+            before.clone()
+        } else {
+            let mut fbas = fbas_original.clone();
+            fbas.nodes[node_id].quorum_set = quorum_set;
+            let organizations = self
+                .organizations_original
+                .map(|orgs| Organizations::new(orgs.organizations.clone(), &fbas));
+            Analysis::new(&fbas, organizations.as_ref()).summary()
+        };
+        HypotheticalChange {
+            node_id,
+            before,
+            after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn hypothetical_change_of_leaf_node_changes_nothing() {
+        let mut fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let mut leaf = Node::new("leaf".into());
+        leaf.quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1, 2],
+            inner_quorum_sets: vec![],
+        };
+        let leaf_id = fbas.add_node(leaf);
+        let mut analysis = Analysis::new(&fbas, None);
+
+        let change = analysis.with_hypothetical_qset(
+            leaf_id,
+            QuorumSet {
+                threshold: 1,
+                validators: vec![0, 1],
+                inner_quorum_sets: vec![],
+            },
+        );
+        assert!(!change.changes_anything());
+        assert_eq!(vec![0, 1, 2], change.after.top_tier);
+    }
+
+    #[test]
+    fn hypothetical_change_matches_analysis_of_changed_fbas() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let mut analysis = Analysis::new(&fbas, None);
+        let node_id = analysis.top_tier().into_vec()[0];
+        let mut quorum_set = fbas.nodes[node_id].quorum_set.clone();
+        quorum_set.threshold -= 1;
+
+        let change = analysis.with_hypothetical_qset(node_id, quorum_set.clone());
+        assert_eq!(analysis.summary(), change.before);
+
+        let mut changed_fbas = fbas.clone();
+        changed_fbas.nodes[node_id].quorum_set = quorum_set;
+        assert_eq!(Analysis::new(&changed_fbas, None).summary(), change.after);
+    }
+
+    #[test]
+    fn hypothetical_change_with_organizations() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/stellarbeat_nodes_2019-09-17.json"));
+        let organizations = Organizations::from_json_file(
+            Path::new("test_data/stellarbeat_organizations_2019-09-17.json"),
+            &fbas,
+        );
+        let mut analysis = Analysis::new(&fbas, Some(&organizations));
+        let node_id = fbas
+            .get_node_id("GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH")
+            .unwrap();
+
+        // SDF's node 1 stops requiring anybody but itself
+        let change = analysis.with_hypothetical_qset(
+            node_id,
+            QuorumSet {
+                threshold: 1,
+                validators: vec![node_id],
+                inner_quorum_sets: vec![],
+            },
+        );
+        assert!(change.changes_anything());
+        assert!(change.before.has_quorum_intersection);
+        assert!(!change.after.has_quorum_intersection);
+    }
+}
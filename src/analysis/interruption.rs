@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Ask running (and future) searches to stop as soon as possible and return what they have found
+/// so far. Only sets a flag, so it is safe to call from a signal handler or another thread.
+///
+/// The `_up_to_*` variants of the search functions report stopped searches as truncated; the
+/// other variants promise complete results and hence panic when stopped. `Analysis` doesn't cache
+/// results of stopped searches and reports them via `Analysis::stopped_early`. Quorum intersection
+/// checks (`find_nonintersecting_quorums*` and `Analysis::has_quorum_intersection*`) aren't
+/// stopped, as a partial check has no meaningful result.
+///
+/// ```
+/// use fbas_analyzer::*;
+/// use std::path::Path;
+///
+/// let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+/// request_stop();
+/// let (_, truncated) = find_minimal_quorums_up_to_count(&fbas, usize::MAX);
+/// assert!(truncated);
+/// clear_stop_request();
+/// ```
+pub fn request_stop() {
+    STOP_REQUESTED.store(true, Ordering::Relaxed);
+}
+pub fn stop_requested() -> bool {
+    STOP_REQUESTED.load(Ordering::Relaxed)
+}
+pub fn clear_stop_request() {
+    STOP_REQUESTED.store(false, Ordering::Relaxed);
+}
+
+/// For search functions that promise complete results.
+pub(crate) fn assert_search_complete(truncated: bool, result_name: &str) {
+    if truncated {
+        panic!(
+            "Search for {} was stopped early; use the `_up_to_count` variant for searches that \
+             may be stopped.",
+            result_name
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+/// Which quorum set (and metadata) to keep for validators that are part of both joined FBASs,
+/// i.e., for nodes with the same public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OverlapResolution {
+    KeepFirst,
+    KeepSecond,
+}
+
+impl Fbas {
+    /// Joins two separate FBASs (e.g., a public network and a private consortium considering
+    /// joining it) into one, for modelling federation or merger scenarios. Nodes are identified
+    /// across FBASs by public key; overlapping nodes are resolved according to `resolution`.
+    /// Node IDs of `first` stay the same; also returns the table for mapping node IDs of
+    /// `second` to node IDs in the joined FBAS (`second_to_joined[second_id]`).
+    pub fn joined(
+        first: &Self,
+        second: &Self,
+        resolution: OverlapResolution,
+    ) -> (Self, Vec<NodeId>) {
+        let mut joined = first.clone();
+        let mut next_new_id = first.number_of_nodes();
+        let second_to_joined: Vec<NodeId> = second
+            .nodes
+            .iter()
+            .map(|node| match first.pk_to_id.get(&node.public_key) {
+                Some(&first_id) => first_id,
+                None => {
+                    next_new_id += 1;
+                    next_new_id - 1
+                }
+            })
+            .collect();
+        let join_map: HashMap<NodeId, NodeId> =
+            second_to_joined.iter().copied().enumerate().collect();
+
+        let mut number_of_overlapping_nodes = 0;
+        for (second_id, &joined_id) in second_to_joined.iter().enumerate() {
+            let node = Node::shrunken(&second.nodes[second_id], &join_map);
+            if joined_id < first.number_of_nodes() {
+                number_of_overlapping_nodes += 1;
+                if resolution == OverlapResolution::KeepSecond {
+                    joined.nodes[joined_id] = node;
+                }
+            } else {
+                joined.add_node(node);
+            }
+        }
+        debug!(
+            "Joined FBASs with {} and {} nodes; {} nodes overlap.",
+            first.number_of_nodes(),
+            second.number_of_nodes(),
+            number_of_overlapping_nodes
+        );
+        (joined, second_to_joined)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joining_resolves_overlapping_nodes() {
+        let first = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "a",
+                "quorumSet": { "threshold": 2, "validators": ["a", "b"] }
+            },
+            {
+                "publicKey": "b",
+                "quorumSet": { "threshold": 2, "validators": ["a", "b"] }
+            }
+        ]"#,
+        );
+        let second = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "c",
+                "quorumSet": { "threshold": 2, "validators": ["b", "c"] }
+            },
+            {
+                "publicKey": "b",
+                "quorumSet": { "threshold": 2, "validators": ["b", "c"] }
+            }
+        ]"#,
+        );
+        let (joined, second_to_joined) =
+            Fbas::joined(&first, &second, OverlapResolution::KeepFirst);
+        assert_eq!(3, joined.number_of_nodes());
+        assert_eq!(vec![2, 1], second_to_joined);
+        assert_eq!(Some(2), joined.get_node_id("c"));
+        assert_eq!(bitset![0, 1], joined.nodes[1].quorum_set.contained_nodes());
+        assert_eq!(bitset![1, 2], joined.nodes[2].quorum_set.contained_nodes());
+        assert!(Analysis::new(&joined, None).has_quorum_intersection());
+
+        let (joined, _) = Fbas::joined(&first, &second, OverlapResolution::KeepSecond);
+        assert_eq!(bitset![1, 2], joined.nodes[1].quorum_set.contained_nodes());
+        assert!(Analysis::new(&joined, None).has_quorum_intersection());
+    }
+
+    #[test]
+    fn joining_disjoint_fbass_breaks_quorum_intersection() {
+        let first = Fbas::from_json_str(
+            r#"[{ "publicKey": "a", "quorumSet": { "threshold": 1, "validators": ["a"] } }]"#,
+        );
+        let second = Fbas::from_json_str(
+            r#"[{ "publicKey": "b", "quorumSet": { "threshold": 1, "validators": ["b"] } }]"#,
+        );
+        let (joined, _) = Fbas::joined(&first, &second, OverlapResolution::KeepFirst);
+        assert_eq!(2, joined.number_of_nodes());
+        assert!(!Analysis::new(&joined, None).has_quorum_intersection());
+    }
+}
@@ -0,0 +1,285 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintKind {
+    /// Node has no quorum set at all (probably not a validator).
+    MissingQuorumSet,
+    /// Node can never be part of a quorum.
+    Unsatisfiable,
+    /// Some (inner) quorum set has a threshold below 2/3 of its entries.
+    LowThreshold,
+    /// Quorum set references nodes that can never be part of a quorum.
+    UnsatisfiableDependency,
+    /// A single organization can satisfy or block the quorum set.
+    OrganizationDominance,
+    /// A single node can block this node, or all quorums.
+    SinglePointOfFailure,
+}
+
+/// Risky configuration pattern found for a (physical) node; see `find_configuration_lints`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationLint {
+    pub node_id: NodeId,
+    pub public_key: PublicKey,
+    pub kind: LintKind,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Checks all nodes for risky configuration patterns:
+///
+/// - thresholds below 2/3 of the respective entries (critical if slices needn't intersect),
+/// - quorum sets that a single organization can satisfy or block (only if `organizations` are
+///   given; the node's own organization counts, too),
+/// - dependencies on nodes that can never be part of a quorum (critical if this makes the node
+///   itself unsatisfiable),
+/// - single points of failure, i.e., other nodes whose failure alone blocks the node (or, which
+///   is critical, all quorums).
+///
+/// Results are sorted by severity (most severe first), then by node ID.
+pub fn find_configuration_lints(
+    fbas: &Fbas,
+    organizations: Option<&Organizations>,
+) -> Vec<ConfigurationLint> {
+    let all_nodes = fbas.all_nodes();
+    // `find_unsatisfiable_nodes` returns (satisfiable, unsatisfiable)
+    let (satisfiable_nodes, unsatisfiable_nodes) = find_unsatisfiable_nodes(&all_nodes, fbas);
+    let liveness_buffers = find_liveness_buffers(fbas);
+
+    let mut lints = vec![];
+    for (node_id, node) in fbas.nodes.iter().enumerate() {
+        let mut lint = |kind: LintKind, severity: Severity, message: String| {
+            lints.push(ConfigurationLint {
+                node_id,
+                public_key: node.public_key.clone(),
+                kind,
+                severity,
+                message,
+            })
+        };
+        let quorum_set = &node.quorum_set;
+        if quorum_set.validators.is_empty() && quorum_set.inner_quorum_sets.is_empty() {
+            lint(
+                LintKind::MissingQuorumSet,
+                Severity::Info,
+                String::from("Node has no quorum set."),
+            );
+            continue;
+        }
+        let mut dependencies = quorum_set.contained_nodes();
+        dependencies.remove(node_id);
+        dependencies.intersect_with(&unsatisfiable_nodes);
+        let unsatisfiable_dependencies = dependencies.len();
+        if unsatisfiable_nodes.contains(node_id) {
+            lint(
+                LintKind::Unsatisfiable,
+                Severity::Critical,
+                String::from("Node can never be part of a quorum."),
+            );
+        } else if unsatisfiable_dependencies > 0 {
+            lint(
+                LintKind::UnsatisfiableDependency,
+                Severity::Warning,
+                format!(
+                    "Quorum set references {} nodes that can never be part of a quorum.",
+                    unsatisfiable_dependencies
+                ),
+            );
+        }
+        for (threshold, number_of_entries, level) in low_thresholds(quorum_set, 0) {
+            let (severity, problem) = if 2 * threshold <= number_of_entries {
+                (Severity::Critical, "allows non-intersecting quorum slices")
+            } else {
+                (Severity::Warning, "is below 2/3")
+            };
+            lint(
+                LintKind::LowThreshold,
+                severity,
+                format!(
+                    "Threshold {} of {} at nesting level {} {}.",
+                    threshold, number_of_entries, level, problem
+                ),
+            );
+        }
+        if let Some(orgs) = organizations {
+            for org in orgs.organizations.iter() {
+                let mut org_nodes: NodeIdSet = org.validators.iter().copied().collect();
+                org_nodes.insert(node_id);
+                if quorum_set.is_quorum_slice(&org_nodes) {
+                    lint(
+                        LintKind::OrganizationDominance,
+                        Severity::Warning,
+                        format!("Quorum set can be satisfied by {} alone.", org.name),
+                    );
+                }
+                let mut other_nodes = all_nodes.clone();
+                for &validator in org.validators.iter().filter(|&&v| v != node_id) {
+                    other_nodes.remove(validator);
+                }
+                if !quorum_set.is_quorum_slice(&other_nodes) {
+                    lint(
+                        LintKind::OrganizationDominance,
+                        Severity::Warning,
+                        format!("Quorum set can be blocked by {} alone.", org.name),
+                    );
+                }
+            }
+        }
+        if liveness_buffers[node_id] == Some(1) {
+            lint(
+                LintKind::SinglePointOfFailure,
+                Severity::Warning,
+                String::from("A single failing node suffices for blocking this node."),
+            );
+        }
+        if satisfiable_nodes.contains(node_id) {
+            let mut remaining_nodes = all_nodes.clone();
+            remaining_nodes.remove(node_id);
+            if find_unsatisfiable_nodes(&remaining_nodes, fbas)
+                .0
+                .is_empty()
+            {
+                lint(
+                    LintKind::SinglePointOfFailure,
+                    Severity::Critical,
+                    String::from("Failure of this node alone blocks all quorums."),
+                );
+            }
+        }
+    }
+    lints.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.node_id.cmp(&b.node_id)));
+    lints
+}
+
+/// (threshold, number of entries, nesting level) of all (inner) quorum sets whose threshold is
+/// below 2/3 of their entries. Unsatisfiable quorum sets are skipped.
+fn low_thresholds(quorum_set: &QuorumSet, level: usize) -> Vec<(usize, usize, usize)> {
+    let number_of_entries = quorum_set.validators.len() + quorum_set.inner_quorum_sets.len();
+    let mut result = vec![];
+    if quorum_set.threshold > 0
+        && quorum_set.threshold <= number_of_entries
+        && 3 * quorum_set.threshold < 2 * number_of_entries
+    {
+        result.push((quorum_set.threshold, number_of_entries, level));
+    }
+    for inner_quorum_set in quorum_set.inner_quorum_sets.iter() {
+        result.extend(low_thresholds(inner_quorum_set, level + 1));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn lints_of(lints: &[ConfigurationLint], node_id: NodeId) -> Vec<(LintKind, Severity)> {
+        lints
+            .iter()
+            .filter(|lint| lint.node_id == node_id)
+            .map(|lint| (lint.kind, lint.severity))
+            .collect()
+    }
+
+    #[test]
+    fn lints_of_handmade_fbas() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 3, "validators": ["n0", "n1", "n2", "n3"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n3",
+                "quorumSet": { "threshold": 1, "validators": ["n0", "n4"] }
+            },
+            {
+                "publicKey": "n4",
+                "quorumSet": { "threshold": 2, "validators": ["n4", "n5"] }
+            },
+            {
+                "publicKey": "n5"
+            }
+        ]"#,
+        );
+        let lints = find_configuration_lints(&fbas, None);
+
+        assert_eq!(Vec::<(LintKind, Severity)>::new(), lints_of(&lints, 0));
+        assert_eq!(Vec::<(LintKind, Severity)>::new(), lints_of(&lints, 1));
+        assert_eq!(
+            vec![
+                (LintKind::LowThreshold, Severity::Critical),
+                (LintKind::UnsatisfiableDependency, Severity::Warning),
+            ],
+            lints_of(&lints, 3)
+        );
+        assert_eq!(
+            vec![
+                (LintKind::Unsatisfiable, Severity::Critical),
+                (LintKind::SinglePointOfFailure, Severity::Warning),
+            ],
+            lints_of(&lints, 4)
+        );
+        assert_eq!(
+            vec![(LintKind::MissingQuorumSet, Severity::Info)],
+            lints_of(&lints, 5)
+        );
+        assert!(lints.windows(2).all(|w| w[0].severity >= w[1].severity));
+    }
+
+    #[test]
+    fn single_node_blocking_all_quorums_is_critical() {
+        let mut fbas = Fbas::new_generic_unconfigured(3);
+        for node in fbas.nodes.iter_mut() {
+            node.quorum_set = QuorumSet {
+                threshold: 3,
+                validators: vec![0, 1],
+                inner_quorum_sets: vec![QuorumSet {
+                    threshold: 1,
+                    validators: vec![1, 2],
+                    inner_quorum_sets: vec![],
+                }],
+            };
+        }
+        let lints = find_configuration_lints(&fbas, None);
+        assert!(lints_of(&lints, 0).contains(&(LintKind::SinglePointOfFailure, Severity::Critical)));
+        assert!(lints_of(&lints, 1).contains(&(LintKind::SinglePointOfFailure, Severity::Critical)));
+        assert!(
+            !lints_of(&lints, 2).contains(&(LintKind::SinglePointOfFailure, Severity::Critical))
+        );
+    }
+
+    #[test]
+    fn organization_dominance() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/stellarbeat_nodes_2019-09-17.json"));
+        let organizations = Organizations::from_json_file(
+            Path::new("test_data/stellarbeat_organizations_2019-09-17.json"),
+            &fbas,
+        );
+        let lints = find_configuration_lints(&fbas, Some(&organizations));
+        assert!(lints
+            .iter()
+            .any(|lint| lint.kind == LintKind::OrganizationDominance));
+        assert!(find_configuration_lints(&fbas, None)
+            .iter()
+            .all(|lint| lint.kind != LintKind::OrganizationDominance));
+    }
+}
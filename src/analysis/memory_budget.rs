@@ -0,0 +1,74 @@
+use super::*;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// in bytes; 0 means unlimited
+static MEMORY_BUDGET: AtomicUsize = AtomicUsize::new(0);
+
+/// Cap the (approximate) memory that each search may use for the node sets it collects. Once a
+/// search exceeds the budget, it stops early and reports its result as incomplete, like a search
+/// stopped via `request_stop` (but without affecting any other searches), so that pathological
+/// inputs yield truncated results instead of getting the process killed.
+///
+/// Only result sets are accounted for, so leave some headroom for everything else.
+///
+/// ```
+/// use fbas_analyzer::*;
+/// use std::path::Path;
+///
+/// let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+/// set_memory_budget(Some(1));
+/// let (_, truncated) = find_minimal_quorums_up_to_count(&fbas, usize::MAX);
+/// assert!(truncated);
+/// assert!(!stop_requested());
+///
+/// // incomplete minimal quorums are neither cached nor used for checking quorum intersection
+/// let mut analysis = Analysis::new(&fbas, None);
+/// assert!(!analysis.has_quorum_intersection());
+/// assert!(analysis.stopped_early());
+/// ```
+pub fn set_memory_budget(bytes: Option<usize>) {
+    MEMORY_BUDGET.store(bytes.unwrap_or(0), Ordering::Relaxed);
+}
+pub fn memory_budget() -> Option<usize> {
+    match MEMORY_BUDGET.load(Ordering::Relaxed) {
+        0 => None,
+        bytes => Some(bytes),
+    }
+}
+
+/// To be called whenever a search has collected `number_of_node_sets` node sets that look like
+/// `latest_node_set`; returns `false` if they (approximately) exceed the memory budget, in which
+/// case the search should stop and report its result as incomplete.
+pub(crate) fn check_memory_budget(number_of_node_sets: usize, latest_node_set: &NodeIdSet) -> bool {
+    if let Some(budget) = memory_budget() {
+        let estimated_usage = number_of_node_sets * estimated_size(latest_node_set);
+        if estimated_usage > budget {
+            warn!(
+                "Collected node sets use about {} bytes, exceeding the memory budget of {} bytes; \
+                stopping early.",
+                estimated_usage, budget
+            );
+            return false;
+        }
+    }
+    true
+}
+
+/// Bit sets need a bit per possible node ID (up to the biggest one contained), plus some
+/// bookkeeping.
+fn estimated_size(node_set: &NodeIdSet) -> usize {
+    let max_node_id = node_set.iter().last().unwrap_or(0);
+    mem::size_of::<NodeIdSet>() + max_node_id / 8 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_sets_with_bigger_ids_are_estimated_bigger() {
+        assert!(estimated_size(&bitset![0, 1, 2]) < estimated_size(&bitset![0, 1, 1000]));
+        assert!(estimated_size(&bitset![]) > 0);
+    }
+}
@@ -1,33 +1,97 @@
 use super::*;
 use log::log_enabled;
 use log::Level::Warn;
+use serde::Serialize;
 
 mod blocking_sets;
+pub mod combinatorics;
+mod fault_tolerance;
+mod flat_quorum_sets;
+mod hypothetical;
+mod interruption;
+mod joining;
+mod lints;
+mod memory_budget;
+mod node_reports;
+mod node_set_tries;
+mod parallelism;
+mod progress;
+mod quorum_set_diffs;
 mod quorums;
 mod rank;
+mod recommendation;
+mod repairs;
 mod shrink;
 mod splitting_sets;
+mod statistics;
+mod sub_fbas;
+mod threshold_percentages;
 
-pub use blocking_sets::find_minimal_blocking_sets;
-pub use quorums::{find_minimal_quorums, find_nonintersecting_quorums, find_symmetric_clusters};
-pub use splitting_sets::find_minimal_splitting_sets;
+pub(crate) use blocking_sets::find_minimal_blocking_sets_until_stopped;
+pub use blocking_sets::{
+    find_blocking_set_up_to_size, find_liveness_buffers, find_minimal_blocking_sets,
+    find_minimal_blocking_sets_up_to_count, find_slice_redundancies, is_k_resilient,
+};
+pub(crate) use fault_tolerance::with_byzantine_nodes_deleted;
+pub use fault_tolerance::{find_smallest_splitting_faulty_set, has_quorum_intersection_despite};
+pub(crate) use flat_quorum_sets::SatisfactionTracker;
+pub use flat_quorum_sets::{FlatFbas, FlatQuorumSet};
+pub use hypothetical::{AnalysisSummary, HypotheticalChange};
+pub(crate) use interruption::assert_search_complete;
+pub use interruption::{clear_stop_request, request_stop, stop_requested};
+pub use joining::OverlapResolution;
+pub use lints::{find_configuration_lints, ConfigurationLint, LintKind, Severity};
+pub(crate) use memory_budget::check_memory_budget;
+pub use memory_budget::{memory_budget, set_memory_budget};
+pub use node_reports::NodeReport;
+pub use node_set_tries::NodeSetTrie;
+pub use parallelism::{max_threads, set_max_threads, THREADS_ENV_VAR};
+pub(crate) use progress::ProgressTracker;
+pub use progress::{clear_progress_callback, set_progress_callback, SearchProgress};
+pub use quorum_set_diffs::{diff_quorum_sets, QuorumSetDiff};
+pub use quorums::{
+    find_minimal_quorums, find_minimal_quorums_up_to_count, find_minimal_quorums_up_to_size,
+    find_nonintersecting_quorums, find_nonintersecting_quorums_streaming, find_symmetric_clusters,
+    find_symmetric_top_tier, quorum_closure,
+};
+pub use recommendation::{recommend_quorum_set, RecommendationConstraints};
+pub use repairs::{
+    apply_quorum_set_edits, find_distance_to_safety, find_quorum_intersection_repairs,
+    QuorumSetEdit,
+};
+pub(crate) use splitting_sets::find_minimal_splitting_sets_until_stopped;
+pub use splitting_sets::{find_minimal_splitting_sets, find_minimal_splitting_sets_up_to_count};
+pub(crate) use statistics::{count, count_result_set_size};
+pub use statistics::{reset_search_statistics, search_statistics, SearchStatistics};
+pub use sub_fbas::{
+    find_minimal_blocking_sets_in, find_minimal_quorums_in, find_minimal_splitting_sets_in,
+    find_nonintersecting_quorums_in, is_self_sufficient,
+};
+pub use threshold_percentages::{
+    find_threshold_percentages, summarize_threshold_percentages, ThresholdPercentage,
+    ThresholdPercentageDistribution,
+};
 
 pub(crate) use rank::*;
 
-use quorums::{find_unsatisfiable_nodes, reduce_to_strongly_connected_nodes}; // TODO why in quorums?
-use shrink::{reshrink_sets, unshrink_set, unshrink_sets};
+use quorums::reduce_to_strongly_connected_nodes; // TODO why in quorums?
+pub(crate) use quorums::{find_minimal_quorums_until_stopped, find_unsatisfiable_nodes};
+use shrink::reshrink_sets;
+pub use shrink::{shrink_set, shrink_sets, unshrink_set, unshrink_sets};
 
 /// Front end for all interesting FBAS analyses. Caches intermediate results
-/// (hence some methods require `&mut`).
+/// (hence some methods require `&mut`), except for results of searches that were stopped early
+/// (see `stopped_early`).
 pub struct Analysis<'a> {
-    fbas_original: &'a Fbas,
-    organizations_original: Option<&'a Organizations<'a>>,
+    pub(crate) fbas_original: &'a Fbas,
+    pub(crate) organizations_original: Option<&'a Organizations<'a>>,
     fbas_shrunken: Fbas,
     unshrink_table: Vec<NodeId>,
     has_quorum_intersection: Option<bool>,
     minimal_quorums_shrunken: Option<Vec<NodeIdSet>>,
     minimal_blocking_sets_shrunken: Option<Vec<NodeIdSet>>,
     minimal_splitting_sets_shrunken: Option<Vec<NodeIdSet>>,
+    stopped_early: bool,
 }
 impl<'a> Analysis<'a> {
     pub fn new(fbas: &'a Fbas, organizations: Option<&'a Organizations<'a>>) -> Self {
@@ -51,6 +115,7 @@ impl<'a> Analysis<'a> {
             minimal_quorums_shrunken: None,
             minimal_blocking_sets_shrunken: None,
             minimal_splitting_sets_shrunken: None,
+            stopped_early: false,
         }
     }
     /// Actual raw nodes, not filtered and not merged by organization
@@ -76,14 +141,18 @@ impl<'a> Analysis<'a> {
             None,
         )
     }
-    /// Regular check via finding all minimal quorums.
+    /// Regular check via finding all minimal quorums. If the search for minimal quorums is
+    /// stopped early, falls back to the alternative check, which can't be stopped.
     pub fn has_quorum_intersection(&mut self) -> bool {
         if self.has_quorum_intersection.is_none() {
             info!("Checking for intersection of all minimal quorums...");
-            let minimal_quorums_shrunken = self.minimal_quorums_shrunken();
-            self.has_quorum_intersection = Some(
-                !minimal_quorums_shrunken.is_empty() && all_intersect(&minimal_quorums_shrunken),
-            )
+            let (minimal_quorums_shrunken, truncated) = self.minimal_quorums_shrunken_checked();
+            self.has_quorum_intersection = Some(if truncated {
+                warn!("Minimal quorums are incomplete; using the alternative check instead...");
+                self.has_quorum_intersection_via_alternative_check().0
+            } else {
+                !minimal_quorums_shrunken.is_empty() && all_intersect(&minimal_quorums_shrunken)
+            })
         }
         self.has_quorum_intersection.unwrap()
     }
@@ -104,9 +173,112 @@ impl<'a> Analysis<'a> {
             (true, None)
         }
     }
+    /// Checks each quorum for intersection while enumerating minimal quorums, without storing
+    /// them. Use this if there are too many minimal quorums to keep in memory.
+    pub fn has_quorum_intersection_via_streaming_check(
+        &self,
+    ) -> (bool, Option<NodeIdSetVecResult>) {
+        if let Some(quorums) = find_nonintersecting_quorums_streaming(&self.fbas_shrunken) {
+            assert!(quorums[0].is_disjoint(&quorums[1]));
+            (
+                false,
+                Some(NodeIdSetVecResult::new(
+                    quorums.to_vec(),
+                    Some(&self.unshrink_table),
+                )),
+            )
+        } else {
+            (true, None)
+        }
+    }
+    /// Runs both the regular check (via minimal quorums) and the alternative check and verifies
+    /// that they agree, for when redundancy is worth the extra runtime. Nonintersecting quorums
+    /// found by the alternative check are also verified to be quorums. Panics loudly on any
+    /// disagreement, as that would mean that one of the algorithms is broken.
+    pub fn has_quorum_intersection_cross_checked(
+        &mut self,
+    ) -> (bool, Option<NodeIdSetVecResult<'_>>) {
+        let regular_result = self.has_quorum_intersection();
+        let (alternative_result, quorums) = self.has_quorum_intersection_via_alternative_check();
+        if regular_result != alternative_result {
+            panic!(
+                "Quorum intersection checks disagree! Regular check: {}, alternative check: {} \
+                 (nonintersecting quorums: {:?}). Please report this as a bug.",
+                regular_result,
+                alternative_result,
+                quorums.map(|quorums| quorums.into_vec_vec())
+            );
+        }
+        if let Some(ref quorums) = quorums {
+            for quorum in quorums.clone().unwrap().iter() {
+                assert!(
+                    self.fbas_original.is_quorum(quorum),
+                    "Alternative check returned a non-quorum: {:?}",
+                    quorum
+                );
+            }
+        }
+        info!("Both quorum intersection checks agree: {}", regular_result);
+        (regular_result, quorums)
+    }
     pub fn minimal_quorums(&mut self) -> NodeIdSetVecResult {
         NodeIdSetVecResult::new(self.minimal_quorums_shrunken(), Some(&self.unshrink_table))
     }
+    /// Minimal quorums with at most `max_size` physical nodes (see
+    /// `find_minimal_quorums_up_to_size`), plus whether the result is truncated. Not cached, and
+    /// merged by organization only after the size cap has been applied.
+    pub fn minimal_quorums_up_to_size(&self, max_size: usize) -> (NodeIdSetVecResult, bool) {
+        let (minimal_quorums_shrunken, truncated) =
+            find_minimal_quorums_up_to_size(&self.fbas_shrunken, max_size);
+        let minimal_quorums = unshrink_sets(&minimal_quorums_shrunken, &self.unshrink_table);
+        let minimal_quorums = if let Some(orgs) = self.organizations_original {
+            orgs.merge_minimal_node_sets(minimal_quorums)
+        } else {
+            minimal_quorums
+        };
+        (NodeIdSetVecResult::new(minimal_quorums, None), truncated)
+    }
+    /// Up to `max_count` minimal quorums (see `find_minimal_quorums_up_to_count`), plus whether
+    /// the result is truncated. Not cached.
+    pub fn minimal_quorums_up_to_count(&self, max_count: usize) -> (NodeIdSetVecResult, bool) {
+        let (minimal_quorums_shrunken, truncated) =
+            find_minimal_quorums_up_to_count(&self.fbas_shrunken, max_count);
+        let minimal_quorums = unshrink_sets(&minimal_quorums_shrunken, &self.unshrink_table);
+        let minimal_quorums = if let Some(orgs) = self.organizations_original {
+            orgs.merge_minimal_node_sets(minimal_quorums)
+        } else {
+            minimal_quorums
+        };
+        (NodeIdSetVecResult::new(minimal_quorums, None), truncated)
+    }
+    /// Up to `max_count` minimal blocking sets, plus whether the result is truncated. Still needs
+    /// all minimal quorums. Not cached.
+    pub fn minimal_blocking_sets_up_to_count(
+        &mut self,
+        max_count: usize,
+    ) -> (NodeIdSetVecResult, bool) {
+        let (minimal_quorums_shrunken, quorums_truncated) = self.minimal_quorums_shrunken_checked();
+        let (minimal_blocking_sets, truncated) =
+            find_minimal_blocking_sets_up_to_count(&minimal_quorums_shrunken, max_count);
+        (
+            NodeIdSetVecResult::new(minimal_blocking_sets, Some(&self.unshrink_table)),
+            truncated || quorums_truncated,
+        )
+    }
+    /// Up to `max_count` minimal splitting sets, plus whether the result is truncated (see
+    /// `find_minimal_splitting_sets_up_to_count`). Still needs all minimal quorums. Not cached.
+    pub fn minimal_splitting_sets_up_to_count(
+        &mut self,
+        max_count: usize,
+    ) -> (NodeIdSetVecResult, bool) {
+        let (minimal_quorums_shrunken, quorums_truncated) = self.minimal_quorums_shrunken_checked();
+        let (minimal_splitting_sets, truncated) =
+            find_minimal_splitting_sets_up_to_count(&minimal_quorums_shrunken, max_count);
+        (
+            NodeIdSetVecResult::new(minimal_splitting_sets, Some(&self.unshrink_table)),
+            truncated || quorums_truncated,
+        )
+    }
     pub fn minimal_blocking_sets(&mut self) -> NodeIdSetVecResult {
         NodeIdSetVecResult::new(
             self.minimal_blocking_sets_shrunken(),
@@ -119,34 +291,192 @@ impl<'a> Analysis<'a> {
             Some(&self.unshrink_table),
         )
     }
+    /// Minimal blocking sets measured in organizations, i.e., minimal sets of organizations that
+    /// can halt the network by failing together. Each organization is represented by the merged
+    /// ID of its nodes; nodes that don't belong to any organization count as organizations of
+    /// their own. Use `into_pretty_string` with `organizations` to report organization names.
+    /// Unlike passing `organizations` to `Analysis::new`, this leaves all other results unmerged.
+    pub fn minimal_blocking_sets_of_organizations(
+        &mut self,
+        organizations: &Organizations,
+    ) -> NodeIdSetVecResult {
+        NodeIdSetVecResult::new(
+            organizations.merge_minimal_node_sets(self.minimal_blocking_sets().unwrap()),
+            None,
+        )
+    }
+    /// Minimal splitting sets measured in organizations, i.e., minimal coalitions of organizations
+    /// that could cause a fork. Organizations are represented as in
+    /// `minimal_blocking_sets_of_organizations`. Merging happens after finding splitting sets on
+    /// node level, so that nodes of the same organization that merely appear in two different
+    /// quorums don't count as an intersection.
+    pub fn minimal_splitting_sets_of_organizations(
+        &mut self,
+        organizations: &Organizations,
+    ) -> NodeIdSetVecResult {
+        NodeIdSetVecResult::new(
+            organizations.merge_minimal_node_sets(self.minimal_splitting_sets().unwrap()),
+            None,
+        )
+    }
     fn minimal_quorums_shrunken(&mut self) -> Vec<NodeIdSet> {
-        if self.minimal_quorums_shrunken.is_none() {
-            self.find_and_cache_minimal_quorums();
-        } else {
+        self.minimal_quorums_shrunken_checked().0
+    }
+    /// Also returns whether the result is incomplete (and hence not cached).
+    fn minimal_quorums_shrunken_checked(&mut self) -> (Vec<NodeIdSet>, bool) {
+        if let Some(ref minimal_quorums_shrunken) = self.minimal_quorums_shrunken {
             info!("Using cached minimal quorums.");
+            (minimal_quorums_shrunken.clone(), false)
+        } else {
+            self.find_and_cache_minimal_quorums()
         }
-        self.minimal_quorums_shrunken.clone().unwrap()
     }
     fn minimal_blocking_sets_shrunken(&mut self) -> Vec<NodeIdSet> {
-        if self.minimal_blocking_sets_shrunken.is_none() {
-            warn!("Computing minimal blocking sets...");
-            self.minimal_blocking_sets_shrunken =
-                Some(find_minimal_blocking_sets(&self.minimal_quorums_shrunken()));
-        } else {
+        if let Some(ref minimal_blocking_sets) = self.minimal_blocking_sets_shrunken {
             info!("Using cached minimal blocking sets.");
+            return minimal_blocking_sets.clone();
         }
-        self.minimal_blocking_sets_shrunken.clone().unwrap()
+        warn!("Computing minimal blocking sets...");
+        let (minimal_blocking_sets, truncated) =
+            if let Some(top_tier_qset) = self.symmetric_top_tier_quorum_set() {
+                info!("Deriving minimal blocking sets from symmetric top tier...");
+                (
+                    remove_non_minimal_node_sets(top_tier_qset.to_blocking_sets()),
+                    false,
+                )
+            } else {
+                let (minimal_quorums, quorums_truncated) = self.minimal_quorums_shrunken_checked();
+                let (minimal_blocking_sets, truncated) =
+                    find_minimal_blocking_sets_until_stopped(&minimal_quorums);
+                (minimal_blocking_sets, truncated || quorums_truncated)
+            };
+        self.cache_unless_truncated(minimal_blocking_sets, truncated, |analysis| {
+            &mut analysis.minimal_blocking_sets_shrunken
+        })
     }
     fn minimal_splitting_sets_shrunken(&mut self) -> Vec<NodeIdSet> {
-        if self.minimal_splitting_sets_shrunken.is_none() {
-            warn!("Computing minimal splitting sets...");
-            self.minimal_splitting_sets_shrunken = Some(find_minimal_splitting_sets(
-                &self.minimal_quorums_shrunken(),
-            ));
-        } else {
+        if let Some(ref minimal_splitting_sets) = self.minimal_splitting_sets_shrunken {
             info!("Using cached minimal splitting sets.");
+            return minimal_splitting_sets.clone();
+        }
+        warn!("Computing minimal splitting sets...");
+        let (minimal_splitting_sets, truncated) =
+            if let Some(top_tier_qset) = self.symmetric_top_tier_quorum_set() {
+                info!("Deriving minimal splitting sets from symmetric top tier...");
+                (
+                    remove_non_minimal_node_sets(top_tier_qset.to_splitting_sets()),
+                    false,
+                )
+            } else {
+                let (minimal_quorums, quorums_truncated) = self.minimal_quorums_shrunken_checked();
+                let (minimal_splitting_sets, truncated) =
+                    find_minimal_splitting_sets_until_stopped(&minimal_quorums);
+                (minimal_splitting_sets, truncated || quorums_truncated)
+            };
+        self.cache_unless_truncated(minimal_splitting_sets, truncated, |analysis| {
+            &mut analysis.minimal_splitting_sets_shrunken
+        })
+    }
+    fn cache_unless_truncated<F>(
+        &mut self,
+        node_sets: Vec<NodeIdSet>,
+        truncated: bool,
+        cache: F,
+    ) -> Vec<NodeIdSet>
+    where
+        F: FnOnce(&mut Self) -> &mut Option<Vec<NodeIdSet>>,
+    {
+        if truncated {
+            warn!("Search was stopped early; not caching its incomplete results.");
+            self.stopped_early = true;
+        } else {
+            *cache(self) = Some(node_sets.clone());
+        }
+        node_sets
+    }
+    /// Quorum set of the top tier, in terms of shrunken node IDs, if the top tier is symmetric and
+    /// its quorum set allows deriving blocking and splitting sets analytically. Never returns
+    /// anything when merging by organization, as the thresholds refer to physical nodes.
+    fn symmetric_top_tier_quorum_set(&mut self) -> Option<QuorumSet> {
+        if self.organizations_original.is_some() {
+            return None;
         }
-        self.minimal_splitting_sets_shrunken.clone().unwrap()
+        // makes sure that `fbas_shrunken` is shrunken to the top tier; no search is needed for
+        // that if the top tier is symmetric
+        self.minimal_quorums_shrunken();
+        find_symmetric_top_tier(&self.fbas_shrunken).filter(|qset| !qset.contains_duplicates())
+    }
+    /// Physical top tier nodes; when merging by organization, all nodes of top tier
+    /// organizations.
+    fn top_tier_candidates(&mut self) -> NodeIdSet {
+        let mut candidates = self.top_tier().unwrap();
+        if let Some(orgs) = self.organizations_original {
+            for org in orgs.organizations.iter() {
+                if org.validators.iter().any(|&v| candidates.contains(v)) {
+                    candidates.extend(org.validators.iter().copied());
+                }
+            }
+        }
+        candidates
+    }
+    /// Organizations and countries spanned by each minimal quorum, in the same order as
+    /// `minimal_quorums`. See `NodeSetSpan` for details.
+    pub fn minimal_quorum_spans(&mut self) -> Vec<NodeSetSpan> {
+        let minimal_quorums = self.minimal_quorums().unwrap();
+        minimal_quorums
+            .iter()
+            .map(|quorum| NodeSetSpan::new(quorum, self.fbas_original, self.organizations_original))
+            .collect()
+    }
+    /// Smallest set of at most `max_size` top tier nodes that, if faulty, can break quorum
+    /// intersection (see `find_smallest_splitting_faulty_set`). When merging by organization,
+    /// all nodes of top tier organizations are candidates; results always use physical node IDs.
+    pub fn smallest_splitting_faulty_set(&mut self, max_size: usize) -> Option<NodeIdSetResult> {
+        let candidates = self.top_tier_candidates();
+        find_smallest_splitting_faulty_set(self.fbas_original, &candidates, max_size)
+            .map(|faulty_nodes| NodeIdSetResult::new(faulty_nodes, None))
+    }
+    /// Smallest sets of at most `max_edits` quorum set edits that restore quorum intersection,
+    /// for physical nodes (see `find_quorum_intersection_repairs`).
+    pub fn quorum_intersection_repairs(&self, max_edits: usize) -> Vec<Vec<QuorumSetEdit>> {
+        find_quorum_intersection_repairs(self.fbas_original, max_edits)
+    }
+    /// Minimum number of physical nodes whose quorum sets need to change to restore quorum
+    /// intersection, if at most `max_distance` (see `find_distance_to_safety`).
+    pub fn distance_to_safety(&self, max_distance: usize) -> Option<usize> {
+        find_distance_to_safety(self.fbas_original, max_distance)
+    }
+    /// Quorum set for a new validator that trusts top tier nodes (see `recommend_quorum_set`).
+    /// When merging by organization, all nodes of top tier organizations are candidates; the
+    /// result always uses physical node IDs.
+    pub fn recommended_quorum_set(
+        &mut self,
+        constraints: &RecommendationConstraints,
+    ) -> Option<QuorumSet> {
+        let candidates = self.top_tier_candidates();
+        recommend_quorum_set(
+            self.fbas_original,
+            self.organizations_original,
+            &candidates,
+            constraints,
+        )
+    }
+    /// Risky configuration patterns, for physical nodes (see `find_configuration_lints`).
+    pub fn configuration_lints(&self) -> Vec<ConfigurationLint> {
+        find_configuration_lints(self.fbas_original, self.organizations_original)
+    }
+    /// Network-wide distribution of thresholds relative to the number of quorum set entries, per
+    /// nesting level, for physical nodes (see `summarize_threshold_percentages`).
+    pub fn threshold_percentage_distributions(&self) -> Vec<ThresholdPercentageDistribution> {
+        summarize_threshold_percentages(&find_threshold_percentages(self.fbas_original))
+    }
+    /// Per-node liveness buffers, for physical nodes (see `find_liveness_buffers`).
+    pub fn liveness_buffers(&self) -> Vec<Option<usize>> {
+        find_liveness_buffers(self.fbas_original)
+    }
+    /// Per-node slice redundancies, for physical nodes (see `find_slice_redundancies`).
+    pub fn slice_redundancies(&self) -> Vec<Option<usize>> {
+        find_slice_redundancies(self.fbas_original)
     }
     pub fn symmetric_clusters(&self) -> Vec<QuorumSet> {
         let clusters = find_symmetric_clusters(self.fbas_original);
@@ -156,15 +486,73 @@ impl<'a> Analysis<'a> {
             clusters
         }
     }
+    /// Alternative top tier characterization: nodes contained in every minimal blocking set.
+    /// Comparing this with `top_tier` (derived from minimal quorums) reveals structural
+    /// asymmetries, e.g., top tier nodes that are much easier to route around than others.
+    pub fn blocking_set_core(&mut self) -> NodeIdSetResult<'_> {
+        NodeIdSetResult::new(
+            common_nodes(&self.minimal_blocking_sets_shrunken()),
+            Some(&self.unshrink_table),
+        )
+    }
+    /// Nodes contained in every smallest minimal blocking set; a superset of
+    /// `blocking_set_core`.
+    pub fn smallest_blocking_sets_core(&mut self) -> NodeIdSetResult<'_> {
+        let minimal_blocking_sets = self.minimal_blocking_sets_shrunken();
+        let smallest_size = minimal_blocking_sets.iter().map(|s| s.len()).min();
+        let smallest_blocking_sets: Vec<NodeIdSet> = minimal_blocking_sets
+            .into_iter()
+            .filter(|blocking_set| Some(blocking_set.len()) == smallest_size)
+            .collect();
+        NodeIdSetResult::new(
+            common_nodes(&smallest_blocking_sets),
+            Some(&self.unshrink_table),
+        )
+    }
+    /// Nodes contained in every minimal quorum, i.e., nodes without which no quorum can be
+    /// formed. If there are any, they are de-facto central authorities. Cheap once minimal
+    /// quorums are cached.
+    pub fn nodes_in_all_minimal_quorums(&mut self) -> NodeIdSetResult<'_> {
+        NodeIdSetResult::new(
+            common_nodes(&self.minimal_quorums_shrunken()),
+            Some(&self.unshrink_table),
+        )
+    }
     pub fn top_tier(&mut self) -> NodeIdSetResult {
         NodeIdSetResult::new(
             involved_nodes(&self.minimal_quorums_shrunken()),
             Some(&self.unshrink_table),
         )
     }
-    fn find_and_cache_minimal_quorums(&mut self) {
-        warn!("Computing minimal quorums...");
-        let mut minimal_quorums_shrunken = find_minimal_quorums(&self.fbas_shrunken);
+    /// Also returns whether the result is incomplete (and hence not cached).
+    fn find_and_cache_minimal_quorums(&mut self) -> (Vec<NodeIdSet>, bool) {
+        let (mut minimal_quorums_shrunken, truncated) =
+            if let Some(top_tier_qset) = find_symmetric_top_tier(&self.fbas_shrunken) {
+                info!("Deriving minimal quorums from symmetric top tier...");
+                (
+                    remove_non_minimal_node_sets(top_tier_qset.to_quorum_slices()),
+                    false,
+                )
+            } else {
+                warn!("Computing minimal quorums...");
+                find_minimal_quorums_until_stopped(&self.fbas_shrunken)
+            };
+        if truncated {
+            // shrinking to the top tier of an incomplete result could lose nodes
+            let shrink_map = self
+                .unshrink_table
+                .iter()
+                .enumerate()
+                .map(|(shrunken_id, &original_id)| (original_id, shrunken_id))
+                .collect();
+            minimal_quorums_shrunken =
+                self.maybe_merge_minimal_node_sets_shrunken(minimal_quorums_shrunken, &shrink_map);
+            let minimal_quorums_shrunken =
+                self.cache_unless_truncated(minimal_quorums_shrunken, true, |analysis| {
+                    &mut analysis.minimal_quorums_shrunken
+                });
+            return (minimal_quorums_shrunken, true);
+        }
         debug!("Shrinking FBAS again, to top tier (for performance)...",);
         let top_tier_original = unshrink_set(
             &involved_nodes(&minimal_quorums_shrunken),
@@ -184,32 +572,42 @@ impl<'a> Analysis<'a> {
         );
         self.fbas_shrunken = new_fbas_shrunken;
         self.unshrink_table = new_unshrink_table;
-        let shrink_map = new_shrink_map;
 
-        // if an organizations structure has been passed: merge nodes
+        minimal_quorums_shrunken =
+            self.maybe_merge_minimal_node_sets_shrunken(minimal_quorums_shrunken, &new_shrink_map);
+        self.minimal_quorums_shrunken = Some(minimal_quorums_shrunken.clone());
+
+        if log_enabled!(Warn) {
+            if self.has_quorum_intersection() {
+                debug!("FBAS enjoys quorum intersection.");
+            } else {
+                warn!("FBAS doesn't enjoy quorum intersection!");
+            }
+        }
+        (minimal_quorums_shrunken, false)
+    }
+    /// If an organizations structure has been passed: merge nodes.
+    fn maybe_merge_minimal_node_sets_shrunken(
+        &self,
+        node_sets: Vec<NodeIdSet>,
+        shrink_map: &HashMap<NodeId, NodeId>,
+    ) -> Vec<NodeIdSet> {
         if let Some(ref orgs) = self.organizations_original {
             debug!("Collapsing nodes by organization...");
             info!(
                 "{} involved nodes before collapsing by organization.",
-                involved_nodes(&minimal_quorums_shrunken).len()
-            );
-            let orgs_shrunken = Organizations::shrunken(&orgs, &shrink_map, &self.fbas_shrunken);
-            minimal_quorums_shrunken = remove_non_minimal_node_sets(
-                orgs_shrunken.merge_node_sets(minimal_quorums_shrunken),
+                involved_nodes(&node_sets).len()
             );
+            let orgs_shrunken = Organizations::shrunken(&orgs, shrink_map, &self.fbas_shrunken);
+            let merged_node_sets =
+                remove_non_minimal_node_sets(orgs_shrunken.merge_node_sets(node_sets));
             info!(
                 "{} involved nodes after collapsing by organization.",
-                involved_nodes(&minimal_quorums_shrunken).len()
+                involved_nodes(&merged_node_sets).len()
             );
-        }
-        self.minimal_quorums_shrunken = Some(minimal_quorums_shrunken);
-
-        if log_enabled!(Warn) {
-            if self.has_quorum_intersection() {
-                debug!("FBAS enjoys quorum intersection.");
-            } else {
-                warn!("FBAS doesn't enjoy quorum intersection!");
-            }
+            merged_node_sets
+        } else {
+            node_sets
         }
     }
     fn maybe_merge_node_ids(&self, node_set: NodeIdSet) -> NodeIdSet {
@@ -222,6 +620,28 @@ impl<'a> Analysis<'a> {
     pub fn merging_by_organization(&self) -> bool {
         self.organizations_original.is_some()
     }
+    /// Whether some search was stopped early (see `request_stop`), so that some of the results
+    /// returned so far are incomplete. Such results aren't cached, so asking again (after
+    /// `clear_stop_request`) repeats the search.
+    ///
+    /// ```
+    /// use fbas_analyzer::*;
+    /// use std::path::Path;
+    ///
+    /// let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+    /// let mut analysis = Analysis::new(&fbas, None);
+    /// request_stop();
+    /// assert!(analysis.minimal_quorums().unwrap().is_empty());
+    /// assert!(analysis.stopped_early());
+    /// // never answered from incomplete minimal quorums
+    /// assert!(!analysis.has_quorum_intersection());
+    ///
+    /// clear_stop_request();
+    /// assert!(!analysis.minimal_quorums().unwrap().is_empty());
+    /// ```
+    pub fn stopped_early(&self) -> bool {
+        self.stopped_early
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -246,6 +666,11 @@ impl<'a> NodeIdSetResult<'a> {
     pub fn into_vec(self) -> Vec<NodeId> {
         self.unwrap().into_iter().collect()
     }
+    /// Total weight of the contained nodes. Pass the `organizations` used for merging, if any,
+    /// so that each (merged) node weighs as much as all nodes of its organization together.
+    pub fn weight(&self, weights: &NodeWeights, organizations: Option<&Organizations>) -> f64 {
+        weights.total(&physical_nodes(&self.clone().unwrap(), organizations))
+    }
     pub fn involved_nodes(&self) -> NodeIdSet {
         self.node_set.clone()
     }
@@ -291,6 +716,9 @@ impl<'a> NodeIdSetVecResult<'a> {
     pub fn involved_nodes(&self) -> NodeIdSet {
         involved_nodes(&self.node_sets)
     }
+    pub fn common_nodes(&self) -> NodeIdSet {
+        common_nodes(&self.node_sets)
+    }
     pub fn len(&self) -> usize {
         self.node_sets.len()
     }
@@ -318,6 +746,36 @@ impl<'a> NodeIdSetVecResult<'a> {
         };
         (min, max, mean)
     }
+    /// Total weight of each node set, in order (see `NodeIdSetResult::weight`).
+    pub fn weights(
+        &self,
+        weights: &NodeWeights,
+        organizations: Option<&Organizations>,
+    ) -> Vec<f64> {
+        self.clone()
+            .unwrap()
+            .iter()
+            .map(|node_set| weights.total(&physical_nodes(node_set, organizations)))
+            .collect()
+    }
+    /// Returns (min_weight, max_weight, mean_weight). For minimal blocking sets, `min_weight` is
+    /// the smallest total weight that suffices for halting the network; for minimal splitting
+    /// sets, the smallest total weight that suffices for breaking safety.
+    pub fn weighted_minmaxmean(
+        &self,
+        weights: &NodeWeights,
+        organizations: Option<&Organizations>,
+    ) -> (f64, f64, f64) {
+        let set_weights = self.weights(weights, organizations);
+        if set_weights.is_empty() {
+            (0., 0., 0.)
+        } else {
+            let min = set_weights.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = set_weights.iter().copied().fold(0., f64::max);
+            let mean = set_weights.iter().sum::<f64>() / (set_weights.len() as f64);
+            (min, max, mean)
+        }
+    }
     /// Returns [ #members with size 0, #members with size 1, ... , #members with maximum size ]
     pub fn histogram(&self) -> Vec<usize> {
         let max = self.node_sets.iter().map(|s| s.len()).max().unwrap_or(0);
@@ -330,6 +788,49 @@ impl<'a> NodeIdSetVecResult<'a> {
     }
 }
 
+/// Expands merged nodes to all nodes of their respective organizations.
+fn physical_nodes(node_set: &NodeIdSet, organizations: Option<&Organizations>) -> NodeIdSet {
+    if let Some(orgs) = organizations {
+        node_set
+            .iter()
+            .flat_map(|node_id| match orgs.get_by_member(node_id) {
+                Some(org) => org.validators.clone(),
+                None => vec![node_id],
+            })
+            .collect()
+    } else {
+        node_set.clone()
+    }
+}
+
+/// Organizations and countries spanned by a node set. If merging by organization, organizations
+/// are identified by name and each (merged) node stands for all nodes of its organization.
+/// Otherwise, and for nodes that don't belong to any organization, nodes are identified by their
+/// public key. Nodes without known country are ignored when collecting countries.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct NodeSetSpan {
+    pub organizations: BTreeSet<String>,
+    pub countries: BTreeSet<String>,
+}
+impl NodeSetSpan {
+    pub fn new(node_set: &NodeIdSet, fbas: &Fbas, organizations: Option<&Organizations>) -> Self {
+        let mut span = NodeSetSpan::default();
+        for node_id in node_set.iter() {
+            let (name, members) = match organizations.and_then(|orgs| orgs.get_by_member(node_id)) {
+                Some(org) => (org.name.clone(), org.validators.clone()),
+                None => (fbas.nodes[node_id].public_key.clone(), vec![node_id]),
+            };
+            span.organizations.insert(name);
+            span.countries.extend(
+                members
+                    .into_iter()
+                    .filter_map(|member| fbas.nodes[member].metadata.country.clone()),
+            );
+        }
+        span
+    }
+}
+
 pub fn all_intersect(node_sets: &[NodeIdSet]) -> bool {
     // quick check
     let max_size = involved_nodes(node_sets).len();
@@ -352,6 +853,65 @@ pub fn involved_nodes(node_sets: &[NodeIdSet]) -> NodeIdSet {
     all_nodes
 }
 
+/// Nodes contained in all of `node_sets` (none if there are no node sets).
+pub fn common_nodes(node_sets: &[NodeIdSet]) -> NodeIdSet {
+    let mut node_sets_iter = node_sets.iter();
+    let mut common_nodes = node_sets_iter.next().cloned().unwrap_or_default();
+    for node_set in node_sets_iter {
+        common_nodes.intersect_with(node_set);
+    }
+    common_nodes
+}
+
+/// Node sets from `node_sets` that contain all nodes in `node_set`, e.g., all minimal quorums
+/// that a group of nodes is part of.
+///
+/// ```
+/// #[macro_use] extern crate fbas_analyzer;
+/// use fbas_analyzer::{subsets_of, supersets_of};
+///
+/// let node_sets = bitsetvec![[0, 1], [0, 2], [1, 2, 3]];
+///
+/// assert_eq!(bitsetvec![[0, 1], [0, 2]], supersets_of(&node_sets, &bitset![0]));
+/// assert_eq!(bitsetvec![[0, 1]], subsets_of(&node_sets, &bitset![0, 1, 3]));
+/// ```
+pub fn supersets_of(node_sets: &[NodeIdSet], node_set: &NodeIdSet) -> Vec<NodeIdSet> {
+    node_sets
+        .iter()
+        .filter(|other| other.is_superset(node_set))
+        .cloned()
+        .collect()
+}
+
+/// Node sets from `node_sets` that only contain nodes from `node_set`.
+pub fn subsets_of(node_sets: &[NodeIdSet], node_set: &NodeIdSet) -> Vec<NodeIdSet> {
+    node_sets
+        .iter()
+        .filter(|other| other.is_subset(node_set))
+        .cloned()
+        .collect()
+}
+
+/// Node sets from `node_sets` that contain `node_id`.
+pub fn node_sets_containing(node_sets: &[NodeIdSet], node_id: NodeId) -> Vec<NodeIdSet> {
+    node_sets
+        .iter()
+        .filter(|node_set| node_set.contains(node_id))
+        .cloned()
+        .collect()
+}
+
+/// Is any of `node_sets` contained in `node_set`? E.g., does a set of nodes contain a minimal
+/// quorum? For many queries against the same node sets, see `NodeSetTrie`.
+pub fn contains_subset_of(node_sets: &[NodeIdSet], node_set: &NodeIdSet) -> bool {
+    node_sets.iter().any(|other| other.is_subset(node_set))
+}
+
+/// Does any of `node_sets` contain all nodes in `node_set`?
+pub fn contains_superset_of(node_sets: &[NodeIdSet], node_set: &NodeIdSet) -> bool {
+    node_sets.iter().any(|other| other.is_superset(node_set))
+}
+
 /// Reduce to minimal node sets, i.e. to a set of node sets so that no member set is a superset of another.
 pub fn remove_non_minimal_node_sets(mut node_sets: Vec<NodeIdSet>) -> Vec<NodeIdSet> {
     debug!("Removing duplicates...");
@@ -392,6 +952,7 @@ fn remove_non_minimal_node_sets_from_buckets(
     debug!("Filtering non-minimal node sets...");
     let mut minimal_node_sets: Vec<NodeIdSet> = vec![];
     let mut minimal_node_sets_current_len: Vec<NodeIdSet> = vec![];
+    let mut minimal_node_sets_trie = NodeSetTrie::new();
     for (i, bucket) in buckets_by_len.into_iter().enumerate() {
         debug!(
             "...at bucket {}; {} minimal node sets",
@@ -399,10 +960,16 @@ fn remove_non_minimal_node_sets_from_buckets(
             minimal_node_sets.len()
         );
         for node_set in bucket.into_iter() {
-            if minimal_node_sets.iter().all(|x| !x.is_subset(&node_set)) {
+            if !minimal_node_sets_trie.contains_subset_of(&node_set) {
                 minimal_node_sets_current_len.push(node_set);
+            } else {
+                count(|s| s.sets_pruned += 1);
             }
         }
+        // inserted only now so that equally long node sets aren't compared with each other
+        for node_set in minimal_node_sets_current_len.iter() {
+            minimal_node_sets_trie.insert(node_set);
+        }
         minimal_node_sets.append(&mut minimal_node_sets_current_len);
     }
     debug!("Filtering done.");
@@ -442,6 +1009,8 @@ fn remove_node_sets_that_are_non_minimal_by_one(node_sets: HashSet<NodeIdSet>) -
         }
         if is_minimal_by_one {
             remaining_sets.push(node_set.clone());
+        } else {
+            count(|s| s.sets_pruned += 1);
         }
     }
     debug!("Filtering done.");
@@ -464,6 +1033,11 @@ impl<'fbas> Organizations<'fbas> {
             .map(|x| self.merge_node_set(x))
             .collect()
     }
+    /// Merge a list of minimal node ID sets so that all nodes by the same organization get the
+    /// same ID, and reduce the result to minimal sets again.
+    pub fn merge_minimal_node_sets(self: &Self, node_sets: Vec<NodeIdSet>) -> Vec<NodeIdSet> {
+        remove_non_minimal_node_sets(self.merge_node_sets(node_sets))
+    }
     /// Merge a quorum set so that all nodes by the same organization get the same ID and
     /// validator lists consisting of only of one organization are collapsed into one validator.
     pub fn merge_quorum_set(self: &Self, quorum_set: QuorumSet) -> QuorumSet {
@@ -577,6 +1151,97 @@ mod tests {
         assert!(!Analysis::new(&fbas, None).has_quorum_intersection());
     }
 
+    #[test]
+    fn blocking_and_splitting_sets_of_symmetric_top_tier_match_search() {
+        let inner_quorum_sets: Vec<QuorumSet> = (0..3)
+            .map(|i| QuorumSet {
+                threshold: 2,
+                validators: vec![3 * i, 3 * i + 1, 3 * i + 2],
+                inner_quorum_sets: vec![],
+            })
+            .collect();
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![],
+            inner_quorum_sets,
+        };
+        let mut fbas = Fbas::new();
+        for _ in 0..9 {
+            fbas.add_generic_node(quorum_set.clone());
+        }
+        let minimal_quorums = find_minimal_quorums(&fbas);
+        let expected_blocking_sets = find_minimal_blocking_sets(&minimal_quorums);
+        let expected_splitting_sets = find_minimal_splitting_sets(&minimal_quorums);
+
+        let mut analysis = Analysis::new(&fbas, None);
+        assert!(find_symmetric_top_tier(&fbas).is_some());
+        assert_eq!(
+            expected_blocking_sets,
+            analysis.minimal_blocking_sets().unwrap()
+        );
+        assert_eq!(
+            expected_splitting_sets,
+            analysis.minimal_splitting_sets().unwrap()
+        );
+    }
+
+    #[test]
+    fn minimal_quorums_of_symmetric_top_tier_are_derived_without_search() {
+        let top_tier_quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1, 2, 3],
+            inner_quorum_sets: vec![],
+        };
+        let mut fbas = Fbas::new();
+        for _ in 0..4 {
+            fbas.add_generic_node(top_tier_quorum_set.clone());
+        }
+        // lower tier nodes that need the top tier
+        for _ in 0..3 {
+            fbas.add_generic_node(QuorumSet {
+                threshold: 4,
+                validators: vec![4, 5, 6],
+                inner_quorum_sets: vec![top_tier_quorum_set.clone()],
+            });
+        }
+        let expected = find_minimal_quorums(&fbas);
+
+        let mut analysis = Analysis::new(&fbas, None);
+        reset_search_statistics();
+        let actual = analysis.minimal_quorums().unwrap();
+        analysis.minimal_blocking_sets();
+        analysis.minimal_splitting_sets();
+        assert_eq!(expected, actual);
+        assert_eq!(0, search_statistics().branches_explored);
+    }
+
+    #[test]
+    fn quorum_set_isnt_symmetric_top_tier_if_not_all_its_nodes_use_it() {
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1, 2],
+            inner_quorum_sets: vec![],
+        };
+        let mut fbas = Fbas::new();
+        fbas.add_generic_node(quorum_set.clone());
+        fbas.add_generic_node(quorum_set.clone());
+        fbas.add_generic_node(QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1, 2, 3],
+            inner_quorum_sets: vec![],
+        });
+        // used by as many nodes as it contains, but by the wrong ones
+        fbas.add_generic_node(quorum_set);
+        assert_eq!(None, find_symmetric_top_tier(&fbas));
+
+        let expected = vec![bitset![0, 1], bitset![0, 2, 3], bitset![1, 2, 3]];
+        assert_eq!(expected, find_minimal_quorums(&fbas));
+        assert_eq!(
+            expected,
+            Analysis::new(&fbas, None).minimal_quorums().unwrap()
+        );
+    }
+
     #[test]
     fn analysis_nontrivial() {
         let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
@@ -616,6 +1281,39 @@ mod tests {
         assert!(quorums[0].is_disjoint(&quorums[1]));
     }
 
+    #[test]
+    fn cross_checked_intersection_check_on_correct_and_broken() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let mut analysis = Analysis::new(&fbas, None);
+        let (has_quorum_intersection, quorums) = analysis.has_quorum_intersection_cross_checked();
+        assert!(has_quorum_intersection);
+        assert!(quorums.is_none());
+
+        let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+        let mut analysis = Analysis::new(&fbas, None);
+        let (has_quorum_intersection, quorums) = analysis.has_quorum_intersection_cross_checked();
+        assert!(!has_quorum_intersection);
+        assert_eq!(2, quorums.unwrap().len());
+    }
+
+    #[test]
+    fn streaming_check_agrees_with_regular_check() {
+        for path in &["test_data/correct.json", "test_data/broken.json"] {
+            let fbas = Fbas::from_json_file(Path::new(path));
+            let mut analysis = Analysis::new(&fbas, None);
+            let expected = analysis.has_quorum_intersection();
+            let (has_intersection, quorums) =
+                analysis.has_quorum_intersection_via_streaming_check();
+            assert_eq!(expected, has_intersection);
+            if let Some(quorums) = quorums {
+                let quorums: Vec<NodeIdSet> = quorums.unwrap();
+                assert!(fbas.is_quorum(&quorums[0]));
+                assert!(fbas.is_quorum(&quorums[1]));
+                assert!(quorums[0].is_disjoint(&quorums[1]));
+            }
+        }
+    }
+
     #[test]
     fn analysis_with_merging_by_organization_nontrivial() {
         let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
@@ -640,6 +1338,145 @@ mod tests {
         assert_eq!(analysis.minimal_splitting_sets().len(), 1);
     }
 
+    #[test]
+    fn minimal_blocking_sets_of_organizations_nontrivial() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let organizations = Organizations::from_json_str(
+            r#"[
+            {
+                "id": "266107f8966d45eedce41fee2581326d",
+                "name": "Stellar Development Foundation",
+                "validators": [
+                    "GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH",
+                    "GCM6QMP3DLRPTAZW2UZPCPX2LF3SXWXKPMP3GKFZBDSF3QZGV2G5QSTK"
+                ]
+            }]"#,
+            &fbas,
+        );
+        let mut analysis = Analysis::new(&fbas, None);
+
+        let actual = analysis.minimal_blocking_sets_of_organizations(&organizations);
+        assert_eq!(vec![bitset![0]], actual.clone().unwrap());
+        assert_eq!(
+            r#"[["Stellar Development Foundation"]]"#,
+            actual.into_pretty_string(&fbas, &Some(organizations))
+        );
+        // other results stay unmerged
+        assert_eq!(analysis.minimal_blocking_sets().len(), 3);
+    }
+
+    #[test]
+    fn minimal_splitting_sets_of_organizations_nontrivial() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let organizations = Organizations::from_json_str(
+            r#"[
+            {
+                "id": "266107f8966d45eedce41fee2581326d",
+                "name": "Stellar Development Foundation",
+                "validators": [
+                    "GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH",
+                    "GCM6QMP3DLRPTAZW2UZPCPX2LF3SXWXKPMP3GKFZBDSF3QZGV2G5QSTK"
+                ]
+            }]"#,
+            &fbas,
+        );
+        let mut analysis = Analysis::new(&fbas, None);
+
+        let actual = analysis.minimal_splitting_sets_of_organizations(&organizations);
+        assert_eq!(vec![bitset![0], bitset![10]], actual.clone().unwrap());
+        assert_eq!(
+            r#"[["Stellar Development Foundation"],["GABMKJM6I25XI4K7U6XWMULOUQIQ27BCTMLS6BYYSOWKTBUXVRJSXHYQ"]]"#,
+            actual.into_pretty_string(&fbas, &Some(organizations))
+        );
+    }
+
+    #[test]
+    fn minimal_quorum_spans_with_and_without_merging() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] },
+                "geoData": { "countryName": "Germany" }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] },
+                "geoData": { "countryName": "United States" }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            }
+        ]"#,
+        );
+        let organizations = Organizations::from_json_str(
+            r#"[{ "name": "Org", "validators": ["n0", "n1"] }]"#,
+            &fbas,
+        );
+        let span = |organizations: &[&str], countries: &[&str]| NodeSetSpan {
+            organizations: organizations.iter().map(|&s| String::from(s)).collect(),
+            countries: countries.iter().map(|&s| String::from(s)).collect(),
+        };
+
+        let expected = vec![
+            span(&["n0", "n1"], &["Germany", "United States"]),
+            span(&["n0", "n2"], &["Germany"]),
+            span(&["n1", "n2"], &["United States"]),
+        ];
+        let actual = Analysis::new(&fbas, None).minimal_quorum_spans();
+        assert_eq!(expected, actual);
+
+        let expected = vec![span(&["Org"], &["Germany", "United States"])];
+        let actual = Analysis::new(&fbas, Some(&organizations)).minimal_quorum_spans();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn weighted_blocking_sets_with_and_without_merging() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            }
+        ]"#,
+        );
+        let organizations = Organizations::from_json_str(
+            r#"[{ "name": "Org", "validators": ["n0", "n1"] }]"#,
+            &fbas,
+        );
+        let weights = NodeWeights::new(vec![1., 2., 4.]);
+
+        let mut analysis = Analysis::new(&fbas, None);
+        let blocking_sets = analysis.minimal_blocking_sets();
+        assert_eq!(vec![3., 5., 6.], blocking_sets.weights(&weights, None));
+        assert_eq!(
+            (3., 6., 14. / 3.),
+            blocking_sets.weighted_minmaxmean(&weights, None)
+        );
+        assert_eq!(7., analysis.top_tier().weight(&weights, None));
+
+        let mut analysis = Analysis::new(&fbas, Some(&organizations));
+        let blocking_sets = analysis.minimal_blocking_sets();
+        assert_eq!(
+            vec![3.],
+            blocking_sets.weights(&weights, Some(&organizations))
+        );
+        assert_eq!(
+            3.,
+            analysis.top_tier().weight(&weights, Some(&organizations))
+        );
+    }
+
     #[test]
     #[ignore]
     fn top_tier_analysis_big() {
@@ -677,6 +1514,91 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn blocking_set_cores_differ_from_top_tier() {
+        // everybody needs node 0 and one of nodes 1 and 2
+        let mut fbas = Fbas::new_generic_unconfigured(3);
+        for node in fbas.nodes.iter_mut() {
+            node.quorum_set = QuorumSet {
+                threshold: 2,
+                validators: vec![0],
+                inner_quorum_sets: vec![QuorumSet {
+                    threshold: 1,
+                    validators: vec![1, 2],
+                    inner_quorum_sets: vec![],
+                }],
+            };
+        }
+        let mut analysis = Analysis::new(&fbas, None);
+
+        assert_eq!(
+            vec![vec![0], vec![1, 2]],
+            analysis.minimal_blocking_sets().into_vec_vec()
+        );
+        assert_eq!(bitset![0, 1, 2], analysis.top_tier().unwrap());
+        assert_eq!(bitset![], analysis.blocking_set_core().unwrap());
+        assert_eq!(bitset![0], analysis.smallest_blocking_sets_core().unwrap());
+    }
+
+    #[test]
+    fn nodes_in_all_minimal_quorums() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        assert_eq!(
+            bitset![],
+            Analysis::new(&fbas, None)
+                .nodes_in_all_minimal_quorums()
+                .unwrap()
+        );
+
+        // everybody needs nodes 0 and 1 and two of nodes 2, 3 and 4
+        let mut fbas = Fbas::new_generic_unconfigured(5);
+        for node in fbas.nodes.iter_mut() {
+            node.quorum_set = QuorumSet {
+                threshold: 3,
+                validators: vec![0, 1],
+                inner_quorum_sets: vec![QuorumSet {
+                    threshold: 2,
+                    validators: vec![2, 3, 4],
+                    inner_quorum_sets: vec![],
+                }],
+            };
+        }
+        let mut analysis = Analysis::new(&fbas, None);
+        assert_eq!(
+            bitset![0, 1],
+            analysis.nodes_in_all_minimal_quorums().unwrap()
+        );
+        assert_eq!(bitset![0, 1, 2, 3, 4], analysis.top_tier().unwrap());
+    }
+
+    #[test]
+    fn common_nodes_of_node_sets() {
+        assert_eq!(bitset![], common_nodes(&[]));
+        assert_eq!(
+            bitset![1, 3],
+            common_nodes(&[bitset![0, 1, 3], bitset![1, 2, 3], bitset![1, 3, 4]])
+        );
+    }
+
+    #[test]
+    fn node_set_family_queries() {
+        let node_sets = bitsetvec![[0, 1], [0, 2], [1, 2, 3]];
+
+        assert_eq!(bitset![0, 1, 2, 3], involved_nodes(&node_sets));
+        assert_eq!(bitset![], common_nodes(&node_sets));
+        assert_eq!(bitset![0], common_nodes(&node_sets[..2]));
+        assert_eq!(
+            bitsetvec![[0, 2], [1, 2, 3]],
+            node_sets_containing(&node_sets, 2)
+        );
+        assert_eq!(node_sets, supersets_of(&node_sets, &bitset![]));
+        assert!(subsets_of(&node_sets, &bitset![0, 3]).is_empty());
+        assert!(contains_subset_of(&node_sets, &bitset![0, 2, 4]));
+        assert!(!contains_subset_of(&node_sets, &bitset![0, 3, 4]));
+        assert!(contains_superset_of(&node_sets, &bitset![1, 3]));
+        assert!(!contains_superset_of(&node_sets, &bitset![0, 3]));
+    }
+
     #[test]
     fn node_sets_histogram() {
         let node_sets_result = NodeIdSetVecResult::new(
@@ -0,0 +1,151 @@
+use super::*;
+
+/// Everything we know about a single (physical) node: how important it is for the FBAS and how
+/// risky its configuration is. If merging by organization, set counts and top tier membership
+/// refer to the node's organization.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeReport {
+    pub node_id: NodeId,
+    pub public_key: PublicKey,
+    /// Number of nodes that reference this node in their quorum sets
+    pub in_degree: usize,
+    /// Number of nodes that this node references in its quorum set
+    pub out_degree: usize,
+    pub in_top_tier: bool,
+    pub minimal_quorums: usize,
+    pub minimal_blocking_sets: usize,
+    pub minimal_splitting_sets: usize,
+    pub warnings: Vec<String>,
+}
+
+impl<'a> Analysis<'a> {
+    /// Aggregates what we know about the physical node `node_id` (see `NodeReport`). Triggers the
+    /// search for minimal quorums, blocking sets and splitting sets, if not already cached.
+    pub fn node_report(&mut self, node_id: NodeId) -> NodeReport {
+        let fbas = self.fbas_original;
+        assert!(
+            node_id < fbas.number_of_nodes(),
+            "Node ID {} out of range!",
+            node_id
+        );
+        let graph = Graph::from_fbas(fbas);
+        let merged_id = match self.organizations_original {
+            Some(orgs) => orgs.merge_node(node_id),
+            None => node_id,
+        };
+        let count_containing = |node_sets: Vec<NodeIdSet>| {
+            node_sets
+                .iter()
+                .filter(|node_set| node_set.contains(merged_id))
+                .count()
+        };
+        NodeReport {
+            node_id,
+            public_key: fbas.nodes[node_id].public_key.clone(),
+            in_degree: graph.get_in_degrees()[node_id],
+            out_degree: graph.get_out_degrees()[node_id],
+            in_top_tier: self.top_tier().unwrap().contains(merged_id),
+            minimal_quorums: count_containing(self.minimal_quorums().unwrap()),
+            minimal_blocking_sets: count_containing(self.minimal_blocking_sets().unwrap()),
+            minimal_splitting_sets: count_containing(self.minimal_splitting_sets().unwrap()),
+            warnings: self.configuration_warnings(node_id),
+        }
+    }
+    fn configuration_warnings(&self, node_id: NodeId) -> Vec<String> {
+        let fbas = self.fbas_original;
+        let quorum_set = &fbas.nodes[node_id].quorum_set;
+        let number_of_entries = quorum_set.validators.len() + quorum_set.inner_quorum_sets.len();
+        let mut warnings = vec![];
+
+        if quorum_set.threshold == 0 || quorum_set.threshold > number_of_entries {
+            warnings.push(format!(
+                "Quorum set is unsatisfiable (threshold {} for {} entries).",
+                quorum_set.threshold, number_of_entries
+            ));
+        } else if 2 * quorum_set.threshold <= number_of_entries {
+            warnings.push(format!(
+                "Quorum set threshold {} of {} allows non-intersecting quorum slices.",
+                quorum_set.threshold, number_of_entries
+            ));
+        }
+        if quorum_set.contains_duplicates() {
+            warnings.push(String::from("Quorum set contains duplicate entries."));
+        }
+        if !quorum_set.contains_node(node_id) {
+            warnings.push(String::from("Quorum set doesn't contain the node itself."));
+        }
+        // `find_unsatisfiable_nodes` returns (satisfiable, unsatisfiable)
+        if find_unsatisfiable_nodes(&fbas.all_nodes(), fbas)
+            .1
+            .contains(node_id)
+        {
+            warnings.push(String::from("Node can never be part of a quorum."));
+        }
+        if find_liveness_buffers(fbas)[node_id] == Some(1) {
+            warnings.push(String::from(
+                "A single failing node suffices for blocking this node.",
+            ));
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_report_aggregates_results_and_warnings() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n3",
+                "quorumSet": { "threshold": 1, "validators": ["n0", "n1"] }
+            }
+        ]"#,
+        );
+        let mut analysis = Analysis::new(&fbas, None);
+
+        let expected = NodeReport {
+            node_id: 0,
+            public_key: String::from("n0"),
+            in_degree: 3,
+            out_degree: 2,
+            in_top_tier: true,
+            minimal_quorums: 2,
+            minimal_blocking_sets: 2,
+            minimal_splitting_sets: 1,
+            warnings: vec![],
+        };
+        assert_eq!(expected, analysis.node_report(0));
+
+        let expected = NodeReport {
+            node_id: 3,
+            public_key: String::from("n3"),
+            in_degree: 0,
+            out_degree: 2,
+            in_top_tier: false,
+            minimal_quorums: 0,
+            minimal_blocking_sets: 0,
+            minimal_splitting_sets: 0,
+            warnings: vec![
+                String::from("Quorum set threshold 1 of 2 allows non-intersecting quorum slices."),
+                String::from("Quorum set doesn't contain the node itself."),
+            ],
+        };
+        assert_eq!(expected, analysis.node_report(3));
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+
+/// Set-trie for fast subset queries over many node sets: "does any stored node set appear as a
+/// subset of this node set?". Node sets are stored as paths of ascending node IDs, so that a
+/// query only needs to descend into branches whose nodes are contained in the queried node set.
+///
+/// ```
+/// #[macro_use] extern crate fbas_analyzer;
+/// use fbas_analyzer::NodeSetTrie;
+///
+/// let mut trie = NodeSetTrie::new();
+/// trie.insert(&bitset![1, 3]);
+/// trie.insert(&bitset![0, 2, 4]);
+///
+/// assert!(trie.contains_subset_of(&bitset![1, 2, 3]));
+/// assert!(!trie.contains_subset_of(&bitset![0, 1, 2]));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NodeSetTrie {
+    // nodes[0] is the root
+    nodes: Vec<TrieNode>,
+    number_of_node_sets: usize,
+}
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    // sorted by node ID; (node ID, index of child in `NodeSetTrie::nodes`)
+    children: Vec<(NodeId, usize)>,
+    is_end_of_node_set: bool,
+}
+impl NodeSetTrie {
+    pub fn new() -> Self {
+        NodeSetTrie {
+            nodes: vec![TrieNode::default()],
+            number_of_node_sets: 0,
+        }
+    }
+    /// Number of (distinct) stored node sets.
+    pub fn len(&self) -> usize {
+        self.number_of_node_sets
+    }
+    pub fn is_empty(&self) -> bool {
+        self.number_of_node_sets == 0
+    }
+    /// Returns `false` if `node_set` was already stored.
+    pub fn insert(&mut self, node_set: &NodeIdSet) -> bool {
+        let mut current = 0;
+        for node_id in node_set.iter() {
+            current = match self.nodes[current]
+                .children
+                .binary_search_by_key(&node_id, |&(x, _)| x)
+            {
+                Ok(i) => self.nodes[current].children[i].1,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[current].children.insert(i, (node_id, child));
+                    child
+                }
+            };
+        }
+        let is_new = !self.nodes[current].is_end_of_node_set;
+        if is_new {
+            self.nodes[current].is_end_of_node_set = true;
+            self.number_of_node_sets += 1;
+        }
+        is_new
+    }
+    /// Is some stored node set a subset of (or equal to) `node_set`?
+    pub fn contains_subset_of(&self, node_set: &NodeIdSet) -> bool {
+        let mut unprocessed = vec![0];
+        while let Some(current) = unprocessed.pop() {
+            let trie_node = &self.nodes[current];
+            if trie_node.is_end_of_node_set {
+                return true;
+            }
+            unprocessed.extend(
+                trie_node
+                    .children
+                    .iter()
+                    .filter(|&&(node_id, _)| node_set.contains(node_id))
+                    .map(|&(_, child)| child),
+            );
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_trie_contains_no_subsets() {
+        let trie = NodeSetTrie::new();
+        assert!(trie.is_empty());
+        assert!(!trie.contains_subset_of(&bitset![0, 1, 2]));
+        assert!(!trie.contains_subset_of(&bitset![]));
+    }
+
+    #[test]
+    fn trie_finds_subsets_only() {
+        let mut trie = NodeSetTrie::new();
+        assert!(trie.insert(&bitset![0, 1, 5]));
+        assert!(trie.insert(&bitset![0, 2]));
+        assert!(!trie.insert(&bitset![0, 2]));
+        assert_eq!(2, trie.len());
+
+        assert!(trie.contains_subset_of(&bitset![0, 2]));
+        assert!(trie.contains_subset_of(&bitset![0, 1, 2]));
+        assert!(trie.contains_subset_of(&bitset![0, 1, 3, 5]));
+        assert!(!trie.contains_subset_of(&bitset![0, 1, 3]));
+        assert!(!trie.contains_subset_of(&bitset![1, 2, 5]));
+        assert!(!trie.contains_subset_of(&bitset![0]));
+    }
+
+    #[test]
+    fn trie_with_empty_set_contains_subset_of_everything() {
+        let mut trie = NodeSetTrie::new();
+        trie.insert(&bitset![]);
+        assert!(trie.contains_subset_of(&bitset![]));
+        assert!(trie.contains_subset_of(&bitset![3]));
+    }
+}
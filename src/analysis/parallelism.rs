@@ -0,0 +1,50 @@
+use super::*;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Environment variable for limiting the number of threads (see `max_threads`).
+pub const THREADS_ENV_VAR: &str = "FBAS_ANALYZER_THREADS";
+
+// 0 means "not set via `set_max_threads`"
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Global thread budget for everything that runs in parallel (currently batch simulation, see
+/// `experiments::run_batch`), so that the tool can be run politely on shared machines.
+///
+/// Set via `set_max_threads` or, if that wasn't called, via the `FBAS_ANALYZER_THREADS`
+/// environment variable; defaults to the available parallelism.
+///
+/// ```
+/// use fbas_analyzer::{max_threads, set_max_threads};
+///
+/// set_max_threads(2);
+/// assert_eq!(2, max_threads());
+/// ```
+pub fn max_threads() -> usize {
+    match MAX_THREADS.load(Ordering::Relaxed) {
+        0 => threads_from_env().unwrap_or_else(available_parallelism),
+        n => n,
+    }
+}
+pub fn set_max_threads(n: usize) {
+    assert!(n > 0, "Need at least one thread!");
+    MAX_THREADS.store(n, Ordering::Relaxed);
+}
+
+fn threads_from_env() -> Option<usize> {
+    let value = env::var(THREADS_ENV_VAR).ok()?;
+    match value.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Some(n),
+        _ => {
+            warn!(
+                "Ignoring {}={:?}; expected a positive number of threads.",
+                THREADS_ENV_VAR, value
+            );
+            None
+        }
+    }
+}
+fn available_parallelism() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
@@ -0,0 +1,130 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Progress of a running search, as passed to the callback set via `set_progress_callback`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchProgress {
+    /// What is being searched for, e.g., "minimal quorums"
+    pub search: &'static str,
+    /// Estimated fraction of the search space explored so far, between 0 and 1. Branch and bound
+    /// searches count each finished (or pruned) branch at depth `d` as `2^-d` of the space, so the
+    /// estimate is exact in hindsight but can move unevenly.
+    pub explored_fraction: f64,
+}
+
+type ProgressCallback = Rc<dyn Fn(SearchProgress)>;
+
+thread_local! {
+    static PROGRESS_CALLBACK: RefCell<Option<ProgressCallback>> = RefCell::new(None);
+}
+
+/// Have long-running searches (in this thread) periodically report their progress to `callback`,
+/// e.g., for rendering a progress bar. Each search reports 0 when starting and 1 when done.
+pub fn set_progress_callback(callback: impl Fn(SearchProgress) + 'static) {
+    PROGRESS_CALLBACK.with(|c| *c.borrow_mut() = Some(Rc::new(callback)));
+}
+pub fn clear_progress_callback() {
+    PROGRESS_CALLBACK.with(|c| *c.borrow_mut() = None);
+}
+
+/// Sums up explored fractions and only calls the (thread-local) callback every now and then, so
+/// that searches without a callback pay next to nothing.
+pub(crate) struct ProgressTracker {
+    search: &'static str,
+    number_of_candidates: usize,
+    callback: Option<ProgressCallback>,
+    explored_fraction: f64,
+    steps_until_report: usize,
+}
+const STEPS_BETWEEN_REPORTS: usize = 1 << 12;
+
+impl ProgressTracker {
+    /// For branch and bound searches, `number_of_candidates` is the number of nodes to decide
+    /// on, i.e., the maximum search depth.
+    pub(crate) fn new(search: &'static str, number_of_candidates: usize) -> Self {
+        let callback = PROGRESS_CALLBACK.with(|c| c.borrow().clone());
+        let tracker = ProgressTracker {
+            search,
+            number_of_candidates,
+            callback,
+            explored_fraction: 0.,
+            steps_until_report: STEPS_BETWEEN_REPORTS,
+        };
+        tracker.report();
+        tracker
+    }
+    /// A branch with `number_of_undecided` candidates left is done (or pruned).
+    pub(crate) fn finish_branch(&mut self, number_of_undecided: usize) {
+        if self.callback.is_some() {
+            let depth = self.number_of_candidates - number_of_undecided;
+            self.explored_fraction += 0.5f64.powi(depth as i32);
+            self.step();
+        }
+    }
+    /// For searches that go through a known number of equally sized chunks.
+    pub(crate) fn set_explored_fraction(&mut self, explored_fraction: f64) {
+        if self.callback.is_some() {
+            self.explored_fraction = explored_fraction;
+            self.step();
+        }
+    }
+    pub(crate) fn finish(mut self) {
+        self.explored_fraction = 1.;
+        self.report();
+    }
+    fn step(&mut self) {
+        self.steps_until_report -= 1;
+        if self.steps_until_report == 0 {
+            self.steps_until_report = STEPS_BETWEEN_REPORTS;
+            self.report();
+        }
+    }
+    fn report(&self) {
+        if let Some(callback) = self.callback.as_ref() {
+            callback(SearchProgress {
+                search: self.search,
+                explored_fraction: self.explored_fraction.min(1.),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::path::Path;
+
+    #[test]
+    fn progress_goes_from_zero_to_one() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
+        let reports: Rc<RefCell<Vec<SearchProgress>>> = Rc::new(RefCell::new(vec![]));
+        let reports_clone = Rc::clone(&reports);
+        set_progress_callback(move |progress| reports_clone.borrow_mut().push(progress));
+        let minimal_quorums = find_minimal_quorums(&fbas);
+        find_minimal_blocking_sets(&minimal_quorums);
+        clear_progress_callback();
+
+        let reports = reports.borrow();
+        for search in &["minimal quorums", "minimal blocking sets"] {
+            let fractions: Vec<f64> = reports
+                .iter()
+                .filter(|p| p.search == *search)
+                .map(|p| p.explored_fraction)
+                .collect();
+            assert_eq!(Some(&0.), fractions.first());
+            assert!(fractions.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(Some(&1.), fractions.last());
+        }
+    }
+
+    #[test]
+    fn explored_fractions_add_up() {
+        let mut tracker = ProgressTracker::new("test", 3);
+        tracker.callback = Some(Rc::new(|_| {}));
+        tracker.finish_branch(2);
+        tracker.finish_branch(1);
+        tracker.finish_branch(1);
+        assert!((tracker.explored_fraction - 1.).abs() < 1e-9);
+    }
+}
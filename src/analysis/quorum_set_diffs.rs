@@ -0,0 +1,278 @@
+use super::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Structured difference between two quorum sets, possibly from different FBASs (e.g., two
+/// snapshots of the same network), so validators are identified by public key. Inner quorum sets
+/// are matched by how many nodes they have in common; inner quorum sets without a counterpart
+/// are diffed against an empty quorum set (with threshold 0).
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuorumSetDiff {
+    pub old_threshold: usize,
+    pub new_threshold: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_validators: Vec<PublicKey>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_validators: Vec<PublicKey>,
+    /// Diffs of changed inner quorum sets only
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inner_quorum_set_diffs: Vec<QuorumSetDiff>,
+}
+impl QuorumSetDiff {
+    pub fn new(old: &QuorumSet, old_fbas: &Fbas, new: &QuorumSet, new_fbas: &Fbas) -> Self {
+        let old_validators = public_keys(&old.validators, old_fbas);
+        let new_validators = public_keys(&new.validators, new_fbas);
+
+        let old_inners: Vec<BTreeSet<PublicKey>> = old
+            .inner_quorum_sets
+            .iter()
+            .map(|inner| contained_public_keys(inner, old_fbas))
+            .collect();
+        let new_inners: Vec<BTreeSet<PublicKey>> = new
+            .inner_quorum_sets
+            .iter()
+            .map(|inner| contained_public_keys(inner, new_fbas))
+            .collect();
+        // greedily match the most similar inner quorum sets first
+        let mut candidate_pairs: Vec<(usize, usize, usize)> = vec![];
+        for (i, old_inner) in old_inners.iter().enumerate() {
+            for (j, new_inner) in new_inners.iter().enumerate() {
+                let overlap = old_inner.intersection(new_inner).count();
+                if overlap > 0 {
+                    candidate_pairs.push((overlap, i, j));
+                }
+            }
+        }
+        candidate_pairs.sort_by(|a, b| b.0.cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+        let mut old_to_new: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut matched_new: BTreeSet<usize> = BTreeSet::new();
+        for (_, i, j) in candidate_pairs.into_iter() {
+            if !old_to_new.contains_key(&i) && !matched_new.contains(&j) {
+                old_to_new.insert(i, j);
+                matched_new.insert(j);
+            }
+        }
+        let empty = QuorumSet::new();
+        let mut inner_quorum_set_diffs = vec![];
+        for (i, old_inner) in old.inner_quorum_sets.iter().enumerate() {
+            let new_inner = old_to_new
+                .get(&i)
+                .map(|&j| &new.inner_quorum_sets[j])
+                .unwrap_or(&empty);
+            inner_quorum_set_diffs.push(Self::new(old_inner, old_fbas, new_inner, new_fbas));
+        }
+        for (j, new_inner) in new.inner_quorum_sets.iter().enumerate() {
+            if !matched_new.contains(&j) {
+                inner_quorum_set_diffs.push(Self::new(&empty, old_fbas, new_inner, new_fbas));
+            }
+        }
+        inner_quorum_set_diffs.retain(|diff| !diff.is_empty());
+
+        QuorumSetDiff {
+            old_threshold: old.threshold,
+            new_threshold: new.threshold,
+            added_validators: new_validators
+                .difference(&old_validators)
+                .cloned()
+                .collect(),
+            removed_validators: old_validators
+                .difference(&new_validators)
+                .cloned()
+                .collect(),
+            inner_quorum_set_diffs,
+        }
+    }
+    /// Are the two quorum sets equivalent (up to the order of their entries)?
+    pub fn is_empty(&self) -> bool {
+        self.old_threshold == self.new_threshold
+            && self.added_validators.is_empty()
+            && self.removed_validators.is_empty()
+            && self.inner_quorum_set_diffs.is_empty()
+    }
+}
+
+impl Fbas {
+    /// Difference between the quorum sets of nodes `node_id` and `other_node_id`.
+    pub fn quorum_set_diff(&self, node_id: NodeId, other_node_id: NodeId) -> QuorumSetDiff {
+        QuorumSetDiff::new(
+            &self.nodes[node_id].quorum_set,
+            self,
+            &self.nodes[other_node_id].quorum_set,
+            self,
+        )
+    }
+}
+
+/// Quorum set changes between two snapshots of an FBAS, for all nodes that are part of both and
+/// whose quorum sets changed, by public key.
+///
+/// ```
+/// use fbas_analyzer::{Fbas, diff_quorum_sets};
+///
+/// let old_fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+/// let new_fbas = Fbas::from_json_file(std::path::Path::new("test_data/broken_trivial.json"));
+///
+/// let diffs = diff_quorum_sets(&old_fbas, &new_fbas);
+/// assert_eq!(1, diffs.len());
+/// let diff = &diffs["GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH"];
+/// assert_eq!((2, 1), (diff.old_threshold, diff.new_threshold));
+/// ```
+pub fn diff_quorum_sets(old_fbas: &Fbas, new_fbas: &Fbas) -> BTreeMap<PublicKey, QuorumSetDiff> {
+    old_fbas
+        .nodes
+        .iter()
+        .filter_map(|old_node| {
+            let new_node_id = new_fbas.get_node_id(&old_node.public_key)?;
+            let diff = QuorumSetDiff::new(
+                &old_node.quorum_set,
+                old_fbas,
+                &new_fbas.nodes[new_node_id].quorum_set,
+                new_fbas,
+            );
+            if diff.is_empty() {
+                None
+            } else {
+                Some((old_node.public_key.clone(), diff))
+            }
+        })
+        .collect()
+}
+
+fn public_keys(node_ids: &[NodeId], fbas: &Fbas) -> BTreeSet<PublicKey> {
+    node_ids
+        .iter()
+        .map(|&node_id| fbas.nodes[node_id].public_key.clone())
+        .collect()
+}
+
+fn contained_public_keys(quorum_set: &QuorumSet, fbas: &Fbas) -> BTreeSet<PublicKey> {
+    quorum_set
+        .contained_nodes()
+        .iter()
+        .map(|node_id| fbas.nodes[node_id].public_key.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_fbas() -> Fbas {
+        let mut fbas = Fbas::new_generic_unconfigured(7);
+        fbas.nodes[0].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![1, 2, 3],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![4, 5],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![1],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![4, 5],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![1, 2, 6],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![6],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        fbas
+    }
+
+    fn pks(node_ids: &[NodeId]) -> Vec<PublicKey> {
+        node_ids.iter().map(|id| format!("n{}", id)).collect()
+    }
+
+    #[test]
+    fn diff_of_nested_quorum_sets() {
+        let fbas = test_fbas();
+        let expected = QuorumSetDiff {
+            old_threshold: 2,
+            new_threshold: 3,
+            added_validators: pks(&[1]),
+            removed_validators: pks(&[0]),
+            inner_quorum_set_diffs: vec![
+                QuorumSetDiff {
+                    old_threshold: 2,
+                    new_threshold: 2,
+                    added_validators: pks(&[6]),
+                    removed_validators: pks(&[3]),
+                    inner_quorum_set_diffs: vec![],
+                },
+                QuorumSetDiff {
+                    old_threshold: 1,
+                    new_threshold: 2,
+                    ..Default::default()
+                },
+                QuorumSetDiff {
+                    old_threshold: 0,
+                    new_threshold: 1,
+                    added_validators: pks(&[6]),
+                    ..Default::default()
+                },
+            ],
+        };
+        assert_eq!(expected, fbas.quorum_set_diff(0, 1));
+    }
+
+    #[test]
+    fn diff_of_equivalent_quorum_sets_is_empty() {
+        let fbas = test_fbas();
+        assert!(fbas.quorum_set_diff(0, 0).is_empty());
+
+        // same quorum set, with inner quorum sets in a different order
+        let mut other_fbas = fbas.clone();
+        other_fbas.nodes[0].quorum_set.inner_quorum_sets.reverse();
+        assert!(diff_quorum_sets(&fbas, &other_fbas).is_empty());
+    }
+
+    #[test]
+    fn snapshot_diff_matches_nodes_by_public_key() {
+        let old_fbas = test_fbas();
+        let mut new_fbas = Fbas::new();
+        // same nodes in reverse order, so that all node IDs differ
+        for node_id in (0..old_fbas.number_of_nodes()).rev() {
+            let mut node = Node::new(format!("n{}", node_id));
+            node.quorum_set = old_fbas.nodes[node_id].quorum_set.clone();
+            new_fbas.add_node(node);
+        }
+        let mapping: HashMap<NodeId, NodeId> = (0..7).map(|id| (id, 6 - id)).collect();
+        for node in new_fbas.nodes.iter_mut() {
+            *node = Node::shrunken(node, &mapping);
+        }
+        assert!(diff_quorum_sets(&old_fbas, &new_fbas).is_empty());
+
+        let new_node_id = new_fbas.get_node_id("n0").unwrap();
+        new_fbas.nodes[new_node_id].quorum_set.threshold = 1;
+        let diffs = diff_quorum_sets(&old_fbas, &new_fbas);
+        assert_eq!(
+            vec![String::from("n0")],
+            diffs.keys().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            (2, 1),
+            (diffs["n0"].old_threshold, diffs["n0"].new_threshold)
+        );
+    }
+}
@@ -1,21 +1,96 @@
 use super::*;
+use std::cell::Cell;
 use std::collections::BTreeMap;
 
 extern crate pathfinding;
 use pathfinding::directed::strongly_connected_components::strongly_connected_components;
 
 /// Find all minimal quorums in the FBAS...
+///
+/// Panics if the search is stopped early (see `request_stop`), as the result would be incomplete;
+/// use `find_minimal_quorums_up_to_count` for searches that may be stopped.
 pub fn find_minimal_quorums(fbas: &Fbas) -> Vec<NodeIdSet> {
+    let (minimal_quorums, truncated) = find_minimal_quorums_until_stopped(fbas);
+    assert_search_complete(truncated, "minimal quorums");
+    minimal_quorums
+}
+
+/// Like `find_minimal_quorums`, but returns what was found so far if the search is stopped
+/// early, plus whether that happened.
+pub(crate) fn find_minimal_quorums_until_stopped(fbas: &Fbas) -> (Vec<NodeIdSet>, bool) {
     info!("Starting to look for minimal quorums...");
-    let quorums = find_quorums(fbas, minimal_quorums_finder);
+    let truncated = Cell::new(false);
+    let quorums = find_quorums(fbas, |consensus_clusters, fbas| {
+        let (quorums, complete) =
+            minimal_quorums_finder_with_limits(consensus_clusters, fbas, None, None);
+        truncated.set(!complete);
+        quorums
+    });
     info!("Found {} (not necessarily minimal) quorums.", quorums.len());
     let minimal_quorums = remove_non_minimal_quorums(quorums, fbas);
     info!("Reduced to {} minimal quorums.", minimal_quorums.len());
-    minimal_quorums
+    if truncated.get() {
+        warn!("Search was stopped early; there might be more minimal quorums.");
+    }
+    (minimal_quorums, truncated.get())
+}
+
+/// Find all minimal quorums with at most `max_size` nodes. Much faster than
+/// `find_minimal_quorums` if there are many big minimal quorums, as branches of the search that
+/// would exceed `max_size` are cut off. Also returns whether the result was truncated, i.e.,
+/// whether there are (or, conservatively, might be) minimal quorums bigger than `max_size`.
+pub fn find_minimal_quorums_up_to_size(fbas: &Fbas, max_size: usize) -> (Vec<NodeIdSet>, bool) {
+    info!(
+        "Starting to look for minimal quorums with at most {} nodes...",
+        max_size
+    );
+    let truncated = Cell::new(false);
+    let quorums = find_quorums(fbas, |consensus_clusters, fbas| {
+        let (quorums, complete) =
+            minimal_quorums_finder_with_limits(consensus_clusters, fbas, Some(max_size), None);
+        truncated.set(!complete);
+        quorums
+    });
+    info!("Found {} (not necessarily minimal) quorums.", quorums.len());
+    let minimal_quorums = remove_non_minimal_quorums(quorums, fbas);
+    info!("Reduced to {} minimal quorums.", minimal_quorums.len());
+    if truncated.get() {
+        warn!(
+            "Search was truncated; there might be minimal quorums with more than {} nodes.",
+            max_size
+        );
+    }
+    (minimal_quorums, truncated.get())
+}
+
+/// Find (up to) `max_count` minimal quorums, stopping the search as soon as more are found.
+/// Also returns whether the result was truncated, i.e., whether there are (or, conservatively,
+/// might be) more minimal quorums.
+pub fn find_minimal_quorums_up_to_count(fbas: &Fbas, max_count: usize) -> (Vec<NodeIdSet>, bool) {
+    info!(
+        "Starting to look for up to {} minimal quorums...",
+        max_count
+    );
+    let truncated = Cell::new(false);
+    let quorums = find_quorums(fbas, |consensus_clusters, fbas| {
+        let (quorums, complete) =
+            minimal_quorums_finder_with_limits(consensus_clusters, fbas, None, Some(max_count));
+        truncated.set(!complete);
+        quorums
+    });
+    info!("Found {} (not necessarily minimal) quorums.", quorums.len());
+    let mut minimal_quorums = remove_non_minimal_quorums(quorums, fbas);
+    info!("Reduced to {} minimal quorums.", minimal_quorums.len());
+    if truncated.get() {
+        warn!("Search was truncated after {} minimal quorums.", max_count);
+        minimal_quorums.truncate(max_count);
+    }
+    (minimal_quorums, truncated.get())
 }
 
 /// Find at least two non-intersecting quorums. Use this function if it is very likely that
-/// the FBAS lacks quorum intersection and you want to stop early in such cases.
+/// the FBAS lacks quorum intersection and you want to stop early in such cases. Not affected by
+/// `request_stop`, as a partial check has no meaningful result.
 pub fn find_nonintersecting_quorums(fbas: &Fbas) -> Option<Vec<NodeIdSet>> {
     info!("Starting to look for potentially non-intersecting quorums...");
     let quorums = find_quorums(fbas, nonintersecting_quorums_finder);
@@ -31,6 +106,26 @@ pub fn find_nonintersecting_quorums(fbas: &Fbas) -> Option<Vec<NodeIdSet>> {
     }
 }
 
+/// Like `find_nonintersecting_quorums`, but checks each quorum for intersection with all other
+/// quorums as soon as it is found during minimal quorum enumeration, instead of collecting
+/// quorums first. Memory use stays bounded even if the number of minimal quorums is huge, and
+/// the search stops at the first quorum that has a non-intersecting counterpart. Like
+/// `find_nonintersecting_quorums`, not affected by `request_stop`.
+pub fn find_nonintersecting_quorums_streaming(fbas: &Fbas) -> Option<Vec<NodeIdSet>> {
+    info!("Starting to check quorums for intersection while enumerating them...");
+    let quorums = find_quorums(fbas, streaming_nonintersecting_quorums_finder);
+    if quorums.len() < 2 {
+        info!("Found no non-intersecting quorums.");
+        None
+    } else {
+        warn!(
+            "Found {} non-intersecting quorums (there could more).",
+            quorums.len()
+        );
+        Some(quorums)
+    }
+}
+
 /// Finds groups of nodes (represented as quorum sets) such that all members of the same group have
 /// the exact identical quorum set, and the nodes contained in this quorum set are exactly the
 /// group of nodes (a symmetric cluster). Once no more such clusters are found, returns the maximum
@@ -43,6 +138,95 @@ pub fn find_symmetric_clusters(fbas: &Fbas) -> Vec<QuorumSet> {
     quorums
 }
 
+/// If the FBAS has exactly one consensus cluster and that cluster contains a symmetric cluster
+/// (see `find_symmetric_clusters`), returns the symmetric cluster's quorum set. As the symmetric
+/// cluster's nodes only trust each other, they then form the only consensus cluster, and the
+/// minimal quorums of the FBAS are exactly the minimal quorum slices of this quorum set. So they
+/// (and further analyses) can be derived from its thresholds instead of via search. Doesn't
+/// enumerate any quorums itself.
+pub fn find_symmetric_top_tier(fbas: &Fbas) -> Option<QuorumSet> {
+    let mut top_tiers = find_quorums(fbas, symmetric_top_tier_finder);
+    if top_tiers.len() == 1 {
+        top_tiers.pop()
+    } else {
+        None
+    }
+}
+
+/// Finds a smallest quorum that contains all nodes in `seed_set`, or `None` if there is no such
+/// quorum. Answers questions like "if these validators collude, what is the smallest quorum they
+/// can drive?". Only nodes that are (transitively) trusted by seed nodes need to be considered,
+/// as all other nodes can be removed from any quorum containing `seed_set`.
+pub fn quorum_closure(fbas: &Fbas, seed_set: &NodeIdSet) -> Option<NodeIdSet> {
+    info!(
+        "Looking for a smallest quorum containing {} seed nodes...",
+        seed_set.len()
+    );
+    let (satisfiable, _) = find_unsatisfiable_nodes(&find_trusted_nodes(seed_set, fbas), fbas);
+    if satisfiable.is_empty() || !seed_set.is_subset(&satisfiable) {
+        info!("Some seed nodes aren't part of any quorum.");
+        return None;
+    }
+    debug!("Sorting nodes by rank...");
+    let mut candidates = satisfiable.clone();
+    candidates.difference_with(seed_set);
+    let sorted_candidates = sort_by_rank(candidates.into_iter().collect(), fbas);
+    debug!("Sorted.");
+
+    let mut selection = seed_set.clone();
+    let mut available = satisfiable.clone();
+    let mut smallest_quorum = satisfiable; // maximal quorum
+    quorum_closure_step(
+        &mut sorted_candidates.into(),
+        &mut selection,
+        &mut available,
+        &mut smallest_quorum,
+        &FlatFbas::new(fbas),
+    );
+    info!("Found quorum of size {}.", smallest_quorum.len());
+    Some(smallest_quorum)
+}
+fn quorum_closure_step(
+    unprocessed: &mut NodeIdDeque,
+    selection: &mut NodeIdSet,
+    available: &mut NodeIdSet,
+    smallest_quorum: &mut NodeIdSet,
+    fbas: &FlatFbas,
+) {
+    if selection.len() >= smallest_quorum.len() {
+        // can't get any smaller on this branch
+    } else if fbas.is_quorum(selection) {
+        smallest_quorum.clone_from(selection);
+    } else if let Some(current_candidate) = unprocessed.pop_front() {
+        selection.insert(current_candidate);
+
+        quorum_closure_step(unprocessed, selection, available, smallest_quorum, fbas);
+
+        selection.remove(current_candidate);
+        available.remove(current_candidate);
+
+        if quorums_possible(selection, available, fbas) {
+            quorum_closure_step(unprocessed, selection, available, smallest_quorum, fbas);
+        }
+        unprocessed.push_front(current_candidate);
+        available.insert(current_candidate);
+    }
+}
+
+/// Nodes in `node_set` and all nodes they (transitively) include in their quorum sets.
+fn find_trusted_nodes(node_set: &NodeIdSet, fbas: &Fbas) -> NodeIdSet {
+    let mut trusted_nodes = node_set.clone();
+    let mut unprocessed: Vec<NodeId> = node_set.iter().collect();
+    while let Some(node_id) = unprocessed.pop() {
+        for trusted_node in fbas.nodes[node_id].quorum_set.contained_nodes().into_iter() {
+            if trusted_nodes.insert(trusted_node) {
+                unprocessed.push(trusted_node);
+            }
+        }
+    }
+    trusted_nodes
+}
+
 /// Does preprocessing common to all finders
 fn find_quorums<F, R>(fbas: &Fbas, finder: F) -> Vec<R>
 where
@@ -83,9 +267,26 @@ where
     finder(consensus_clusters, fbas)
 }
 
-fn minimal_quorums_finder(consensus_clusters: Vec<NodeIdSet>, fbas: &Fbas) -> Vec<NodeIdSet> {
+/// Also returns whether the search was complete, i.e., not cut short by `max_size`, by finding
+/// more than `max_count` quorums, by exceeding the memory budget or by `request_stop`.
+fn minimal_quorums_finder_with_limits(
+    consensus_clusters: Vec<NodeIdSet>,
+    fbas: &Fbas,
+    max_size: Option<usize>,
+    max_count: Option<usize>,
+) -> (Vec<NodeIdSet>, bool) {
+    let max_count = max_count.unwrap_or(usize::MAX);
+    let mut complete = true;
+    let mut within_memory_budget = true;
     let mut found_quorums_in_all_clusters = vec![];
     for (i, nodes) in consensus_clusters.into_iter().enumerate() {
+        if found_quorums_in_all_clusters.len() > max_count
+            || !within_memory_budget
+            || stop_requested()
+        {
+            complete = false;
+            break;
+        }
         debug!("Finding minimal quorums in cluster {}...", i);
         let mut found_quorums: Vec<NodeIdSet> = vec![];
 
@@ -99,64 +300,158 @@ fn minimal_quorums_finder(consensus_clusters: Vec<NodeIdSet>, fbas: &Fbas) -> Ve
                 remaining_nodes.difference_with(&quorum_cluster.contained_nodes());
                 assert!(!contains_quorum(&remaining_nodes, fbas));
             }
-            found_quorums.extend_from_slice(&quorum_cluster.to_quorum_slices());
+            for quorum in quorum_cluster.to_quorum_slices().into_iter() {
+                if quorum.len() <= max_size.unwrap_or(usize::MAX) {
+                    found_quorums.push(quorum);
+                } else {
+                    complete = false;
+                }
+            }
         } else {
-            debug!("Sorting nodes by rank...");
-            let sorted_nodes = sort_by_rank(nodes.into_iter().collect(), fbas);
-            debug!("Sorted.");
-
-            let unprocessed = sorted_nodes;
-            let mut selection = NodeIdSet::with_capacity(fbas.nodes.len());
-            let mut available = unprocessed.iter().cloned().collect();
-
             debug!("Collecting quorums...");
-            minimal_quorums_finder_step(
-                &mut unprocessed.into(),
-                &mut selection,
-                &mut available,
-                &mut found_quorums,
-                fbas,
-                true,
-            );
+            let number_of_previously_found_quorums = found_quorums_in_all_clusters.len();
+            complete &= search_quorums(nodes, fbas, max_size, true, |quorum| {
+                found_quorums.push(quorum.clone());
+                let number_of_found_quorums =
+                    number_of_previously_found_quorums + found_quorums.len();
+                within_memory_budget = check_memory_budget(number_of_found_quorums, quorum);
+                number_of_found_quorums <= max_count && within_memory_budget
+            });
         }
         found_quorums_in_all_clusters.append(&mut found_quorums);
     }
-    found_quorums_in_all_clusters
+    if found_quorums_in_all_clusters.len() > max_count {
+        complete = false;
+    }
+    (found_quorums_in_all_clusters, complete)
 }
-fn minimal_quorums_finder_step(
-    unprocessed: &mut NodeIdDeque,
-    selection: &mut NodeIdSet,
-    available: &mut NodeIdSet,
-    found_quorums: &mut Vec<NodeIdSet>,
+/// Enumerates (a superset of) all minimal quorums in `nodes` with at most `max_size` nodes,
+/// passing each one to `on_quorum` as soon as it is found. Stops early if `on_quorum` returns
+/// `false` (or, if `interruptible`, on `request_stop`). Returns `false` if the search was stopped
+/// early or cut short by `max_size`.
+fn search_quorums<F>(
+    nodes: NodeIdSet,
     fbas: &Fbas,
-    selection_changed: bool,
-) {
-    if selection_changed && fbas.is_quorum(selection) {
-        found_quorums.push(selection.clone());
-        if found_quorums.len() % 100_000 == 0 {
-            debug!("...{} quorums found", found_quorums.len());
-        }
-    } else if let Some(current_candidate) = unprocessed.pop_front() {
-        selection.insert(current_candidate);
-
-        minimal_quorums_finder_step(unprocessed, selection, available, found_quorums, fbas, true);
-
-        selection.remove(current_candidate);
-        available.remove(current_candidate);
-
-        if quorums_possible(selection, available, fbas) {
-            minimal_quorums_finder_step(
-                unprocessed,
-                selection,
-                available,
-                found_quorums,
-                fbas,
-                false,
-            );
+    max_size: Option<usize>,
+    interruptible: bool,
+    on_quorum: F,
+) -> bool
+where
+    F: FnMut(&NodeIdSet) -> bool,
+{
+    debug!("Sorting nodes by rank...");
+    let sorted_nodes = sort_by_rank(nodes.into_iter().collect(), fbas);
+    debug!("Sorted.");
+
+    let unprocessed = sorted_nodes;
+    let flat_fbas = FlatFbas::new(fbas);
+    let mut selection =
+        SatisfactionTracker::new(NodeIdSet::with_capacity(fbas.nodes.len()), &flat_fbas);
+    let mut available = SatisfactionTracker::new(unprocessed.iter().cloned().collect(), &flat_fbas);
+
+    minimal_quorums_finder_step(
+        &mut unprocessed.into(),
+        &mut selection,
+        &mut available,
+        max_size,
+        interruptible,
+        on_quorum,
+    )
+}
+/// `selection` is always a subset of `available`; both keep track of their unsatisfied members,
+/// so that only nodes affected by the last change need to be re-evaluated.
+///
+/// Branch and bound over `unprocessed`, using an explicit stack instead of recursion so that
+/// search depth (up to one level per node) isn't limited by the size of the call stack.
+fn minimal_quorums_finder_step<F>(
+    unprocessed: &mut NodeIdDeque,
+    selection: &mut SatisfactionTracker,
+    available: &mut SatisfactionTracker,
+    max_size: Option<usize>,
+    interruptible: bool,
+    mut on_quorum: F,
+) -> bool
+where
+    F: FnMut(&NodeIdSet) -> bool,
+{
+    let mut complete = true;
+    let mut number_of_found_quorums: usize = 0;
+    let mut progress = ProgressTracker::new("minimal quorums", unprocessed.len());
+    let mut stack = vec![SearchFrame::Enter {
+        selection_changed: true,
+    }];
+    while let Some(frame) = stack.pop() {
+        match frame {
+            SearchFrame::Enter { selection_changed } => {
+                count(|s| {
+                    s.branches_explored += 1;
+                    s.quorum_checks += selection_changed as usize;
+                });
+                if interruptible && stop_requested() {
+                    debug!("Stopping search as requested.");
+                    return false;
+                }
+                if selection_changed && selection.is_quorum() {
+                    number_of_found_quorums += 1;
+                    count_result_set_size(number_of_found_quorums);
+                    if number_of_found_quorums % 100_000 == 0 {
+                        debug!(
+                            "...{} quorums found (search depth: {})",
+                            number_of_found_quorums,
+                            stack.len()
+                        );
+                    }
+                    if !on_quorum(selection.nodes()) {
+                        debug!("Stopping search early.");
+                        return false;
+                    }
+                    progress.finish_branch(unprocessed.len());
+                } else if selection.nodes().len() >= max_size.unwrap_or(usize::MAX) {
+                    // can't grow any further on this branch
+                    complete &= unprocessed.is_empty();
+                    progress.finish_branch(unprocessed.len());
+                } else if let Some(current_candidate) = unprocessed.pop_front() {
+                    selection.insert(current_candidate);
+                    stack.push(SearchFrame::AfterInclusion(current_candidate));
+                    stack.push(SearchFrame::Enter {
+                        selection_changed: true,
+                    });
+                } else {
+                    progress.finish_branch(unprocessed.len());
+                }
+            }
+            SearchFrame::AfterInclusion(current_candidate) => {
+                selection.remove(current_candidate);
+                available.remove(current_candidate);
+                stack.push(SearchFrame::AfterExclusion(current_candidate));
+
+                // quorums possible, i.e., all selected nodes have a quorum slice in `available`
+                if selection.nodes().is_disjoint(available.unsatisfied()) {
+                    stack.push(SearchFrame::Enter {
+                        selection_changed: false,
+                    });
+                } else {
+                    count(|s| s.branches_pruned += 1);
+                    progress.finish_branch(unprocessed.len());
+                }
+            }
+            SearchFrame::AfterExclusion(current_candidate) => {
+                unprocessed.push_front(current_candidate);
+                available.insert(current_candidate);
+            }
         }
-        unprocessed.push_front(current_candidate);
-        available.insert(current_candidate);
     }
+    progress.finish();
+    complete
+}
+/// What remains to be done for a node on the search stack.
+enum SearchFrame {
+    /// Check the current selection and, if necessary, branch on the next unprocessed node.
+    Enter { selection_changed: bool },
+    /// The branch including the candidate is done; next, explore the branch excluding it.
+    AfterInclusion(NodeId),
+    /// Both branches are done; restore the candidate as unprocessed.
+    AfterExclusion(NodeId),
 }
 
 fn nonintersecting_quorums_finder(
@@ -180,13 +475,18 @@ fn nonintersecting_quorums_finder(
         let mut selection = NodeIdSet::with_capacity(fbas.nodes.len());
         let mut available: NodeIdSet = unprocessed.iter().cloned().collect();
         let mut antiselection = available.clone();
-        if let Some(intersecting_quorums) = nonintersecting_quorums_finder_step(
+        let mut progress = ProgressTracker::new("non-intersecting quorums", unprocessed.len());
+        let result = nonintersecting_quorums_finder_step(
             &mut unprocessed.into(),
             &mut selection,
             &mut available,
             &mut antiselection,
             fbas,
-        ) {
+            &FlatFbas::new(fbas),
+            &mut progress,
+        );
+        progress.finish();
+        if let Some(intersecting_quorums) = result {
             assert!(intersecting_quorums.iter().all(|x| fbas.is_quorum(x)));
             assert!(intersecting_quorums[0].is_disjoint(&intersecting_quorums[1]));
             intersecting_quorums.to_vec()
@@ -202,14 +502,21 @@ fn nonintersecting_quorums_finder_step(
     available: &mut NodeIdSet,
     antiselection: &mut NodeIdSet,
     fbas: &Fbas,
+    flat_fbas: &FlatFbas,
+    progress: &mut ProgressTracker,
 ) -> Option<[NodeIdSet; 2]> {
     debug_assert!(selection.is_disjoint(&antiselection));
-    if fbas.is_quorum(selection) {
+    count(|s| {
+        s.branches_explored += 1;
+        s.quorum_checks += 1;
+    });
+    if flat_fbas.is_quorum(selection) {
         let (potential_complement, _) = find_unsatisfiable_nodes(&antiselection, fbas);
 
         if !potential_complement.is_empty() {
             return Some([selection.clone(), potential_complement]);
         }
+        progress.finish_branch(unprocessed.len());
     } else if let Some(current_candidate) = unprocessed.pop_front() {
         selection.insert(current_candidate);
         antiselection.remove(current_candidate);
@@ -219,6 +526,8 @@ fn nonintersecting_quorums_finder_step(
             available,
             antiselection,
             fbas,
+            flat_fbas,
+            progress,
         ) {
             return Some(intersecting_quorums);
         }
@@ -226,23 +535,68 @@ fn nonintersecting_quorums_finder_step(
         antiselection.insert(current_candidate);
         available.remove(current_candidate);
 
-        if quorums_possible(selection, available, fbas) {
-            if let Some(intersecting_quorums) = nonintersecting_quorums_finder_step(
-                unprocessed,
-                selection,
-                available,
-                antiselection,
-                fbas,
-            ) {
-                return Some(intersecting_quorums);
-            }
+        if !quorums_possible(selection, available, flat_fbas) {
+            count(|s| s.branches_pruned += 1);
+            progress.finish_branch(unprocessed.len());
+        } else if let Some(intersecting_quorums) = nonintersecting_quorums_finder_step(
+            unprocessed,
+            selection,
+            available,
+            antiselection,
+            fbas,
+            flat_fbas,
+            progress,
+        ) {
+            return Some(intersecting_quorums);
         }
         unprocessed.push_front(current_candidate);
         available.insert(current_candidate);
+    } else {
+        progress.finish_branch(0);
     }
     None
 }
 
+fn streaming_nonintersecting_quorums_finder(
+    consensus_clusters: Vec<NodeIdSet>,
+    fbas: &Fbas,
+) -> Vec<NodeIdSet> {
+    if consensus_clusters.len() > 1 {
+        debug!("More than one consensus clusters - reducing to maximal quorums.");
+        consensus_clusters
+            .into_iter()
+            .map(|node_set| find_unsatisfiable_nodes(&node_set, fbas).0)
+            .collect()
+    } else {
+        let nodes = consensus_clusters.into_iter().next().unwrap_or_default();
+        let mut nonintersecting_quorums = vec![];
+        let mut number_of_checked_quorums: usize = 0;
+        // a partial check would wrongly report quorum intersection
+        search_quorums(nodes.clone(), fbas, None, false, |quorum| {
+            number_of_checked_quorums += 1;
+            // `quorum` intersects all quorums iff its complement contains no quorum
+            let complement = combinatorics::complement(quorum, &nodes);
+            let (complement_quorum, _) = find_unsatisfiable_nodes(&complement, fbas);
+            if complement_quorum.is_empty() {
+                true
+            } else {
+                nonintersecting_quorums = vec![quorum.clone(), complement_quorum];
+                false
+            }
+        });
+        debug!(
+            "Checked {} quorums against their complements.",
+            number_of_checked_quorums
+        );
+        if nonintersecting_quorums.is_empty() {
+            // nothing to return but some quorum, for `find_quorums` to count
+            vec![find_unsatisfiable_nodes(&nodes, fbas).0]
+        } else {
+            nonintersecting_quorums
+        }
+    }
+}
+
 fn symmetric_clusters_finder(consensus_clusters: Vec<NodeIdSet>, fbas: &Fbas) -> Vec<QuorumSet> {
     let mut found_clusters_in_all_clusters = vec![];
     for (i, nodes) in consensus_clusters.into_iter().enumerate() {
@@ -252,6 +606,13 @@ fn symmetric_clusters_finder(consensus_clusters: Vec<NodeIdSet>, fbas: &Fbas) ->
     }
     found_clusters_in_all_clusters
 }
+fn symmetric_top_tier_finder(consensus_clusters: Vec<NodeIdSet>, fbas: &Fbas) -> Vec<QuorumSet> {
+    if consensus_clusters.len() == 1 {
+        find_symmetric_clusters_in_node_set(&consensus_clusters[0], fbas)
+    } else {
+        vec![]
+    }
+}
 fn find_symmetric_clusters_in_node_set(nodes: &NodeIdSet, fbas: &Fbas) -> Vec<QuorumSet> {
     // qset -> (#occurances, goal #occurances)
     let mut qset_occurances: BTreeMap<QuorumSet, (usize, usize)> = BTreeMap::new();
@@ -263,30 +624,30 @@ fn find_symmetric_clusters_in_node_set(nodes: &NodeIdSet, fbas: &Fbas) -> Vec<Qu
             *counter += 1;
             (*counter, *goal)
         } else {
-            let goal = qset.contained_nodes().len();
+            let goal = qset.number_of_contained_nodes();
             qset_occurances.insert(qset.clone(), (1, goal));
             (1, goal)
         };
-        if count == goal {
+        // the nodes using `qset` could be other nodes than the ones it contains
+        if count == goal
+            && qset.contained_nodes().iter().all(|contained| {
+                nodes.contains(contained) && fbas.nodes[contained].quorum_set == *qset
+            })
+        {
             found_clusters.push(qset.clone());
         }
     }
     found_clusters
 }
 
-fn quorums_possible(selection: &NodeIdSet, available: &NodeIdSet, fbas: &Fbas) -> bool {
-    selection
-        .iter()
-        .all(|x| fbas.nodes[x].is_quorum_slice(available))
+fn quorums_possible(selection: &NodeIdSet, available: &NodeIdSet, fbas: &FlatFbas) -> bool {
+    selection.iter().all(|x| fbas.is_quorum_slice(x, available))
 }
 
 fn contains_quorum(node_set: &NodeIdSet, fbas: &Fbas) -> bool {
     let mut satisfiable = node_set.clone();
 
-    while let Some(unsatisfiable_node) = satisfiable
-        .iter()
-        .find(|&x| !fbas.nodes[x].is_quorum_slice(&satisfiable))
-    {
+    while let Some(unsatisfiable_node) = find_unsatisfiable_node(&satisfiable, fbas) {
         satisfiable.remove(unsatisfiable_node);
     }
     !satisfiable.is_empty()
@@ -298,16 +659,20 @@ pub fn find_unsatisfiable_nodes(node_set: &NodeIdSet, fbas: &Fbas) -> (NodeIdSet
         .iter()
         .partition(|&x| fbas.nodes[x].is_quorum_slice(&node_set));
 
-    while let Some(unsatisfiable_node) = satisfiable
-        .iter()
-        .find(|&x| !fbas.nodes[x].is_quorum_slice(&satisfiable))
-    {
+    while let Some(unsatisfiable_node) = find_unsatisfiable_node(&satisfiable, fbas) {
         satisfiable.remove(unsatisfiable_node);
         unsatisfiable.insert(unsatisfiable_node);
     }
     (satisfiable, unsatisfiable)
 }
 
+/// Some member of `node_set` that doesn't have a quorum slice in `node_set`.
+fn find_unsatisfiable_node(node_set: &NodeIdSet, fbas: &Fbas) -> Option<NodeId> {
+    node_set
+        .iter()
+        .find(|&x| !fbas.nodes[x].is_quorum_slice(node_set))
+}
+
 /// Using implementation from `pathfinding` crate.
 fn partition_into_strongly_connected_components(nodes: &NodeIdSet, fbas: &Fbas) -> Vec<NodeIdSet> {
     let sucessors = |&node_id: &NodeId| -> Vec<NodeId> {
@@ -399,6 +764,37 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn quorum_closure_in_correct_trivial() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+
+        assert_eq!(Some(bitset![0, 1]), quorum_closure(&fbas, &bitset![0]));
+        assert_eq!(2, quorum_closure(&fbas, &bitset![2]).unwrap().len());
+        assert_eq!(
+            Some(bitset![0, 1, 2]),
+            quorum_closure(&fbas, &bitset![0, 2, 1])
+        );
+    }
+
+    #[test]
+    fn quorum_closure_of_unsatisfiable_seed_is_none() {
+        let mut fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        fbas.nodes[2].quorum_set = QuorumSet::new();
+
+        assert_eq!(None, quorum_closure(&fbas, &bitset![0, 2]));
+        assert_eq!(Some(bitset![0, 1]), quorum_closure(&fbas, &bitset![0]));
+    }
+
+    #[test]
+    fn quorum_closure_ignores_untrusted_nodes() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let quorum = quorum_closure(&fbas, &bitset![4]).unwrap();
+
+        assert!(fbas.is_quorum(&quorum));
+        assert!(quorum.contains(4));
+        assert_eq!(3, quorum.len());
+    }
+
     #[test]
     fn find_minimal_quorums_in_broken_trivial() {
         let fbas = Fbas::from_json_file(Path::new("test_data/broken_trivial.json"));
@@ -447,6 +843,50 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn find_minimal_quorums_up_to_size_in_correct() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let all_minimal_quorums = find_minimal_quorums(&fbas);
+        let max_size = all_minimal_quorums.iter().map(|q| q.len()).min().unwrap();
+
+        let (actual, truncated) = find_minimal_quorums_up_to_size(&fbas, max_size);
+        let expected: Vec<NodeIdSet> = all_minimal_quorums
+            .iter()
+            .filter(|q| q.len() <= max_size)
+            .cloned()
+            .collect();
+        assert_eq!(expected, actual);
+        assert_eq!(expected.len() < all_minimal_quorums.len(), truncated);
+
+        let (actual, truncated) = find_minimal_quorums_up_to_size(&fbas, fbas.number_of_nodes());
+        assert_eq!(all_minimal_quorums, actual);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn find_minimal_quorums_up_to_count_in_correct() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let all_minimal_quorums = find_minimal_quorums(&fbas);
+
+        let (actual, truncated) = find_minimal_quorums_up_to_count(&fbas, 1);
+        assert!(truncated);
+        assert_eq!(1, actual.len());
+        assert!(all_minimal_quorums.contains(&actual[0]));
+
+        let (actual, truncated) =
+            find_minimal_quorums_up_to_count(&fbas, all_minimal_quorums.len());
+        assert!(!truncated);
+        assert_eq!(all_minimal_quorums, actual);
+    }
+
+    #[test]
+    fn find_minimal_quorums_up_to_size_in_symmetric_cluster() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let (actual, truncated) = find_minimal_quorums_up_to_size(&fbas, 1);
+        assert!(actual.is_empty());
+        assert!(truncated);
+    }
+
     #[test]
     fn find_nonintersecting_quorums_in_broken() {
         let fbas = Fbas::from_json_file(Path::new("test_data/broken.json"));
@@ -457,6 +897,24 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn find_nonintersecting_quorums_streaming_in_broken_by_hand() {
+        let fbas = Fbas::from_json_file(Path::new(
+            "test_data/stellarbeat_nodes_2020-01-16_broken_by_hand.json",
+        ));
+        let quorums = find_nonintersecting_quorums_streaming(&fbas).unwrap();
+        assert_eq!(2, quorums.len());
+        assert!(fbas.is_quorum(&quorums[0]));
+        assert!(fbas.is_quorum(&quorums[1]));
+        assert!(quorums[0].is_disjoint(&quorums[1]));
+    }
+
+    #[test]
+    fn find_nonintersecting_quorums_streaming_in_correct() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        assert_eq!(None, find_nonintersecting_quorums_streaming(&fbas));
+    }
+
     #[test]
     fn find_symmetric_cluster_in_correct_trivial() {
         let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
@@ -0,0 +1,242 @@
+use super::*;
+
+/// Constraints for `recommend_quorum_set`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecommendationConstraints {
+    /// Maximum number of validators in the recommended quorum set (no limit if `None`).
+    pub max_validators: Option<usize>,
+    /// Maximum number of validators per organization (no limit if `None`).
+    pub max_validators_per_organization: Option<usize>,
+    /// Minimum number of distinct organizations (or nodes, if no organizations are given) that
+    /// the recommended quorum set must reference.
+    pub min_organizations: usize,
+    /// Up to how many faulty candidate nodes to consider when comparing the safety of the new
+    /// node to that of the existing network. Each extra node makes the search much slower.
+    pub max_faulty: usize,
+}
+impl Default for RecommendationConstraints {
+    fn default() -> Self {
+        RecommendationConstraints {
+            max_validators: None,
+            max_validators_per_organization: None,
+            min_organizations: 0,
+            max_faulty: 2,
+        }
+    }
+}
+
+/// Proposes a quorum set for a new validator joining `fbas`, trusting nodes from `candidates`
+/// (e.g., the top tier) in order of their rank (see `rank_nodes`). If `organizations` are given,
+/// each organization becomes an inner quorum set with a simple majority threshold, so that the
+/// new node doesn't depend on any single organization more than on others.
+///
+/// A node that nobody trusts can't break quorum intersection, but a too low threshold makes it
+/// easy for faulty nodes to split it off from the rest of the network. So among the quorum sets
+/// that respect `constraints`, the one with the lowest threshold is returned that still survives
+/// as many faulty candidates (up to `constraints.max_faulty`) as `fbas` does (see
+/// `find_smallest_splitting_faulty_set`). This maximizes the number of trusted entries that can
+/// fail without blocking the new node, without making it the weakest link. Returns `None` if no
+/// such quorum set exists, e.g., because `fbas` lacks quorum intersection to begin with. The new
+/// node isn't listed in its own quorum set.
+///
+/// ```
+/// use fbas_analyzer::{Analysis, Fbas, recommend_quorum_set};
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct.json"));
+/// let top_tier = Analysis::new(&fbas, None).top_tier().unwrap();
+///
+/// let quorum_set = recommend_quorum_set(&fbas, None, &top_tier, &Default::default()).unwrap();
+/// assert!(quorum_set.contained_nodes().is_subset(&top_tier));
+/// ```
+pub fn recommend_quorum_set(
+    fbas: &Fbas,
+    organizations: Option<&Organizations>,
+    candidates: &NodeIdSet,
+    constraints: &RecommendationConstraints,
+) -> Option<QuorumSet> {
+    let groups = choose_trusted_groups(fbas, organizations, candidates, constraints);
+    if groups.is_empty() || groups.len() < constraints.min_organizations {
+        warn!(
+            "Only {} organizations available for the recommended quorum set (need at least {}).",
+            groups.len(),
+            constraints.min_organizations
+        );
+        return None;
+    }
+    let mut entries = QuorumSet::new();
+    for mut group in groups.into_iter() {
+        group.sort_unstable();
+        if group.len() == 1 {
+            entries.validators.push(group[0]);
+        } else {
+            entries.inner_quorum_sets.push(QuorumSet {
+                threshold: group.len() / 2 + 1,
+                validators: group,
+                inner_quorum_sets: vec![],
+            });
+        }
+    }
+    entries.validators.sort_unstable();
+    let number_of_entries = entries.validators.len() + entries.inner_quorum_sets.len();
+
+    let tolerated_faulty =
+        match find_smallest_splitting_faulty_set(fbas, candidates, constraints.max_faulty) {
+            Some(faulty_nodes) if faulty_nodes.is_empty() => {
+                warn!("Can't recommend a quorum set for an FBAS without quorum intersection.");
+                return None;
+            }
+            Some(faulty_nodes) => faulty_nodes.len() - 1,
+            None => constraints.max_faulty,
+        };
+    debug!(
+        "Looking for a quorum set that survives {} faulty nodes...",
+        tolerated_faulty
+    );
+
+    let mut extended_fbas = fbas.clone();
+    let new_node_id = extended_fbas.add_node(Node::new("recommendation_candidate".into()));
+    for threshold in 1..=number_of_entries {
+        let quorum_set = QuorumSet {
+            threshold,
+            ..entries.clone()
+        };
+        extended_fbas.nodes[new_node_id].quorum_set = quorum_set.clone();
+        if find_smallest_splitting_faulty_set(&extended_fbas, candidates, tolerated_faulty)
+            .is_none()
+        {
+            info!(
+                "Recommending quorum set with threshold {} of {} entries.",
+                threshold, number_of_entries
+            );
+            return Some(quorum_set);
+        }
+        if stop_requested() {
+            break;
+        }
+    }
+    warn!("Found no quorum set for a new node that preserves quorum intersection.");
+    None
+}
+
+/// Groups `candidates` by organization (each node on its own if there are no organizations),
+/// highest ranked first, and truncates them according to `constraints`.
+fn choose_trusted_groups(
+    fbas: &Fbas,
+    organizations: Option<&Organizations>,
+    candidates: &NodeIdSet,
+    constraints: &RecommendationConstraints,
+) -> Vec<Vec<NodeId>> {
+    let mut groups: Vec<Vec<NodeId>> = vec![];
+    let mut group_names: Vec<Option<&str>> = vec![];
+    for node_id in sort_by_rank(candidates.iter().collect(), fbas) {
+        let name = organizations
+            .and_then(|orgs| orgs.get_by_member(node_id))
+            .map(|org| org.name.as_str());
+        match group_names.iter().position(|&n| n.is_some() && n == name) {
+            Some(i) => groups[i].push(node_id),
+            None => {
+                groups.push(vec![node_id]);
+                group_names.push(name);
+            }
+        }
+    }
+    let mut remaining = constraints.max_validators.unwrap_or(usize::MAX);
+    let mut chosen_groups = vec![];
+    for mut group in groups.into_iter() {
+        if remaining == 0 {
+            break;
+        }
+        if let Some(max_per_organization) = constraints.max_validators_per_organization {
+            group.truncate(max_per_organization);
+        }
+        group.truncate(remaining);
+        remaining -= group.len();
+        chosen_groups.push(group);
+    }
+    chosen_groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn recommended_quorum_set_is_as_safe_as_the_network() {
+        let mut fbas = Fbas::new_generic_unconfigured(4);
+        for node in fbas.nodes.iter_mut() {
+            node.quorum_set = QuorumSet {
+                threshold: 3,
+                validators: vec![0, 1, 2, 3],
+                inner_quorum_sets: vec![],
+            };
+        }
+        let all_nodes = fbas.all_nodes();
+        // 3-of-4 survives 1 faulty node, but not 2
+        assert_eq!(
+            Some(2),
+            find_smallest_splitting_faulty_set(&fbas, &all_nodes, 2).map(|s| s.len())
+        );
+
+        let quorum_set =
+            recommend_quorum_set(&fbas, None, &all_nodes, &Default::default()).unwrap();
+        // with threshold 1, a single faulty node could make the new node believe anything
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1, 2, 3],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, quorum_set);
+
+        let mut extended_fbas = fbas.clone();
+        let mut node = Node::new("new".into());
+        node.quorum_set = quorum_set;
+        extended_fbas.add_node(node);
+        assert_eq!(
+            None,
+            find_smallest_splitting_faulty_set(&extended_fbas, &all_nodes, 1)
+        );
+    }
+
+    #[test]
+    fn recommended_quorum_set_respects_organizations_and_constraints() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/stellarbeat_nodes_2019-09-17.json"));
+        let organizations = Organizations::from_json_file(
+            Path::new("test_data/stellarbeat_organizations_2019-09-17.json"),
+            &fbas,
+        );
+        let top_tier = Analysis::new(&fbas, None).top_tier().unwrap();
+        let constraints = RecommendationConstraints {
+            max_validators: Some(12),
+            max_validators_per_organization: Some(3),
+            min_organizations: 4,
+            max_faulty: 1,
+        };
+        let quorum_set =
+            recommend_quorum_set(&fbas, Some(&organizations), &top_tier, &constraints).unwrap();
+
+        assert!(quorum_set.number_of_contained_nodes() <= 12);
+        assert!(quorum_set.validators.len() + quorum_set.inner_quorum_sets.len() >= 4);
+        for inner_quorum_set in quorum_set.inner_quorum_sets.iter() {
+            assert!(inner_quorum_set.validators.len() <= 3);
+            let organization = organizations.get_by_member(inner_quorum_set.validators[0]);
+            assert!(inner_quorum_set
+                .validators
+                .iter()
+                .all(|&v| organizations.get_by_member(v) == organization));
+        }
+    }
+
+    #[test]
+    fn no_recommendation_if_constraints_cant_be_met() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let constraints = RecommendationConstraints {
+            min_organizations: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            None,
+            recommend_quorum_set(&fbas, None, &fbas.all_nodes(), &constraints)
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use super::*;
+
+use std::collections::{BTreeSet, HashSet};
+
+/// A single change to the top-level quorum set of a node, as suggested by
+/// `find_quorum_intersection_repairs`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase", tag = "edit")]
+pub enum QuorumSetEdit {
+    /// Raise the threshold of `node`'s quorum set by one.
+    RaiseThreshold { node: NodeId },
+    /// Add `validator` to the validators of `node`'s quorum set and raise its threshold by one,
+    /// so that `node` tolerates as many failures as before but now also depends on `validator`.
+    AddValidator { node: NodeId, validator: NodeId },
+}
+impl QuorumSetEdit {
+    pub fn node(&self) -> NodeId {
+        match *self {
+            QuorumSetEdit::RaiseThreshold { node } => node,
+            QuorumSetEdit::AddValidator { node, .. } => node,
+        }
+    }
+    pub fn apply(&self, fbas: &mut Fbas) {
+        let quorum_set = &mut fbas.nodes[self.node()].quorum_set;
+        if let QuorumSetEdit::AddValidator { validator, .. } = *self {
+            quorum_set.validators.push(validator);
+        }
+        quorum_set.threshold += 1;
+    }
+}
+
+/// Applies all `edits` to a copy of `fbas`.
+pub fn apply_quorum_set_edits(fbas: &Fbas, edits: &[QuorumSetEdit]) -> Fbas {
+    let mut edited_fbas = fbas.clone();
+    for edit in edits.iter() {
+        edit.apply(&mut edited_fbas);
+    }
+    edited_fbas
+}
+
+/// Searches for smallest sets of at most `max_edits` quorum set edits (see `QuorumSetEdit`) that
+/// give `fbas` quorum intersection. Returns all such repairs of the smallest size found first,
+/// followed by bigger repairs that don't contain a smaller one, i.e., ranked by number of edits.
+/// Returns `vec![vec![]]` if `fbas` already enjoys quorum intersection and an empty list if no
+/// repair was found.
+///
+/// Raising thresholds until no node is satisfiable trivially "restores" intersection, so only
+/// repairs that keep all satisfiable nodes satisfiable count. Any repair must make at least one of
+/// two non-intersecting quorums stop being a quorum, so candidate edits are derived from the
+/// non-intersecting quorums found in the FBAS as edited so far: raising the threshold of one of
+/// their members, or making a member of one quorum depend on a member of the other. Each step
+/// runs a full quorum intersection check, so this is only feasible for few edits.
+///
+/// ```
+/// use fbas_analyzer::{Fbas, QuorumSetEdit};
+/// use fbas_analyzer::{apply_quorum_set_edits, find_nonintersecting_quorums, find_quorum_intersection_repairs};
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/broken_trivial.json"));
+/// assert!(find_nonintersecting_quorums(&fbas).is_some());
+///
+/// // node 0 is satisfied by itself alone; either it or one of the others needs to be stricter
+/// let repairs = find_quorum_intersection_repairs(&fbas, 2);
+/// assert_eq!(
+///     vec![
+///         vec![QuorumSetEdit::RaiseThreshold { node: 0 }],
+///         vec![QuorumSetEdit::RaiseThreshold { node: 1 }],
+///         vec![QuorumSetEdit::RaiseThreshold { node: 2 }],
+///     ],
+///     repairs
+/// );
+/// assert!(find_nonintersecting_quorums(&apply_quorum_set_edits(&fbas, &repairs[0])).is_none());
+/// ```
+pub fn find_quorum_intersection_repairs(fbas: &Fbas, max_edits: usize) -> Vec<Vec<QuorumSetEdit>> {
+    let satisfiable_nodes = fbas.satisfiable_nodes();
+    let mut repairs: Vec<Vec<QuorumSetEdit>> = vec![];
+    for number_of_edits in 0..=max_edits {
+        info!("Searching for repairs with {} edits...", number_of_edits);
+        let mut found = BTreeSet::new();
+        search_repairs(
+            fbas,
+            &satisfiable_nodes,
+            &mut vec![],
+            number_of_edits,
+            &repairs,
+            &mut found,
+        );
+        info!(
+            "Found {} repairs with {} edits.",
+            found.len(),
+            number_of_edits
+        );
+        repairs.extend(found);
+        if repairs.first().map(|repair| repair.is_empty()) == Some(true) || stop_requested() {
+            break;
+        }
+    }
+    repairs
+}
+
+fn search_repairs(
+    edited_fbas: &Fbas,
+    satisfiable_nodes: &NodeIdSet,
+    edits: &mut Vec<QuorumSetEdit>,
+    edits_left: usize,
+    smaller_repairs: &[Vec<QuorumSetEdit>],
+    found: &mut BTreeSet<Vec<QuorumSetEdit>>,
+) {
+    if stop_requested() {
+        return;
+    }
+    let mut sorted_edits = edits.clone();
+    sorted_edits.sort();
+    if found.contains(&sorted_edits)
+        || smaller_repairs
+            .iter()
+            .any(|repair| is_sub_multiset(repair, &sorted_edits))
+    {
+        return;
+    }
+    match find_nonintersecting_quorums(edited_fbas) {
+        None => {
+            if edits_left == 0 && satisfiable_nodes.is_subset(&edited_fbas.satisfiable_nodes()) {
+                debug!("Found repair: {:?}", sorted_edits);
+                found.insert(sorted_edits);
+            }
+        }
+        Some(quorums) if edits_left > 0 => {
+            for edit in candidate_edits(edited_fbas, &quorums[0], &quorums[1]) {
+                let mut next_fbas = edited_fbas.clone();
+                edit.apply(&mut next_fbas);
+                edits.push(edit);
+                search_repairs(
+                    &next_fbas,
+                    satisfiable_nodes,
+                    edits,
+                    edits_left - 1,
+                    smaller_repairs,
+                    found,
+                );
+                edits.pop();
+            }
+        }
+        Some(_) => {}
+    }
+}
+
+/// Edits that could make `quorum1` or `quorum2` stop being a quorum.
+fn candidate_edits(fbas: &Fbas, quorum1: &NodeIdSet, quorum2: &NodeIdSet) -> Vec<QuorumSetEdit> {
+    let mut candidates = vec![];
+    let mut nodes = quorum1.clone();
+    nodes.union_with(quorum2);
+    for node in nodes.iter() {
+        let quorum_set = &fbas.nodes[node].quorum_set;
+        if quorum_set.threshold < quorum_set.validators.len() + quorum_set.inner_quorum_sets.len() {
+            candidates.push(QuorumSetEdit::RaiseThreshold { node });
+        }
+    }
+    for (quorum, other_quorum) in [(quorum1, quorum2), (quorum2, quorum1)].iter() {
+        for node in quorum.iter() {
+            let mut missing_validators = (*other_quorum).clone();
+            missing_validators.difference_with(&fbas.nodes[node].quorum_set.contained_nodes());
+            for validator in missing_validators.iter() {
+                candidates.push(QuorumSetEdit::AddValidator { node, validator });
+            }
+        }
+    }
+    candidates
+}
+
+/// Minimum number of nodes whose quorum sets need to change so that `fbas` enjoys quorum
+/// intersection, if that number is at most `max_distance` (`Some(0)` if `fbas` already enjoys
+/// quorum intersection). Unlike `find_quorum_intersection_repairs`, this doesn't say how to
+/// change them, but it is exact and much cheaper, making it usable as a health metric, e.g.,
+/// over a series of network snapshots.
+///
+/// The strictest change for a node that keeps it satisfiable is to require all satisfiable
+/// nodes. Any quorum containing such a node contains all satisfiable nodes and hence intersects
+/// all other quorums, so changing the nodes in `S` suffices iff all quorums that avoid `S`
+/// intersect. We search for a smallest such `S`, branching over the members of two
+/// non-intersecting quorums (one of which `S` must hit).
+///
+/// ```
+/// use fbas_analyzer::{Fbas, find_distance_to_safety};
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/broken_trivial.json"));
+/// assert_eq!(Some(1), find_distance_to_safety(&fbas, 3));
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+/// assert_eq!(Some(0), find_distance_to_safety(&fbas, 3));
+/// ```
+pub fn find_distance_to_safety(fbas: &Fbas, max_distance: usize) -> Option<usize> {
+    let mut changed_node_sets: Vec<NodeIdSet> = vec![bitset![]];
+    for distance in 0..=max_distance {
+        info!("Checking whether {} changed nodes suffice...", distance);
+        let mut next_changed_node_sets: HashSet<NodeIdSet> = HashSet::new();
+        for changed_nodes in changed_node_sets.iter() {
+            if stop_requested() {
+                return None;
+            }
+            // nodes without slices can't be part of any quorum
+            let mut remaining_fbas = fbas.clone();
+            for node_id in changed_nodes.iter() {
+                remaining_fbas.nodes[node_id].quorum_set = QuorumSet::new();
+            }
+            if let Some(quorums) = find_nonintersecting_quorums(&remaining_fbas) {
+                let mut quorum_nodes = quorums[0].clone();
+                quorum_nodes.union_with(&quorums[1]);
+                for node_id in quorum_nodes.iter() {
+                    let mut next_changed_nodes = changed_nodes.clone();
+                    next_changed_nodes.insert(node_id);
+                    next_changed_node_sets.insert(next_changed_nodes);
+                }
+            } else {
+                info!("Found distance to safety: {}.", distance);
+                return Some(distance);
+            }
+        }
+        changed_node_sets = next_changed_node_sets.into_iter().collect();
+    }
+    None
+}
+
+/// Whether each edit occurs in `sorted_edits` at least as often as in `sorted_subset`.
+fn is_sub_multiset(sorted_subset: &[QuorumSetEdit], sorted_edits: &[QuorumSetEdit]) -> bool {
+    let mut remaining = sorted_edits.iter();
+    sorted_subset
+        .iter()
+        .all(|edit| remaining.any(|other| other == edit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn no_repairs_needed_if_fbas_has_quorum_intersection() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let expected: Vec<Vec<QuorumSetEdit>> = vec![vec![]];
+        assert_eq!(expected, find_quorum_intersection_repairs(&fbas, 2));
+    }
+
+    #[test]
+    fn repairs_restore_quorum_intersection() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n3",
+                "quorumSet": { "threshold": 2, "validators": ["n3", "n4"] }
+            },
+            {
+                "publicKey": "n4",
+                "quorumSet": { "threshold": 2, "validators": ["n3", "n4"] }
+            }
+        ]"#,
+        );
+        assert!(find_nonintersecting_quorums(&fbas).is_some());
+
+        let repairs = find_quorum_intersection_repairs(&fbas, 2);
+        assert!(!repairs.is_empty());
+        assert!(repairs.windows(2).all(|w| w[0].len() <= w[1].len()));
+        assert_eq!(1, repairs[0].len());
+        for repair in repairs.iter() {
+            let repaired_fbas = apply_quorum_set_edits(&fbas, repair);
+            assert!(find_nonintersecting_quorums(&repaired_fbas).is_none());
+            assert_eq!(fbas.satisfiable_nodes(), repaired_fbas.satisfiable_nodes());
+        }
+    }
+
+    #[test]
+    fn distance_to_safety_of_disjoint_groups() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 1, "validators": ["n0"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 1, "validators": ["n1"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n2", "n3"] }
+            },
+            {
+                "publicKey": "n3",
+                "quorumSet": { "threshold": 2, "validators": ["n2", "n3"] }
+            }
+        ]"#,
+        );
+        // 3 disjoint quorums, all but one need to be "redirected"
+        assert_eq!(None, find_distance_to_safety(&fbas, 1));
+        assert_eq!(Some(2), find_distance_to_safety(&fbas, 2));
+        assert_eq!(Some(2), find_distance_to_safety(&fbas, 4));
+    }
+}
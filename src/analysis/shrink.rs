@@ -47,6 +47,10 @@ pub fn reshrink_sets(
 }
 
 impl Fbas {
+    /// Induced sub-FBAS of the nodes in `ids_to_keep`, with all other nodes removed from quorum
+    /// sets (thresholds stay the same, i.e., removed nodes are treated like faulty ones).
+    /// Also returns the table for mapping new IDs to old IDs (`unshrink_table[new_id]`) and the
+    /// map for mapping old IDs to new IDs (`shrink_map`).
     pub fn shrunken(
         fbas: &Self,
         ids_to_keep: NodeIdSet,
@@ -58,20 +62,72 @@ impl Fbas {
             .collect();
         let unshrink_table: Vec<NodeId> = ids_to_keep.into_iter().collect();
 
-        let mut fbas_shrunken = Fbas::new_generic_unconfigured(unshrink_table.len());
-        for old_id in 0..fbas.nodes.len() {
-            if let Some(&new_id) = shrink_map.get(&old_id) {
-                fbas_shrunken.nodes[new_id] = Node::shrunken(&fbas.nodes[old_id], &shrink_map);
-            }
+        let mut fbas_shrunken = Fbas::new();
+        for &old_id in unshrink_table.iter() {
+            fbas_shrunken.add_node(Node::shrunken(&fbas.nodes[old_id], &shrink_map));
         }
         (fbas_shrunken, unshrink_table, shrink_map)
     }
+    /// Like `shrunken`, but keeps all nodes for which `predicate` returns `true`. Useful for,
+    /// e.g., restricting analyses to the top tier or ignoring some validators.
+    pub fn shrunken_by<P>(fbas: &Self, predicate: P) -> (Self, Vec<NodeId>, HashMap<NodeId, NodeId>)
+    where
+        P: Fn(NodeId) -> bool,
+    {
+        let ids_to_keep = (0..fbas.nodes.len()).filter(|&id| predicate(id)).collect();
+        Self::shrunken(fbas, ids_to_keep)
+    }
+    /// Sub-FBAS of the nodes whose metadata satisfies `predicate` (e.g., only active nodes with
+    /// a recent enough version, or all nodes not hosted by some ISP), for running any analysis
+    /// on it. Unlike with `shrunken`, node IDs and public keys stay the same, so that results
+    /// can be reported as for the original FBAS: all other nodes are kept, but get empty quorum
+    /// sets (making them unsatisfiable) and are removed from all quorum sets (thresholds stay
+    /// the same, i.e., they are treated like faulty nodes).
+    ///
+    /// ```
+    /// use fbas_analyzer::{Analysis, Fbas};
+    /// use std::path::Path;
+    ///
+    /// let mut fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+    /// fbas.metadata_mut(1).isp = Some(String::from("Hetzner"));
+    ///
+    /// let filtered = fbas.filtered_by_metadata(|m| m.isp.as_deref() != Some("Hetzner"));
+    /// let mut analysis = Analysis::new(&filtered, None);
+    /// assert_eq!(vec![vec![0, 2]], analysis.minimal_quorums().into_vec_vec());
+    /// ```
+    pub fn filtered_by_metadata<P>(&self, predicate: P) -> Self
+    where
+        P: Fn(&NodeMetadata) -> bool,
+    {
+        self.filtered_by_node_id(|node_id| predicate(&self.nodes[node_id].metadata))
+    }
+    /// Like `filtered_by_metadata`, but keeps the nodes whose IDs satisfy `predicate`.
+    pub(crate) fn filtered_by_node_id<P>(&self, predicate: P) -> Self
+    where
+        P: Fn(NodeId) -> bool,
+    {
+        let identity_map: HashMap<NodeId, NodeId> = (0..self.nodes.len())
+            .filter(|&id| predicate(id))
+            .map(|id| (id, id))
+            .collect();
+        let mut fbas_filtered = Fbas::new();
+        for (node_id, node) in self.nodes.iter().enumerate() {
+            let mut node = Node::shrunken(node, &identity_map);
+            if !identity_map.contains_key(&node_id) {
+                node.quorum_set = QuorumSet::new();
+            }
+            fbas_filtered.add_node(node);
+        }
+        fbas_filtered
+    }
 }
 impl Node {
-    fn shrunken(node: &Self, shrink_map: &HashMap<NodeId, NodeId>) -> Self {
+    pub(crate) fn shrunken(node: &Self, shrink_map: &HashMap<NodeId, NodeId>) -> Self {
         Node {
             public_key: node.public_key.clone(),
             quorum_set: QuorumSet::shrunken(&node.quorum_set, shrink_map),
+            name: node.name.clone(),
+            metadata: node.metadata.clone(),
         }
     }
 }
@@ -149,6 +205,46 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn shrunken_by_predicate_keeps_matching_nodes() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        let (fbas_shrunken, unshrink_table, shrink_map) = Fbas::shrunken_by(&fbas, |id| id != 1);
+
+        assert_eq!(vec![0, 2], unshrink_table);
+        assert_eq!(Some(&1), shrink_map.get(&2));
+        assert_eq!(None, shrink_map.get(&1));
+        assert_eq!(
+            fbas_shrunken.pk_to_id[&fbas.nodes[2].public_key],
+            shrink_map[&2]
+        );
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, fbas_shrunken.nodes[0].quorum_set);
+        assert!(fbas_shrunken.is_quorum(&bitset![0, 1]));
+    }
+
+    #[test]
+    fn filtered_by_metadata_keeps_node_ids() {
+        let mut fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        fbas.metadata_mut(1).active = Some(false);
+        let fbas_filtered = fbas.filtered_by_metadata(|metadata| metadata.active != Some(false));
+
+        assert_eq!(fbas.number_of_nodes(), fbas_filtered.number_of_nodes());
+        assert_eq!(fbas.pk_to_id, fbas_filtered.pk_to_id);
+        assert_eq!(QuorumSet::new(), fbas_filtered.nodes[1].quorum_set);
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 2],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, fbas_filtered.nodes[0].quorum_set);
+        assert!(fbas_filtered.is_quorum(&bitset![0, 2]));
+        assert!(!fbas_filtered.is_quorum(&bitset![0, 1]));
+    }
+
     #[test]
     fn shrink_quorum_set() {
         let qset = QuorumSet {
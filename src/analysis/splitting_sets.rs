@@ -1,8 +1,20 @@
 use super::*;
 
+/// Panics if the search is stopped early (see `request_stop`), as the result would be incomplete;
+/// use `find_minimal_splitting_sets_up_to_count` for searches that may be stopped.
 pub fn find_minimal_splitting_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
+    let (minimal_splitting_sets, truncated) = find_minimal_splitting_sets_until_stopped(node_sets);
+    assert_search_complete(truncated, "minimal splitting sets");
+    minimal_splitting_sets
+}
+
+/// Like `find_minimal_splitting_sets`, but returns what was found so far if the search is stopped
+/// early, plus whether that happened.
+pub(crate) fn find_minimal_splitting_sets_until_stopped(
+    node_sets: &[NodeIdSet],
+) -> (Vec<NodeIdSet>, bool) {
     debug!("Enumerating all unique pairwise intersections...");
-    let splitting_sets = find_splitting_sets(node_sets);
+    let (splitting_sets, complete) = find_splitting_sets(node_sets, None);
     info!(
         "Found {} unique pairwise intersections.",
         splitting_sets.len()
@@ -15,14 +27,65 @@ pub fn find_minimal_splitting_sets(node_sets: &[NodeIdSet]) -> Vec<NodeIdSet> {
         "Found {} minimal splitting sets.",
         minimal_splitting_sets.len()
     );
-    minimal_splitting_sets
+    if !complete {
+        warn!("Search was stopped early; there might be more minimal splitting sets.");
+    }
+    (minimal_splitting_sets, !complete)
+}
+
+/// Like `find_minimal_splitting_sets`, but stops enumerating pairwise intersections once more
+/// than `max_count` unique ones have been found. Returns (up to) `max_count` splitting sets and
+/// whether the result was truncated. If it was, the returned splitting sets are only candidates:
+/// they are minimal with respect to each other, but smaller splitting sets might not have been
+/// found.
+pub fn find_minimal_splitting_sets_up_to_count(
+    node_sets: &[NodeIdSet],
+    max_count: usize,
+) -> (Vec<NodeIdSet>, bool) {
+    debug!(
+        "Enumerating up to {} unique pairwise intersections...",
+        max_count
+    );
+    let (splitting_sets, complete) = find_splitting_sets(node_sets, Some(max_count));
+    info!(
+        "Found {} unique pairwise intersections.",
+        splitting_sets.len()
+    );
+    if !complete {
+        warn!("Search was truncated after {} splitting sets.", max_count);
+    }
+
+    debug!("Reducing to minimal splitting sets...");
+    let mut minimal_splitting_sets =
+        remove_non_minimal_node_sets(remove_node_sets_that_are_non_minimal_by_one(splitting_sets));
+    minimal_splitting_sets.truncate(max_count);
+    info!(
+        "Found {} minimal splitting sets.",
+        minimal_splitting_sets.len()
+    );
+    (minimal_splitting_sets, !complete)
 }
 
-fn find_splitting_sets(node_sets: &[NodeIdSet]) -> HashSet<NodeIdSet> {
+/// Also returns whether all pairs were processed, i.e., the enumeration wasn't stopped after
+/// finding more than `max_count` unique intersections (or because of the memory budget or
+/// `request_stop`).
+fn find_splitting_sets(
+    node_sets: &[NodeIdSet],
+    max_count: Option<usize>,
+) -> (HashSet<NodeIdSet>, bool) {
+    let max_count = max_count.unwrap_or(usize::MAX);
     // we use a HashSet here to avoid storing duplicates
     let mut splitting_sets: HashSet<NodeIdSet> = HashSet::new();
     let mut intersection; // defining this here saves allocations...
+    let mut progress = ProgressTracker::new("minimal splitting sets", 0);
     for (i, ns1) in node_sets.iter().enumerate() {
+        if stop_requested() {
+            return (splitting_sets, false);
+        }
+        // the inner loop gets shorter with each `i`
+        let remaining_pairs = (node_sets.len() - i) * (node_sets.len() - i + 1) / 2;
+        let all_pairs = node_sets.len() * (node_sets.len() + 1) / 2;
+        progress.set_explored_fraction(1. - remaining_pairs as f64 / all_pairs as f64);
         if i % 1000 == 0 {
             debug!(
                 "...at pair ({}, {}); {} splitting sets",
@@ -34,10 +97,16 @@ fn find_splitting_sets(node_sets: &[NodeIdSet]) -> HashSet<NodeIdSet> {
         for ns2 in node_sets.iter().skip(i) {
             intersection = ns1.clone();
             intersection.intersect_with(ns2);
+            let within_memory_budget = check_memory_budget(splitting_sets.len() + 1, &intersection);
             splitting_sets.insert(intersection);
+            count_result_set_size(splitting_sets.len());
+            if splitting_sets.len() > max_count || !within_memory_budget || stop_requested() {
+                return (splitting_sets, false);
+            }
         }
     }
-    splitting_sets
+    progress.finish();
+    (splitting_sets, true)
 }
 
 #[cfg(test)]
@@ -68,6 +137,24 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn find_minimal_splitting_sets_up_to_count_truncates() {
+        let node_sets = vec![
+            bitset![0, 1, 2],
+            bitset![0, 1, 3],
+            bitset![1, 2, 3],
+            bitset![0, 3],
+        ];
+        let (actual, truncated) = find_minimal_splitting_sets_up_to_count(&node_sets, 2);
+        assert!(truncated);
+        assert!(actual.len() <= 2);
+
+        let expected = find_minimal_splitting_sets(&node_sets);
+        let (actual, truncated) = find_minimal_splitting_sets_up_to_count(&node_sets, 100);
+        assert!(!truncated);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn find_minimal_splitting_sets_some_dont_intersect() {
         let node_sets = vec![bitset![0, 1], bitset![0, 2], bitset![1, 3]];
@@ -0,0 +1,88 @@
+use super::*;
+use std::cell::Cell;
+use std::cmp;
+
+/// Counters collected by the search algorithms, for seeing where the time goes in long-running
+/// analyses and whether pruning is effective. Counters accumulate per thread until
+/// `reset_search_statistics` is called.
+///
+/// ```
+/// use fbas_analyzer::*;
+/// use std::path::Path;
+///
+/// let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+/// let minimal_quorums = find_minimal_quorums(&fbas);
+///
+/// reset_search_statistics();
+/// let minimal_blocking_sets = find_minimal_blocking_sets(&minimal_quorums);
+///
+/// let statistics = search_statistics();
+/// assert!(statistics.branches_explored > 0);
+/// assert!(statistics.peak_result_set_size >= minimal_blocking_sets.len());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchStatistics {
+    /// Search tree nodes visited by the branch and bound searches
+    pub branches_explored: usize,
+    /// Checks whether the current selection is a quorum
+    pub quorum_checks: usize,
+    /// Branches cut off because they can't lead to (new) results
+    pub branches_pruned: usize,
+    /// Found node sets dropped again for being non-minimal
+    pub sets_pruned: usize,
+    /// Largest number of node sets held by a single search at once
+    pub peak_result_set_size: usize,
+}
+
+thread_local! {
+    static SEARCH_STATISTICS: Cell<SearchStatistics> = Cell::new(SearchStatistics::default());
+}
+
+/// Counters accumulated (in this thread) since the last `reset_search_statistics`.
+pub fn search_statistics() -> SearchStatistics {
+    SEARCH_STATISTICS.with(Cell::get)
+}
+pub fn reset_search_statistics() {
+    SEARCH_STATISTICS.with(|statistics| statistics.set(SearchStatistics::default()));
+}
+
+pub(crate) fn count(update: impl FnOnce(&mut SearchStatistics)) {
+    SEARCH_STATISTICS.with(|statistics| {
+        let mut current = statistics.get();
+        update(&mut current);
+        statistics.set(current);
+    });
+}
+pub(crate) fn count_result_set_size(size: usize) {
+    count(|s| s.peak_result_set_size = cmp::max(s.peak_result_set_size, size));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn search_statistics_count_pruned_sets() {
+        reset_search_statistics();
+        remove_non_minimal_node_sets(vec![bitset![0, 1], bitset![0, 1, 2], bitset![3]]);
+        assert_eq!(1, search_statistics().sets_pruned);
+
+        reset_search_statistics();
+        assert_eq!(SearchStatistics::default(), search_statistics());
+    }
+
+    #[test]
+    fn search_statistics_cover_quorum_search() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/broken_trivial.json"));
+        reset_search_statistics();
+        find_minimal_quorums(&fbas);
+
+        let statistics = search_statistics();
+        assert!(statistics.branches_explored > 0);
+        assert!(statistics.quorum_checks > 0);
+        assert!(statistics.quorum_checks <= statistics.branches_explored);
+        assert!(statistics.peak_result_set_size > 0);
+    }
+}
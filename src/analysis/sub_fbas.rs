@@ -0,0 +1,96 @@
+use super::*;
+
+// Analyses of the induced sub-FBAS of some `subset` of nodes, i.e., as if all other nodes had
+// failed (see `Fbas::shrunken`). Results use the node IDs of the original FBAS.
+
+/// Minimal quorums of the induced sub-FBAS of `subset`, i.e., minimal quorums that only
+/// contain nodes from `subset` (treating all other nodes as failed).
+///
+/// ```
+/// use fbas_analyzer::{bitset, find_minimal_quorums_in, Fbas};
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+///
+/// assert_eq!(vec![bitset![0, 2]], find_minimal_quorums_in(&fbas, &bitset![0, 2]));
+/// assert!(find_minimal_quorums_in(&fbas, &bitset![0]).is_empty());
+/// ```
+pub fn find_minimal_quorums_in(fbas: &Fbas, subset: &NodeIdSet) -> Vec<NodeIdSet> {
+    let (fbas_shrunken, unshrink_table, _) = Fbas::shrunken(fbas, subset.clone());
+    unshrink_sets(&find_minimal_quorums(&fbas_shrunken), &unshrink_table)
+}
+
+/// Like `find_nonintersecting_quorums`, but only considers quorums that are contained in
+/// `subset`.
+pub fn find_nonintersecting_quorums_in(fbas: &Fbas, subset: &NodeIdSet) -> Option<Vec<NodeIdSet>> {
+    let (fbas_shrunken, unshrink_table, _) = Fbas::shrunken(fbas, subset.clone());
+    find_nonintersecting_quorums(&fbas_shrunken)
+        .map(|quorums| unshrink_sets(&quorums, &unshrink_table))
+}
+
+/// Minimal blocking sets of the induced sub-FBAS of `subset`, i.e., minimal sets of nodes from
+/// `subset` that intersect all quorums contained in `subset`.
+pub fn find_minimal_blocking_sets_in(fbas: &Fbas, subset: &NodeIdSet) -> Vec<NodeIdSet> {
+    find_minimal_blocking_sets(&find_minimal_quorums_in(fbas, subset))
+}
+
+/// Minimal splitting sets of the induced sub-FBAS of `subset`, i.e., minimal sets of nodes from
+/// `subset` that can cause two quorums contained in `subset` to not intersect.
+pub fn find_minimal_splitting_sets_in(fbas: &Fbas, subset: &NodeIdSet) -> Vec<NodeIdSet> {
+    find_minimal_splitting_sets(&find_minimal_quorums_in(fbas, subset))
+}
+
+/// Can the nodes in `subset` form a quorum without relying on any other node, e.g., can a
+/// consortium of organizations keep going if everybody else fails? This says nothing about
+/// whether quorums within `subset` intersect; for that, see `find_nonintersecting_quorums_in`.
+pub fn is_self_sufficient(fbas: &Fbas, subset: &NodeIdSet) -> bool {
+    let (satisfiable_nodes, _) = find_unsatisfiable_nodes(subset, fbas);
+    !satisfiable_nodes.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn analyses_of_sub_fbas_use_original_node_ids() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let top_tier = Analysis::new(&fbas, None).top_tier().unwrap();
+        let subset: NodeIdSet = top_tier.iter().skip(1).collect();
+        let expected: Vec<NodeIdSet> = find_minimal_quorums(&fbas)
+            .into_iter()
+            .filter(|quorum| quorum.is_subset(&subset))
+            .collect();
+
+        let actual = find_minimal_quorums_in(&fbas, &subset);
+        assert!(!actual.is_empty());
+        assert_eq!(expected, actual);
+        assert!(find_minimal_blocking_sets_in(&fbas, &subset)
+            .iter()
+            .all(|blocking_set| blocking_set.is_subset(&subset)));
+        assert_eq!(None, find_nonintersecting_quorums_in(&fbas, &subset));
+    }
+
+    #[test]
+    fn sub_fbas_of_broken_fbas_can_have_quorum_intersection() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/broken_trivial.json"));
+        assert!(find_nonintersecting_quorums(&fbas).is_some());
+        assert_eq!(None, find_nonintersecting_quorums_in(&fbas, &bitset![1, 2]));
+        assert_eq!(
+            vec![bitset![1], bitset![2]],
+            find_minimal_blocking_sets_in(&fbas, &bitset![1, 2])
+        );
+        assert!(find_minimal_splitting_sets_in(&fbas, &bitset![1, 2])
+            .iter()
+            .all(|splitting_set| splitting_set.is_subset(&bitset![1, 2])));
+    }
+
+    #[test]
+    fn self_sufficiency() {
+        let fbas = Fbas::from_json_file(Path::new("test_data/correct_trivial.json"));
+        assert!(is_self_sufficient(&fbas, &fbas.all_nodes()));
+        assert!(is_self_sufficient(&fbas, &bitset![1, 2]));
+        assert!(!is_self_sufficient(&fbas, &bitset![2]));
+        assert!(!is_self_sufficient(&fbas, &bitset![]));
+    }
+}
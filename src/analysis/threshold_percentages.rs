@@ -0,0 +1,163 @@
+use super::*;
+
+/// Threshold of one (inner) quorum set of a node, relative to its number of entries (validators
+/// and inner quorum sets). The top-level quorum set is at nesting level 0.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdPercentage {
+    pub node_id: NodeId,
+    pub level: usize,
+    pub threshold: usize,
+    pub entries: usize,
+    pub percentage: f64,
+}
+
+/// Network-wide distribution of threshold percentages at one nesting level.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdPercentageDistribution {
+    pub level: usize,
+    pub quorum_sets: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Number of quorum sets per 10% bucket (`[0%, 10%)`, ..., `[90%, 100%)`, `100%`)
+    pub histogram: Vec<usize>,
+}
+
+/// Threshold percentages of all (inner) quorum sets of all nodes, ordered by node ID and then
+/// depth-first. Quorum sets without entries (i.e., of unconfigured nodes) are skipped.
+pub fn find_threshold_percentages(fbas: &Fbas) -> Vec<ThresholdPercentage> {
+    let mut result = vec![];
+    for (node_id, node) in fbas.nodes.iter().enumerate() {
+        collect_threshold_percentages(node_id, &node.quorum_set, 0, &mut result);
+    }
+    result
+}
+
+fn collect_threshold_percentages(
+    node_id: NodeId,
+    quorum_set: &QuorumSet,
+    level: usize,
+    result: &mut Vec<ThresholdPercentage>,
+) {
+    let entries = quorum_set.validators.len() + quorum_set.inner_quorum_sets.len();
+    if entries == 0 {
+        return;
+    }
+    result.push(ThresholdPercentage {
+        node_id,
+        level,
+        threshold: quorum_set.threshold,
+        entries,
+        percentage: 100. * quorum_set.threshold as f64 / entries as f64,
+    });
+    for inner_quorum_set in quorum_set.inner_quorum_sets.iter() {
+        collect_threshold_percentages(node_id, inner_quorum_set, level + 1, result);
+    }
+}
+
+/// Aggregates threshold percentages per nesting level, see `ThresholdPercentageDistribution`.
+///
+/// ```
+/// use fbas_analyzer::{Fbas, find_threshold_percentages, summarize_threshold_percentages};
+///
+/// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+/// let distributions = summarize_threshold_percentages(&find_threshold_percentages(&fbas));
+///
+/// assert_eq!(1, distributions.len());
+/// assert_eq!(3, distributions[0].quorum_sets);
+/// assert_eq!(200. / 3., distributions[0].mean);
+/// assert_eq!(3, distributions[0].histogram[6]);
+/// ```
+pub fn summarize_threshold_percentages(
+    percentages: &[ThresholdPercentage],
+) -> Vec<ThresholdPercentageDistribution> {
+    let max_level = match percentages.iter().map(|p| p.level).max() {
+        Some(max_level) => max_level,
+        None => return vec![],
+    };
+    (0..=max_level)
+        .map(|level| {
+            let values: Vec<f64> = percentages
+                .iter()
+                .filter(|p| p.level == level)
+                .map(|p| p.percentage)
+                .collect();
+            let mut histogram = vec![0; 11];
+            for &value in values.iter() {
+                // thresholds above 100% (unsatisfiable quorum sets) go into the last bucket
+                let bucket = ((value / 10.).floor() as usize).min(10);
+                histogram[bucket] += 1;
+            }
+            ThresholdPercentageDistribution {
+                level,
+                quorum_sets: values.len(),
+                min: values.iter().copied().fold(f64::INFINITY, f64::min),
+                max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                mean: values.iter().sum::<f64>() / values.len() as f64,
+                histogram,
+            }
+        })
+        .filter(|distribution| distribution.quorum_sets > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_percentages_of_nested_quorum_sets() {
+        let mut fbas = Fbas::new_generic_unconfigured(4);
+        fbas.nodes[0].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![1, 2],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 3,
+                    validators: vec![0, 1, 2],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1, 2, 3],
+            inner_quorum_sets: vec![],
+        };
+
+        let actual: Vec<(NodeId, usize, f64)> = find_threshold_percentages(&fbas)
+            .into_iter()
+            .map(|p| (p.node_id, p.level, p.percentage))
+            .collect();
+        let expected = vec![(0, 0, 200. / 3.), (0, 1, 50.), (0, 1, 100.), (1, 0, 75.)];
+        assert_eq!(expected, actual);
+
+        let distributions = summarize_threshold_percentages(&find_threshold_percentages(&fbas));
+        assert_eq!(2, distributions.len());
+        assert_eq!(
+            ThresholdPercentageDistribution {
+                level: 1,
+                quorum_sets: 2,
+                min: 50.,
+                max: 100.,
+                mean: 75.,
+                histogram: vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1],
+            },
+            distributions[1]
+        );
+    }
+
+    #[test]
+    fn no_threshold_percentages_for_unconfigured_nodes() {
+        let fbas = Fbas::new_generic_unconfigured(3);
+        assert!(find_threshold_percentages(&fbas).is_empty());
+        assert!(summarize_threshold_percentages(&[]).is_empty());
+    }
+}
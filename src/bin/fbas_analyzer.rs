@@ -1,24 +1,65 @@
 extern crate fbas_analyzer;
 
+use fbas_analyzer::synthetic::{SyntheticFbas, SyntheticPolicy};
 use fbas_analyzer::*;
 
 use quicli::prelude::*;
 use structopt::StructOpt;
 
-use std::path::PathBuf;
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-/// Learn things about a given FBAS (parses data from stellarbeat.org)
+/// Learn things about a given FBAS (parses data from stellarbeat.org). Run `fbas_analyzer
+/// generate --help` for generating synthetic FBASs or graphs instead.
 #[derive(Debug, StructOpt)]
 struct Cli {
-    /// Path to JSON file describing the FBAS in stellarbeat.org "nodes" format.
-    /// Will use STDIN if omitted.
+    /// Path to file describing the FBAS, either as JSON in stellarbeat.org "nodes" format or as
+    /// a stellar-core config (the format is detected automatically). Will use STDIN if omitted
+    /// or "-".
     nodes_path: Option<PathBuf>,
 
+    /// Join the FBAS with the one described in this file (same formats as above) and analyze
+    /// the joined system, e.g., for modelling a private network joining the public one.
+    /// Nodes with the same public key are treated as the same node and keep their quorum sets
+    /// from the first FBAS.
+    #[structopt(long = "join-with")]
+    join_with_path: Option<PathBuf>,
+
     /// Output (and find) minimal quorums.
     #[structopt(short = "q", long = "minimal-quorums")]
     minimal_quorums: bool,
 
+    /// Only find and output minimal quorums with at most this many (physical) nodes (implies -q).
+    /// Results are marked as truncated if bigger minimal quorums might exist. Skips the quorum
+    /// intersection check, as that needs all minimal quorums.
+    #[structopt(long = "max-quorum-size")]
+    max_quorum_size: Option<usize>,
+
+    /// Stop after finding this many minimal quorums, minimal blocking sets or minimal splitting
+    /// sets, and mark the respective results as truncated. Truncated splitting sets are reported
+    /// as "splitting_set_candidates", as they might not be minimal. Skips the quorum intersection
+    /// check.
+    #[structopt(long = "max-results")]
+    max_results: Option<usize>,
+
+    /// Output the organizations and countries spanned by each minimal quorum (implies -q).
+    /// Organizations are only known if -m is set.
+    #[structopt(long = "quorum-spans")]
+    quorum_spans: bool,
+
+    /// Write the membership relation of minimal quorums to this file as a sparse incidence
+    /// matrix (implies -q); in Matrix Market format if the file name ends in ".mtx", as CSV
+    /// otherwise. Rows are (physical) node IDs, columns are minimal quorums in output order.
+    #[structopt(long = "export-quorum-incidence")]
+    quorum_incidence_path: Option<PathBuf>,
+
+    /// Write the trust graph to this file, with node positions from a force-directed layout:
+    /// as Graphviz DOT if the file name ends in ".dot" or ".gv", as GraphML if it ends in
+    /// ".graphml" and as JSON otherwise. Nodes are identified by (physical) node IDs.
+    #[structopt(long = "export-trust-graph")]
+    trust_graph_path: Option<PathBuf>,
+
     /// Output (and find) minimal blocking sets (minimal indispensable sets for global liveness).
     #[structopt(short = "b", long = "minimal-blocking-sets")]
     minimal_blocking_sets: bool,
@@ -27,6 +68,74 @@ struct Cli {
     #[structopt(short = "s", long = "minimal-splitting-sets")]
     minimal_splitting_sets: bool,
 
+    /// Find a smallest set of at most this many faulty top tier nodes that can break quorum
+    /// intersection (i.e., check how many faulty nodes safety can survive).
+    #[structopt(long = "max-faulty")]
+    max_faulty: Option<usize>,
+
+    /// If quorum intersection is broken, search for sets of at most this many quorum set edits
+    /// (raising a threshold, adding a validator) that would restore it, and output them ranked
+    /// by number of edits. Needs one intersection check per explored edit, so keep this small.
+    #[structopt(long = "suggest-repairs")]
+    suggest_repairs: Option<usize>,
+
+    /// Compute the minimum number of nodes whose quorum set configurations need to change to
+    /// restore quorum intersection ("distance to safety"; 0 if all quorums intersect), looking
+    /// for at most this many. Useful for tracking network health over time.
+    #[structopt(long = "distance-to-safety")]
+    max_distance_to_safety: Option<usize>,
+
+    /// Recommend a quorum set for a new validator: trust top tier nodes (grouped by
+    /// organization if -m is set), with the lowest threshold that keeps it as safe as the rest
+    /// of the network.
+    #[structopt(long = "recommend-quorum-set")]
+    recommend_quorum_set: bool,
+
+    /// Include at most this many validators in the recommended quorum set.
+    #[structopt(long = "recommend-max-validators")]
+    recommend_max_validators: Option<usize>,
+
+    /// Include at most this many validators per organization in the recommended quorum set.
+    #[structopt(long = "recommend-max-per-organization")]
+    recommend_max_per_organization: Option<usize>,
+
+    /// Require the recommended quorum set to reference at least this many organizations (or
+    /// nodes, if -m is not set).
+    #[structopt(long = "recommend-min-organizations", default_value = "0")]
+    recommend_min_organizations: usize,
+
+    /// Make the recommended quorum set survive as many faulty top tier nodes as the rest of the
+    /// network does, considering up to this many faulty nodes.
+    #[structopt(long = "recommend-max-faulty", default_value = "2")]
+    recommend_max_faulty: usize,
+
+    /// Output the nodes contained in all minimal quorums, i.e., nodes without which no quorum
+    /// can be formed.
+    #[structopt(long = "nodes-in-all-quorums")]
+    nodes_in_all_quorums: bool,
+
+    /// Output an alternative top tier characterization derived from minimal blocking sets: the
+    /// nodes contained in all minimal blocking sets, and those contained in all smallest ones.
+    #[structopt(long = "blocking-set-top-tier")]
+    blocking_set_top_tier: bool,
+
+    /// Output the distribution of quorum set thresholds as percentages of the respective number
+    /// of entries, per nesting level (min, max, mean and a histogram in 10% steps).
+    #[structopt(long = "threshold-percentages")]
+    threshold_percentages: bool,
+
+    /// Check all nodes for risky configuration patterns (low thresholds, dependence on single
+    /// organizations or unsatisfiable nodes, single points of failure) and output them as
+    /// warnings with severity levels.
+    #[structopt(long = "lint")]
+    lint: bool,
+
+    /// Output a summary report for the node with this public key (or unique name): trust graph
+    /// degrees, top tier membership, the numbers of minimal quorums, blocking sets and splitting
+    /// sets it is part of, and warnings about its configuration.
+    #[structopt(long = "node-report")]
+    node_report: Option<String>,
+
     /// Output (and find) all minimal quorums, minimal blocking sets and minimal splitting sets.
     #[structopt(short = "a", long = "all")]
     all: bool,
@@ -37,6 +146,34 @@ struct Cli {
     #[structopt(long = "expect-no-intersection")]
     expect_no_intersection: bool,
 
+    /// Check quorum intersection while enumerating minimal quorums, without keeping them in
+    /// memory. Use this if there are too many minimal quorums to store. Ignored if
+    /// --expect-no-intersection is set.
+    #[structopt(long = "streaming-intersection-check")]
+    streaming_intersection_check: bool,
+
+    /// Run both the regular and the alternative quorum intersection check and abort loudly if
+    /// they disagree (for high-stakes audits). Ignored if --expect-no-intersection or
+    /// --streaming-intersection-check is set.
+    #[structopt(long = "cross-check")]
+    cross_check: bool,
+
+    /// Also report counters from the search algorithms (branches explored, quorum checks
+    /// performed, branches and sets pruned, peak result set size), for seeing where the time goes
+    /// in long runs.
+    #[structopt(long = "search-statistics")]
+    search_statistics: bool,
+
+    /// Stop searches early (reporting what was found so far as truncated) once the node sets
+    /// they collect take up about this many MiB, instead of running out of memory.
+    #[structopt(long = "max-memory")]
+    max_memory_mib: Option<usize>,
+
+    /// Show a progress bar (on STDERR) for long-running searches, with an ETA estimated from
+    /// the explored fraction of the search space.
+    #[structopt(long = "progress")]
+    progress: bool,
+
     /// Output metrics instead of lists of node lists.
     #[structopt(short = "d", long = "describe")]
     describe: bool,
@@ -46,6 +183,17 @@ struct Cli {
     #[structopt(short = "p", long = "pretty")]
     output_pretty: bool,
 
+    /// Instead of the usual output, print a Markdown report of the full analysis (quorum
+    /// intersection, top tier, smallest blocking and splitting sets, histograms), e.g., for
+    /// pasting into GitHub issues.
+    #[structopt(long = "markdown")]
+    markdown: bool,
+
+    /// Instead of the usual output, print a self-contained HTML report of the full analysis,
+    /// including a drawing of the trust graph with nodes colored by analysis results.
+    #[structopt(long = "html", raw(conflicts_with = r#""markdown""#))]
+    html: bool,
+
     /// Silence the commentary about what is what and what it means.
     #[structopt(long = "results-only")]
     results_only: bool,
@@ -55,59 +203,455 @@ struct Cli {
     #[structopt(short = "m", long = "merge-by-org")]
     organizations_path: Option<PathBuf>,
 
+    /// Merge nodes that look like mirrors run by the same organization (same organization ID,
+    /// or identical quorum sets and similar names) - a heuristic alternative to -m for when no
+    /// organizations JSON file is at hand.
+    #[structopt(
+        long = "merge-mirrors",
+        raw(conflicts_with = r#""organizations_path""#)
+    )]
+    merge_mirrors: bool,
+
+    /// Also report the total weights of minimal quorums, minimal blocking sets, minimal splitting
+    /// sets and the top tier, as [min_weight, max_weight, mean_weight] for lists of sets; you
+    /// must provide the path to a JSON file mapping public keys to numeric weights (e.g., stake
+    /// proxies, uptimes or trust scores). Nodes without a weight weigh 0.
+    #[structopt(long = "weights")]
+    weights_path: Option<PathBuf>,
+
+    /// Only analyze nodes marked as active (stellarbeat.org "active" field). All other nodes are
+    /// treated like faulty nodes; results still use the node IDs and public keys of the input.
+    #[structopt(long = "only-active")]
+    only_active: bool,
+
+    /// Only analyze nodes known to run at least this version (e.g., "13.2"); other nodes are
+    /// handled as with --only-active.
+    #[structopt(long = "min-version")]
+    min_version: Option<String>,
+
+    /// Don't analyze nodes hosted by this ISP (can be given multiple times); these nodes are
+    /// handled as with --only-active.
+    #[structopt(long = "exclude-isp")]
+    excluded_isps: Vec<String>,
+
     #[structopt(flatten)]
     verbosity: Verbosity,
 }
 
+/// Generate input data instead of analyzing it; invoked as `fbas_analyzer generate ...`.
+#[derive(Debug, StructOpt)]
+enum GenerateCli {
+    /// Generate a synthetic FBAS and write it as JSON in stellarbeat.org "nodes" format.
+    #[structopt(name = "fbas")]
+    Fbas {
+        /// Number of nodes
+        #[structopt(long = "nodes")]
+        nodes: usize,
+
+        /// Sizes of the tiers, starting with the top tier; all nodes that don't fit go into the
+        /// lowest tier. Defaults to a single tier with all nodes.
+        #[structopt(long = "tier-sizes")]
+        tier_sizes: Vec<usize>,
+
+        /// How nodes pick their quorum sets: "tiered" (each tier trusts the next higher tier),
+        /// "random-upstream" (random members of higher tiers) or "random" (random nodes).
+        #[structopt(
+            long = "qsc",
+            default_value = "tiered",
+            raw(possible_values = r#"&["tiered", "random-upstream", "random"]"#)
+        )]
+        qsc: String,
+
+        /// Quorum set size for the random policies (including the node itself).
+        #[structopt(long = "quorum-set-size", default_value = "5")]
+        quorum_set_size: usize,
+
+        /// Relative threshold for all quorum sets; defaults to 67%.
+        #[structopt(long = "relative-threshold")]
+        relative_threshold: Option<f64>,
+
+        /// Seed for reproducible output; random if omitted.
+        #[structopt(long = "seed")]
+        seed: Option<u64>,
+
+        /// Write the FBAS to this file instead of STDOUT.
+        #[structopt(short = "o", long = "output")]
+        output_path: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        verbosity: Verbosity,
+    },
+    /// Generate a random (undirected) graph and write it in the AS relationship format used by
+    /// the graph-based quorum set configurators.
+    #[structopt(name = "graph")]
+    Graph {
+        /// Random graph model: "scale-free" (Barabási–Albert; uses --m0 and --m),
+        /// "small-world" (Watts-Strogatz; uses --k and --beta) or "erdos" (Erdős–Rényi; uses -p).
+        #[structopt(
+            long = "model",
+            raw(possible_values = r#"&["scale-free", "small-world", "erdos"]"#)
+        )]
+        model: String,
+
+        /// Number of nodes
+        #[structopt(long = "nodes")]
+        nodes: usize,
+
+        /// Scale-free: size of the initial fully connected core
+        #[structopt(long = "m0", default_value = "3")]
+        m0: usize,
+
+        /// Scale-free: number of links each further node attaches with
+        #[structopt(long = "m", default_value = "2")]
+        m: usize,
+
+        /// Small-world: number of neighbours per node (even)
+        #[structopt(long = "k", default_value = "4")]
+        k: usize,
+
+        /// Small-world: rewiring probability
+        #[structopt(long = "beta", default_value = "0.05")]
+        beta: f64,
+
+        /// Erdős–Rényi: probability of each edge
+        #[structopt(short = "p", default_value = "0.1")]
+        p: f64,
+
+        /// Write the graph to this file instead of STDOUT; bzip2-compressed if the file name ends
+        /// with ".bz2".
+        #[structopt(short = "o", long = "output")]
+        output_path: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        verbosity: Verbosity,
+    },
+}
+
 fn main() -> CliResult {
+    if std::env::args()
+        .nth(1)
+        .filter(|arg| arg == "generate")
+        .is_some()
+    {
+        let bin_name = "fbas_analyzer generate".to_string();
+        let args = std::iter::once(bin_name).chain(std::env::args().skip(2));
+        return generate(GenerateCli::from_iter(args));
+    }
     let args = Cli::from_args();
     args.verbosity.setup_env_logger("fbas_analyzer")?;
 
     let fbas = load_fbas(args.nodes_path.as_ref());
-    let organizations = maybe_load_organizations(args.organizations_path.as_ref(), &fbas);
+    let fbas = if let Some(join_with_path) = args.join_with_path.as_ref() {
+        join_fbas(fbas, join_with_path)
+    } else {
+        fbas
+    };
+    let fbas = filter_fbas(fbas, &args);
+    let organizations = if args.merge_mirrors {
+        Some(guess_organizations(&fbas))
+    } else {
+        maybe_load_organizations(args.organizations_path.as_ref(), &fbas)
+    };
+    let weights = maybe_load_weights(args.weights_path.as_ref(), &fbas);
+    stop_gracefully_on_interrupt();
+    set_memory_budget(args.max_memory_mib.map(|mib| mib * 1024 * 1024));
+    if args.progress {
+        set_progress_callback(progress_bar());
+    }
     let mut analysis = Analysis::new(&fbas, organizations.as_ref());
 
+    if args.markdown {
+        print!("{}", analysis.to_markdown_report());
+        return Ok(());
+    }
+
+    if args.html {
+        print!("{}", analysis.to_html_report());
+        return Ok(());
+    }
     let (q, b, s) = extract_main_todos(&args);
     let output = Output::init(&args, &fbas, &organizations);
+    reset_search_statistics();
 
     report_overview(&mut analysis, &output);
     output.comment_newline();
 
+    if let Some(path) = args.trust_graph_path.as_ref() {
+        export_trust_graph(&fbas, &output, path);
+    }
+
     find_and_report_symmetric_clusters(&mut analysis, &output);
 
-    if q {
+    let results_capped = args.max_quorum_size.is_some() || args.max_results.is_some();
+
+    if let Some(max_size) = args.max_quorum_size {
+        find_and_report_minimal_quorums_up_to_size(&mut analysis, &output, max_size);
+    } else if let (true, Some(max_count)) = (q, args.max_results) {
+        find_and_report_minimal_quorums_up_to_count(&mut analysis, &output, max_count);
+    } else if q {
         find_and_report_minimal_quorums(&mut analysis, &output);
     }
+    if q && report_if_interrupted("minimal_quorums", &analysis, &output) {
+        return Ok(());
+    }
+    if args.quorum_spans {
+        report_minimal_quorum_spans(&mut analysis, &output);
+    }
+    if let Some(path) = args.quorum_incidence_path.as_ref() {
+        export_minimal_quorum_incidence(&mut analysis, &output, path, fbas.number_of_nodes());
+    }
 
-    check_and_report_if_has_quorum_intersection(
-        &mut analysis,
-        &output,
-        args.expect_no_intersection,
-    );
+    if !results_capped {
+        check_and_report_if_has_quorum_intersection(
+            &mut analysis,
+            &output,
+            args.expect_no_intersection,
+            args.streaming_intersection_check,
+            args.cross_check,
+        );
+        if stop_requested() {
+            output.comment("\nInterrupted before quorum intersection could be determined!");
+            return Ok(());
+        }
+        if let Some(max_distance) = args.max_distance_to_safety {
+            find_and_report_distance_to_safety(&analysis, &output, max_distance);
+        }
+        if let Some(max_edits) = args.suggest_repairs {
+            if !analysis.has_quorum_intersection() {
+                find_and_report_quorum_intersection_repairs(&analysis, &output, max_edits);
+            }
+        }
+    } else {
+        output.comment("Skipping quorum intersection check (needs all minimal quorums).\n");
+    }
 
-    if b {
+    if let (true, Some(max_count)) = (b, args.max_results) {
+        find_and_report_minimal_blocking_sets_up_to_count(&mut analysis, &output, max_count);
+    } else if b {
         find_and_report_minimal_blocking_sets(&mut analysis, &output);
     }
-    if s {
+    if b && report_if_interrupted("minimal_blocking_sets", &analysis, &output) {
+        return Ok(());
+    }
+    if let (true, Some(max_count)) = (s, args.max_results) {
+        find_and_report_minimal_splitting_sets_up_to_count(&mut analysis, &output, max_count);
+    } else if s {
         find_and_report_minimal_splitting_sets(&mut analysis, &output);
     }
-    if q || b || s {
+    if s && report_if_interrupted("minimal_splitting_sets", &analysis, &output) {
+        return Ok(());
+    }
+    if let Some(max_size) = args.max_faulty {
+        find_and_report_smallest_splitting_faulty_set(&mut analysis, &output, max_size);
+    }
+    if (q || b || s) && !results_capped {
         report_top_tier_uncondensed(&mut analysis, &output);
     }
+    if args.nodes_in_all_quorums && !results_capped {
+        report_nodes_in_all_minimal_quorums(&mut analysis, &output);
+    }
+    if args.blocking_set_top_tier && !results_capped {
+        report_blocking_set_cores(&mut analysis, &output);
+    }
+    if args.threshold_percentages {
+        report_threshold_percentages(&analysis, &output);
+    }
+    if args.lint {
+        find_and_report_configuration_lints(&analysis, &output);
+    }
+    if args.recommend_quorum_set {
+        let constraints = RecommendationConstraints {
+            max_validators: args.recommend_max_validators,
+            max_validators_per_organization: args.recommend_max_per_organization,
+            min_organizations: args.recommend_min_organizations,
+            max_faulty: args.recommend_max_faulty,
+        };
+        find_and_report_recommended_quorum_set(&mut analysis, &output, &constraints);
+    }
+    if let Some(public_key) = args.node_report.as_ref() {
+        report_node(&mut analysis, &output, &fbas, public_key);
+    }
+    if let Some(weights) = weights {
+        if !results_capped {
+            report_weights(
+                &mut analysis,
+                &output,
+                &weights,
+                organizations.as_ref(),
+                (q, b, s),
+            );
+        } else {
+            output.comment("Skipping weights (need all results).\n");
+        }
+    }
+    if args.search_statistics {
+        output.result("search_statistics", search_statistics());
+    }
     Ok(())
 }
 
+fn generate(command: GenerateCli) -> CliResult {
+    match command {
+        GenerateCli::Fbas {
+            nodes,
+            tier_sizes,
+            qsc,
+            quorum_set_size,
+            relative_threshold,
+            seed,
+            output_path,
+            verbosity,
+        } => {
+            verbosity.setup_env_logger("fbas_analyzer")?;
+            generate_fbas(
+                nodes,
+                tier_sizes,
+                &qsc,
+                quorum_set_size,
+                relative_threshold,
+                seed,
+                output_path,
+            )
+        }
+        GenerateCli::Graph {
+            model,
+            nodes,
+            m0,
+            m,
+            k,
+            beta,
+            p,
+            output_path,
+            verbosity,
+        } => {
+            verbosity.setup_env_logger("fbas_analyzer")?;
+            let graph = match model.as_str() {
+                "scale-free" => Graph::new_random_scale_free(nodes, m0, m),
+                "small-world" => Graph::new_random_small_world(nodes, k, beta),
+                _ => Graph::new_random_erdos_renyi(nodes, p),
+            };
+            generate_graph(graph, output_path)
+        }
+    }
+}
+fn generate_fbas(
+    nodes: usize,
+    tier_sizes: Vec<usize>,
+    qsc: &str,
+    quorum_set_size: usize,
+    relative_threshold: Option<f64>,
+    seed: Option<u64>,
+    output_path: Option<PathBuf>,
+) -> CliResult {
+    if nodes == 0 {
+        return Err(invalid_input("--nodes must be at least 1.").into());
+    }
+    if tier_sizes.first() == Some(&0) {
+        return Err(invalid_input("--tier-sizes: the top tier mustn't be empty.").into());
+    }
+    let tier_sizes = if tier_sizes.is_empty() {
+        vec![nodes]
+    } else {
+        tier_sizes
+    };
+    let policy = match qsc {
+        "tiered" => SyntheticPolicy::Tiered { relative_threshold },
+        "random-upstream" => SyntheticPolicy::RandomUpstream {
+            quorum_set_size,
+            relative_threshold,
+        },
+        _ => SyntheticPolicy::Random {
+            quorum_set_size,
+            relative_threshold,
+        },
+    };
+    let blueprint = SyntheticFbas::new(nodes, tier_sizes, policy);
+    let fbas = if let Some(seed) = seed {
+        blueprint.generate_seeded(seed)
+    } else {
+        blueprint.generate()
+    };
+    eprintln!("Generated FBAS with {} nodes.", fbas.number_of_nodes());
+
+    let json = fbas.to_json_string_pretty();
+    if let Some(output_path) = output_path {
+        std::fs::write(&output_path, json)?;
+        eprintln!("Wrote FBAS to {:?}.", output_path);
+    } else {
+        println!("{}", json);
+    }
+    Ok(())
+}
+fn generate_graph(graph: Graph, output_path: Option<PathBuf>) -> CliResult {
+    eprintln!("Generated graph with {} nodes.", graph.number_of_nodes());
+    match output_path {
+        Some(output_path) if output_path.extension().filter(|&e| e == "bz2").is_some() => {
+            Graph::to_as_rel_file(&graph, &output_path)?;
+            eprintln!("Wrote graph to {:?}.", output_path);
+        }
+        Some(output_path) => {
+            let mut lines = Graph::to_as_rel_string(&graph)?.join("\n");
+            lines.push('\n');
+            std::fs::write(&output_path, lines)?;
+            eprintln!("Wrote graph to {:?}.", output_path);
+        }
+        None => {
+            for line in Graph::to_as_rel_string(&graph)?.iter() {
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
+}
 fn load_fbas(o_nodes_path: Option<&PathBuf>) -> Fbas {
-    let fbas = if let Some(nodes_path) = o_nodes_path {
-        eprintln!("Reading FBAS JSON from file...");
-        Fbas::from_json_file(nodes_path)
+    let fbas = if let Some(nodes_path) = o_nodes_path.filter(|&path| path.as_os_str() != "-") {
+        eprintln!("Reading FBAS from file...");
+        Fbas::from_file_detecting_format(nodes_path)
     } else {
-        eprintln!("Reading FBAS JSON from STDIN...");
-        Fbas::from_json_stdin()
+        eprintln!("Reading FBAS from STDIN...");
+        Fbas::from_stdin_detecting_format()
     };
     eprintln!("Loaded FBAS with {} nodes.", fbas.number_of_nodes());
     fbas
 }
+fn filter_fbas(fbas: Fbas, args: &Cli) -> Fbas {
+    if !args.only_active && args.min_version.is_none() && args.excluded_isps.is_empty() {
+        return fbas;
+    }
+    let keep = |metadata: &NodeMetadata| {
+        (!args.only_active || metadata.active == Some(true))
+            && args
+                .min_version
+                .as_ref()
+                .filter(|min_version| !metadata.version_at_least(min_version))
+                .is_none()
+            && metadata
+                .isp
+                .as_ref()
+                .filter(|isp| args.excluded_isps.contains(isp))
+                .is_none()
+    };
+    let number_of_kept_nodes = (0..fbas.number_of_nodes())
+        .filter(|&node_id| keep(fbas.metadata(node_id)))
+        .count();
+    eprintln!(
+        "Filtered FBAS by node metadata; analyzing {} of {} nodes.",
+        number_of_kept_nodes,
+        fbas.number_of_nodes()
+    );
+    fbas.filtered_by_metadata(keep)
+}
+fn join_fbas(fbas: Fbas, join_with_path: &Path) -> Fbas {
+    eprintln!("Reading FBAS to join with from file...");
+    let other = Fbas::from_file_detecting_format(join_with_path);
+    let (joined, _) = Fbas::joined(&fbas, &other, OverlapResolution::KeepFirst);
+    eprintln!(
+        "Joined with FBAS of {} nodes; the joined FBAS has {} nodes.",
+        other.number_of_nodes(),
+        joined.number_of_nodes()
+    );
+    joined
+}
 fn maybe_load_organizations<'a>(
     o_organizations_path: Option<&PathBuf>,
     fbas: &'a Fbas,
@@ -121,12 +665,107 @@ fn maybe_load_organizations<'a>(
         None
     }
 }
+fn guess_organizations(fbas: &Fbas) -> Organizations<'_> {
+    eprintln!("Will merge nodes that look like mirrors run by the same organization...");
+    let orgs = Organizations::from_mirror_heuristic(fbas);
+    eprintln!(
+        "Guessed {} organizations running mirrored nodes.",
+        orgs.number_of_organizations()
+    );
+    orgs
+}
+fn maybe_load_weights(o_weights_path: Option<&PathBuf>, fbas: &Fbas) -> Option<NodeWeights> {
+    if let Some(weights_path) = o_weights_path {
+        eprintln!("Reading node weights JSON from file...");
+        Some(NodeWeights::from_json_file(weights_path, fbas))
+    } else {
+        None
+    }
+}
+/// On the first Ctrl-C, let searches stop early so that what was found so far can still be
+/// reported; a second Ctrl-C terminates right away.
+#[cfg(unix)]
+fn stop_gracefully_on_interrupt() {
+    extern "C" fn handle_sigint(_: libc::c_int) {
+        request_stop();
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            handle_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+#[cfg(not(unix))]
+fn stop_gracefully_on_interrupt() {}
+/// Returns `true` (after marking the last result as incomplete) if the user interrupted us or
+/// the memory budget was exceeded.
+fn report_if_interrupted(result_name: &str, analysis: &Analysis, output: &Output) -> bool {
+    if stop_requested() || analysis.stopped_early() {
+        output.result(&format!("{}_truncated", result_name), true);
+        output.comment(&format!(
+            "\nStopped early (interrupted or out of memory budget)! The {} results above are \
+            incomplete; skipping all further analyses.",
+            result_name.replace('_', " ")
+        ));
+        true
+    } else {
+        false
+    }
+}
+/// Renders search progress as a single, continuously overwritten line on STDERR.
+fn progress_bar() -> impl Fn(SearchProgress) {
+    const WIDTH: usize = 30;
+    let started = Cell::new(Instant::now());
+    move |progress: SearchProgress| {
+        if progress.explored_fraction == 0. {
+            started.set(Instant::now());
+        }
+        let elapsed = started.get().elapsed();
+        let filled = (progress.explored_fraction * WIDTH as f64) as usize;
+        let bar = format!("{}{}", "#".repeat(filled), "-".repeat(WIDTH - filled));
+        let eta = if progress.explored_fraction >= 1. {
+            format!("done in {}", format_duration(elapsed))
+        } else if progress.explored_fraction > 0. {
+            let remaining = elapsed.as_secs_f64() * (1. / progress.explored_fraction - 1.);
+            format!(
+                "ETA {}",
+                format_duration(Duration::from_secs_f64(remaining))
+            )
+        } else {
+            "ETA unknown".to_string()
+        };
+        eprint!(
+            "\r{}: [{}] {:5.1}% ({})\x1b[K",
+            progress.search,
+            bar,
+            progress.explored_fraction * 100.,
+            eta
+        );
+        if progress.explored_fraction >= 1. {
+            eprintln!();
+        }
+    }
+}
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
 fn extract_main_todos(args: &Cli) -> (bool, bool, bool) {
     if args.all {
         (true, true, true)
     } else {
         (
-            args.minimal_quorums,
+            args.minimal_quorums || args.quorum_spans || args.quorum_incidence_path.is_some(),
             args.minimal_blocking_sets,
             args.minimal_splitting_sets,
         )
@@ -161,23 +800,36 @@ fn check_and_report_if_has_quorum_intersection(
     analysis: &mut Analysis,
     output: &Output,
     alternative_check: bool,
+    streaming_check: bool,
+    cross_check: bool,
 ) {
-    let has_quorum_intersection = if alternative_check {
-        output.comment("Alternative quorum intersection check...");
-        let ((has_quorum_intersection, quorums), duration) =
-            time_measured!(analysis.has_quorum_intersection_via_alternative_check());
+    let has_quorum_intersection = if alternative_check || streaming_check || cross_check {
+        let ((has_quorum_intersection, quorums), duration) = if alternative_check {
+            output.comment("Alternative quorum intersection check...");
+            time_measured!(analysis.has_quorum_intersection_via_alternative_check())
+        } else if streaming_check {
+            output.comment("Streaming quorum intersection check...");
+            time_measured!(analysis.has_quorum_intersection_via_streaming_check())
+        } else {
+            output.comment("Cross-checked quorum intersection check (regular and alternative)...");
+            time_measured!(analysis.has_quorum_intersection_cross_checked())
+        };
+        if stop_requested() {
+            return;
+        }
         output.timed_result("has_quorum_intersection", has_quorum_intersection, duration);
         if let Some(nonintersecting_quorums) = quorums {
             output.result("nonintersecting_quorums", nonintersecting_quorums);
         }
         has_quorum_intersection
     } else {
-        do_time_and_report!(
-            "has_quorum_intersection",
-            analysis.has_quorum_intersection(),
-            output
-        );
-        analysis.has_quorum_intersection() // from cache
+        let (has_quorum_intersection, duration) =
+            time_measured!(analysis.has_quorum_intersection());
+        if stop_requested() {
+            return;
+        }
+        output.timed_result("has_quorum_intersection", has_quorum_intersection, duration);
+        has_quorum_intersection
     };
     if has_quorum_intersection {
         output.comment("\nAll quorums intersect 👍\n");
@@ -188,6 +840,44 @@ fn check_and_report_if_has_quorum_intersection(
         );
     }
 }
+fn find_and_report_distance_to_safety(analysis: &Analysis, output: &Output, max_distance: usize) {
+    let (result, duration) = time_measured!(analysis.distance_to_safety(max_distance));
+    output.timed_result("distance_to_safety", result, duration);
+    if let Some(distance) = result {
+        output.comment(&format!(
+            "\nQuorum intersection can be restored by changing the quorum sets of {} nodes \
+                (but not fewer).\n",
+            distance
+        ));
+    } else {
+        output.comment(&format!(
+            "\nRestoring quorum intersection needs changes to more than {} quorum sets.\n",
+            max_distance
+        ));
+    }
+}
+fn find_and_report_quorum_intersection_repairs(
+    analysis: &Analysis,
+    output: &Output,
+    max_edits: usize,
+) {
+    let (repairs, duration) = time_measured!(analysis.quorum_intersection_repairs(max_edits));
+    let number_of_repairs = repairs.len();
+    let smallest_size = repairs.first().map(|edits| edits.len());
+    output.timed_result("quorum_intersection_repairs", repairs, duration);
+    if let Some(size) = smallest_size {
+        output.comment(&format!(
+            "\nWe found {} ways to restore quorum intersection; the smallest need {} quorum set \
+                edits.\n",
+            number_of_repairs, size
+        ));
+    } else {
+        output.comment(&format!(
+            "\nWe found no way to restore quorum intersection with at most {} quorum set edits.\n",
+            max_edits
+        ));
+    }
+}
 fn find_and_report_symmetric_clusters(analysis: &mut Analysis, output: &Output) {
     let mut output_uncondensed = output.clone();
     output_uncondensed.describe = false;
@@ -205,6 +895,109 @@ fn find_and_report_minimal_quorums(analysis: &mut Analysis, output: &Output) {
         analysis.minimal_quorums().len()
     ));
 }
+fn find_and_report_minimal_quorums_up_to_size(
+    analysis: &mut Analysis,
+    output: &Output,
+    max_size: usize,
+) {
+    let ((minimal_quorums, truncated), duration) =
+        time_measured!(analysis.minimal_quorums_up_to_size(max_size));
+    let number_of_minimal_quorums = minimal_quorums.len();
+    output.timed_result("minimal_quorums", minimal_quorums, duration);
+    output.result("minimal_quorums_truncated", truncated);
+    output.comment(&format!(
+        "\nWe found {} minimal quorums with at most {} nodes{}.\n",
+        number_of_minimal_quorums,
+        max_size,
+        if truncated {
+            "; there might be bigger ones"
+        } else {
+            " - and no bigger ones"
+        }
+    ));
+}
+fn find_and_report_minimal_quorums_up_to_count(
+    analysis: &mut Analysis,
+    output: &Output,
+    max_count: usize,
+) {
+    let (result, duration) = time_measured!(analysis.minimal_quorums_up_to_count(max_count));
+    report_truncatable_result("minimal_quorums", result, duration, output);
+}
+fn find_and_report_minimal_blocking_sets_up_to_count(
+    analysis: &mut Analysis,
+    output: &Output,
+    max_count: usize,
+) {
+    let (result, duration) = time_measured!(analysis.minimal_blocking_sets_up_to_count(max_count));
+    report_truncatable_result("minimal_blocking_sets", result, duration, output);
+}
+fn find_and_report_minimal_splitting_sets_up_to_count(
+    analysis: &mut Analysis,
+    output: &Output,
+    max_count: usize,
+) {
+    let ((result, truncated), duration) =
+        time_measured!(analysis.minimal_splitting_sets_up_to_count(max_count));
+    // smaller splitting sets might be among the pairwise intersections we didn't get to
+    let result_name = if truncated {
+        "splitting_set_candidates"
+    } else {
+        "minimal_splitting_sets"
+    };
+    report_truncatable_result(result_name, (result, truncated), duration, output);
+}
+fn report_truncatable_result(
+    result_name: &str,
+    (result, truncated): (NodeIdSetVecResult, bool),
+    duration: Duration,
+    output: &Output,
+) {
+    let number_of_node_sets = result.len();
+    output.timed_result(result_name, result, duration);
+    output.result(&format!("{}_truncated", result_name), truncated);
+    output.comment(&format!(
+        "\nWe found {} {}{}.\n",
+        number_of_node_sets,
+        result_name.replace('_', " "),
+        if truncated {
+            " (truncated - there might be more)"
+        } else {
+            ""
+        }
+    ));
+}
+fn report_minimal_quorum_spans(analysis: &mut Analysis, output: &Output) {
+    output.result("minimal_quorum_spans", analysis.minimal_quorum_spans());
+    output.comment(
+        "\n(Organizations and countries spanned by each of the above minimal quorums, in order.)\n",
+    );
+}
+fn export_minimal_quorum_incidence(
+    analysis: &mut Analysis,
+    output: &Output,
+    path: &PathBuf,
+    number_of_nodes: usize,
+) {
+    analysis
+        .minimal_quorums()
+        .to_incidence_matrix_file(path, number_of_nodes)
+        .unwrap_or_else(|_| panic!("Error writing file {:?}", path));
+    output.comment(&format!(
+        "(Wrote node-quorum incidence matrix of minimal quorums to {:?}.)\n",
+        path
+    ));
+}
+const TRUST_GRAPH_LAYOUT_ITERATIONS: usize = 300;
+fn export_trust_graph(fbas: &Fbas, output: &Output, path: &Path) {
+    Graph::from_fbas(fbas)
+        .to_layout_file(path, TRUST_GRAPH_LAYOUT_ITERATIONS)
+        .unwrap_or_else(|_| panic!("Error writing file {:?}", path));
+    output.comment(&format!(
+        "(Wrote trust graph with force-directed layout to {:?}.)\n",
+        path
+    ));
+}
 fn find_and_report_minimal_blocking_sets(analysis: &mut Analysis, output: &Output) {
     do_time_and_report!(
         "minimal_blocking_sets",
@@ -232,6 +1025,26 @@ fn find_and_report_minimal_splitting_sets(analysis: &mut Analysis, output: &Outp
         analysis.minimal_splitting_sets().len()
     ));
 }
+fn find_and_report_smallest_splitting_faulty_set(
+    analysis: &mut Analysis,
+    output: &Output,
+    max_size: usize,
+) {
+    let (result, duration) = time_measured!(analysis.smallest_splitting_faulty_set(max_size));
+    let size = result.as_ref().map(|faulty_nodes| faulty_nodes.len());
+    output.timed_result("smallest_splitting_faulty_set", result, duration);
+    if let Some(size) = size {
+        output.comment(&format!(
+            "\nQuorum intersection can be broken by {} faulty nodes (but not by fewer).\n",
+            size
+        ));
+    } else {
+        output.comment(&format!(
+            "\nQuorum intersection survives any {} faulty top tier nodes 👍\n",
+            max_size
+        ));
+    }
+}
 fn report_top_tier_uncondensed(analysis: &mut Analysis, output: &Output) {
     output.result_uncondensed("top_tier", analysis.top_tier());
     output.comment(
@@ -241,6 +1054,135 @@ fn report_top_tier_uncondensed(analysis: &mut Analysis, output: &Output) {
         )
     );
 }
+fn report_nodes_in_all_minimal_quorums(analysis: &mut Analysis, output: &Output) {
+    do_time_and_report!(
+        "nodes_in_all_minimal_quorums",
+        analysis.nodes_in_all_minimal_quorums(),
+        output
+    );
+    let number_of_nodes = analysis.nodes_in_all_minimal_quorums().len();
+    if number_of_nodes > 0 {
+        output.comment(&format!(
+            "\nNo quorum can be formed without any of these {} nodes!\n",
+            number_of_nodes
+        ));
+    }
+}
+fn report_blocking_set_cores(analysis: &mut Analysis, output: &Output) {
+    do_time_and_report!("blocking_set_core", analysis.blocking_set_core(), output);
+    do_time_and_report!(
+        "smallest_blocking_sets_core",
+        analysis.smallest_blocking_sets_core(),
+        output
+    );
+    let top_tier = analysis.top_tier().unwrap();
+    let mut top_tier_in_core = top_tier.clone();
+    top_tier_in_core.intersect_with(&analysis.smallest_blocking_sets_core().unwrap());
+    output.comment(&format!(
+        "\n{} of {} top tier nodes are contained in all smallest blocking sets.\n",
+        top_tier_in_core.len(),
+        top_tier.len()
+    ));
+}
+fn report_threshold_percentages(analysis: &Analysis, output: &Output) {
+    do_time_and_report!(
+        "threshold_percentages",
+        analysis.threshold_percentage_distributions(),
+        output
+    );
+}
+fn find_and_report_configuration_lints(analysis: &Analysis, output: &Output) {
+    let (lints, duration) = time_measured!(analysis.configuration_lints());
+    let number_of_critical_lints = lints
+        .iter()
+        .filter(|lint| lint.severity == Severity::Critical)
+        .count();
+    let number_of_lints = lints.len();
+    output.timed_result("configuration_lints", lints, duration);
+    output.comment(&format!(
+        "\nWe found {} risky configuration patterns, {} of them critical.\n",
+        number_of_lints, number_of_critical_lints
+    ));
+}
+fn find_and_report_recommended_quorum_set(
+    analysis: &mut Analysis,
+    output: &Output,
+    constraints: &RecommendationConstraints,
+) {
+    let (result, duration) = time_measured!(analysis.recommended_quorum_set(constraints));
+    if let Some(ref quorum_set) = result {
+        match quorum_set.to_stellar_core_config_string(output.fbas) {
+            Ok(config) => output.comment(&format!("\nAs stellar-core config:\n\n{}", config)),
+            Err(error) => output.comment(&format!("\nNo stellar-core config: {}\n", error)),
+        }
+    }
+    let found_nothing = result.is_none();
+    output.timed_result("recommended_quorum_set", result, duration);
+    if found_nothing {
+        output.comment(
+            "\nFound no quorum set for a new node that meets the constraints and preserves \
+                quorum intersection.\n",
+        );
+    }
+}
+fn report_node(analysis: &mut Analysis, output: &Output, fbas: &Fbas, public_key: &str) {
+    let node_id = fbas
+        .get_node_id_by_identifier(public_key)
+        .unwrap_or_else(|| panic!("Unknown node {}", public_key));
+    output.result_uncondensed("node_report", analysis.node_report(node_id));
+}
+fn report_weights(
+    analysis: &mut Analysis,
+    output: &Output,
+    weights: &NodeWeights,
+    organizations: Option<&Organizations>,
+    (q, b, s): (bool, bool, bool),
+) {
+    output.result(
+        "nodes_total_weight",
+        analysis.all_physical_nodes().weight(weights, None),
+    );
+    if q {
+        output.result(
+            "minimal_quorums_weight",
+            analysis
+                .minimal_quorums()
+                .weighted_minmaxmean(weights, organizations),
+        );
+    }
+    if b {
+        let (min_weight, max_weight, mean_weight) = analysis
+            .minimal_blocking_sets()
+            .weighted_minmaxmean(weights, organizations);
+        output.result(
+            "minimal_blocking_sets_weight",
+            (min_weight, max_weight, mean_weight),
+        );
+        output.comment(&format!(
+            "\nHalting the network requires control over nodes with a total weight of at least {}.\n",
+            min_weight
+        ));
+    }
+    if s {
+        let (min_weight, max_weight, mean_weight) = analysis
+            .minimal_splitting_sets()
+            .weighted_minmaxmean(weights, organizations);
+        output.result(
+            "minimal_splitting_sets_weight",
+            (min_weight, max_weight, mean_weight),
+        );
+        output.comment(&format!(
+            "\nBreaking safety requires control over nodes with a total weight of at least {}.\n",
+            min_weight
+        ));
+    }
+    if q || b || s {
+        output.result(
+            "top_tier_weight",
+            analysis.top_tier().weight(weights, organizations),
+        );
+    }
+}
 
 #[derive(Clone)]
 struct Output<'a> {
@@ -3,9 +3,10 @@ extern crate fbas_analyzer;
 use fbas_analyzer::*;
 
 use quicli::prelude::*;
+use serde::Serialize;
 use structopt::StructOpt;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 /// FBAS quorum set configuration (QSC) simulation sandbox.
@@ -20,6 +21,45 @@ struct Cli {
     #[structopt(short = "g", long = "grow-by", default_value = "0")]
     grow_by_n: usize,
 
+    /// Replace generic public keys ("n0", "n1", ...) with synthetic identities: valid-looking
+    /// Stellar public keys, names like "sim-node-042" and organization IDs, for tools that expect
+    /// real-looking networks.
+    #[structopt(long = "synthetic-identities")]
+    synthetic_identities: bool,
+
+    /// With --synthetic-identities, assign this many consecutive nodes to each organization.
+    #[structopt(long = "nodes-per-organization", default_value = "1")]
+    nodes_per_organization: usize,
+
+    /// With --synthetic-identities, also write the synthetic organizations (stellarbeat.org
+    /// organizations JSON) to this file.
+    #[structopt(long = "organizations-out")]
+    organizations_out_path: Option<PathBuf>,
+
+    /// Write a snapshot of the FBAS to this directory (which must exist) every
+    /// --snapshot-interval simulation steps, for replaying the simulation later.
+    #[structopt(long = "snapshots-dir")]
+    snapshots_dir: Option<PathBuf>,
+
+    /// With --snapshots-dir, take a snapshot after every k-th simulation step (global
+    /// reevaluation).
+    #[structopt(long = "snapshot-interval", default_value = "1")]
+    snapshot_interval: usize,
+
+    /// Maximum number of reevaluation rounds after each change to the FBAS, in case quorum set
+    /// configurations don't stabilize. Default is the number of nodes in the FBAS.
+    #[structopt(long = "max-rounds")]
+    max_rounds: Option<usize>,
+
+    /// Seed for the simulator's decisions (reevaluation orders etc.); random if omitted.
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// Write a manifest (crate version, seed, QSC and graph parameters) to this file, for
+    /// reproducing the simulation later.
+    #[structopt(long = "manifest-out")]
+    manifest_out_path: Option<PathBuf>,
+
     /// Quorum set configuration strategy to simulate
     #[structopt(subcommand)]
     qscc: QuorumSetConfiguratorConfig,
@@ -27,7 +67,7 @@ struct Cli {
     #[structopt(flatten)]
     verbosity: Verbosity,
 }
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Serialize, StructOpt)]
 enum QuorumSetConfiguratorConfig {
     /// Creates threshold=n quorum sets containing all n nodes in the FBAS
     SuperSafe,
@@ -90,6 +130,11 @@ enum QuorumSetConfiguratorConfig {
     },
     /// TODO - might be removed again soon
     QualityAware { graph_size: Option<usize> },
+    /// Runs a complete simulation scenario (graph model, QSC, growth schedule and analyses)
+    /// defined in a TOML file. Of all other options, only --seed and --max-rounds (overriding the
+    /// scenario's seed and max_rounds) and --manifest-out can be used. Analysis results are
+    /// printed to STDERR.
+    Scenario { scenario_path: PathBuf },
 }
 
 fn parse_qscc(
@@ -188,6 +233,7 @@ fn parse_qscc(
             // shuffled because fbas join order shouldn't be correlated with importance in graph
             Graph::new_random_scale_free(graph_size.unwrap_or(fbas_size), 2, 2).shuffled(),
         )),
+        Scenario { .. } => unreachable!("Scenarios are handled separately"),
     }
 }
 
@@ -195,21 +241,109 @@ fn main() -> CliResult {
     let args = Cli::from_args();
     args.verbosity.setup_env_logger("fbas_analyzer")?;
 
+    if let QuorumSetConfiguratorConfig::Scenario { ref scenario_path } = args.qscc {
+        return run_scenario(scenario_path, &args);
+    }
+
     let n = args.initial_n + args.grow_by_n;
 
+    let qsc_parameters = serde_json::json!({ "fbasSize": n, "config": args.qscc });
     let qsc = parse_qscc(args.qscc, n);
-    let monitor = Rc::new(monitors::DebugMonitor::new());
+    let (snapshots_dir, snapshot_interval) = (args.snapshots_dir, args.snapshot_interval);
+    let snapshot_monitor = snapshots_dir
+        .as_ref()
+        .map(|_| Rc::new(monitors::SnapshotMonitor::new(snapshot_interval)));
+    let monitor: Rc<dyn SimulationMonitor> = match snapshot_monitor.as_ref() {
+        Some(snapshot_monitor) => Rc::clone(snapshot_monitor) as Rc<dyn SimulationMonitor>,
+        None => Rc::new(monitors::DebugMonitor::new()),
+    };
 
-    let mut simulator = Simulator::new(
-        Fbas::new_generic_unconfigured(args.initial_n),
-        qsc,
-        Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
-    );
+    let fbas = Fbas::new_generic_unconfigured(args.initial_n);
+    let mut simulator = match args.seed {
+        Some(seed) => Simulator::new_seeded(fbas, qsc, monitor, seed),
+        None => Simulator::new(fbas, qsc, monitor),
+    };
+    if let Some(max_rounds) = args.max_rounds {
+        simulator = simulator.with_maximum_number_of_rounds(max_rounds);
+    }
     eprintln!("Starting simulation...");
-    simulator.simulate_global_reevaluation(args.initial_n);
+    simulator.simulate_global_reevaluation(args.max_rounds.unwrap_or(args.initial_n));
     simulator.simulate_growth(args.grow_by_n);
-    let fbas = simulator.finalize();
+    report_convergence(simulator.has_converged());
+    if let Some(path) = args.manifest_out_path.as_ref() {
+        eprintln!("Writing manifest to file...");
+        let manifest = simulator.manifest().with_qsc_parameters(&qsc_parameters);
+        manifest.to_json_file(path)?;
+    }
+    let mut fbas = simulator.finalize();
+    if let (Some(snapshot_monitor), Some(dir)) = (snapshot_monitor, snapshots_dir.as_ref()) {
+        eprintln!(
+            "Writing {} FBAS snapshots to directory...",
+            snapshot_monitor.snapshots_ref().len()
+        );
+        snapshot_monitor.write_to_dir(dir)?;
+    }
+    if args.synthetic_identities {
+        fbas = fbas.with_synthetic_identities(args.nodes_per_organization);
+        if let Some(path) = args.organizations_out_path.as_ref() {
+            let organizations = Organizations::from_organization_ids(&fbas);
+            eprintln!(
+                "Writing {} synthetic organizations to file...",
+                organizations.number_of_organizations()
+            );
+            std::fs::write(path, organizations.to_json_string_pretty())?;
+        }
+    }
     eprintln!("Finished simulation, dumping FBAS...");
     println!("{}", fbas.to_json_string_pretty());
     Ok(())
 }
+
+fn run_scenario(scenario_path: &Path, args: &Cli) -> CliResult {
+    let ignored_options = [
+        ("--initial", args.initial_n != 0),
+        ("--grow-by", args.grow_by_n != 0),
+        ("--synthetic-identities", args.synthetic_identities),
+        ("--nodes-per-organization", args.nodes_per_organization != 1),
+        ("--organizations-out", args.organizations_out_path.is_some()),
+        ("--snapshots-dir", args.snapshots_dir.is_some()),
+        ("--snapshot-interval", args.snapshot_interval != 1),
+    ];
+    for (option, used) in ignored_options.iter() {
+        if *used {
+            return Err(invalid_input(&format!(
+                "{} can't be used with scenarios; configure the scenario file instead.",
+                option
+            ))
+            .into());
+        }
+    }
+    let mut scenario = scenarios::Scenario::from_toml_file(scenario_path);
+    if args.seed.is_some() {
+        scenario.seed = args.seed;
+    }
+    if args.max_rounds.is_some() {
+        scenario.max_rounds = args.max_rounds;
+    }
+    eprintln!("Starting simulation...");
+    let result = scenario.run();
+    report_convergence(result.converged);
+    if let Some(path) = args.manifest_out_path.as_ref() {
+        eprintln!("Writing manifest to file...");
+        result.manifest.to_json_file(path)?;
+    }
+    eprintln!("Finished simulation, dumping FBAS...");
+    println!("{}", result.fbas.to_json_string_pretty());
+    for (analysis, analysis_result) in result.analysis_results.iter() {
+        eprintln!("{:?}: {}", analysis, analysis_result);
+    }
+    Ok(())
+}
+
+fn report_convergence(converged: bool) {
+    if !converged {
+        eprintln!(
+            "Warning: Quorum set configurations didn't always stabilize before the round limit!"
+        );
+    }
+}
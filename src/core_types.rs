@@ -1,5 +1,6 @@
 pub use bit_set::BitSet;
 use itertools::Itertools;
+use std::collections::BTreeMap;
 pub use std::collections::BTreeSet;
 pub use std::collections::HashMap;
 pub use std::collections::HashSet;
@@ -7,10 +8,18 @@ pub use std::collections::VecDeque;
 
 use serde::Serialize;
 
+#[cfg(feature = "quickcheck")]
+mod arbitrary;
+#[cfg(feature = "roaring")]
+mod roaring_node_id_set;
+
 pub type NodeId = usize; // internal and possibly different between runs
 pub type PublicKey = String;
 
+#[cfg(not(feature = "roaring"))]
 pub type NodeIdSet = BitSet;
+#[cfg(feature = "roaring")]
+pub use roaring_node_id_set::NodeIdSet;
 pub type NodeIdDeque = VecDeque<NodeId>;
 
 /// Create a **BitSet** from a list of elements.
@@ -30,12 +39,12 @@ macro_rules! bitset {
     (@single $($x:tt)*) => (());
     (@count $($rest:expr),*) => (<[()]>::len(&[$(bitset!(@single $rest)),*]));
 
-    () => { ::bit_set::BitSet::new(); };
+    () => { $crate::NodeIdSet::new(); };
     ($($key:expr,)+) => { bitset!($($key),+) };
     ($($key:expr),*) => {
         {
             let _cap = bitset!(@count $($key),*);
-            let mut _set = ::bit_set::BitSet::with_capacity(_cap);
+            let mut _set = $crate::NodeIdSet::with_capacity(_cap);
             $(
                 let _ = _set.insert($key);
             )*
@@ -98,6 +107,15 @@ impl Fbas {
     pub fn all_nodes(&self) -> NodeIdSet {
         (0..self.nodes.len()).collect()
     }
+    pub fn get_node_id(&self, public_key: &str) -> Option<NodeId> {
+        self.pk_to_id.get(public_key).copied()
+    }
+    pub fn metadata(&self, node_id: NodeId) -> &NodeMetadata {
+        &self.nodes[node_id].metadata
+    }
+    pub fn metadata_mut(&mut self, node_id: NodeId) -> &mut NodeMetadata {
+        &mut self.nodes[node_id].metadata
+    }
     pub fn is_quorum(&self, node_set: &NodeIdSet) -> bool {
         !node_set.is_empty()
             && node_set
@@ -110,6 +128,8 @@ impl Fbas {
 pub struct Node {
     pub(crate) public_key: PublicKey,
     pub(crate) quorum_set: QuorumSet,
+    pub(crate) name: Option<String>,
+    pub(crate) metadata: NodeMetadata,
 }
 impl Node {
     pub fn new(public_key: PublicKey) -> Self {
@@ -117,6 +137,8 @@ impl Node {
         Node {
             public_key,
             quorum_set,
+            name: None,
+            metadata: NodeMetadata::default(),
         }
     }
     pub fn is_quorum_slice(&self, node_set: &NodeIdSet) -> bool {
@@ -124,6 +146,55 @@ impl Node {
     }
 }
 
+/// Everything we know about a node besides its identity and quorum set, e.g., for grouping or
+/// filtering nodes and for metadata-aware quorum set configurators. Read from and written to
+/// stellarbeat.org JSON (`active`, `organizationId`, `geoData`, `isp`, `versionStr`);
+/// `continent` and `tags` are extensions of that format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeMetadata {
+    pub active: Option<bool>,
+    pub organization_id: Option<String>,
+    pub country: Option<String>,
+    pub continent: Option<String>,
+    pub isp: Option<String>,
+    pub version: Option<String>,
+    /// Custom key-value tags
+    pub tags: BTreeMap<String, String>,
+}
+impl NodeMetadata {
+    /// Whether the node is known to run at least `min_version`. Versions are compared
+    /// component-wise, using the first version-like word of each string, so that, e.g.,
+    /// "stellar-core 11.3.0 (5f7821d)" and "v11.3.0" both count as "11.3".
+    pub fn version_at_least(&self, min_version: &str) -> bool {
+        let version = self.version.as_deref().and_then(parse_version);
+        match (version, parse_version(min_version)) {
+            (Some(version), Some(min_version)) => version >= min_version,
+            _ => false,
+        }
+    }
+}
+/// Numeric components of the first version-like word in `version_str`, without trailing zeros
+/// (so that "11.3" and "11.3.0" compare as equal). Suffixes like "-rc1" are ignored.
+fn parse_version(version_str: &str) -> Option<Vec<u64>> {
+    version_str.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches('v');
+        let mut components = word
+            .split('.')
+            .map(|component| {
+                let digits: String = component
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                digits.parse().ok()
+            })
+            .collect::<Option<Vec<u64>>>()?;
+        while components.last() == Some(&0) {
+            components.pop();
+        }
+        Some(components)
+    })
+}
+
 #[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QuorumSet {
@@ -141,6 +212,20 @@ impl QuorumSet {
             inner_quorum_sets: vec![],
         }
     }
+    /// Quorum set that is satisfied by `threshold` of its `validators` and `inner_quorum_sets`
+    /// (e.g., for proposing configuration changes via `Analysis::with_hypothetical_qset`).
+    pub fn from_entries(
+        threshold: usize,
+        validators: Vec<NodeId>,
+        inner_quorum_sets: Vec<QuorumSet>,
+    ) -> Self {
+        QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets,
+        }
+    }
+    /// All nodes referenced anywhere in this quorum set, as validators or in inner quorum sets.
     pub fn contained_nodes(&self) -> NodeIdSet {
         let mut nodes: NodeIdSet = self.validators.iter().cloned().collect();
         for inner_quorum_set in self.inner_quorum_sets.iter() {
@@ -148,6 +233,18 @@ impl QuorumSet {
         }
         nodes
     }
+    /// Number of distinct nodes referenced anywhere in this quorum set.
+    pub fn number_of_contained_nodes(&self) -> usize {
+        self.contained_nodes().len()
+    }
+    /// Is `node_id` referenced anywhere in this quorum set?
+    pub fn contains_node(&self, node_id: NodeId) -> bool {
+        self.validators.contains(&node_id)
+            || self
+                .inner_quorum_sets
+                .iter()
+                .any(|qset| qset.contains_node(node_id))
+    }
     pub fn is_quorum_slice(&self, node_set: &NodeIdSet) -> bool {
         if self.threshold == 0 {
             false // badly configured quorum set
@@ -171,36 +268,97 @@ impl QuorumSet {
     /// Each valid quorum slice for this quorum set is a superset (i.e., equal to or a proper superset of)
     /// of at least one of the sets returned by this function.
     pub fn to_quorum_slices(&self) -> Vec<NodeIdSet> {
-        let mut subslice_groups: Vec<Vec<NodeIdSet>> = vec![];
-        subslice_groups.extend(
-            self.validators
-                .iter()
-                .map(|&node_id| vec![bitset![node_id]]),
+        if self.threshold == 0 {
+            return vec![]; // unsatisfiable
+        }
+        let subslice_groups = self.map_entries(
+            |validator| vec![bitset![validator]],
+            |qset| qset.to_quorum_slices(),
         );
-        subslice_groups.extend(
-            self.inner_quorum_sets
+        unions_of_combinations(subslice_groups, self.threshold)
+    }
+    /// Each node set that intersects all quorum slices of this quorum set (i.e., that is
+    /// "v-blocking" for this quorum set) is a superset of at least one of the sets returned by this
+    /// function. To block a quorum set, at least `n - threshold + 1` of its `n` entries need to be
+    /// blocked.
+    pub fn to_blocking_sets(&self) -> Vec<NodeIdSet> {
+        let number_of_entries = self.validators.len() + self.inner_quorum_sets.len();
+        if self.threshold == 0 || self.threshold > number_of_entries {
+            return vec![bitset![]]; // unsatisfiable, so blocked by anything
+        }
+        let subset_groups = self.map_entries(
+            |validator| vec![bitset![validator]],
+            |qset| qset.to_blocking_sets(),
+        );
+        unions_of_combinations(subset_groups, number_of_entries - self.threshold + 1)
+    }
+    /// Each intersection of two quorum slices of this quorum set is a superset of at least one of
+    /// the sets returned by this function. Any two slices share at least `2 * threshold - n` of
+    /// the `n` entries. Only correct if no node appears more than once in this quorum set, as
+    /// otherwise slices can also intersect via different entries.
+    pub fn to_splitting_sets(&self) -> Vec<NodeIdSet> {
+        let number_of_entries = self.validators.len() + self.inner_quorum_sets.len();
+        let number_of_shared_entries = (2 * self.threshold).saturating_sub(number_of_entries);
+        let subset_groups = self.map_entries(
+            |validator| vec![bitset![validator]],
+            |qset| qset.to_splitting_sets(),
+        );
+        unions_of_combinations(subset_groups, number_of_shared_entries)
+    }
+    /// Does any node appear more than once (as validator or in inner quorum sets)?
+    pub fn contains_duplicates(&self) -> bool {
+        let number_of_entries_with_duplicates = self.validators.len()
+            + self
+                .inner_quorum_sets
+                .iter()
+                .map(|qset| qset.number_of_contained_nodes())
+                .sum::<usize>();
+        self.inner_quorum_sets
+            .iter()
+            .any(|qset| qset.contains_duplicates())
+            || number_of_entries_with_duplicates != self.number_of_contained_nodes()
+    }
+    /// One group of node sets per entry (validator or inner quorum set), in entry order.
+    fn map_entries<F, G>(&self, map_validator: F, map_inner_quorum_set: G) -> Vec<Vec<NodeIdSet>>
+    where
+        F: Fn(NodeId) -> Vec<NodeIdSet>,
+        G: Fn(&QuorumSet) -> Vec<NodeIdSet>,
+    {
+        let mut groups: Vec<Vec<NodeIdSet>> = vec![];
+        groups.extend(
+            self.validators
                 .iter()
-                .map(|qset| qset.to_quorum_slices()),
+                .map(|&node_id| map_validator(node_id)),
         );
-        subslice_groups
-            .into_iter()
-            .combinations(self.threshold)
-            .map(|group_combination| {
-                group_combination
-                    .into_iter()
-                    .map(|subslice_group| subslice_group.into_iter())
-                    .multi_cartesian_product()
-                    .map(|subslice_combination| {
-                        let mut slice = bitset![];
-                        for node_set in subslice_combination.into_iter() {
-                            slice.union_with(&node_set);
-                        }
-                        slice
-                    })
-                    .collect()
-            })
-            .concat()
+        groups.extend(self.inner_quorum_sets.iter().map(map_inner_quorum_set));
+        groups
+    }
+}
+
+/// For each combination of `k` groups, all unions of one node set from each group.
+fn unions_of_combinations(groups: Vec<Vec<NodeIdSet>>, k: usize) -> Vec<NodeIdSet> {
+    if k == 0 {
+        // `multi_cartesian_product` of nothing yields nothing, but we want the empty union
+        return vec![bitset![]];
     }
+    groups
+        .into_iter()
+        .combinations(k)
+        .map(|group_combination| {
+            group_combination
+                .into_iter()
+                .map(|subslice_group| subslice_group.into_iter())
+                .multi_cartesian_product()
+                .map(|subslice_combination| {
+                    let mut slice = bitset![];
+                    for node_set in subslice_combination.into_iter() {
+                        slice.union_with(&node_set);
+                    }
+                    slice
+                })
+                .collect()
+        })
+        .concat()
 }
 
 #[derive(Debug, PartialEq)]
@@ -211,7 +369,7 @@ pub struct Organizations<'fbas> {
     // for ensuring fbas remains stable + serializeability via Serialize trait
     pub(crate) fbas: &'fbas Fbas,
 }
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Organization {
     pub(crate) name: String,
     pub(crate) validators: Vec<NodeId>,
@@ -248,6 +406,109 @@ impl<'fbas> Organizations<'fbas> {
     pub fn number_of_organizations(&self) -> usize {
         self.organizations.len()
     }
+    /// Groups nodes by organization ID (see `NodeMetadata`), naming each organization after its
+    /// ID. Nodes without organization ID aren't part of any organization.
+    pub fn from_organization_ids(fbas: &'fbas Fbas) -> Self {
+        let mut groups_by_organization_id: BTreeMap<&str, Vec<NodeId>> = BTreeMap::new();
+        for (node_id, node) in fbas.nodes.iter().enumerate() {
+            if let Some(organization_id) = &node.metadata.organization_id {
+                groups_by_organization_id
+                    .entry(organization_id)
+                    .or_default()
+                    .push(node_id);
+            }
+        }
+        let organizations = groups_by_organization_id
+            .into_iter()
+            .map(|(organization_id, validators)| Organization {
+                name: organization_id.to_string(),
+                validators,
+            })
+            .collect();
+        Organizations::new(organizations, fbas)
+    }
+    /// Guesses which nodes are mirrors run by the same organization (e.g., the three validators
+    /// that many organizations run for redundancy), for when no organizations file is available.
+    /// Nodes are grouped if they have the same organization ID, or, if they have no organization
+    /// ID, if they have identical quorum sets and names with the same prefix (first word, minus
+    /// trailing digits and punctuation; "SDF 1" and "SDF 2" both become "SDF"). Only groups with
+    /// more than one node are returned as organizations.
+    pub fn from_mirror_heuristic(fbas: &'fbas Fbas) -> Self {
+        let mut groups_by_organization_id: BTreeMap<&str, Vec<NodeId>> = BTreeMap::new();
+        let mut groups_by_name_and_quorum_set: BTreeMap<(&str, &QuorumSet), Vec<NodeId>> =
+            BTreeMap::new();
+        for (node_id, node) in fbas.nodes.iter().enumerate() {
+            if let Some(organization_id) = &node.metadata.organization_id {
+                groups_by_organization_id
+                    .entry(organization_id)
+                    .or_default()
+                    .push(node_id);
+            } else if let Some(name_prefix) = node.name.as_deref().and_then(mirror_name_prefix) {
+                groups_by_name_and_quorum_set
+                    .entry((name_prefix, &node.quorum_set))
+                    .or_default()
+                    .push(node_id);
+            }
+        }
+        let mut organizations: Vec<Organization> = groups_by_organization_id
+            .into_iter()
+            .filter(|(_, validators)| validators.len() > 1)
+            .map(|(organization_id, validators)| Organization {
+                name: fbas.nodes[validators[0]]
+                    .name
+                    .as_deref()
+                    .and_then(mirror_name_prefix)
+                    .unwrap_or(organization_id)
+                    .to_string(),
+                validators,
+            })
+            .collect();
+        organizations.extend(
+            groups_by_name_and_quorum_set
+                .into_iter()
+                .filter(|(_, validators)| validators.len() > 1)
+                .map(|((name_prefix, _), validators)| Organization {
+                    name: name_prefix.to_string(),
+                    validators,
+                }),
+        );
+        Organizations::new(organizations, fbas)
+    }
+}
+
+fn mirror_name_prefix(name: &str) -> Option<&str> {
+    let prefix = name
+        .split_whitespace()
+        .next()?
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c.is_ascii_punctuation());
+    if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix)
+    }
+}
+
+/// Numeric weights of nodes (e.g., stake proxies, uptimes or trust scores), indexed by node ID.
+/// Nodes without an assigned weight have weight 0. Weights are assumed to be non-negative.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeWeights {
+    pub(crate) weights: Vec<f64>,
+}
+impl NodeWeights {
+    pub fn new(weights: Vec<f64>) -> Self {
+        assert!(
+            weights.iter().all(|&weight| weight >= 0.),
+            "Node weights must be non-negative!"
+        );
+        NodeWeights { weights }
+    }
+    pub fn get(&self, node_id: NodeId) -> f64 {
+        self.weights.get(node_id).copied().unwrap_or(0.)
+    }
+    /// Sum of the weights of all nodes in `node_set`.
+    pub fn total(&self, node_set: &NodeIdSet) -> f64 {
+        node_set.iter().map(|node_id| self.get(node_id)).sum()
+    }
 }
 
 #[cfg(test)]
@@ -263,6 +524,8 @@ mod tests {
                 validators: validators.iter().copied().collect(),
                 inner_quorum_sets: vec![],
             },
+            name: None,
+            metadata: NodeMetadata::default(),
         }
     }
 
@@ -289,6 +552,21 @@ mod tests {
         fbas.add_node(node);
     }
 
+    #[test]
+    fn version_at_least_compares_version_components() {
+        let with_version = |version: &str| NodeMetadata {
+            version: Some(String::from(version)),
+            ..Default::default()
+        };
+        assert!(with_version("stellar-core 11.3.0 (5f7821d)").version_at_least("11.3"));
+        assert!(with_version("v11.4.0").version_at_least("11.3.0"));
+        assert!(with_version("11.10.1").version_at_least("11.9"));
+        assert!(with_version("12.0.0-rc1").version_at_least("12"));
+        assert!(!with_version("stellar-core 11.1.0-unstablerc1").version_at_least("11.3.0"));
+        assert!(!with_version("unknown").version_at_least("1"));
+        assert!(!NodeMetadata::default().version_at_least("0"));
+    }
+
     #[test]
     fn is_quorum_slice_if_not_quorum_slice() {
         let node = test_node(&[0, 1, 2], 3);
@@ -396,4 +674,157 @@ mod tests {
         let actual = quorum_set.to_quorum_slices();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn quorum_set_to_blocking_sets() {
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![1, 2, 3],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![4, 5],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        let expected = bitsetvec![
+            [0, 1, 2],
+            [0, 1, 3],
+            [0, 2, 3],
+            [0, 4, 5],
+            [1, 2, 4, 5],
+            [1, 3, 4, 5],
+            [2, 3, 4, 5]
+        ];
+        let mut actual = quorum_set.to_blocking_sets();
+        actual.sort();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn quorum_set_to_splitting_sets() {
+        let quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![0],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 3,
+                    validators: vec![1, 2, 3, 4],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![5, 6],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        // all 3 entries are shared, but only the inner set with threshold 3 of 4 always intersects
+        let expected = bitsetvec![
+            [0, 1, 2],
+            [0, 1, 3],
+            [0, 1, 4],
+            [0, 2, 3],
+            [0, 2, 4],
+            [0, 3, 4]
+        ];
+        let mut actual = quorum_set.to_splitting_sets();
+        actual.sort();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn quorum_set_contains_duplicates() {
+        let inner_quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![1, 2],
+            inner_quorum_sets: vec![],
+        };
+        let without_duplicates = QuorumSet {
+            threshold: 2,
+            validators: vec![0],
+            inner_quorum_sets: vec![inner_quorum_set.clone()],
+        };
+        let with_duplicates = QuorumSet {
+            threshold: 2,
+            validators: vec![1],
+            inner_quorum_sets: vec![inner_quorum_set],
+        };
+        assert!(!without_duplicates.contains_duplicates());
+        assert!(with_duplicates.contains_duplicates());
+    }
+
+    #[test]
+    fn quorum_set_member_utilities() {
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 1,
+                validators: vec![1, 4],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        assert_eq!(bitset![0, 1, 4], quorum_set.contained_nodes());
+        assert_eq!(3, quorum_set.number_of_contained_nodes());
+        assert!(quorum_set.contains_node(0));
+        assert!(quorum_set.contains_node(4));
+        assert!(!quorum_set.contains_node(2));
+    }
+
+    #[test]
+    fn mirror_heuristic_groups_by_organization_id_or_name_and_quorum_set() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "name": "LOBSTR 1 (Europe)",
+                "organizationId": "ad7337"
+            },
+            {
+                "publicKey": "n1",
+                "name": "LOBSTR 2 (Asia)",
+                "organizationId": "ad7337"
+            },
+            {
+                "publicKey": "n2",
+                "name": "keybase1",
+                "quorumSet": { "threshold": 1, "validators": ["n0"] }
+            },
+            {
+                "publicKey": "n3",
+                "name": "keybase2",
+                "quorumSet": { "threshold": 1, "validators": ["n0"] }
+            },
+            {
+                "publicKey": "n4",
+                "name": "keybase3",
+                "quorumSet": { "threshold": 1, "validators": ["n1"] }
+            },
+            {
+                "publicKey": "n5",
+                "name": "lonely 1",
+                "organizationId": "c0ffee"
+            }
+        ]"#,
+        );
+        let organizations = Organizations::from_mirror_heuristic(&fbas);
+        let expected = vec![
+            Organization {
+                name: String::from("LOBSTR"),
+                validators: vec![0, 1],
+            },
+            Organization {
+                name: String::from("keybase"),
+                validators: vec![2, 3],
+            },
+        ];
+        assert_eq!(expected, organizations.organizations);
+    }
 }
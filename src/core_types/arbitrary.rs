@@ -0,0 +1,198 @@
+//! `quickcheck::Arbitrary` implementations for property-based testing, enabled via the
+//! `quickcheck` feature. Generated FBASs are kept small (at most `MAX_ARBITRARY_FBAS_SIZE` nodes)
+//! so that even exponential analyses finish quickly.
+
+use super::*;
+use quickcheck::{Arbitrary, Gen};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp;
+
+pub const MAX_ARBITRARY_FBAS_SIZE: usize = 8;
+const MAX_QUORUM_SET_DEPTH: usize = 2;
+
+impl Arbitrary for QuorumSet {
+    /// Quorum set referencing node IDs below `g.size()` (but at least 1).
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let number_of_nodes = cmp::max(1, g.size());
+        arbitrary_quorum_set(g, number_of_nodes, MAX_QUORUM_SET_DEPTH)
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let mut candidates = vec![];
+        for i in 0..self.validators.len() {
+            let mut candidate = self.clone();
+            candidate.validators.remove(i);
+            candidates.push(candidate);
+        }
+        for i in 0..self.inner_quorum_sets.len() {
+            let mut candidate = self.clone();
+            candidate.inner_quorum_sets.remove(i);
+            candidates.push(candidate);
+        }
+        if self.threshold > 1 {
+            let mut candidate = self.clone();
+            candidate.threshold -= 1;
+            candidates.push(candidate);
+        }
+        Box::new(candidates.into_iter().map(well_formed))
+    }
+}
+
+impl Arbitrary for Node {
+    /// Node with a generic public key and a quorum set as in `QuorumSet::arbitrary`.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let mut node = Node::new(format!("n{}", g.gen::<u16>()));
+        node.quorum_set = QuorumSet::arbitrary(g);
+        node
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let node = self.clone();
+        Box::new(self.quorum_set.shrink().map(move |quorum_set| Node {
+            quorum_set,
+            ..node.clone()
+        }))
+    }
+}
+
+impl Arbitrary for Fbas {
+    /// FBAS of 1 to `MAX_ARBITRARY_FBAS_SIZE` nodes with public keys "n0", "n1", ... whose
+    /// quorum sets only reference nodes of the same FBAS. Like in Stellar, each node lists itself
+    /// as a validator.
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let number_of_nodes = g.gen_range(1, MAX_ARBITRARY_FBAS_SIZE + 1);
+        let mut fbas = Fbas::new();
+        for node_id in 0..number_of_nodes {
+            let mut node = Node::new(format!("n{}", node_id));
+            node.quorum_set = arbitrary_quorum_set(g, number_of_nodes, MAX_QUORUM_SET_DEPTH);
+            if let Err(i) = node.quorum_set.validators.binary_search(&node_id) {
+                node.quorum_set.validators.insert(i, node_id);
+                node.quorum_set.threshold = cmp::max(1, node.quorum_set.threshold);
+            }
+            fbas.add_node(node);
+        }
+        fbas
+    }
+    /// Candidates with one node less each (see `Fbas::shrunken_by`), with thresholds lowered
+    /// where necessary.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let fbas = self.clone();
+        let removable = if fbas.number_of_nodes() > 1 {
+            0..fbas.number_of_nodes()
+        } else {
+            0..0
+        };
+        Box::new(removable.map(move |removed| {
+            let mut candidate = Fbas::shrunken_by(&fbas, |node_id| node_id != removed).0;
+            for node in candidate.nodes.iter_mut() {
+                node.quorum_set = well_formed(node.quorum_set.clone());
+            }
+            candidate
+        }))
+    }
+}
+
+/// Well-formed quorum set: `1 <= threshold <= #entries`, unless it has no entries at all (like
+/// the quorum sets of unconfigured nodes); inner quorum sets always have entries.
+fn arbitrary_quorum_set<G: Gen>(g: &mut G, number_of_nodes: usize, depth: usize) -> QuorumSet {
+    let number_of_inner_quorum_sets = if depth > 0 { g.gen_range(0, 3) } else { 0 };
+    let inner_quorum_sets: Vec<QuorumSet> = (0..number_of_inner_quorum_sets)
+        .map(|_| arbitrary_quorum_set(g, number_of_nodes, depth - 1))
+        .filter(|inner_quorum_set| inner_quorum_set.threshold > 0)
+        .collect();
+
+    let number_of_validators = g.gen_range(0, number_of_nodes + 1);
+    let mut validators: Vec<NodeId> = (0..number_of_nodes).collect();
+    validators.shuffle(g);
+    validators.truncate(number_of_validators);
+    validators.sort_unstable();
+
+    let number_of_entries = validators.len() + inner_quorum_sets.len();
+    let threshold = if number_of_entries > 0 {
+        g.gen_range(1, number_of_entries + 1)
+    } else {
+        0
+    };
+    QuorumSet {
+        threshold,
+        validators,
+        inner_quorum_sets,
+    }
+}
+
+/// Restores well-formedness (see `arbitrary_quorum_set`) after nodes have been removed.
+fn well_formed(quorum_set: QuorumSet) -> QuorumSet {
+    let inner_quorum_sets: Vec<QuorumSet> = quorum_set
+        .inner_quorum_sets
+        .into_iter()
+        .map(well_formed)
+        .filter(|inner_quorum_set| inner_quorum_set.threshold > 0)
+        .collect();
+    let number_of_entries = quorum_set.validators.len() + inner_quorum_sets.len();
+    QuorumSet {
+        threshold: cmp::min(quorum_set.threshold, number_of_entries),
+        validators: quorum_set.validators,
+        inner_quorum_sets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::*;
+    use quickcheck::{QuickCheck, TestResult};
+
+    fn is_minimal_quorum(fbas: &Fbas, node_set: &NodeIdSet) -> bool {
+        let members: Vec<NodeId> = node_set.iter().collect();
+        // all proper, non-empty subsets
+        fbas.is_quorum(node_set)
+            && (1..(1usize << members.len()) - 1).all(|mask| {
+                let subset: NodeIdSet = members
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, &node_id)| node_id)
+                    .collect();
+                !fbas.is_quorum(&subset)
+            })
+    }
+
+    #[test]
+    fn arbitrary_fbass_are_small_and_consistent() {
+        fn prop(fbas: Fbas) -> bool {
+            fbas.number_of_nodes() <= MAX_ARBITRARY_FBAS_SIZE
+                && fbas.nodes.iter().all(|node| {
+                    node.quorum_set
+                        .contained_nodes()
+                        .iter()
+                        .all(|node_id| node_id < fbas.number_of_nodes())
+                })
+        }
+        QuickCheck::new().quickcheck(prop as fn(Fbas) -> bool);
+    }
+
+    #[test]
+    fn minimal_quorums_are_minimal_quorums() {
+        fn prop(fbas: Fbas) -> bool {
+            find_minimal_quorums(&fbas)
+                .iter()
+                .all(|quorum| is_minimal_quorum(&fbas, quorum))
+        }
+        QuickCheck::new().quickcheck(prop as fn(Fbas) -> bool);
+    }
+
+    #[test]
+    fn quorum_intersection_checks_agree() {
+        fn prop(fbas: Fbas) -> TestResult {
+            let all_nodes = fbas.all_nodes();
+            if find_unsatisfiable_nodes(&all_nodes, &fbas).0.is_empty() {
+                // no quorums at all
+                return TestResult::discard();
+            }
+            let minimal_quorums = find_minimal_quorums(&fbas);
+            TestResult::from_bool(
+                all_intersect(&minimal_quorums) == find_nonintersecting_quorums(&fbas).is_none(),
+            )
+        }
+        QuickCheck::new().quickcheck(prop as fn(Fbas) -> TestResult);
+    }
+}
@@ -0,0 +1,139 @@
+//! Alternative `NodeIdSet` backend based on Roaring bitmaps, enabled via the `roaring` feature.
+//! Mirrors the subset of the `bit_set::BitSet` API used throughout this crate, so that the rest
+//! of the code doesn't need to know which backend is in use.
+//!
+//! For the node counts of today's networks, `BitSet` tends to be faster; Roaring bitmaps pay off
+//! for large, sparse node ID ranges.
+
+use super::NodeId;
+use roaring::RoaringBitmap;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+
+#[derive(Clone, Default, PartialEq)]
+pub struct NodeIdSet(RoaringBitmap);
+
+impl NodeIdSet {
+    pub fn new() -> Self {
+        NodeIdSet(RoaringBitmap::new())
+    }
+    /// Roaring bitmaps grow as needed; `nbits` is ignored.
+    pub fn with_capacity(_nbits: usize) -> Self {
+        Self::new()
+    }
+    pub fn insert(&mut self, value: NodeId) -> bool {
+        self.0.insert(to_u32(value))
+    }
+    pub fn remove(&mut self, value: NodeId) -> bool {
+        self.0.remove(to_u32(value))
+    }
+    pub fn contains(&self, value: NodeId) -> bool {
+        self.0.contains(to_u32(value))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.0.iter().map(to_node_id)
+    }
+    pub fn union_with(&mut self, other: &Self) {
+        self.0 |= &other.0;
+    }
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.0 &= &other.0;
+    }
+    pub fn difference_with(&mut self, other: &Self) {
+        self.0 -= &other.0;
+    }
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        self.0 ^= &other.0;
+    }
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.is_subset(&other.0)
+    }
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.0.is_superset(&other.0)
+    }
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.is_disjoint(&other.0)
+    }
+    /// Same as `self.intersection(other).count()` but without materializing the intersection.
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.0.intersection_len(&other.0) as usize
+    }
+}
+
+fn to_u32(value: NodeId) -> u32 {
+    assert!(
+        value <= u32::MAX as NodeId,
+        "Node ID {} too big for Roaring bitmaps!",
+        value
+    );
+    value as u32
+}
+fn to_node_id(value: u32) -> NodeId {
+    value as NodeId
+}
+
+impl Eq for NodeIdSet {}
+
+// Same semantics as for `BitSet`: lexicographic comparison of the ordered elements.
+impl Ord for NodeIdSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+impl PartialOrd for NodeIdSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Hash for NodeIdSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in self.0.iter() {
+            value.hash(state);
+        }
+    }
+}
+impl fmt::Debug for NodeIdSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<NodeId> for NodeIdSet {
+    fn from_iter<I: IntoIterator<Item = NodeId>>(iter: I) -> Self {
+        let mut node_set = NodeIdSet::new();
+        node_set.extend(iter);
+        node_set
+    }
+}
+impl Extend<NodeId> for NodeIdSet {
+    fn extend<I: IntoIterator<Item = NodeId>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+impl IntoIterator for NodeIdSet {
+    type Item = NodeId;
+    type IntoIter = std::iter::Map<roaring::bitmap::IntoIter, fn(u32) -> NodeId>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter().map(to_node_id as fn(u32) -> NodeId)
+    }
+}
+impl<'a> IntoIterator for &'a NodeIdSet {
+    type Item = NodeId;
+    type IntoIter = std::iter::Map<roaring::bitmap::Iter<'a>, fn(u32) -> NodeId>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().map(to_node_id as fn(u32) -> NodeId)
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+// Conversions from and to adjacency matrices, for interoperating with numerical tools (e.g.,
+// for spectral analyses). Entry (i, j) is 1 if there is a link from `i` to `j` and 0 otherwise.
+impl Graph {
+    /// Dense adjacency matrix, as a list of rows. Takes space quadratic in the number of nodes;
+    /// use `to_adjacency_triplets` for bigger graphs.
+    pub fn to_adjacency_matrix(&self) -> Vec<Vec<u8>> {
+        let n = self.number_of_nodes();
+        self.outlinks
+            .iter()
+            .map(|outlinks| {
+                let mut row = vec![0; n];
+                for &j in outlinks.iter() {
+                    row[j] = 1;
+                }
+                row
+            })
+            .collect()
+    }
+    /// Graph from a dense, square adjacency matrix (list of rows); all non-zero entries count
+    /// as links.
+    pub fn from_adjacency_matrix(matrix: &[Vec<u8>]) -> Self {
+        let n = matrix.len();
+        let outlinks = matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                assert_eq!(
+                    n,
+                    row.len(),
+                    "Row {} of the adjacency matrix has the wrong length!",
+                    i
+                );
+                (0..n).filter(|&j| row[j] != 0).collect()
+            })
+            .collect();
+        Self::new(outlinks)
+    }
+    /// Sparse adjacency matrix in triplet (coordinate) form: one `(row, column, value)` entry
+    /// per link, sorted by row and column.
+    pub fn to_adjacency_triplets(&self) -> Vec<(NodeId, NodeId, u8)> {
+        let mut triplets: Vec<(NodeId, NodeId, u8)> = self
+            .outlinks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, outlinks)| outlinks.iter().map(move |&j| (i, j, 1)))
+            .collect();
+        triplets.sort_unstable();
+        triplets.dedup();
+        triplets
+    }
+    /// Graph of `n` nodes from a sparse adjacency matrix in triplet form (see
+    /// `to_adjacency_triplets`); entries with value 0 are ignored.
+    pub fn from_adjacency_triplets(n: usize, triplets: &[(NodeId, NodeId, u8)]) -> Self {
+        let mut outlinks: Vec<Vec<NodeId>> = vec![vec![]; n];
+        for &(i, j, _) in triplets.iter().filter(|&&(_, _, value)| value != 0) {
+            assert!(
+                i < n && j < n,
+                "Adjacency matrix entry ({}, {}) out of range for {} nodes!",
+                i,
+                j,
+                n
+            );
+            outlinks[i].push(j);
+        }
+        for node_outlinks in outlinks.iter_mut() {
+            node_outlinks.sort_unstable();
+            node_outlinks.dedup();
+        }
+        Self::new(outlinks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_adjacency_matrix_round_trip() {
+        let graph = Graph::new(vec![vec![1, 2], vec![2], vec![]]);
+        let expected = vec![vec![0, 1, 1], vec![0, 0, 1], vec![0, 0, 0]];
+        let actual = graph.to_adjacency_matrix();
+        assert_eq!(expected, actual);
+        assert_eq!(graph, Graph::from_adjacency_matrix(&actual));
+    }
+
+    #[test]
+    fn sparse_adjacency_matrix_round_trip() {
+        let graph = Graph::new_random_scale_free(20, 3, 2);
+        let triplets = graph.to_adjacency_triplets();
+
+        let mut expected = graph;
+        for outlinks in expected.outlinks.iter_mut() {
+            outlinks.sort_unstable();
+            outlinks.dedup();
+        }
+        let number_of_links: usize = expected.outlinks.iter().map(|x| x.len()).sum();
+        assert_eq!(number_of_links, triplets.len());
+        assert_eq!(expected, Graph::from_adjacency_triplets(20, &triplets));
+    }
+
+    #[test]
+    fn dense_and_sparse_forms_agree() {
+        let graph = Graph::new_random_small_world(10, 4, 0.2);
+        let from_dense = Graph::from_adjacency_matrix(&graph.to_adjacency_matrix());
+        let from_sparse = Graph::from_adjacency_triplets(10, &graph.to_adjacency_triplets());
+        assert_eq!(from_dense, from_sparse);
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 of the adjacency matrix has the wrong length!")]
+    fn from_adjacency_matrix_panics_if_not_square() {
+        Graph::from_adjacency_matrix(&[vec![0, 1], vec![1]]);
+    }
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+use std::cmp;
+use std::f64::consts::PI;
+
+/// 2D coordinates of a node in a graph layout, within the unit square.
+pub type Position = (f64, f64);
+
+const GOLDEN_ANGLE: f64 = PI * 0.763_932_022_500_210_3; // (3 - sqrt(5)) * PI
+const INITIAL_TEMPERATURE: f64 = 0.1;
+
+impl Graph {
+    /// Force-directed layout (Fruchterman-Reingold): neighboring nodes attract each other, all
+    /// nodes repel each other. Link directions are ignored. Returns one position per node,
+    /// scaled to fit into the unit square (keeping the aspect ratio).
+    ///
+    /// The layout is deterministic (nodes start out on a spiral in node ID order), so that
+    /// repeated exports of the same graph look the same. Each iteration takes time quadratic in
+    /// the number of nodes; a few hundred iterations are usually enough.
+    pub fn force_directed_layout(&self, iterations: usize) -> Vec<Position> {
+        let n = self.number_of_nodes();
+        let neighbors = self.undirected_neighbors();
+        // ideal distance between nodes
+        let k = (1. / cmp::max(n, 1) as f64).sqrt();
+
+        let mut positions: Vec<Position> = (0..n)
+            .map(|i| {
+                let radius = 0.5 * ((i as f64 + 0.5) / n as f64).sqrt();
+                let angle = GOLDEN_ANGLE * i as f64;
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+
+        for iteration in 0..iterations {
+            let mut displacements = vec![(0., 0.); n];
+            for i in 0..n {
+                for j in i + 1..n {
+                    let (dx, dy, distance) = difference(positions[i], positions[j]);
+                    let force = k * k / distance;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    displacements[i].0 += fx;
+                    displacements[i].1 += fy;
+                    displacements[j].0 -= fx;
+                    displacements[j].1 -= fy;
+                }
+                for &j in neighbors[i].iter().filter(|&&j| j > i) {
+                    let (dx, dy, distance) = difference(positions[i], positions[j]);
+                    let force = distance * distance / k;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    displacements[i].0 -= fx;
+                    displacements[i].1 -= fy;
+                    displacements[j].0 += fx;
+                    displacements[j].1 += fy;
+                }
+            }
+            // linear cooling; limits how far nodes can move per iteration
+            let temperature = INITIAL_TEMPERATURE * (1. - iteration as f64 / iterations as f64);
+            for (position, (dx, dy)) in positions.iter_mut().zip(displacements) {
+                let length = (dx * dx + dy * dy).sqrt();
+                if length > 0. {
+                    let step = length.min(temperature);
+                    position.0 += dx / length * step;
+                    position.1 += dy / length * step;
+                }
+            }
+        }
+        fit_into_unit_square(positions)
+    }
+}
+
+/// Difference vector between `a` and `b`, and its length (never 0, to avoid divisions by 0).
+fn difference(a: Position, b: Position) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = (dx * dx + dy * dy).sqrt().max(1e-9);
+    (dx, dy, distance)
+}
+
+/// Scales and shifts `positions` so that they span the unit square along their longer side and
+/// are centered along the shorter side.
+fn fit_into_unit_square(positions: Vec<Position>) -> Vec<Position> {
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for &(x, y) in positions.iter() {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    let scale = (max_x - min_x).max(max_y - min_y);
+    if scale <= 0. {
+        return vec![(0.5, 0.5); positions.len()];
+    }
+    let offset_x = (1. - (max_x - min_x) / scale) / 2.;
+    let offset_y = (1. - (max_y - min_y) / scale) / 2.;
+    positions
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                offset_x + (x - min_x) / scale,
+                offset_y + (y - min_y) / scale,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: Position, b: Position) -> f64 {
+        difference(a, b).2
+    }
+
+    #[test]
+    fn layout_is_deterministic_and_fits_into_unit_square() {
+        let graph = Graph::new_random_scale_free(30, 3, 2);
+        let positions = graph.force_directed_layout(100);
+        assert_eq!(30, positions.len());
+        assert_eq!(positions, graph.force_directed_layout(100));
+        assert!(positions
+            .iter()
+            .all(|&(x, y)| (0. ..=1.).contains(&x) && (0. ..=1.).contains(&y)));
+    }
+
+    #[test]
+    fn layout_of_trivial_graphs() {
+        assert!(Graph::new(vec![]).force_directed_layout(10).is_empty());
+        assert_eq!(
+            vec![(0.5, 0.5)],
+            Graph::new(vec![vec![]]).force_directed_layout(10)
+        );
+    }
+
+    #[test]
+    fn layout_keeps_clusters_together() {
+        // two full meshes of 5 nodes each, connected by a single link
+        let mut outlinks: Vec<Vec<NodeId>> = (0..10)
+            .map(|i| {
+                let cluster_start = i / 5 * 5;
+                (cluster_start..cluster_start + 5)
+                    .filter(|&j| j != i)
+                    .collect()
+            })
+            .collect();
+        outlinks[0].push(5);
+        let positions = Graph::new(outlinks).force_directed_layout(200);
+
+        let max_distance_within_clusters = (0..10)
+            .flat_map(|i| (i / 5 * 5..i / 5 * 5 + 5).map(move |j| (i, j)))
+            .map(|(i, j)| distance(positions[i], positions[j]))
+            .fold(0., f64::max);
+        let min_distance_between_clusters = (0..5)
+            .flat_map(|i| (5..10).map(move |j| (i, j)))
+            .filter(|&(i, j)| (i, j) != (0, 5))
+            .map(|(i, j)| distance(positions[i], positions[j]))
+            .fold(f64::MAX, f64::min);
+        assert!(max_distance_within_clusters < min_distance_between_clusters);
+    }
+}
@@ -0,0 +1,564 @@
+use super::*;
+
+use std::collections::BTreeMap;
+
+// Structural metrics for characterizing (generated or imported) graphs. Unless noted otherwise,
+// edges are treated as undirected, i.e., `i` and `j` are neighbors if there is a link from `i`
+// to `j` or from `j` to `i`. Self-links are ignored.
+impl Graph {
+    /// Local clustering coefficient of each node, i.e., the fraction of pairs of neighbors that
+    /// are neighbors themselves. Nodes with fewer than 2 neighbors get a coefficient of 0.
+    pub fn clustering_coefficients(&self) -> Vec<f64> {
+        let neighbors = self.undirected_neighbors();
+        let neighbor_sets: Vec<NodeIdSet> = neighbors
+            .iter()
+            .map(|x| x.iter().copied().collect())
+            .collect();
+        neighbors
+            .iter()
+            .zip(neighbor_sets.iter())
+            .map(|(node_neighbors, node_neighbor_set)| {
+                let k = node_neighbors.len();
+                if k < 2 {
+                    0.
+                } else {
+                    let links_between_neighbors: usize = node_neighbors
+                        .iter()
+                        .map(|&j| {
+                            neighbors[j]
+                                .iter()
+                                .filter(|&&l| l > j && node_neighbor_set.contains(l))
+                                .count()
+                        })
+                        .sum();
+                    2. * links_between_neighbors as f64 / (k * (k - 1)) as f64
+                }
+            })
+            .collect()
+    }
+    /// Mean of all local clustering coefficients (0 for empty graphs).
+    pub fn average_clustering_coefficient(&self) -> f64 {
+        mean(&self.clustering_coefficients())
+    }
+    /// Mean length of shortest paths between all pairs of distinct nodes that are connected by a
+    /// path; unreachable pairs are ignored. Returns 0 if there are no such pairs.
+    pub fn average_shortest_path_length(&self) -> f64 {
+        let neighbors = self.undirected_neighbors();
+        let (mut sum, mut count) = (0, 0);
+        for source in 0..self.number_of_nodes() {
+            for distance in bfs_distances(&neighbors, source).into_iter().flatten() {
+                if distance > 0 {
+                    sum += distance;
+                    count += 1;
+                }
+            }
+        }
+        if count == 0 {
+            0.
+        } else {
+            sum as f64 / count as f64
+        }
+    }
+    /// Length of the longest shortest path between any two nodes that are connected by a path.
+    pub fn diameter(&self) -> usize {
+        let neighbors = self.undirected_neighbors();
+        (0..self.number_of_nodes())
+            .filter_map(|source| {
+                bfs_distances(&neighbors, source)
+                    .into_iter()
+                    .flatten()
+                    .max()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    /// Betweenness centrality of each node, i.e., the number of shortest paths between other
+    /// nodes that pass through it (fractionally, if there are several shortest paths between a
+    /// pair of nodes). Unnormalized; each unordered pair of nodes is counted once.
+    /// Uses Brandes' algorithm.
+    pub fn betweenness_centralities(&self) -> Vec<f64> {
+        let neighbors = self.undirected_neighbors();
+        let n = self.number_of_nodes();
+        let mut centralities = vec![0.; n];
+
+        for source in 0..n {
+            // single-source shortest paths, with nodes in order of non-decreasing distance
+            let mut visit_order: Vec<NodeId> = Vec::with_capacity(n);
+            let mut predecessors: Vec<Vec<NodeId>> = vec![vec![]; n];
+            let mut number_of_paths: Vec<f64> = vec![0.; n];
+            let mut distances: Vec<Option<usize>> = vec![None; n];
+            number_of_paths[source] = 1.;
+            distances[source] = Some(0);
+
+            let mut queue = NodeIdDeque::new();
+            queue.push_back(source);
+            while let Some(i) = queue.pop_front() {
+                visit_order.push(i);
+                let next_distance = distances[i].unwrap() + 1;
+                for &j in neighbors[i].iter() {
+                    if distances[j].is_none() {
+                        distances[j] = Some(next_distance);
+                        queue.push_back(j);
+                    }
+                    if distances[j] == Some(next_distance) {
+                        number_of_paths[j] += number_of_paths[i];
+                        predecessors[j].push(i);
+                    }
+                }
+            }
+
+            // accumulate dependencies, farthest nodes first
+            let mut dependencies: Vec<f64> = vec![0.; n];
+            for &j in visit_order.iter().rev() {
+                for &i in predecessors[j].iter() {
+                    dependencies[i] +=
+                        number_of_paths[i] / number_of_paths[j] * (1. + dependencies[j]);
+                }
+                if j != source {
+                    centralities[j] += dependencies[j];
+                }
+            }
+        }
+        // each path was counted once from each of its ends
+        centralities.into_iter().map(|x| x / 2.).collect()
+    }
+    /// Closeness centrality of each node, i.e., the number of other nodes reachable from it,
+    /// divided by the sum of the distances to these nodes. Nodes that can't reach any other nodes
+    /// get a closeness of 0.
+    pub fn closeness_centralities(&self) -> Vec<f64> {
+        let neighbors = self.undirected_neighbors();
+        (0..self.number_of_nodes())
+            .map(|source| {
+                let (reachable, distance_sum) = bfs_distances(&neighbors, source)
+                    .into_iter()
+                    .flatten()
+                    .filter(|&d| d > 0)
+                    .fold((0, 0), |(count, sum), d| (count + 1, sum + d));
+                if reachable == 0 {
+                    0.
+                } else {
+                    reachable as f64 / distance_sum as f64
+                }
+            })
+            .collect()
+    }
+    /// Coreness of each node, i.e., the largest `k` such that the node is part of the graph's
+    /// `k`-core (the maximal subgraph in which all nodes have a degree of at least `k`).
+    pub fn k_cores(&self) -> Vec<usize> {
+        let neighbors = self.undirected_neighbors();
+        let n = self.number_of_nodes();
+        let mut degrees: Vec<usize> = neighbors.iter().map(|x| x.len()).collect();
+        let mut coreness = vec![0; n];
+        let mut removed = NodeIdSet::with_capacity(n);
+
+        // repeatedly peel off the node with the lowest remaining degree
+        let mut buckets: Vec<BTreeSet<NodeId>> =
+            vec![BTreeSet::new(); degrees.iter().max().map_or(0, |&d| d + 1)];
+        for (i, &degree) in degrees.iter().enumerate() {
+            buckets[degree].insert(i);
+        }
+        let mut k = 0;
+        for _ in 0..n {
+            let degree = (0..buckets.len())
+                .find(|&d| !buckets[d].is_empty())
+                .unwrap();
+            let i = *buckets[degree].iter().next().unwrap();
+            buckets[degree].remove(&i);
+            removed.insert(i);
+            k = k.max(degree);
+            coreness[i] = k;
+            for &j in neighbors[i].iter().filter(|&&j| !removed.contains(j)) {
+                buckets[degrees[j]].remove(&j);
+                degrees[j] -= 1;
+                buckets[degrees[j]].insert(j);
+            }
+        }
+        coreness
+    }
+    /// Partitions all nodes into connected components (ignoring link directions). Components are
+    /// ordered by their smallest node ID.
+    pub fn connected_components(&self) -> Vec<NodeIdSet> {
+        let neighbors = self.undirected_neighbors();
+        let mut unvisited: NodeIdSet = (0..self.number_of_nodes()).collect();
+        let mut components = vec![];
+        while !unvisited.is_empty() {
+            let start = unvisited.iter().next().unwrap();
+            let component: NodeIdSet = bfs_distances(&neighbors, start)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, d)| d.map(|_| i))
+                .collect();
+            unvisited.difference_with(&component);
+            components.push(component);
+        }
+        components
+    }
+    /// Degree distribution as `(degree, number_of_nodes_with_that_degree)` pairs, sorted by
+    /// degree. Degrees that no node has are omitted.
+    pub fn degree_distribution(&self) -> Vec<(usize, usize)> {
+        let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+        for node_neighbors in self.undirected_neighbors().iter() {
+            *counts.entry(node_neighbors.len()).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+    /// Estimates the exponent `alpha` of a power-law degree distribution `p(k) ~ k^-alpha`, using
+    /// the approximate maximum likelihood estimator for discrete data by Clauset, Shalizi and
+    /// Newman (2009). Only nodes with a degree of at least `min_degree` (> 0) are considered.
+    /// Returns `None` if there are no such nodes.
+    pub fn power_law_exponent(&self, min_degree: usize) -> Option<f64> {
+        assert!(min_degree > 0, "`min_degree` must be positive.");
+        let shifted_min_degree = min_degree as f64 - 0.5;
+        let (count, log_sum) = self
+            .degree_distribution()
+            .into_iter()
+            .filter(|&(degree, _)| degree >= min_degree)
+            .fold((0, 0.), |(count, log_sum), (degree, number)| {
+                (
+                    count + number,
+                    log_sum + number as f64 * (degree as f64 / shifted_min_degree).ln(),
+                )
+            });
+        if count == 0 {
+            None
+        } else {
+            Some(1. + count as f64 / log_sum)
+        }
+    }
+    /// Degree assortativity coefficient, i.e., the Pearson correlation coefficient of the degrees
+    /// at both ends of each edge (Newman, 2002). Negative values mean that high-degree nodes tend
+    /// to link to low-degree nodes (disassortative). Returns `NaN` if all edges connect nodes
+    /// of equal degree (e.g., in full meshes) or if there are no edges.
+    pub fn assortativity(&self) -> f64 {
+        let neighbors = self.undirected_neighbors();
+        let degrees: Vec<f64> = neighbors.iter().map(|n| n.len() as f64).collect();
+        let (mut number_of_edges, mut product_sum, mut sum, mut square_sum) = (0., 0., 0., 0.);
+        for (i, node_neighbors) in neighbors.iter().enumerate() {
+            for &j in node_neighbors.iter().filter(|&&j| j > i) {
+                let (di, dj) = (degrees[i], degrees[j]);
+                number_of_edges += 1.;
+                product_sum += di * dj;
+                sum += (di + dj) / 2.;
+                square_sum += (di * di + dj * dj) / 2.;
+            }
+        }
+        let squared_mean = (sum / number_of_edges).powi(2);
+        (product_sum / number_of_edges - squared_mean)
+            / (square_sum / number_of_edges - squared_mean)
+    }
+    /// Detects communities via (asynchronous) label propagation (Raghavan et al., 2007): nodes
+    /// repeatedly, in random order, adopt the label that is most frequent among their neighbors
+    /// (breaking ties randomly), until no node changes its label. Returns the community ID of
+    /// each node; community IDs are numbered `0..number_of_communities` in order of their lowest
+    /// member node ID.
+    pub fn communities(&self) -> Vec<usize> {
+        let neighbors = self.undirected_neighbors();
+        let n = neighbors.len();
+        let mut labels: Vec<usize> = (0..n).collect();
+        let mut order: Vec<NodeId> = (0..n).collect();
+        let mut rng = thread_rng();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            order.shuffle(&mut rng);
+            for &node_id in order.iter() {
+                if neighbors[node_id].is_empty() {
+                    continue;
+                }
+                let mut label_counts: BTreeMap<usize, usize> = BTreeMap::new();
+                for &neighbor in neighbors[node_id].iter() {
+                    *label_counts.entry(labels[neighbor]).or_insert(0) += 1;
+                }
+                let max_count = *label_counts.values().max().unwrap();
+                // keep the current label on ties to guarantee termination
+                if label_counts.get(&labels[node_id]) != Some(&max_count) {
+                    let candidates: Vec<usize> = label_counts
+                        .into_iter()
+                        .filter(|&(_, count)| count == max_count)
+                        .map(|(label, _)| label)
+                        .collect();
+                    labels[node_id] = *candidates.choose(&mut rng).unwrap();
+                    changed = true;
+                }
+            }
+        }
+        let mut renumbering: HashMap<usize, usize> = HashMap::new();
+        labels
+            .into_iter()
+            .map(|label| {
+                let next_id = renumbering.len();
+                *renumbering.entry(label).or_insert(next_id)
+            })
+            .collect()
+    }
+    /// Sorted and deduplicated neighbor lists, ignoring link directions and self-links.
+    pub(crate) fn undirected_neighbors(&self) -> Vec<Vec<NodeId>> {
+        let mut neighbors: Vec<Vec<NodeId>> = vec![vec![]; self.number_of_nodes()];
+        for (i, outlinks) in self.outlinks.iter().enumerate() {
+            for &j in outlinks.iter().filter(|&&j| j != i) {
+                neighbors[i].push(j);
+                neighbors[j].push(i);
+            }
+        }
+        for node_neighbors in neighbors.iter_mut() {
+            node_neighbors.sort_unstable();
+            node_neighbors.dedup();
+        }
+        neighbors
+    }
+}
+
+/// Hop distances from `source` to all nodes; `None` for nodes that aren't reachable.
+fn bfs_distances(neighbors: &[Vec<NodeId>], source: NodeId) -> Vec<Option<usize>> {
+    let mut distances = vec![None; neighbors.len()];
+    let mut queue = NodeIdDeque::new();
+    distances[source] = Some(0);
+    queue.push_back(source);
+    while let Some(i) = queue.pop_front() {
+        let next_distance = distances[i].unwrap() + 1;
+        for &j in neighbors[i].iter() {
+            if distances[j].is_none() {
+                distances[j] = Some(next_distance);
+                queue.push_back(j);
+            }
+        }
+    }
+    distances
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undirected_neighbors_ignore_direction_and_self_links() {
+        let graph = Graph::new(vec![vec![1, 0], vec![], vec![1, 1]]);
+        let expected = vec![vec![1], vec![0, 2], vec![1]];
+        let actual = graph.undirected_neighbors();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn clustering_coefficients_of_full_mesh_are_1() {
+        let graph = Graph::new_full_mesh(5);
+        assert_eq!(vec![1.; 5], graph.clustering_coefficients());
+        assert_eq!(1., graph.average_clustering_coefficient());
+    }
+
+    #[test]
+    fn clustering_coefficients_of_triangle_with_tail() {
+        let graph = Graph::new(vec![vec![1, 2], vec![0, 2], vec![0, 1, 3], vec![2]]);
+        let expected = vec![1., 1., 1. / 3., 0.];
+        let actual = graph.clustering_coefficients();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn bfs_distances_in_path_graph() {
+        let graph = Graph::new(vec![vec![1], vec![2], vec![], vec![]]);
+        let expected = vec![Some(1), Some(0), Some(1), None];
+        let actual = bfs_distances(&graph.undirected_neighbors(), 1);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn shortest_paths_of_full_mesh() {
+        let graph = Graph::new_full_mesh(6);
+        assert_eq!(1., graph.average_shortest_path_length());
+        assert_eq!(1, graph.diameter());
+    }
+
+    #[test]
+    fn shortest_paths_of_path_graph() {
+        // 0 - 1 - 2 - 3, plus isolated node 4
+        let graph = Graph::new(vec![vec![1], vec![2], vec![3], vec![], vec![]]);
+        // distances: 3x 1, 2x 2, 1x 3; each counted in both directions
+        assert_eq!(10. / 6., graph.average_shortest_path_length());
+        assert_eq!(3, graph.diameter());
+    }
+
+    #[test]
+    fn shortest_paths_of_empty_graph() {
+        let graph = Graph::new(vec![]);
+        assert_eq!(0., graph.average_shortest_path_length());
+        assert_eq!(0, graph.diameter());
+    }
+
+    #[test]
+    fn small_world_graph_has_short_paths() {
+        let (n, k) = (200, 10);
+        let lattice = Graph::new_random_small_world(n, k, 0.);
+        let small_world = Graph::new_random_small_world(n, k, 0.2);
+        assert_eq!(n / k, lattice.diameter());
+        assert!(
+            small_world.average_shortest_path_length() < lattice.average_shortest_path_length()
+        );
+    }
+
+    #[test]
+    fn betweenness_of_star_graph() {
+        let graph = Graph::new(vec![vec![1, 2, 3, 4], vec![], vec![], vec![], vec![]]);
+        // all 6 pairs of leaves are connected via the center
+        let expected = vec![6., 0., 0., 0., 0.];
+        let actual = graph.betweenness_centralities();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn betweenness_with_multiple_shortest_paths() {
+        // square: 0 - 1 - 2 - 3 - 0; each node lies on one of two shortest paths between its
+        // two non-adjacent neighbors
+        let graph = Graph::new(vec![vec![1], vec![2], vec![3], vec![0]]);
+        let expected = vec![0.5; 4];
+        let actual = graph.betweenness_centralities();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn betweenness_of_full_mesh_is_0() {
+        let graph = Graph::new_full_mesh(5);
+        assert_eq!(vec![0.; 5], graph.betweenness_centralities());
+    }
+
+    #[test]
+    fn closeness_of_path_graph() {
+        // 0 - 1 - 2, plus isolated node 3
+        let graph = Graph::new(vec![vec![1], vec![2], vec![], vec![]]);
+        let expected = vec![2. / 3., 1., 2. / 3., 0.];
+        let actual = graph.closeness_centralities();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn k_cores_of_full_mesh() {
+        let graph = Graph::new_full_mesh(5);
+        assert_eq!(vec![4; 5], graph.k_cores());
+    }
+
+    #[test]
+    fn k_cores_of_triangle_with_tail() {
+        // triangle 0, 1, 2; tail 2 - 3 - 4; isolated node 5
+        let graph = Graph::new(vec![vec![1, 2], vec![2], vec![3], vec![4], vec![], vec![]]);
+        let expected = vec![2, 2, 2, 1, 1, 0];
+        let actual = graph.k_cores();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn k_cores_of_tiered_full_mesh() {
+        let graph = Graph::new_tiered_full_mesh(&[4, 2, 2]);
+        // the two upper tiers form a 5-core, the lowest tier only adds 3 links per node
+        let expected = vec![5, 5, 5, 5, 5, 5, 3, 3];
+        let actual = graph.k_cores();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn scale_free_graph_cores_at_least_m() {
+        let (n, m0, m) = (50, 3, 3);
+        let graph = Graph::new_random_scale_free(n, m0, m);
+        assert!(graph.k_cores().into_iter().all(|k| k >= m));
+    }
+
+    #[test]
+    fn connected_components_ignore_direction() {
+        let graph = Graph::new(vec![vec![], vec![0], vec![3], vec![], vec![2], vec![]]);
+        let expected = vec![bitset![0, 1], bitset![2, 3, 4], bitset![5]];
+        let actual = graph.connected_components();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn degree_distribution_of_star_graph() {
+        let graph = Graph::new(vec![
+            vec![1, 2, 3, 4],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        ]);
+        let expected = vec![(0, 1), (1, 4), (4, 1)];
+        let actual = graph.degree_distribution();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn power_law_exponent_none_if_no_nodes_considered() {
+        let graph = Graph::new_full_mesh(5);
+        assert_eq!(None, graph.power_law_exponent(5));
+    }
+
+    #[test]
+    fn scale_free_graph_has_power_law_exponent_near_3() {
+        let (n, m0, m) = (1000, 3, 3);
+        let graph = Graph::new_random_scale_free(n, m0, m);
+        let alpha = graph.power_law_exponent(m).unwrap();
+        assert!(2.3 < alpha && alpha < 3.7, "alpha = {}", alpha);
+    }
+
+    #[test]
+    fn assortativity_of_star_graph_is_minus_1() {
+        let graph = Graph::new(vec![vec![1, 2, 3, 4], vec![0], vec![0], vec![0], vec![0]]);
+        assert!((graph.assortativity() + 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn assortativity_of_path_graph() {
+        let graph = Graph::new(vec![vec![1], vec![2], vec![3], vec![]]);
+        assert!((graph.assortativity() + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn assortativity_of_full_mesh_is_nan() {
+        let graph = Graph::new_full_mesh(4);
+        assert!(graph.assortativity().is_nan());
+    }
+
+    #[test]
+    fn scale_free_graph_is_not_assortative() {
+        let graph = Graph::new_random_scale_free(500, 3, 3);
+        assert!(graph.assortativity() < 0.);
+    }
+
+    #[test]
+    fn communities_of_two_loosely_connected_meshes() {
+        let mut graph = Graph::new_full_mesh(40);
+        for i in 0..40 {
+            graph.outlinks[i].retain(|&j| (i < 20) == (j < 20));
+        }
+        graph.outlinks[0].push(20);
+        let expected: Vec<usize> = (0..40).map(|i| if i < 20 { 0 } else { 1 }).collect();
+        let actual = graph.communities();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn isolated_nodes_form_own_communities() {
+        let graph = Graph::new(vec![vec![1], vec![], vec![], vec![]]);
+        let expected = vec![0, 0, 1, 2];
+        let actual = graph.communities();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn small_world_graph_clustering_drops_with_beta() {
+        let (n, k) = (200, 10);
+        let lattice = Graph::new_random_small_world(n, k, 0.);
+        let random = Graph::new_random_small_world(n, k, 1.);
+        // ring lattice: C = 3(k-2) / 4(k-1)
+        let expected_lattice_clustering = 3. * (k - 2) as f64 / (4. * (k - 1) as f64);
+        assert!(
+            (lattice.average_clustering_coefficient() - expected_lattice_clustering).abs() < 1e-9
+        );
+        assert!(random.average_clustering_coefficient() < 0.5 * expected_lattice_clustering);
+    }
+}
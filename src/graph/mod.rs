@@ -3,17 +3,91 @@ use super::*;
 use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
 
+mod adjacency;
+mod layout;
+mod metrics;
+
+pub use layout::Position;
+
 pub type RankScore = f64;
 
+/// Weight of an edge, e.g., a measured latency. Edges of unweighted graphs have weight 1.
+pub type EdgeWeight = f64;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Graph {
     // outgoing edges per node
     pub(crate) outlinks: Vec<Vec<NodeId>>,
+    // optional edge weights, parallel to `outlinks`
+    pub(crate) weights: Option<Vec<Vec<EdgeWeight>>>,
 }
 impl Graph {
     pub fn new(outlinks: Vec<Vec<NodeId>>) -> Self {
         info!("New graph with {} nodes.", outlinks.len());
-        Graph { outlinks }
+        Graph {
+            outlinks,
+            weights: None,
+        }
+    }
+    /// Graph with edge weights; `weights[i][k]` is the weight of the edge from `i` to
+    /// `outlinks[i][k]`.
+    pub fn new_weighted(outlinks: Vec<Vec<NodeId>>, weights: Vec<Vec<EdgeWeight>>) -> Self {
+        assert!(
+            outlinks.len() == weights.len()
+                && outlinks
+                    .iter()
+                    .zip(weights.iter())
+                    .all(|(links, link_weights)| links.len() == link_weights.len()),
+            "Need exactly one weight per edge!"
+        );
+        let mut graph = Self::new(outlinks);
+        graph.weights = Some(weights);
+        graph
+    }
+    /// Same graph, with the weight of each edge `(i, j)` set to `weight(i, j)`, e.g., for
+    /// modelling latencies as distances between nodes.
+    pub fn with_edge_weights<F>(self, weight: F) -> Self
+    where
+        F: Fn(NodeId, NodeId) -> EdgeWeight,
+    {
+        let weights = self
+            .outlinks
+            .iter()
+            .enumerate()
+            .map(|(i, links)| links.iter().map(|&j| weight(i, j)).collect())
+            .collect();
+        Self::new_weighted(self.outlinks, weights)
+    }
+    /// Weight of the edge from `i` to `j`, if there is one.
+    pub fn get_edge_weight(&self, i: NodeId, j: NodeId) -> Option<EdgeWeight> {
+        let k = self.outlinks.get(i)?.iter().position(|&x| x == j)?;
+        Some(self.weights.as_ref().map_or(1., |weights| weights[i][k]))
+    }
+    /// Outgoing edges of `i` together with their weights, in the order of `outlinks`.
+    pub fn get_weighted_outlinks(&self, i: NodeId) -> Vec<(NodeId, EdgeWeight)> {
+        self.outlinks[i]
+            .iter()
+            .enumerate()
+            .map(|(k, &j)| (j, self.weights.as_ref().map_or(1., |weights| weights[i][k])))
+            .collect()
+    }
+    /// Trust graph of `fbas`: there is a link from `i` to `j` if `j` is contained somewhere in
+    /// the quorum set of `i`. Nodes including themselves in their quorum sets yield no
+    /// self-links.
+    pub fn from_fbas(fbas: &Fbas) -> Self {
+        let outlinks = fbas
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                node.quorum_set
+                    .contained_nodes()
+                    .into_iter()
+                    .filter(|&j| j != i)
+                    .collect()
+            })
+            .collect();
+        Self::new(outlinks)
     }
     /// Build a graph where every node is connected to every other node (i.e., a complete graph).
     pub fn new_full_mesh(n: usize) -> Self {
@@ -148,14 +222,40 @@ impl Graph {
         debug_assert!(result.is_undirected());
         result
     }
+    /// Build a random graph using the Erdős–Rényi G(n, p) model, i.e., each undirected edge
+    /// exists with probability `p`.
+    pub fn new_random_erdos_renyi(n: usize, p: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&p),
+            "For the Erdős–Rényi model, `p` must be a probability!"
+        );
+        let mut outlinks: Vec<Vec<NodeId>> = vec![vec![]; n];
+        let mut rng = thread_rng();
+        for i in 0..n {
+            for j in i + 1..n {
+                if rng.gen_bool(p) {
+                    outlinks[i].push(j);
+                    outlinks[j].push(i);
+                }
+            }
+        }
+        let result = Self::new(outlinks);
+        debug_assert!(result.is_undirected());
+        result
+    }
     /// Shuffle the node IDs
     pub fn shuffled(self) -> Self {
+        self.shuffled_with_mapping(&mut thread_rng()).0
+    }
+    /// Shuffle the node IDs using `rng`. The returned table maps new node IDs to old node IDs
+    /// (like for `induced_subgraph`), e.g., for carrying per-node data (such as quality scores)
+    /// over to the shuffled graph.
+    pub fn shuffled_with_mapping<R: Rng + ?Sized>(self, rng: &mut R) -> (Self, Vec<NodeId>) {
         let n = self.outlinks.len();
-        let mut rng = thread_rng();
 
         // mappings
         let mut old_to_new: Vec<NodeId> = (0..n).collect();
-        old_to_new.shuffle(&mut rng);
+        old_to_new.shuffle(rng);
         let mut new_to_old = vec![0; n];
         for (old, &new) in old_to_new.iter().enumerate() {
             new_to_old[new] = old;
@@ -166,7 +266,50 @@ impl Graph {
             .iter()
             .map(|&oi| self.outlinks[oi].iter().map(|&oj| old_to_new[oj]).collect())
             .collect();
-        Self::new(new_outlinks)
+        let shuffled = match self.weights {
+            Some(weights) => {
+                let new_weights = new_to_old.iter().map(|&oi| weights[oi].clone()).collect();
+                Self::new_weighted(new_outlinks, new_weights)
+            }
+            None => Self::new(new_outlinks),
+        };
+        (shuffled, new_to_old)
+    }
+    /// Subgraph consisting of the nodes in `ids_to_keep` and all links between them. Nodes are
+    /// renumbered; the returned table maps new node IDs to old node IDs.
+    pub fn induced_subgraph(&self, ids_to_keep: &NodeIdSet) -> (Self, Vec<NodeId>) {
+        let new_to_old: Vec<NodeId> = ids_to_keep.iter().collect();
+        let old_to_new: HashMap<NodeId, NodeId> = new_to_old
+            .iter()
+            .enumerate()
+            .map(|(new, &old)| (old, new))
+            .collect();
+        let (new_outlinks, new_weights): (Vec<Vec<NodeId>>, Vec<Vec<EdgeWeight>>) = new_to_old
+            .iter()
+            .map(|&oi| {
+                self.get_weighted_outlinks(oi)
+                    .into_iter()
+                    .filter_map(|(oj, weight)| old_to_new.get(&oj).map(|&nj| (nj, weight)))
+                    .unzip()
+            })
+            .unzip();
+        let subgraph = if self.is_weighted() {
+            Self::new_weighted(new_outlinks, new_weights)
+        } else {
+            Self::new(new_outlinks)
+        };
+        (subgraph, new_to_old)
+    }
+    /// Induced subgraph of the largest connected component (see `connected_components`), plus a
+    /// table mapping new node IDs to old node IDs.
+    pub fn largest_component(&self) -> (Self, Vec<NodeId>) {
+        let largest = self
+            .connected_components()
+            .into_iter()
+            .rev() // so that ties are resolved in favor of lower node IDs
+            .max_by_key(|component| component.len())
+            .unwrap_or_default();
+        self.induced_subgraph(&largest)
     }
     pub fn is_undirected(&self) -> bool {
         self.outlinks.iter().enumerate().all(|(i, cons_i)| {
@@ -179,6 +322,10 @@ impl Graph {
     pub fn number_of_nodes(&self) -> usize {
         self.outlinks.len()
     }
+    /// Whether the graph has explicit edge weights (see `new_weighted`)
+    pub fn is_weighted(&self) -> bool {
+        self.weights.is_some()
+    }
     pub fn get_in_degrees(&self) -> Vec<usize> {
         let mut result: Vec<usize> = vec![0; self.outlinks.len()];
         for outlinks in self.outlinks.iter() {
@@ -191,6 +338,29 @@ impl Graph {
     pub fn get_out_degrees(&self) -> Vec<usize> {
         self.outlinks.iter().map(|x| x.len()).collect()
     }
+    /// Sum of the weights of incoming edges, per node; equals `get_in_degrees` for unweighted
+    /// graphs.
+    pub fn get_weighted_in_degrees(&self) -> Vec<EdgeWeight> {
+        let mut result: Vec<EdgeWeight> = vec![0.; self.outlinks.len()];
+        for i in 0..self.outlinks.len() {
+            for (j, weight) in self.get_weighted_outlinks(i) {
+                result[j] += weight;
+            }
+        }
+        result
+    }
+    /// Sum of the weights of outgoing edges, per node; equals `get_out_degrees` for unweighted
+    /// graphs.
+    pub fn get_weighted_out_degrees(&self) -> Vec<EdgeWeight> {
+        (0..self.outlinks.len())
+            .map(|i| {
+                self.get_weighted_outlinks(i)
+                    .into_iter()
+                    .map(|(_, weight)| weight)
+                    .sum()
+            })
+            .collect()
+    }
     /// Returns all nodes that have nonzero degree
     pub fn get_connected_nodes(&self) -> NodeIdSet {
         let mut result = NodeIdSet::new();
@@ -244,6 +414,7 @@ mod tests {
     fn full_mesh() {
         let expected = Graph {
             outlinks: vec![vec![1, 2, 3], vec![0, 2, 3], vec![0, 1, 3], vec![0, 1, 2]],
+            weights: None,
         };
         let actual = Graph::new_full_mesh(4);
         assert_eq!(expected, actual);
@@ -260,6 +431,7 @@ mod tests {
                 vec![0, 1, 2, 3],
                 vec![2, 3, 4],
             ],
+            weights: None,
         };
         let actual = Graph::new_tiered_full_mesh(&vec![2, 3, 1]);
         assert_eq!(expected, actual);
@@ -327,6 +499,28 @@ mod tests {
         assert_ne!(graph1, graph2);
     }
 
+    #[test]
+    fn erdos_renyi_graph_has_sane_number_of_edges() {
+        let (n, p) = (100, 0.1);
+        let graph = Graph::new_random_erdos_renyi(n, p);
+        let number_of_edges = graph.outlinks.iter().map(|x| x.len()).sum::<usize>() / 2;
+        // expected: 495 edges
+        assert!(350 < number_of_edges && number_of_edges < 650);
+        assert!(graph.is_undirected());
+    }
+
+    #[test]
+    fn erdos_renyi_graph_with_extreme_p() {
+        assert_eq!(
+            Graph::new_random_erdos_renyi(5, 1.),
+            Graph::new_full_mesh(5)
+        );
+        assert_eq!(
+            Graph::new_random_erdos_renyi(5, 0.),
+            Graph::new(vec![vec![]; 5])
+        );
+    }
+
     #[test]
     fn graph_shuffle_shuffles() {
         let (n, m0, m) = (23, 3, 2);
@@ -349,6 +543,150 @@ mod tests {
         assert_eq!(degrees(graph), degrees(shuffled));
     }
 
+    #[test]
+    fn graph_shuffle_mapping_maps_links() {
+        let (n, m0, m) = (23, 3, 2);
+        let graph = Graph::new_random_scale_free(n, m0, m);
+        let (shuffled, new_to_old) = graph.clone().shuffled_with_mapping(&mut thread_rng());
+        for (new_i, new_links) in shuffled.outlinks.iter().enumerate() {
+            let mut expected = graph.outlinks[new_to_old[new_i]].clone();
+            let mut actual: Vec<NodeId> = new_links.iter().map(|&j| new_to_old[j]).collect();
+            expected.sort();
+            actual.sort();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn graph_shuffle_with_seeded_rng_is_reproducible() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let graph = Graph::new_random_scale_free(23, 3, 2);
+        let shuffled1 = graph
+            .clone()
+            .shuffled_with_mapping(&mut StdRng::seed_from_u64(42));
+        let shuffled2 = graph.shuffled_with_mapping(&mut StdRng::seed_from_u64(42));
+        assert_eq!(shuffled1, shuffled2);
+    }
+
+    #[test]
+    fn edge_weights() {
+        let graph = Graph::new_full_mesh(3).with_edge_weights(|i, j| (i * 10 + j) as f64);
+        assert_eq!(Some(12.), graph.get_edge_weight(1, 2));
+        assert_eq!(None, graph.get_edge_weight(1, 1));
+        assert_eq!(vec![(0, 20.), (1, 21.)], graph.get_weighted_outlinks(2));
+        assert_eq!(Some(1.), Graph::new_full_mesh(3).get_edge_weight(1, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Need exactly one weight per edge!")]
+    fn weighted_graph_needs_one_weight_per_edge() {
+        Graph::new_weighted(vec![vec![1], vec![0]], vec![vec![1.], vec![]]);
+    }
+
+    #[test]
+    fn weighted_degrees() {
+        let graph = Graph::new_weighted(
+            vec![vec![1, 2], vec![2], vec![]],
+            vec![vec![0.5, 2.], vec![1.], vec![]],
+        );
+        assert_eq!(vec![0., 0.5, 3.], graph.get_weighted_in_degrees());
+        assert_eq!(vec![2.5, 1., 0.], graph.get_weighted_out_degrees());
+        let unweighted = Graph::new_full_mesh(3);
+        assert_eq!(vec![2., 2., 2.], unweighted.get_weighted_in_degrees());
+        assert_eq!(vec![2., 2., 2.], unweighted.get_weighted_out_degrees());
+    }
+
+    #[test]
+    fn graph_shuffle_preserves_edge_weights() {
+        let graph = Graph::new_random_scale_free(23, 3, 2).with_edge_weights(|i, j| (i * j) as f64);
+        let (shuffled, new_to_old) = graph.clone().shuffled_with_mapping(&mut thread_rng());
+        assert!(shuffled.is_weighted());
+        for i in 0..shuffled.number_of_nodes() {
+            for (j, weight) in shuffled.get_weighted_outlinks(i) {
+                assert_eq!(
+                    Some(weight),
+                    graph.get_edge_weight(new_to_old[i], new_to_old[j])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn induced_subgraph_preserves_edge_weights() {
+        let graph = Graph::new_full_mesh(4).with_edge_weights(|i, j| (i + j) as f64);
+        let (subgraph, new_to_old) = graph.induced_subgraph(&bitset![1, 3]);
+        assert_eq!(vec![1, 3], new_to_old);
+        assert_eq!(Some(4.), subgraph.get_edge_weight(0, 1));
+        assert_eq!(vec![4., 4.], subgraph.get_weighted_out_degrees());
+        assert!(!Graph::new_full_mesh(4)
+            .induced_subgraph(&bitset![1, 3])
+            .0
+            .is_weighted());
+    }
+
+    #[test]
+    fn graph_from_fbas_follows_quorum_sets() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": {
+                    "threshold": 1,
+                    "validators": [],
+                    "innerQuorumSets": [{ "threshold": 1, "validators": ["n2"] }]
+                }
+            },
+            {
+                "publicKey": "n2"
+            }
+        ]"#,
+        );
+        let expected = Graph::new(vec![vec![1], vec![2], vec![]]);
+        let actual = Graph::from_fbas(&fbas);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn graph_from_fbas_roundtrips_simple_graph_qsc() {
+        let mut graph = Graph::new_random_small_world(20, 4, 0.05);
+        for links in graph.outlinks.iter_mut() {
+            links.sort();
+        }
+        let fbas = Fbas::from_graph(
+            graph.clone(),
+            quorum_set_configurators::SimpleGraphQsc::new_67p,
+        );
+        assert_eq!(graph, Graph::from_fbas(&fbas));
+    }
+
+    #[test]
+    fn induced_subgraph_renumbers_nodes() {
+        let graph = Graph::new(vec![vec![1, 3], vec![0, 3], vec![0, 3], vec![2, 1]]);
+        let expected = (Graph::new(vec![vec![1], vec![0]]), vec![1, 3]);
+        let actual = graph.induced_subgraph(&bitset![1, 3]);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn largest_component_is_extracted() {
+        let graph = Graph::new(vec![vec![], vec![0], vec![3], vec![], vec![2], vec![]]);
+        let expected = (Graph::new(vec![vec![1], vec![], vec![0]]), vec![2, 3, 4]);
+        let actual = graph.largest_component();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn largest_component_prefers_lower_ids_on_ties() {
+        let graph = Graph::new(vec![vec![], vec![2], vec![1], vec![]]);
+        let (_, new_to_old) = graph.largest_component();
+        assert_eq!(vec![1, 2], new_to_old);
+    }
+
     #[test]
     fn node_degrees_undirected() {
         let (n, m0, m) = (23, 3, 2);
@@ -381,6 +719,7 @@ mod tests {
     fn get_alive_nodes_directed() {
         let graph = Graph {
             outlinks: vec![vec![], vec![0], vec![0, 3], vec![2], vec![]],
+            weights: None,
         };
         let actual = graph.get_connected_nodes();
         let expected = bitset![0, 1, 2, 3];
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt;
 
 use super::*;
@@ -9,10 +10,32 @@ struct RawFbas(Vec<RawNode>);
 #[serde(rename_all = "camelCase")]
 struct RawNode {
     public_key: PublicKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
     #[serde(default)]
     quorum_set: RawQuorumSet,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geo_data: Option<RawGeoData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    organization_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    isp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version_str: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
 }
-#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawGeoData {
+    #[serde(default)]
+    country_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    continent: Option<String>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct RawQuorumSet {
     pub(crate) threshold: usize,
@@ -22,8 +45,11 @@ pub(crate) struct RawQuorumSet {
 }
 
 impl Fbas {
+    /// Panics with a list of everything that is wrong if `json` doesn't describe an FBAS in
+    /// stellarbeat.org "nodes" format. References to unknown validators are only warned about.
     pub fn from_json_str(json: &str) -> Self {
-        serde_json::from_str(json).expect("Error parsing FBAS JSON")
+        let value: serde_json::Value = serde_json::from_str(json).expect("Error parsing FBAS JSON");
+        Self::from_json_value(value)
     }
     pub fn from_json_file(path: &Path) -> Self {
         let json =
@@ -31,7 +57,16 @@ impl Fbas {
         Self::from_json_str(&json)
     }
     pub fn from_json_stdin() -> Self {
-        serde_json::from_reader(io::stdin()).expect("Error reading FBAS JSON from STDIN")
+        let value: serde_json::Value =
+            serde_json::from_reader(io::stdin()).expect("Error reading FBAS JSON from STDIN");
+        Self::from_json_value(value)
+    }
+    fn from_json_value(value: serde_json::Value) -> Self {
+        let errors = validate_fbas_json(&value);
+        if !errors.is_empty() {
+            panic!("Invalid FBAS JSON:\n{}", errors.join("\n"));
+        }
+        serde_json::from_value(value).expect("Error parsing FBAS JSON")
     }
     pub fn to_json_string(&self) -> String {
         serde_json::to_string(&self).expect("Error converting FBAS to JSON!")
@@ -39,6 +74,28 @@ impl Fbas {
     pub fn to_json_string_pretty(&self) -> String {
         serde_json::to_string_pretty(&self).expect("Error converting FBAS to pretty JSON!")
     }
+    /// FBAS in which all nodes have the same quorum set.
+    pub(crate) fn from_shared_raw_quorum_set(
+        public_keys: Vec<PublicKey>,
+        raw_quorum_set: &RawQuorumSet,
+    ) -> Self {
+        Self::from_raw(RawFbas(
+            public_keys
+                .into_iter()
+                .map(|public_key| RawNode {
+                    public_key,
+                    name: None,
+                    quorum_set: raw_quorum_set.clone(),
+                    geo_data: None,
+                    organization_id: None,
+                    active: None,
+                    isp: None,
+                    version_str: None,
+                    tags: BTreeMap::new(),
+                })
+                .collect(),
+        ))
+    }
     fn from_raw(raw_fbas: RawFbas) -> Self {
         let raw_nodes: Vec<RawNode> = raw_fbas.0.into_iter().collect();
 
@@ -86,12 +143,40 @@ impl Node {
         Node {
             public_key: raw_node.public_key,
             quorum_set: QuorumSet::from_raw(raw_node.quorum_set, pk_to_id),
+            name: raw_node.name,
+            metadata: NodeMetadata {
+                active: raw_node.active,
+                organization_id: raw_node.organization_id,
+                country: raw_node
+                    .geo_data
+                    .as_ref()
+                    .and_then(|geo_data| geo_data.country_name.clone()),
+                continent: raw_node.geo_data.and_then(|geo_data| geo_data.continent),
+                isp: raw_node.isp,
+                version: raw_node.version_str,
+                tags: raw_node.tags,
+            },
         }
     }
     fn to_raw(&self, fbas: &Fbas) -> RawNode {
+        let metadata = &self.metadata;
         RawNode {
             public_key: self.public_key.clone(),
+            name: self.name.clone(),
             quorum_set: self.quorum_set.to_raw(&fbas),
+            geo_data: if metadata.country.is_some() || metadata.continent.is_some() {
+                Some(RawGeoData {
+                    country_name: metadata.country.clone(),
+                    continent: metadata.continent.clone(),
+                })
+            } else {
+                None
+            },
+            organization_id: metadata.organization_id.clone(),
+            active: metadata.active,
+            isp: metadata.isp.clone(),
+            version_str: metadata.version.clone(),
+            tags: metadata.tags.clone(),
         }
     }
 }
@@ -138,6 +223,161 @@ impl QuorumSet {
     }
 }
 
+/// Checks `json` against the structure expected by `RawFbas`, so that we can report errors like
+/// "node 14: quorumSet.threshold missing" instead of serde's generic messages. Returns a list of
+/// errors; empty if everything is fine.
+fn validate_fbas_json(json: &serde_json::Value) -> Vec<String> {
+    let raw_nodes = if let Some(raw_nodes) = json.as_array() {
+        raw_nodes
+    } else {
+        return vec![String::from("expected a JSON array of nodes")];
+    };
+    let mut errors = vec![];
+    let mut pk_to_id: HashMap<&str, NodeId> = HashMap::new();
+    for (node_id, raw_node) in raw_nodes.iter().enumerate() {
+        if let Some(public_key) = raw_node.get("publicKey").and_then(|pk| pk.as_str()) {
+            if let Some(other_node_id) = pk_to_id.insert(public_key, node_id) {
+                errors.push(format!(
+                    "node {}: publicKey {} already used by node {}",
+                    node_id, public_key, other_node_id
+                ));
+            }
+        }
+    }
+    for (node_id, raw_node) in raw_nodes.iter().enumerate() {
+        let mut node_errors = vec![];
+        if raw_node.is_object() {
+            match raw_node.get("publicKey") {
+                None => node_errors.push(String::from("publicKey missing")),
+                Some(pk) if !pk.is_string() => {
+                    node_errors.push(String::from("publicKey must be a string"))
+                }
+                _ => {}
+            }
+            for field in &["name", "organizationId", "isp", "versionStr"] {
+                if !is_string_or_null_if_present(raw_node.get(field)) {
+                    node_errors.push(format!("{} must be a string or null", field));
+                }
+            }
+            if let Some(active) = raw_node.get("active").filter(|a| !a.is_null()) {
+                if !active.is_boolean() {
+                    node_errors.push(String::from("active must be a boolean or null"));
+                }
+            }
+            if let Some(geo_data) = raw_node.get("geoData").filter(|g| !g.is_null()) {
+                if !geo_data.is_object() {
+                    node_errors.push(String::from("geoData must be an object or null"));
+                } else {
+                    for field in &["countryName", "continent"] {
+                        if !is_string_or_null_if_present(geo_data.get(field)) {
+                            node_errors.push(format!("geoData.{} must be a string or null", field));
+                        }
+                    }
+                }
+            }
+            if let Some(tags) = raw_node.get("tags").filter(|t| !t.is_null()) {
+                let tags_are_strings = tags
+                    .as_object()
+                    .map(|tags| tags.values().all(|value| value.is_string()));
+                if tags_are_strings != Some(true) {
+                    node_errors.push(String::from(
+                        "tags must be an object with string values or null",
+                    ));
+                }
+            }
+            if let Some(quorum_set) = raw_node.get("quorumSet") {
+                validate_quorum_set_json(quorum_set, "quorumSet", &mut node_errors);
+                let unknown_validators =
+                    unknown_validators_in_quorum_set_json(quorum_set, &pk_to_id);
+                if !unknown_validators.is_empty() {
+                    warn!(
+                        "Node {}: quorum set references {} unknown validators (ignoring them): {}",
+                        node_id,
+                        unknown_validators.len(),
+                        unknown_validators.join(", ")
+                    );
+                }
+            }
+        } else {
+            node_errors.push(String::from("expected a JSON object"));
+        }
+        errors.extend(
+            node_errors
+                .into_iter()
+                .map(|error| format!("node {}: {}", node_id, error)),
+        );
+    }
+    errors
+}
+fn validate_quorum_set_json(quorum_set: &serde_json::Value, path: &str, errors: &mut Vec<String>) {
+    if !quorum_set.is_object() {
+        errors.push(format!("{} must be an object", path));
+        return;
+    }
+    match quorum_set.get("threshold") {
+        None => errors.push(format!("{}.threshold missing", path)),
+        Some(threshold) if !threshold.is_u64() => errors.push(format!(
+            "{}.threshold must be a non-negative integer, got {}",
+            path, threshold
+        )),
+        _ => {}
+    }
+    match quorum_set.get("validators").map(|v| v.as_array()) {
+        None => errors.push(format!("{}.validators missing", path)),
+        Some(None) => errors.push(format!("{}.validators must be an array", path)),
+        Some(Some(validators)) => {
+            if !validators.iter().all(|v| v.is_string()) {
+                errors.push(format!(
+                    "{}.validators must only contain public keys (strings)",
+                    path
+                ));
+            }
+        }
+    }
+    match quorum_set.get("innerQuorumSets").map(|v| v.as_array()) {
+        None => {}
+        Some(None) => errors.push(format!("{}.innerQuorumSets must be an array", path)),
+        Some(Some(inner_quorum_sets)) => {
+            for (i, inner_quorum_set) in inner_quorum_sets.iter().enumerate() {
+                let inner_path = format!("{}.innerQuorumSets[{}]", path, i);
+                validate_quorum_set_json(inner_quorum_set, &inner_path, errors);
+            }
+        }
+    }
+}
+fn unknown_validators_in_quorum_set_json(
+    quorum_set: &serde_json::Value,
+    pk_to_id: &HashMap<&str, NodeId>,
+) -> Vec<String> {
+    let mut unknown_validators: Vec<String> = quorum_set
+        .get("validators")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .filter(|pk| !pk_to_id.contains_key(pk))
+        .map(String::from)
+        .collect();
+    for inner_quorum_set in quorum_set
+        .get("innerQuorumSets")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        unknown_validators.extend(unknown_validators_in_quorum_set_json(
+            inner_quorum_set,
+            pk_to_id,
+        ));
+    }
+    unknown_validators
+}
+fn is_string_or_null_if_present(value: Option<&serde_json::Value>) -> bool {
+    match value {
+        Some(value) => value.is_string() || value.is_null(),
+        None => true,
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct RawOrganizations(Vec<RawOrganization>);
 #[derive(Serialize, Deserialize)]
@@ -157,6 +397,9 @@ impl<'fbas> Organizations<'fbas> {
             fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
         Self::from_json_str(&json, fbas)
     }
+    pub fn to_json_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self).expect("Error converting Organizations to pretty JSON!")
+    }
     fn from_raw(raw_organizations: RawOrganizations, fbas: &'fbas Fbas) -> Self {
         let organizations: Vec<Organization> = raw_organizations
             .0
@@ -207,6 +450,29 @@ impl Organization {
     }
 }
 
+impl NodeWeights {
+    /// Reads weights from a JSON object mapping public keys to numbers. Unknown public keys are
+    /// ignored, nodes without an entry get weight 0.
+    pub fn from_json_str(json: &str, fbas: &Fbas) -> Self {
+        let raw_weights: HashMap<PublicKey, f64> =
+            serde_json::from_str(json).expect("Error parsing node weights JSON");
+        let mut weights = vec![0.; fbas.number_of_nodes()];
+        for (public_key, weight) in raw_weights.into_iter() {
+            if let Some(&node_id) = fbas.pk_to_id.get(&public_key) {
+                weights[node_id] = weight;
+            } else {
+                warn!("Ignoring weight of unknown node {}", public_key);
+            }
+        }
+        NodeWeights::new(weights)
+    }
+    pub fn from_json_file(path: &Path, fbas: &Fbas) -> Self {
+        let json =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
+        Self::from_json_str(&json, fbas)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,6 +580,40 @@ mod tests {
         assert_eq!(expected_quorum_sets, actual_quorum_sets);
     }
 
+    #[test]
+    fn validation_finds_structural_errors() {
+        let input = serde_json::json!([
+            { "publicKey": "n0", "quorumSet": { "validators": ["n0"] } },
+            {
+                "publicKey": "n1",
+                "quorumSet": {
+                    "threshold": -1,
+                    "validators": ["n0", 1],
+                    "innerQuorumSets": [{ "threshold": 1 }]
+                }
+            },
+            { "quorumSet": { "threshold": 1, "validators": [] } },
+            { "publicKey": "n0" },
+            "n4"
+        ]);
+        let expected = vec![
+            "node 3: publicKey n0 already used by node 0",
+            "node 0: quorumSet.threshold missing",
+            "node 1: quorumSet.threshold must be a non-negative integer, got -1",
+            "node 1: quorumSet.validators must only contain public keys (strings)",
+            "node 1: quorumSet.innerQuorumSets[0].validators missing",
+            "node 2: publicKey missing",
+            "node 4: expected a JSON object",
+        ];
+        assert_eq!(expected, validate_fbas_json(&input));
+    }
+
+    #[test]
+    #[should_panic(expected = "node 0: quorumSet.threshold missing")]
+    fn from_json_panics_with_helpful_message() {
+        Fbas::from_json_str(r#"[{ "publicKey": "n0", "quorumSet": { "validators": [] } }]"#);
+    }
+
     #[test]
     fn from_json_keeps_inactive_nodes() {
         // otherwise IDs don't match indices
@@ -331,6 +631,8 @@ mod tests {
             }]"#;
 
         let fbas = Fbas::from_json_str(&input);
+        let actives: Vec<Option<bool>> = fbas.nodes.iter().map(|x| x.metadata.active).collect();
+        assert_eq!(vec![Some(false), Some(true), None], actives);
 
         let expected = vec!["GCGB2", "GCM6Q", "GABMK"];
         let actual: Vec<PublicKey> = fbas.nodes.into_iter().map(|x| x.public_key).collect();
@@ -346,6 +648,134 @@ mod tests {
         assert_eq!(original, recombined);
     }
 
+    #[test]
+    fn from_json_reads_countries() {
+        let input = r#"[
+            {
+                "publicKey": "n0",
+                "geoData": { "countryCode": "DE", "countryName": "Germany", "city": null }
+            },
+            {
+                "publicKey": "n1",
+                "geoData": { "countryCode": null, "countryName": null }
+            },
+            {
+                "publicKey": "n2"
+            }]"#;
+        let fbas = Fbas::from_json_str(input);
+        let expected = vec![Some(String::from("Germany")), None, None];
+        let actual: Vec<Option<String>> = fbas
+            .nodes
+            .iter()
+            .map(|x| x.metadata.country.clone())
+            .collect();
+        assert_eq!(expected, actual);
+
+        let recombined = Fbas::from_json_str(&fbas.to_json_string());
+        assert_eq!(fbas, recombined);
+    }
+
+    #[test]
+    fn from_json_reads_continents() {
+        let input = r#"[
+            {
+                "publicKey": "n0",
+                "geoData": { "countryName": "Germany", "continent": "Europe" }
+            },
+            {
+                "publicKey": "n1",
+                "geoData": { "continent": "Asia" }
+            }]"#;
+        let fbas = Fbas::from_json_str(input);
+        let expected = vec![Some(String::from("Europe")), Some(String::from("Asia"))];
+        let actual: Vec<Option<String>> = fbas
+            .nodes
+            .iter()
+            .map(|x| x.metadata.continent.clone())
+            .collect();
+        assert_eq!(expected, actual);
+        assert_eq!(None, fbas.nodes[1].metadata.country);
+
+        let recombined = Fbas::from_json_str(&fbas.to_json_string());
+        assert_eq!(fbas, recombined);
+    }
+
+    #[test]
+    fn from_json_reads_isps_versions_and_tags() {
+        let input = r#"[
+            {
+                "publicKey": "n0",
+                "isp": "Amazon.com",
+                "versionStr": "stellar-core 11.3.0",
+                "tags": { "tier": "1" }
+            },
+            {
+                "publicKey": "n1"
+            }]"#;
+        let fbas = Fbas::from_json_str(input);
+        assert_eq!(Some(String::from("Amazon.com")), fbas.metadata(0).isp);
+        assert_eq!(
+            Some(String::from("stellar-core 11.3.0")),
+            fbas.metadata(0).version
+        );
+        assert_eq!(Some(&String::from("1")), fbas.metadata(0).tags.get("tier"));
+        assert_eq!(NodeMetadata::default(), *fbas.metadata(1));
+
+        let recombined = Fbas::from_json_str(&fbas.to_json_string());
+        assert_eq!(fbas, recombined);
+    }
+
+    #[test]
+    fn metadata_can_be_set_programmatically() {
+        let mut fbas = Fbas::new_generic_unconfigured(2);
+        fbas.metadata_mut(1).isp = Some(String::from("Hetzner"));
+        fbas.metadata_mut(1)
+            .tags
+            .insert(String::from("operator"), String::from("alice"));
+
+        let recombined = Fbas::from_json_str(&fbas.to_json_string());
+        assert_eq!(None, recombined.metadata(0).isp);
+        assert_eq!(fbas.metadata(1), recombined.metadata(1));
+    }
+
+    #[test]
+    fn from_json_reads_names_and_organization_ids() {
+        let input = r#"[
+            {
+                "publicKey": "n0",
+                "name": "SDF 1",
+                "organizationId": "266107"
+            },
+            {
+                "publicKey": "n1"
+            }]"#;
+        let fbas = Fbas::from_json_str(input);
+        assert_eq!(Some(String::from("SDF 1")), fbas.nodes[0].name);
+        assert_eq!(
+            Some(String::from("266107")),
+            fbas.metadata(0).organization_id
+        );
+        assert_eq!(None, fbas.nodes[1].name);
+        assert_eq!(None, fbas.metadata(1).organization_id);
+
+        let recombined = Fbas::from_json_str(&fbas.to_json_string());
+        assert_eq!(fbas, recombined);
+    }
+
+    #[test]
+    fn node_weights_from_json() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            { "publicKey": "n0" },
+            { "publicKey": "n1" },
+            { "publicKey": "n2" }
+        ]"#,
+        );
+        let weights = NodeWeights::from_json_str(r#"{ "n0": 0.5, "n2": 3, "n9": 7 }"#, &fbas);
+        assert_eq!(NodeWeights::new(vec![0.5, 0., 3.]), weights);
+        assert_eq!(3.5, weights.total(&bitset![0, 1, 2]));
+    }
+
     #[test]
     fn can_serizalize_quorum_sets_with_unknown_nodes() {
         let fbas = Fbas::new();
@@ -1,27 +1,36 @@
 use super::*;
-use crate::graph::Graph;
+use crate::graph::{EdgeWeight, Graph, Position};
 
 use bzip2::read::BzDecoder;
 use bzip2::write;
 use bzip2::Compression;
 use std::cmp::max;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 
+use std::fmt::Write as _;
 use std::fs;
 use std::fs::File;
 
 impl Graph {
+    /// Reads a CAIDA AS Relationships dataset (serial-1 or serial-2 format). Files ending in
+    /// `.bz2` are decompressed on the fly, all other files are read as plain text.
+    /// AS numbers are used as node IDs.
     pub fn from_as_rel_file(path: &Path) -> Self {
-        Self::from_as_rel_string(
-            &read_bz2_file_to_string(path).expect("Error reading AS Relationships file"),
-        )
+        let contents = if is_bz2_file(path) {
+            read_bz2_file_to_string(path)
+        } else {
+            fs::read_to_string(path)
+        };
+        Self::from_as_rel_string(&contents.expect("Error reading AS Relationships file"))
     }
     pub fn from_as_rel_string(as_rel_file_contents: &str) -> Self {
         let mut outlinks: Vec<BTreeSet<NodeId>> = vec![];
 
         for (sink, source, peering) in as_rel_file_contents
             .lines()
-            .map(get_edge_from_as_rel_line)
-            .filter_map(|x| x)
+            .enumerate()
+            .filter_map(|(i, line)| get_edge_from_as_rel_line(line, i + 1))
         {
             outlinks.resize_with(max(outlinks.len(), max(sink, source) + 1), BTreeSet::new);
             outlinks[source].insert(sink);
@@ -62,29 +71,212 @@ impl Graph {
     }
 }
 
-fn get_edge_from_as_rel_line(line: &str) -> Option<(NodeId, NodeId, bool)> {
-    if line.is_empty() || line.starts_with('#') {
-        return None;
+impl Graph {
+    /// Reads a plain edge list: one directed link per line, given as source and target node IDs
+    /// and an optional weight (e.g., a measured round-trip time), separated by whitespace. If any
+    /// line has a weight, all lines need one, and the result is a weighted graph (see
+    /// `Graph::new_weighted`). Empty lines and lines starting with `#` are ignored; for duplicate
+    /// links, the last weight counts. Files ending in `.bz2` are decompressed on the fly.
+    pub fn from_edge_list_file(path: &Path) -> Self {
+        let contents = if is_bz2_file(path) {
+            read_bz2_file_to_string(path)
+        } else {
+            fs::read_to_string(path)
+        };
+        Self::from_edge_list_string(&contents.expect("Error reading edge list file"))
     }
-    let e = "Error parsing AS Relationships data";
-    let mut parts = line.split('|');
-    let sink = parts.next().expect(e).parse::<NodeId>().expect(e);
-    let source = parts.next().expect(e).parse::<NodeId>().expect(e);
-    let peering;
-    match parts.next().expect(e).parse::<i32>().expect(e) {
-        -1 => {
-            peering = false;
+    pub fn from_edge_list_string(edge_list: &str) -> Self {
+        let edges: Vec<(usize, NodeId, NodeId, Option<EdgeWeight>)> = edge_list
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| get_edge_from_edge_list_line(line, i + 1))
+            .collect();
+        let weighted = edges.iter().any(|&(_, _, _, weight)| weight.is_some());
+
+        let mut outlinks: Vec<BTreeMap<NodeId, EdgeWeight>> = vec![];
+        for &(line_number, source, target, weight) in edges.iter() {
+            let weight = match (weighted, weight) {
+                (_, Some(weight)) => weight,
+                (false, None) => 1.,
+                (true, None) => panic!(
+                    "Missing weight in edge list line {} ({} -> {}); either all or no links \
+                     need weights",
+                    line_number, source, target
+                ),
+            };
+            outlinks.resize_with(max(outlinks.len(), max(source, target) + 1), BTreeMap::new);
+            outlinks[source].insert(target, weight);
+        }
+        let (outlinks, weights) = outlinks
+            .into_iter()
+            .map(|links| links.into_iter().unzip())
+            .unzip();
+        if weighted {
+            Graph::new_weighted(outlinks, weights)
+        } else {
+            Graph::new(outlinks)
+        }
+    }
+}
+
+// Exports for visualization tools. Coordinates are scaled from the unit square (see
+// `Graph::force_directed_layout`) to `LAYOUT_SCALE` units.
+const LAYOUT_SCALE: f64 = 1000.;
+impl Graph {
+    /// Graph in Graphviz DOT format. Positions, if given, are added as pinned `pos` attributes
+    /// (in points), so that, e.g., `neato -n` uses them as they are.
+    pub fn to_dot_string(&self, positions: Option<&[Position]>) -> String {
+        let mut dot = String::from("digraph {\n");
+        for node_id in 0..self.number_of_nodes() {
+            if let Some(positions) = positions {
+                let (x, y) = scaled(positions[node_id]);
+                writeln!(dot, "  {} [pos=\"{:.1},{:.1}!\"];", node_id, x, y).unwrap();
+            } else {
+                writeln!(dot, "  {};", node_id).unwrap();
+            }
         }
-        0 => {
-            peering = true;
+        for (i, j) in self.edges() {
+            writeln!(dot, "  {} -> {};", i, j).unwrap();
         }
-        _ => {
-            panic!(e);
+        dot.push_str("}\n");
+        dot
+    }
+    /// Graph in GraphML format. Positions, if given, are added as `x` and `y` node attributes.
+    pub fn to_graphml_string(&self, positions: Option<&[Position]>) -> String {
+        let mut graphml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20 <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"double\"/>\n\
+             \x20 <graph edgedefault=\"directed\">\n",
+        );
+        for node_id in 0..self.number_of_nodes() {
+            if let Some(positions) = positions {
+                let (x, y) = scaled(positions[node_id]);
+                writeln!(
+                    graphml,
+                    "    <node id=\"n{}\"><data key=\"x\">{:.1}</data>\
+                     <data key=\"y\">{:.1}</data></node>",
+                    node_id, x, y
+                )
+                .unwrap();
+            } else {
+                writeln!(graphml, "    <node id=\"n{}\"/>", node_id).unwrap();
+            }
+        }
+        for (i, j) in self.edges() {
+            writeln!(graphml, "    <edge source=\"n{}\" target=\"n{}\"/>", i, j).unwrap();
         }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+    /// Graph as JSON, for web-based tools: `{"nodes": [{"id": 0, "x": .., "y": ..}, ..],
+    /// "links": [{"source": 0, "target": 1}, ..]}`.
+    pub fn to_layout_json_string(&self, positions: &[Position]) -> String {
+        let nodes: Vec<serde_json::Value> = positions
+            .iter()
+            .map(|&position| scaled(position))
+            .enumerate()
+            .map(|(node_id, (x, y))| serde_json::json!({ "id": node_id, "x": x, "y": y }))
+            .collect();
+        let links: Vec<serde_json::Value> = self
+            .edges()
+            .map(|(i, j)| serde_json::json!({ "source": i, "target": j }))
+            .collect();
+        serde_json::json!({ "nodes": nodes, "links": links }).to_string()
+    }
+    /// Writes the graph with a force-directed layout (see `force_directed_layout`) to `path`,
+    /// as DOT if the file name ends in `.dot` or `.gv`, as GraphML if it ends in `.graphml` and
+    /// as JSON otherwise.
+    pub fn to_layout_file(&self, path: &Path, iterations: usize) -> io::Result<()> {
+        let positions = self.force_directed_layout(iterations);
+        let contents = match path.extension().and_then(OsStr::to_str) {
+            Some("dot") | Some("gv") => self.to_dot_string(Some(&positions)),
+            Some("graphml") => self.to_graphml_string(Some(&positions)),
+            _ => self.to_layout_json_string(&positions),
+        };
+        fs::write(path, contents)
+    }
+    fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.outlinks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, outlinks)| outlinks.iter().map(move |&j| (i, j)))
+    }
+}
+fn scaled((x, y): Position) -> Position {
+    (x * LAYOUT_SCALE, y * LAYOUT_SCALE)
+}
+
+fn get_edge_from_as_rel_line(line: &str, line_number: usize) -> Option<(NodeId, NodeId, bool)> {
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let edge = parse_as_rel_line(line).unwrap_or_else(|| {
+        panic!(
+            "Error parsing AS Relationships data in line {}: {:?}",
+            line_number, line
+        )
+    });
+    Some(edge)
+}
+
+/// Returns `(sink, source, peering)`, or `None` if `line` is malformed.
+fn parse_as_rel_line(line: &str) -> Option<(NodeId, NodeId, bool)> {
+    let mut parts = line.split('|');
+    let sink = parts.next()?.parse::<NodeId>().ok()?;
+    let source = parts.next()?.parse::<NodeId>().ok()?;
+    let peering = match parts.next()?.parse::<i32>().ok()? {
+        -1 => false,
+        0 => true,
+        _ => return None,
     };
     Some((sink, source, peering))
 }
 
+/// Returns `(line_number, source, target, weight)` unless `line` is empty or a comment.
+fn get_edge_from_edge_list_line(
+    line: &str,
+    line_number: usize,
+) -> Option<(usize, NodeId, NodeId, Option<EdgeWeight>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (source, target, weight) = parse_edge_list_line(line).unwrap_or_else(|| {
+        panic!(
+            "Error parsing edge list in line {}: {:?}",
+            line_number, line
+        )
+    });
+    if matches!(weight, Some(weight) if weight.is_nan() || weight < 0.) {
+        panic!(
+            "Error parsing edge list in line {}: {:?} (weights must be non-negative numbers)",
+            line_number, line
+        );
+    }
+    Some((line_number, source, target, weight))
+}
+
+/// Returns `(source, target, weight)`, or `None` if `line` is malformed.
+fn parse_edge_list_line(line: &str) -> Option<(NodeId, NodeId, Option<EdgeWeight>)> {
+    let mut parts = line.split_whitespace();
+    let source = parts.next()?.parse::<NodeId>().ok()?;
+    let target = parts.next()?.parse::<NodeId>().ok()?;
+    let weight = match parts.next() {
+        Some(part) => Some(part.parse::<EdgeWeight>().ok()?),
+        None => None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((source, target, weight))
+}
+
+fn is_bz2_file(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("bz2"))
+}
+
 fn read_bz2_file_to_string(path: &Path) -> io::Result<String> {
     let f = fs::File::open(path)?;
     let mut decompressor = BzDecoder::new(f);
@@ -111,7 +303,7 @@ mod tests {
     fn parse_as_rel_line() {
         let line = "1|2|0|bgp".to_string();
         let expected = Some((1, 2, true));
-        let actual = get_edge_from_as_rel_line(&line);
+        let actual = get_edge_from_as_rel_line(&line, 1);
         assert_eq!(expected, actual);
     }
 
@@ -144,6 +336,74 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn reads_uncompressed_as_rel_file() {
+        let path = Path::new("test_data/test_graph_uncompressed.txt");
+        fs::write(path, "1|2|0|bgp\n2|4|-1|mlp\n").unwrap();
+        let expected = Graph::new(vec![vec![], vec![2], vec![1], vec![], vec![2]]);
+        let actual = Graph::from_as_rel_file(path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2")]
+    fn as_rel_parse_errors_name_line_number() {
+        Graph::from_as_rel_string("1|2|0|bgp\n2|4|7|mlp");
+    }
+
+    #[test]
+    fn parses_edge_list_without_weights() {
+        let contents = "# source target\n0 2\n\n2 1\n0 2";
+        let expected = Graph::new(vec![vec![2], vec![], vec![1]]);
+        assert_eq!(expected, Graph::from_edge_list_string(contents));
+    }
+
+    #[test]
+    fn parses_weighted_edge_list() {
+        let contents = "0 1 12.5\n1\t0 13\n0 3 80";
+        let graph = Graph::from_edge_list_string(contents);
+        assert!(graph.is_weighted());
+        assert_eq!(4, graph.number_of_nodes());
+        assert_eq!(Some(12.5), graph.get_edge_weight(0, 1));
+        assert_eq!(Some(13.), graph.get_edge_weight(1, 0));
+        assert_eq!(vec![(1, 12.5), (3, 80.)], graph.get_weighted_outlinks(0));
+    }
+
+    #[test]
+    fn reads_uncompressed_edge_list_file() {
+        let path = Path::new("test_data/test_edge_list_uncompressed.txt");
+        fs::write(path, "0 1 0.5\n1 0 0.5\n").unwrap();
+        let expected = Graph::new_weighted(vec![vec![1], vec![0]], vec![vec![0.5], vec![0.5]]);
+        let actual = Graph::from_edge_list_file(path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing weight in edge list line 4")]
+    fn edge_list_needs_all_or_no_weights() {
+        Graph::from_edge_list_string("# RTTs in ms\n0 1 3.5\n\n1 0");
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2")]
+    fn edge_list_rejects_nan_weights() {
+        Graph::from_edge_list_string("0 1 3.5\n1 0 NaN");
+    }
+
+    #[test]
+    #[should_panic(expected = "weights must be non-negative")]
+    fn edge_list_rejects_negative_weights() {
+        Graph::from_edge_list_string("0 1 -3.5");
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2")]
+    fn edge_list_parse_errors_name_line_number() {
+        Graph::from_edge_list_string("0 1\n1 zero");
+    }
+
     #[test]
     fn writes_graph_correctly() {
         let path = Path::new("test_data/test_graph.txt.bz2");
@@ -153,4 +413,33 @@ mod tests {
         assert_eq!(expected, actual);
         Some(fs::remove_file(path));
     }
+
+    #[test]
+    fn exports_graph_with_positions() {
+        let graph = Graph::new(vec![vec![1], vec![]]);
+        let positions = vec![(0., 0.5), (1., 0.5)];
+
+        let expected_dot =
+            "digraph {\n  0 [pos=\"0.0,500.0!\"];\n  1 [pos=\"1000.0,500.0!\"];\n  0 -> 1;\n}\n";
+        assert_eq!(expected_dot, graph.to_dot_string(Some(&positions)));
+        assert_eq!(
+            "digraph {\n  0;\n  1;\n  0 -> 1;\n}\n",
+            graph.to_dot_string(None)
+        );
+
+        let graphml = graph.to_graphml_string(Some(&positions));
+        assert!(graphml.contains(
+            "<node id=\"n1\"><data key=\"x\">1000.0</data><data key=\"y\">500.0</data></node>"
+        ));
+        assert!(graphml.contains("<edge source=\"n0\" target=\"n1\"/>"));
+        assert!(graphml.ends_with("</graphml>\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&graph.to_layout_json_string(&positions)).unwrap();
+        let expected_json = serde_json::json!({
+            "nodes": [{ "id": 0, "x": 0., "y": 500. }, { "id": 1, "x": 1000., "y": 500. }],
+            "links": [{ "source": 0, "target": 1 }],
+        });
+        assert_eq!(expected_json, json);
+    }
 }
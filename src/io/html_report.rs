@@ -0,0 +1,226 @@
+use super::*;
+
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+
+const SVG_SIZE: f64 = 640.;
+const TOP_TIER_COLOR: &str = "#d62728";
+const SATISFIABLE_COLOR: &str = "#1f77b4";
+const UNSATISFIABLE_COLOR: &str = "#aaaaaa";
+
+impl<'a> Analysis<'a> {
+    /// Renders the main analysis results as a single self-contained HTML document, including an
+    /// inline SVG drawing of the trust graph. Top tier nodes are drawn in an inner circle (red),
+    /// all other nodes in an outer circle (blue, or gray if they can never be part of a quorum).
+    /// Hovering over a node shows its name. Triggers all underlying searches, if not already
+    /// cached.
+    pub fn to_html_report(&mut self) -> String {
+        let fbas = self.fbas_original;
+        let organizations = self.organizations_original;
+        let has_quorum_intersection = self.has_quorum_intersection();
+        let top_tier = self.top_tier().unwrap();
+        let summary = [
+            ("Nodes", self.all_physical_nodes().len().to_string()),
+            ("Top tier size", top_tier.len().to_string()),
+            (
+                "Minimal quorums",
+                describe_for_html(&self.minimal_quorums()),
+            ),
+            (
+                "Minimal blocking sets",
+                describe_for_html(&self.minimal_blocking_sets()),
+            ),
+            (
+                "Minimal splitting sets",
+                describe_for_html(&self.minimal_splitting_sets()),
+            ),
+        ];
+        let top_tier_physical: NodeIdSet = fbas
+            .all_nodes()
+            .into_iter()
+            .filter(|&node_id| {
+                top_tier.contains(match organizations {
+                    Some(orgs) => orgs.merge_node(node_id),
+                    None => node_id,
+                })
+            })
+            .collect();
+
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>FBAS analysis report</title>\n\
+             <style>body { font-family: sans-serif; margin: 2em; } \
+             td, th { padding: 0.2em 1em; text-align: left; }</style>\n\
+             </head>\n<body>\n<h1>FBAS analysis report</h1>\n",
+        );
+        if has_quorum_intersection {
+            html.push_str("<p>All quorums intersect 👍</p>\n");
+        } else {
+            html.push_str(
+                "<p><strong>Some quorums don't intersect 👎</strong> \
+                 Safety is severely threatened!</p>\n",
+            );
+        }
+        html.push_str("<table>\n");
+        for (label, value) in summary.iter() {
+            writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, value).unwrap();
+        }
+        html.push_str("</table>\n<h2>Top tier</h2>\n<ul>\n");
+        for node_id in top_tier.iter() {
+            writeln!(
+                html,
+                "<li>{}</li>",
+                escape_html(display_name(node_id, fbas, organizations))
+            )
+            .unwrap();
+        }
+        html.push_str("</ul>\n<h2>Trust graph</h2>\n");
+        writeln!(
+            html,
+            "<p><span style=\"color: {}\">&#9679;</span> top tier \
+             <span style=\"color: {}\">&#9679;</span> other nodes \
+             <span style=\"color: {}\">&#9679;</span> nodes that can never be part of a quorum</p>",
+            TOP_TIER_COLOR, SATISFIABLE_COLOR, UNSATISFIABLE_COLOR
+        )
+        .unwrap();
+        html.push_str(&trust_graph_svg(fbas, organizations, &top_tier_physical));
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn trust_graph_svg(
+    fbas: &Fbas,
+    organizations: Option<&Organizations>,
+    top_tier: &NodeIdSet,
+) -> String {
+    // `find_unsatisfiable_nodes` returns (satisfiable, unsatisfiable)
+    let unsatisfiable = find_unsatisfiable_nodes(&fbas.all_nodes(), fbas).1;
+    let others: Vec<NodeId> = (0..fbas.number_of_nodes())
+        .filter(|&node_id| !top_tier.contains(node_id))
+        .collect();
+    let mut positions = vec![(0., 0.); fbas.number_of_nodes()];
+    place_on_circle(
+        &top_tier.iter().collect::<Vec<NodeId>>(),
+        SVG_SIZE * 0.2,
+        &mut positions,
+    );
+    place_on_circle(&others, SVG_SIZE * 0.45, &mut positions);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" \
+         viewBox=\"0 0 {0} {0}\">",
+        SVG_SIZE
+    )
+    .unwrap();
+    svg.push_str("<g stroke=\"#888888\" stroke-opacity=\"0.2\">\n");
+    for (i, outlinks) in Graph::from_fbas(fbas).outlinks.iter().enumerate() {
+        for &j in outlinks.iter() {
+            let ((x1, y1), (x2, y2)) = (positions[i], positions[j]);
+            writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>",
+                x1, y1, x2, y2
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</g>\n");
+    for (node_id, &(x, y)) in positions.iter().enumerate() {
+        let color = if top_tier.contains(node_id) {
+            TOP_TIER_COLOR
+        } else if unsatisfiable.contains(node_id) {
+            UNSATISFIABLE_COLOR
+        } else {
+            SATISFIABLE_COLOR
+        };
+        writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"5\" fill=\"{}\"><title>{}</title></circle>",
+            x,
+            y,
+            color,
+            escape_html(display_name(node_id, fbas, organizations))
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn place_on_circle(node_ids: &[NodeId], radius: f64, positions: &mut [(f64, f64)]) {
+    let center = SVG_SIZE / 2.;
+    for (i, &node_id) in node_ids.iter().enumerate() {
+        let angle = 2. * PI * (i as f64) / (node_ids.len() as f64);
+        positions[node_id] = (center + radius * angle.cos(), center + radius * angle.sin());
+    }
+}
+
+fn describe_for_html(result: &NodeIdSetVecResult) -> String {
+    let (min_size, max_size, mean_size) = result.minmaxmean();
+    format!(
+        "{} (sizes {} to {}, mean {:.2})",
+        result.len(),
+        min_size,
+        max_size,
+        mean_size
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_report_contains_results_and_graph() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "name": "<Alice>",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n3",
+                "quorumSet": { "threshold": 2, "validators": ["n3", "n4"] }
+            }
+        ]"#,
+        );
+        let html = Analysis::new(&fbas, None).to_html_report();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("All quorums intersect"));
+        assert!(html.contains("<li>&lt;Alice&gt;</li>"));
+        assert!(!html.contains("<Alice>"));
+        assert_eq!(4, html.matches("<circle ").count());
+        // 3 * 2 links in the top tier, n3 trusts no other existing nodes
+        assert_eq!(6, html.matches("<line ").count());
+        assert_eq!(
+            3,
+            html.matches(&format!("fill=\"{}\"", TOP_TIER_COLOR))
+                .count()
+        );
+        assert_eq!(
+            1,
+            html.matches(&format!("fill=\"{}\"", UNSATISFIABLE_COLOR))
+                .count()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+
+impl<'a> NodeIdSetVecResult<'a> {
+    /// Sparse node/set incidence matrix as CSV, one `node_id,set_index` line per membership.
+    /// Node sets are numbered in order, starting at 0.
+    pub fn to_incidence_csv_string(&self) -> String {
+        let mut csv = String::from("node_id,set_index\n");
+        for (set_index, node_set) in self.clone().unwrap().iter().enumerate() {
+            for node_id in node_set.iter() {
+                writeln!(csv, "{},{}", node_id, set_index).unwrap();
+            }
+        }
+        csv
+    }
+    /// Sparse node/set incidence matrix in Matrix Market coordinate format; rows are nodes and
+    /// columns are node sets, both 1-indexed (i.e., node ID + 1 and set index + 1).
+    /// `number_of_nodes` fixes the number of rows, which must exceed all contained node IDs.
+    pub fn to_incidence_matrix_market_string(&self, number_of_nodes: usize) -> String {
+        let node_sets = self.clone().unwrap();
+        let number_of_entries: usize = node_sets.iter().map(|node_set| node_set.len()).sum();
+        let mut mtx = String::from("%%MatrixMarket matrix coordinate pattern general\n");
+        writeln!(
+            mtx,
+            "{} {} {}",
+            number_of_nodes,
+            node_sets.len(),
+            number_of_entries
+        )
+        .unwrap();
+        for (set_index, node_set) in node_sets.iter().enumerate() {
+            for node_id in node_set.iter() {
+                assert!(
+                    node_id < number_of_nodes,
+                    "Node ID {} out of range for {} nodes!",
+                    node_id,
+                    number_of_nodes
+                );
+                writeln!(mtx, "{} {}", node_id + 1, set_index + 1).unwrap();
+            }
+        }
+        mtx
+    }
+    /// Writes the incidence matrix to `path`, in Matrix Market format if the file name ends in
+    /// `.mtx` and as CSV otherwise.
+    pub fn to_incidence_matrix_file(&self, path: &Path, number_of_nodes: usize) -> io::Result<()> {
+        let contents = if path.extension() == Some(OsStr::new("mtx")) {
+            self.to_incidence_matrix_market_string(number_of_nodes)
+        } else {
+            self.to_incidence_csv_string()
+        };
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incidence_matrix_formats() {
+        let result = NodeIdSetVecResult::new(vec![bitset![0, 1], bitset![1, 3]], None);
+        let expected = "node_id,set_index\n0,0\n1,0\n1,1\n3,1\n";
+        assert_eq!(expected, result.to_incidence_csv_string());
+        let expected = "%%MatrixMarket matrix coordinate pattern general\n\
+                        5 2 4\n1 1\n2 1\n2 2\n4 2\n";
+        assert_eq!(expected, result.to_incidence_matrix_market_string(5));
+    }
+
+    #[test]
+    fn incidence_matrix_uses_unshrunken_node_ids() {
+        let unshrink_table = vec![2, 5, 7];
+        let result = NodeIdSetVecResult::new(vec![bitset![0, 2]], Some(&unshrink_table));
+        let expected = "node_id,set_index\n2,0\n7,0\n";
+        assert_eq!(expected, result.to_incidence_csv_string());
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+
+use std::fmt::Write as _;
+
+/// At most this many of the smallest blocking/splitting sets are listed by name.
+const MAX_LISTED_NODE_SETS: usize = 10;
+
+impl<'a> Analysis<'a> {
+    /// Renders the main analysis results (quorum intersection, top tier, smallest minimal
+    /// blocking and splitting sets, set size histograms) as a Markdown document. Nodes are
+    /// identified by organization name if merging by organization, otherwise by node name or,
+    /// failing that, public key. Triggers all underlying searches, if not already cached.
+    pub fn to_markdown_report(&mut self) -> String {
+        let fbas = self.fbas_original;
+        let organizations = self.organizations_original;
+        let mut md = String::from("# FBAS analysis report\n\n");
+
+        writeln!(md, "- Nodes: {}", self.all_physical_nodes().len()).unwrap();
+        if self.merging_by_organization() {
+            writeln!(
+                md,
+                "- Nodes after merging by organization: {}",
+                self.all_nodes().len()
+            )
+            .unwrap();
+        }
+
+        md.push_str("\n## Quorum intersection\n\n");
+        if self.has_quorum_intersection() {
+            md.push_str("All quorums intersect 👍\n");
+        } else {
+            md.push_str("**Some quorums don't intersect 👎** Safety is severely threatened!\n");
+        }
+
+        let top_tier = self.top_tier().unwrap();
+        writeln!(md, "\n## Top tier\n\n{} members:\n", top_tier.len()).unwrap();
+        for node_id in top_tier.iter() {
+            writeln!(md, "- {}", markdown_name(node_id, fbas, organizations)).unwrap();
+        }
+
+        write_markdown_section(
+            &mut md,
+            "Minimal quorums",
+            &self.minimal_quorums(),
+            false,
+            fbas,
+            organizations,
+        );
+        write_markdown_section(
+            &mut md,
+            "Minimal blocking sets",
+            &self.minimal_blocking_sets(),
+            true,
+            fbas,
+            organizations,
+        );
+        write_markdown_section(
+            &mut md,
+            "Minimal splitting sets",
+            &self.minimal_splitting_sets(),
+            true,
+            fbas,
+            organizations,
+        );
+        md
+    }
+}
+
+fn write_markdown_section(
+    md: &mut String,
+    title: &str,
+    result: &NodeIdSetVecResult,
+    list_smallest: bool,
+    fbas: &Fbas,
+    organizations: Option<&Organizations>,
+) {
+    writeln!(md, "\n## {}\n\nFound {}.", title, result.len()).unwrap();
+    if result.is_empty() {
+        return;
+    }
+    let (min_size, max_size, mean_size) = result.minmaxmean();
+    writeln!(
+        md,
+        "Sizes range from {} to {} (mean {:.2}).",
+        min_size, max_size, mean_size
+    )
+    .unwrap();
+    if list_smallest {
+        let smallest: Vec<NodeIdSet> = result
+            .clone()
+            .unwrap()
+            .into_iter()
+            .filter(|node_set| node_set.len() == min_size)
+            .collect();
+        writeln!(md, "\nSmallest (size {}):\n", min_size).unwrap();
+        for node_set in smallest.iter().take(MAX_LISTED_NODE_SETS) {
+            let names: Vec<String> = node_set
+                .iter()
+                .map(|node_id| markdown_name(node_id, fbas, organizations))
+                .collect();
+            writeln!(md, "- {}", names.join(", ")).unwrap();
+        }
+        if smallest.len() > MAX_LISTED_NODE_SETS {
+            writeln!(
+                md,
+                "- ... and {} more",
+                smallest.len() - MAX_LISTED_NODE_SETS
+            )
+            .unwrap();
+        }
+    }
+    md.push_str("\n| Size | Count |\n| ---: | ---: |\n");
+    for (size, &count) in result.histogram().iter().enumerate() {
+        if count > 0 {
+            writeln!(md, "| {} | {} |", size, count).unwrap();
+        }
+    }
+}
+fn markdown_name(node_id: NodeId, fbas: &Fbas, organizations: Option<&Organizations>) -> String {
+    let name = display_name(node_id, fbas, organizations);
+    // backticks protect against names containing Markdown syntax
+    format!("`{}`", name.replace('`', "'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_report_for_simple_fbas() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            {
+                "publicKey": "n0",
+                "name": "Alice",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n1",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            },
+            {
+                "publicKey": "n2",
+                "quorumSet": { "threshold": 2, "validators": ["n0", "n1", "n2"] }
+            }
+        ]"#,
+        );
+        let expected = "# FBAS analysis report\n\
+            \n\
+            - Nodes: 3\n\
+            \n\
+            ## Quorum intersection\n\
+            \n\
+            All quorums intersect 👍\n\
+            \n\
+            ## Top tier\n\
+            \n\
+            3 members:\n\
+            \n\
+            - `Alice`\n\
+            - `n1`\n\
+            - `n2`\n\
+            \n\
+            ## Minimal quorums\n\
+            \n\
+            Found 3.\n\
+            Sizes range from 2 to 2 (mean 2.00).\n\
+            \n\
+            | Size | Count |\n\
+            | ---: | ---: |\n\
+            | 2 | 3 |\n\
+            \n\
+            ## Minimal blocking sets\n\
+            \n\
+            Found 3.\n\
+            Sizes range from 2 to 2 (mean 2.00).\n\
+            \n\
+            Smallest (size 2):\n\
+            \n\
+            - `Alice`, `n1`\n\
+            - `Alice`, `n2`\n\
+            - `n1`, `n2`\n\
+            \n\
+            | Size | Count |\n\
+            | ---: | ---: |\n\
+            | 2 | 3 |\n\
+            \n\
+            ## Minimal splitting sets\n\
+            \n\
+            Found 3.\n\
+            Sizes range from 1 to 1 (mean 1.00).\n\
+            \n\
+            Smallest (size 1):\n\
+            \n\
+            - `Alice`\n\
+            - `n1`\n\
+            - `n2`\n\
+            \n\
+            | Size | Count |\n\
+            | ---: | ---: |\n\
+            | 1 | 3 |\n";
+        let actual = Analysis::new(&fbas, None).to_markdown_report();
+        assert_eq!(expected, actual);
+    }
+}
@@ -13,3 +13,34 @@ pub use results::*;
 
 mod graph;
 pub use graph::*;
+
+mod html_report;
+
+mod incidence;
+
+mod markdown_report;
+
+mod stellar_core_config;
+pub use stellar_core_config::*;
+
+mod translation;
+
+/// Error of kind `InvalidInput` with the given message, e.g., for rejecting command line
+/// arguments that can't be checked by the argument parser itself.
+pub fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Organization name if merging by organization, otherwise node name or, failing that, public
+/// key; for human-readable reports.
+fn display_name<'a>(
+    node_id: NodeId,
+    fbas: &'a Fbas,
+    organizations: Option<&'a Organizations>,
+) -> &'a str {
+    let node = &fbas.nodes[node_id];
+    match organizations.and_then(|orgs| orgs.get_by_member(node_id)) {
+        Some(org) => &org.name,
+        None => node.name.as_ref().unwrap_or(&node.public_key),
+    }
+}
@@ -39,6 +39,54 @@ impl AnalysisResult for usize {
     }
 }
 
+// semantically strange, but for convenience
+impl AnalysisResult for f64 {
+    fn into_id_string(self) -> String {
+        self.to_string()
+    }
+    fn into_describe_string(self) -> String {
+        self.to_string()
+    }
+}
+
+/// `null` if there is no result, e.g., because nothing was found within some search limit
+impl<T: AnalysisResult> AnalysisResult for Option<T> {
+    fn into_id_string(self) -> String {
+        self.map_or_else(|| "null".to_string(), T::into_id_string)
+    }
+    fn into_pretty_string(self, fbas: &Fbas, organizations: &Option<Organizations>) -> String {
+        self.map_or_else(
+            || "null".to_string(),
+            |result| result.into_pretty_string(fbas, organizations),
+        )
+    }
+    fn into_describe_string(self) -> String {
+        self.map_or_else(|| "null".to_string(), T::into_describe_string)
+    }
+}
+
+/// (min, max, mean), e.g., of the total weights of a list of node sets
+impl AnalysisResult for (f64, f64, f64) {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_describe_string(self) -> String {
+        self.into_id_string()
+    }
+}
+
+impl AnalysisResult for QuorumSet {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, fbas: &Fbas, organizations: &Option<Organizations>) -> String {
+        json_format_pretty!(self.into_raw(fbas, organizations))
+    }
+    fn into_describe_string(self) -> String {
+        self.into_id_string()
+    }
+}
+
 impl AnalysisResult for Vec<QuorumSet> {
     fn into_id_string(self) -> String {
         json_format_single_line!(self)
@@ -55,6 +103,111 @@ impl AnalysisResult for Vec<QuorumSet> {
     }
 }
 
+/// Lists of quorum set edits, e.g., repairs from `find_quorum_intersection_repairs`
+impl AnalysisResult for Vec<Vec<QuorumSetEdit>> {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, fbas: &Fbas, _: &Option<Organizations>) -> String {
+        let pretty_self: Vec<Vec<serde_json::Value>> = self
+            .iter()
+            .map(|edits| {
+                edits
+                    .iter()
+                    .map(|edit| match *edit {
+                        QuorumSetEdit::RaiseThreshold { node } => serde_json::json!({
+                            "edit": "raiseThreshold",
+                            "node": fbas.nodes[node].public_key,
+                        }),
+                        QuorumSetEdit::AddValidator { node, validator } => serde_json::json!({
+                            "edit": "addValidator",
+                            "node": fbas.nodes[node].public_key,
+                            "validator": fbas.nodes[validator].public_key,
+                        }),
+                    })
+                    .collect()
+            })
+            .collect();
+        json_format_pretty!(pretty_self)
+    }
+    fn into_describe_string(self) -> String {
+        let sizes: Vec<usize> = self.iter().map(|edits| edits.len()).collect();
+        json_format_single_line!(sizes)
+    }
+}
+
+impl AnalysisResult for Vec<NodeSetSpan> {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_describe_string(self) -> String {
+        let counts: Vec<(usize, usize)> = self
+            .iter()
+            .map(|span| (span.organizations.len(), span.countries.len()))
+            .collect();
+        json_format_single_line!(counts)
+    }
+}
+
+impl AnalysisResult for NodeReport {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, _: &Fbas, _: &Option<Organizations>) -> String {
+        json_format_pretty!(self)
+    }
+    fn into_describe_string(self) -> String {
+        self.into_id_string()
+    }
+}
+
+impl AnalysisResult for Vec<ConfigurationLint> {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, _: &Fbas, _: &Option<Organizations>) -> String {
+        json_format_pretty!(self)
+    }
+    /// Number of lints per severity level
+    fn into_describe_string(self) -> String {
+        let count = |severity| self.iter().filter(|lint| lint.severity == severity).count();
+        json_format_single_line!(serde_json::json!({
+            "critical": count(Severity::Critical),
+            "warning": count(Severity::Warning),
+            "info": count(Severity::Info),
+        }))
+    }
+}
+
+impl AnalysisResult for Vec<ThresholdPercentageDistribution> {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, _: &Fbas, _: &Option<Organizations>) -> String {
+        json_format_pretty!(self)
+    }
+    /// (min, max, mean) per nesting level
+    fn into_describe_string(self) -> String {
+        let minmaxmeans: Vec<(f64, f64, f64)> = self
+            .iter()
+            .map(|distribution| (distribution.min, distribution.max, distribution.mean))
+            .collect();
+        json_format_single_line!(minmaxmeans)
+    }
+}
+
+impl AnalysisResult for SearchStatistics {
+    fn into_id_string(self) -> String {
+        json_format_single_line!(self)
+    }
+    fn into_pretty_string(self, _: &Fbas, _: &Option<Organizations>) -> String {
+        json_format_pretty!(self)
+    }
+    fn into_describe_string(self) -> String {
+        self.into_id_string()
+    }
+}
+
 impl<'a> AnalysisResult for NodeIdSetResult<'a> {
     fn into_id_string(self) -> String {
         json_format_single_line!(self.into_vec())
@@ -263,6 +416,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn missing_results_output_as_null() {
+        let fbas = Fbas::new();
+        let found: Option<usize> = Some(3);
+        let not_found: Option<QuorumSet> = None;
+        assert_eq!("3", found.into_id_string());
+        assert_eq!("null", not_found.clone().into_id_string());
+        assert_eq!("null", not_found.clone().into_pretty_string(&fbas, &None));
+        assert_eq!("null", not_found.into_describe_string());
+    }
+
     #[test]
     fn symmetric_clusters_id_output_correctly() {
         let fbas = Fbas::from_json_file(Path::new("test_data/stellarbeat_nodes_2019-09-17.json"));
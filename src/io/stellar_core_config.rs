@@ -0,0 +1,526 @@
+use super::*;
+use std::collections::BTreeMap;
+use toml::value::Table;
+use toml::Value;
+
+/// Input formats that `Fbas::from_str_detecting_format` can tell apart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    /// stellarbeat.org "nodes" JSON, including all the fields we don't need.
+    StellarbeatJson,
+    /// The subset of the stellarbeat.org format that this crate writes (e.g., via
+    /// `Fbas::to_json_string`): only public keys, quorum sets and optional metadata.
+    SimplifiedJson,
+    /// A stellar-core configuration file (TOML), using either a `[QUORUM_SET]` table or
+    /// `[[VALIDATORS]]` entries for automatic quorum set generation.
+    StellarCoreConfig,
+}
+impl InputFormat {
+    /// Returns `None` if `input` looks like none of the supported formats.
+    pub fn detect(input: &str) -> Option<Self> {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(input) {
+            // if it isn't simplified JSON, validation will tell what is wrong with it
+            Some(if uses_only_simplified_fields(&json) {
+                InputFormat::SimplifiedJson
+            } else {
+                InputFormat::StellarbeatJson
+            })
+        } else if let Ok(Value::Table(config)) = toml::from_str::<Value>(input) {
+            if config.contains_key("QUORUM_SET") || config.contains_key("VALIDATORS") {
+                Some(InputFormat::StellarCoreConfig)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+const SIMPLIFIED_JSON_FIELDS: [&str; 9] = [
+    "publicKey",
+    "name",
+    "quorumSet",
+    "geoData",
+    "organizationId",
+    "active",
+    "isp",
+    "versionStr",
+    "tags",
+];
+fn uses_only_simplified_fields(json: &serde_json::Value) -> bool {
+    let raw_nodes = match json.as_array() {
+        Some(raw_nodes) => raw_nodes,
+        None => return false,
+    };
+    raw_nodes.iter().all(|raw_node| match raw_node.as_object() {
+        Some(fields) => fields
+            .keys()
+            .all(|key| SIMPLIFIED_JSON_FIELDS.contains(&key.as_str())),
+        None => false,
+    })
+}
+
+impl Fbas {
+    /// Detects the format of `input` (see `InputFormat`) and parses it accordingly.
+    pub fn from_str_detecting_format(input: &str) -> Self {
+        match InputFormat::detect(input) {
+            Some(InputFormat::StellarbeatJson) | Some(InputFormat::SimplifiedJson) => {
+                Self::from_json_str(input)
+            }
+            Some(InputFormat::StellarCoreConfig) => Self::from_stellar_core_config_str(input),
+            None => panic!(
+                "Unknown input format; expected stellarbeat.org nodes JSON, \
+                 fbas_analyzer JSON or a stellar-core config (TOML)"
+            ),
+        }
+    }
+    pub fn from_file_detecting_format(path: &Path) -> Self {
+        let input =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
+        Self::from_str_detecting_format(&input)
+    }
+    pub fn from_stdin_detecting_format() -> Self {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .expect("Error reading FBAS from STDIN");
+        Self::from_str_detecting_format(&input)
+    }
+    /// A stellar-core config only describes the quorum set of a single node. We therefore assume
+    /// that all validators referenced in it use that same quorum set, i.e., we analyze the FBAS
+    /// that results if everyone configured their node like this one.
+    ///
+    /// Quorum sets are read from the `[QUORUM_SET]` table (with nested tables as inner quorum
+    /// sets) if present. Otherwise, they are generated from `[[VALIDATORS]]` entries, similarly to
+    /// stellar-core: validators are grouped into organizations by `HOME_DOMAIN` (threshold 51%),
+    /// organizations by `QUALITY` (threshold 67%), with each quality level containing the next
+    /// lower one as an additional inner quorum set.
+    pub fn from_stellar_core_config_str(config: &str) -> Self {
+        let config: Table = toml::from_str(config).expect("Error parsing stellar-core config TOML");
+        let names = validator_names(&config);
+        let raw_quorum_set = if let Some(quorum_set) = config.get("QUORUM_SET") {
+            quorum_set_from_table(
+                quorum_set.as_table().expect("QUORUM_SET must be a table"),
+                &names,
+            )
+        } else if let Some(validators) = config.get("VALIDATORS") {
+            quorum_set_from_validator_entries(
+                validators
+                    .as_array()
+                    .expect("VALIDATORS must be an array of tables"),
+                &config,
+            )
+        } else {
+            panic!("stellar-core config contains neither QUORUM_SET nor VALIDATORS");
+        };
+        let mut public_keys = vec![];
+        collect_public_keys(&raw_quorum_set, &mut public_keys);
+        public_keys.sort();
+        public_keys.dedup();
+        info!(
+            "Read quorum set with {} validators from stellar-core config.",
+            public_keys.len()
+        );
+        Self::from_shared_raw_quorum_set(public_keys, &raw_quorum_set)
+    }
+}
+
+impl QuorumSet {
+    /// Renders the quorum set as a stellar-core `[QUORUM_SET]` table (inner quorum sets become
+    /// nested tables), e.g., for copying a recommended quorum set into a validator's config.
+    /// Thresholds are converted to the highest `THRESHOLD_PERCENT` that stellar-core maps to the
+    /// same threshold; node names are added as comments. Fails with `InvalidData` if some threshold
+    /// can't be expressed as a percentage (e.g., because it is higher than the number of entries).
+    ///
+    /// ```
+    /// use fbas_analyzer::{Fbas, QuorumSet};
+    ///
+    /// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+    /// let quorum_set = QuorumSet::from_entries(2, vec![0, 1, 2], vec![]);
+    ///
+    /// let config = quorum_set.to_stellar_core_config_string(&fbas).unwrap();
+    /// assert!(config.starts_with("[QUORUM_SET]\nTHRESHOLD_PERCENT = 66\n"));
+    ///
+    /// let fbas_from_config = Fbas::from_stellar_core_config_str(&config);
+    /// assert_eq!(3, fbas_from_config.number_of_nodes());
+    /// ```
+    pub fn to_stellar_core_config_string(&self, fbas: &Fbas) -> io::Result<String> {
+        let mut config = String::new();
+        self.write_stellar_core_config_table("QUORUM_SET", fbas, &mut config)?;
+        Ok(config)
+    }
+    fn write_stellar_core_config_table(
+        &self,
+        table_name: &str,
+        fbas: &Fbas,
+        config: &mut String,
+    ) -> io::Result<()> {
+        let number_of_entries = self.validators.len() + self.inner_quorum_sets.len();
+        let threshold_percent = (0..=100)
+            .rev()
+            .find(|&percent| threshold_from_percent(number_of_entries, percent) == self.threshold)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Threshold {} of {} entries in [{}] can't be expressed as THRESHOLD_PERCENT",
+                        self.threshold, number_of_entries, table_name
+                    ),
+                )
+            })?;
+        config.push_str(&format!("[{}]\n", table_name));
+        config.push_str(&format!("THRESHOLD_PERCENT = {}\n", threshold_percent));
+        config.push_str("VALIDATORS = [\n");
+        for &validator in self.validators.iter() {
+            let node = &fbas.nodes[validator];
+            match node.name {
+                Some(ref name) => {
+                    config.push_str(&format!("    \"{}\", # {}\n", node.public_key, name))
+                }
+                None => config.push_str(&format!("    \"{}\",\n", node.public_key)),
+            }
+        }
+        config.push_str("]\n");
+        for (i, inner_quorum_set) in self.inner_quorum_sets.iter().enumerate() {
+            config.push('\n');
+            inner_quorum_set.write_stellar_core_config_table(
+                &format!("{}.INNER_{}", table_name, i + 1),
+                fbas,
+                config,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Names that can be used as `$name` instead of public keys in `VALIDATORS` lists.
+fn validator_names(config: &Table) -> HashMap<String, PublicKey> {
+    let mut names = HashMap::new();
+    if let Some(validators) = config.get("VALIDATORS").and_then(|v| v.as_array()) {
+        for validator in validators.iter().filter_map(|v| v.as_table()) {
+            if let (Some(name), Some(public_key)) = (
+                validator.get("NAME").and_then(|n| n.as_str()),
+                validator.get("PUBLIC_KEY").and_then(|pk| pk.as_str()),
+            ) {
+                names.insert(name.to_string(), public_key.to_string());
+            }
+        }
+    }
+    if let Some(quorum_set) = config.get("QUORUM_SET").and_then(|q| q.as_table()) {
+        collect_legacy_validator_names(quorum_set, &mut names);
+    }
+    names
+}
+fn collect_legacy_validator_names(quorum_set: &Table, names: &mut HashMap<String, PublicKey>) {
+    for (key, value) in quorum_set.iter() {
+        if key == "VALIDATORS" {
+            for entry in value.as_array().into_iter().flatten() {
+                let mut tokens = entry.as_str().unwrap_or_default().split_whitespace();
+                if let (Some(public_key), Some(name)) = (tokens.next(), tokens.next()) {
+                    names.insert(name.to_string(), public_key.to_string());
+                }
+            }
+        } else if let Some(inner_quorum_set) = value.as_table() {
+            collect_legacy_validator_names(inner_quorum_set, names);
+        }
+    }
+}
+
+fn quorum_set_from_table(table: &Table, names: &HashMap<String, PublicKey>) -> RawQuorumSet {
+    let threshold_percent = table
+        .get("THRESHOLD_PERCENT")
+        .map(|t| {
+            t.as_integer()
+                .expect("THRESHOLD_PERCENT must be an integer") as usize
+        })
+        .unwrap_or(67);
+    let validators: Vec<PublicKey> = table
+        .get("VALIDATORS")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let token = entry
+                .as_str()
+                .expect("VALIDATORS entries must be strings")
+                .split_whitespace()
+                .next()?;
+            if let Some(name) = token.strip_prefix('$') {
+                let public_key = names.get(name).cloned();
+                if public_key.is_none() {
+                    warn!("Ignoring unknown validator name ${} in QUORUM_SET.", name);
+                }
+                public_key
+            } else {
+                Some(token.to_string())
+            }
+        })
+        .collect();
+    let inner_quorum_sets: Vec<RawQuorumSet> = table
+        .values()
+        .filter_map(|v| v.as_table())
+        .map(|inner_table| quorum_set_from_table(inner_table, names))
+        .collect();
+    let threshold = threshold_from_percent(
+        validators.len() + inner_quorum_sets.len(),
+        threshold_percent,
+    );
+    RawQuorumSet {
+        threshold,
+        validators,
+        inner_quorum_sets,
+    }
+}
+
+const QUALITY_LEVELS: [&str; 4] = ["CRITICAL", "HIGH", "MEDIUM", "LOW"];
+
+fn quorum_set_from_validator_entries(validators: &[Value], config: &Table) -> RawQuorumSet {
+    let domain_qualities: HashMap<&str, &str> = config
+        .get("HOME_DOMAINS")
+        .and_then(|h| h.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            Some((
+                entry.get("HOME_DOMAIN")?.as_str()?,
+                entry.get("QUALITY")?.as_str()?,
+            ))
+        })
+        .collect();
+    // quality level -> home domain -> public keys
+    let mut organizations: Vec<BTreeMap<&str, Vec<PublicKey>>> =
+        vec![BTreeMap::new(); QUALITY_LEVELS.len()];
+    for validator in validators.iter() {
+        let field = |key: &str| validator.get(key).and_then(|v| v.as_str());
+        let public_key = field("PUBLIC_KEY").expect("VALIDATORS entries need a PUBLIC_KEY");
+        let home_domain = field("HOME_DOMAIN").unwrap_or(public_key);
+        let quality = field("QUALITY")
+            .or_else(|| domain_qualities.get(home_domain).copied())
+            .unwrap_or("LOW");
+        let level = QUALITY_LEVELS
+            .iter()
+            .position(|&q| q == quality)
+            .unwrap_or_else(|| panic!("Unknown validator QUALITY {}", quality));
+        organizations[level]
+            .entry(home_domain)
+            .or_default()
+            .push(public_key.to_string());
+    }
+    let mut quorum_set: Option<RawQuorumSet> = None;
+    for organizations_of_level in organizations.into_iter().rev() {
+        if organizations_of_level.is_empty() {
+            continue;
+        }
+        let mut inner_quorum_sets: Vec<RawQuorumSet> = organizations_of_level
+            .values()
+            .map(|validators| RawQuorumSet {
+                threshold: threshold_from_percent(validators.len(), 51),
+                validators: validators.clone(),
+                inner_quorum_sets: vec![],
+            })
+            .collect();
+        inner_quorum_sets.extend(quorum_set.take());
+        quorum_set = Some(RawQuorumSet {
+            threshold: threshold_from_percent(inner_quorum_sets.len(), 67),
+            validators: vec![],
+            inner_quorum_sets,
+        });
+    }
+    quorum_set.unwrap_or_default()
+}
+
+/// Same rounding as stellar-core: the smallest threshold that is at least `percent` percent.
+fn threshold_from_percent(number_of_entries: usize, percent: usize) -> usize {
+    let scaled_entries = number_of_entries * percent;
+    if scaled_entries == 0 {
+        0
+    } else {
+        1 + (scaled_entries - 1) / 100
+    }
+}
+
+fn collect_public_keys(raw_quorum_set: &RawQuorumSet, public_keys: &mut Vec<PublicKey>) {
+    public_keys.extend(raw_quorum_set.validators.iter().cloned());
+    for inner_quorum_set in raw_quorum_set.inner_quorum_sets.iter() {
+        collect_public_keys(inner_quorum_set, public_keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_input_formats() {
+        let stellarbeat = fs::read_to_string("test_data/correct_trivial.json").unwrap();
+        let simplified = Fbas::from_json_str(&stellarbeat).to_json_string();
+        let config = r#"
+            [QUORUM_SET]
+            VALIDATORS = ["GA", "GB", "GC"]
+        "#;
+        assert_eq!(
+            Some(InputFormat::StellarbeatJson),
+            InputFormat::detect(&stellarbeat)
+        );
+        assert_eq!(
+            Some(InputFormat::SimplifiedJson),
+            InputFormat::detect(&simplified)
+        );
+        assert_eq!(
+            Some(InputFormat::StellarCoreConfig),
+            InputFormat::detect(config)
+        );
+        assert_eq!(None, InputFormat::detect("HTTP_PORT = 11626"));
+        assert_eq!(None, InputFormat::detect("not an FBAS"));
+    }
+
+    #[test]
+    fn reads_legacy_quorum_set_from_stellar_core_config() {
+        let config = r#"
+            NODE_SEED = "SA... self"
+
+            [QUORUM_SET]
+            THRESHOLD_PERCENT = 66
+            VALIDATORS = ["GA sdf1", "GB sdf2"]
+
+            [QUORUM_SET.inner]
+            THRESHOLD_PERCENT = 100
+            VALIDATORS = ["GC", "$sdf1"]
+        "#;
+        let fbas = Fbas::from_str_detecting_format(config);
+        assert_eq!(3, fbas.number_of_nodes());
+        let expected = QuorumSet {
+            // 66% of 3 entries, rounded up
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 2,
+                validators: vec![0, 2],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        for node in fbas.nodes.iter() {
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn writes_quorum_sets_that_read_back_the_same() {
+        let fbas = Fbas::from_json_str(
+            r#"[
+            { "publicKey": "GA", "name": "alice" },
+            { "publicKey": "GB" },
+            { "publicKey": "GC" },
+            { "publicKey": "GD" },
+            { "publicKey": "GE" }
+        ]"#,
+        );
+        let quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 3,
+                    validators: vec![1, 2, 3, 4],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![1, 2],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        let config = quorum_set.to_stellar_core_config_string(&fbas).unwrap();
+        assert!(config.contains("\"GA\", # alice\n"));
+        assert!(config.contains("[QUORUM_SET.INNER_1]\nTHRESHOLD_PERCENT = 75\n"));
+        assert!(config.contains("[QUORUM_SET.INNER_2]\nTHRESHOLD_PERCENT = 50\n"));
+
+        let fbas_from_config = Fbas::from_stellar_core_config_str(&config);
+        assert_eq!(fbas.number_of_nodes(), fbas_from_config.number_of_nodes());
+        for node in fbas_from_config.nodes.iter() {
+            // inner quorum sets might be read back in a different order
+            assert!(
+                QuorumSetDiff::new(&quorum_set, &fbas, &node.quorum_set, &fbas_from_config)
+                    .is_empty()
+            );
+        }
+    }
+
+    #[test]
+    fn unsatisfiable_threshold_cant_be_written_as_stellar_core_config() {
+        let fbas = Fbas::new_generic_unconfigured(2);
+        let quorum_set = QuorumSet {
+            threshold: 1,
+            validators: vec![0],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 3,
+                validators: vec![0, 1],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        let error = quorum_set.to_stellar_core_config_string(&fbas).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert!(error.to_string().contains("[QUORUM_SET.INNER_1]"));
+    }
+
+    #[test]
+    fn generates_quorum_set_from_stellar_core_validator_entries() {
+        let config = r#"
+            [[HOME_DOMAINS]]
+            HOME_DOMAIN = "a.example"
+            QUALITY = "HIGH"
+
+            [[VALIDATORS]]
+            NAME = "a1"
+            HOME_DOMAIN = "a.example"
+            PUBLIC_KEY = "GA1"
+
+            [[VALIDATORS]]
+            NAME = "a2"
+            HOME_DOMAIN = "a.example"
+            PUBLIC_KEY = "GA2"
+
+            [[VALIDATORS]]
+            NAME = "b1"
+            HOME_DOMAIN = "b.example"
+            PUBLIC_KEY = "GB1"
+            QUALITY = "HIGH"
+
+            [[VALIDATORS]]
+            NAME = "c1"
+            HOME_DOMAIN = "c.example"
+            PUBLIC_KEY = "GC1"
+            QUALITY = "MEDIUM"
+        "#;
+        let fbas = Fbas::from_stellar_core_config_str(config);
+        assert_eq!(4, fbas.number_of_nodes());
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![],
+                    inner_quorum_sets: vec![QuorumSet {
+                        threshold: 1,
+                        validators: vec![3],
+                        inner_quorum_sets: vec![],
+                    }],
+                },
+                QuorumSet {
+                    threshold: 1,
+                    validators: vec![2],
+                    inner_quorum_sets: vec![],
+                },
+                QuorumSet {
+                    threshold: 2,
+                    validators: vec![0, 1],
+                    inner_quorum_sets: vec![],
+                },
+            ],
+        };
+        assert_eq!(expected, fbas.nodes[0].quorum_set);
+        assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+    }
+}
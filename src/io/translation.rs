@@ -0,0 +1,161 @@
+use super::*;
+
+impl Fbas {
+    /// Public keys of the nodes in `node_set`, ordered by node ID.
+    pub fn to_public_keys(&self, node_set: &NodeIdSet) -> Vec<PublicKey> {
+        node_set
+            .iter()
+            .map(|node_id| self.nodes[node_id].public_key.clone())
+            .collect()
+    }
+    /// Like `to_public_keys`, for each of `node_sets` (e.g., all minimal quorums).
+    ///
+    /// ```
+    /// #[macro_use] extern crate fbas_analyzer;
+    /// use fbas_analyzer::Fbas;
+    ///
+    /// let fbas = Fbas::from_json_file(std::path::Path::new("test_data/correct_trivial.json"));
+    /// let node_sets = bitsetvec![[0, 1], [2]];
+    ///
+    /// let public_keys = fbas.to_public_keys_vec(&node_sets);
+    /// assert_eq!(
+    ///     vec![
+    ///         "GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH",
+    ///         "GCM6QMP3DLRPTAZW2UZPCPX2LF3SXWXKPMP3GKFZBDSF3QZGV2G5QSTK"
+    ///     ],
+    ///     public_keys[0]
+    /// );
+    /// assert_eq!(node_sets, fbas.node_sets_from_identifiers(&public_keys));
+    /// ```
+    pub fn to_public_keys_vec(&self, node_sets: &[NodeIdSet]) -> Vec<Vec<PublicKey>> {
+        node_sets
+            .iter()
+            .map(|node_set| self.to_public_keys(node_set))
+            .collect()
+    }
+    /// Names of the nodes in `node_set` (public keys for nodes without a name), ordered by node
+    /// ID. Names aren't necessarily unique.
+    pub fn to_names(&self, node_set: &NodeIdSet) -> Vec<String> {
+        node_set
+            .iter()
+            .map(|node_id| {
+                let node = &self.nodes[node_id];
+                node.name.as_ref().unwrap_or(&node.public_key).clone()
+            })
+            .collect()
+    }
+    /// Like `to_names`, for each of `node_sets`.
+    pub fn to_names_vec(&self, node_sets: &[NodeIdSet]) -> Vec<Vec<String>> {
+        node_sets
+            .iter()
+            .map(|node_set| self.to_names(node_set))
+            .collect()
+    }
+    /// ID of the node with public key `identifier` or, failing that, of the only node named
+    /// `identifier`.
+    pub fn get_node_id_by_identifier(&self, identifier: &str) -> Option<NodeId> {
+        self.get_node_id(identifier).or_else(|| {
+            let mut matching_nodes = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.name.as_deref() == Some(identifier))
+                .map(|(node_id, _)| node_id);
+            match (matching_nodes.next(), matching_nodes.next()) {
+                (Some(node_id), None) => Some(node_id),
+                (Some(_), Some(_)) => {
+                    warn!("Node name {} is ambiguous; use a public key.", identifier);
+                    None
+                }
+                _ => None,
+            }
+        })
+    }
+    /// Inverse of `to_public_keys` and `to_names`, e.g., for reading node sets given as command
+    /// line arguments. Panics on unknown or ambiguous identifiers, as these are probably typos.
+    pub fn node_set_from_identifiers<S: AsRef<str>>(&self, identifiers: &[S]) -> NodeIdSet {
+        identifiers
+            .iter()
+            .map(|identifier| {
+                let identifier = identifier.as_ref();
+                self.get_node_id_by_identifier(identifier)
+                    .unwrap_or_else(|| panic!("Unknown node {}", identifier))
+            })
+            .collect()
+    }
+    /// Like `node_set_from_identifiers`, for each of `identifier_sets`.
+    pub fn node_sets_from_identifiers<S: AsRef<str>>(
+        &self,
+        identifier_sets: &[Vec<S>],
+    ) -> Vec<NodeIdSet> {
+        identifier_sets
+            .iter()
+            .map(|identifiers| self.node_set_from_identifiers(identifiers))
+            .collect()
+    }
+    /// Reads a node set from a JSON array of public keys or node names.
+    pub fn node_set_from_json_str(&self, json: &str) -> NodeIdSet {
+        let identifiers: Vec<String> =
+            serde_json::from_str(json).expect("Error parsing node set JSON");
+        self.node_set_from_identifiers(&identifiers)
+    }
+    /// Reads node sets from a JSON array of arrays of public keys or node names, i.e., the
+    /// format of pretty-printed results such as minimal quorums.
+    pub fn node_sets_from_json_str(&self, json: &str) -> Vec<NodeIdSet> {
+        let identifier_sets: Vec<Vec<String>> =
+            serde_json::from_str(json).expect("Error parsing node sets JSON");
+        self.node_sets_from_identifiers(&identifier_sets)
+    }
+    pub fn node_sets_from_json_file(&self, path: &Path) -> Vec<NodeIdSet> {
+        let json =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
+        self.node_sets_from_json_str(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named_fbas() -> Fbas {
+        Fbas::from_json_str(
+            r#"[
+            { "publicKey": "GA", "name": "alice" },
+            { "publicKey": "GB", "name": "bob" },
+            { "publicKey": "GC", "name": "bob" },
+            { "publicKey": "GD" }
+        ]"#,
+        )
+    }
+
+    #[test]
+    fn translate_node_sets_to_names() {
+        let fbas = named_fbas();
+        assert_eq!(
+            vec![vec!["alice", "bob"], vec!["bob", "GD"]],
+            fbas.to_names_vec(&bitsetvec![[0, 1], [2, 3]])
+        );
+    }
+
+    #[test]
+    fn read_node_sets_by_public_key_or_unique_name() {
+        let fbas = named_fbas();
+        assert_eq!(Some(0), fbas.get_node_id_by_identifier("alice"));
+        assert_eq!(Some(2), fbas.get_node_id_by_identifier("GC"));
+        assert_eq!(None, fbas.get_node_id_by_identifier("bob"));
+        assert_eq!(
+            bitsetvec![[0, 3], []],
+            fbas.node_sets_from_json_str(r#"[["alice", "GD"], []]"#)
+        );
+        assert_eq!(
+            bitset![1, 2],
+            fbas.node_set_from_json_str(r#"["GB", "GC"]"#)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown node carol")]
+    fn reading_unknown_node_panics() {
+        named_fbas().node_set_from_identifiers(&["alice", "carol"]);
+    }
+}
@@ -5,11 +5,12 @@ mod io;
 mod simulation;
 
 pub use analysis::*;
-pub use core_types::{Fbas, NodeIdSet, Organizations};
-pub use graph::Graph;
-pub use io::AnalysisResult;
+pub use core_types::{Fbas, NodeIdSet, NodeMetadata, NodeWeights, Organizations, QuorumSet};
+pub use graph::{EdgeWeight, Graph, Position};
+pub use io::{invalid_input, AnalysisResult, InputFormat};
 pub use simulation::{
-    monitors, quorum_set_configurators, QuorumSetConfigurator, SimulationMonitor, Simulator,
+    attacks, checkpoints, experiments, failures, manifests, monitors, quorum_set_configurators,
+    scenarios, synthetic, QuorumSetConfigurator, Region, SimulationMonitor, Simulator,
 };
 
 use core_types::*;
@@ -0,0 +1,351 @@
+use super::*;
+
+/// How to rank nodes by importance when choosing whom to attack first.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AttackStrategy {
+    /// Nodes that appear in the most quorum sets first
+    Degree,
+    /// Nodes with the highest rank scores (PageRank-like, on the trust graph) first
+    Rank,
+    /// Nodes that are members of the most minimal quorums first
+    MinimalQuorumMembership,
+}
+
+/// State of the FBAS after one more node has failed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttackStep {
+    pub failed_node: NodeId,
+    pub has_quorums: bool,
+    /// As in `Analysis::has_quorum_intersection`, `false` if there are no quorums at all
+    pub has_quorum_intersection: bool,
+}
+
+/// Lets up to `maximum_number_of_failures` nodes fail (crash), in order of decreasing importance
+/// according to `strategy`, and records after each failure whether the remaining FBAS still has
+/// quorums and whether all of them intersect. Importance is determined once, for the intact
+/// FBAS; ties are broken in favor of lower node IDs. Failed nodes are removed from all quorum
+/// sets without lowering any thresholds, i.e., nodes don't get to adapt their configurations.
+pub fn simulate_targeted_attack(
+    fbas: &Fbas,
+    strategy: AttackStrategy,
+    maximum_number_of_failures: usize,
+) -> Vec<AttackStep> {
+    let scores = get_importance_scores(fbas, strategy);
+    let mut order: Vec<NodeId> = (0..fbas.number_of_nodes()).collect();
+    // stable sort, so lower node IDs win ties
+    order.sort_by(|&i, &j| scores[j].partial_cmp(&scores[i]).unwrap());
+
+    let mut remaining_nodes = fbas.all_nodes();
+    order
+        .into_iter()
+        .take(maximum_number_of_failures)
+        .map(|failed_node| {
+            remaining_nodes.remove(failed_node);
+            let (remaining_fbas, _, _) = Fbas::shrunken(fbas, remaining_nodes.clone());
+            let mut analysis = Analysis::new(&remaining_fbas, None);
+            let has_quorums = !analysis.minimal_quorums().is_empty();
+            let has_quorum_intersection = analysis.has_quorum_intersection();
+            debug!(
+                "Node {} failed; has quorums: {}, has quorum intersection: {}",
+                failed_node, has_quorums, has_quorum_intersection
+            );
+            AttackStep {
+                failed_node,
+                has_quorums,
+                has_quorum_intersection,
+            }
+        })
+        .collect()
+}
+
+fn get_importance_scores(fbas: &Fbas, strategy: AttackStrategy) -> Vec<f64> {
+    match strategy {
+        AttackStrategy::Degree => Graph::from_fbas(fbas)
+            .get_in_degrees()
+            .into_iter()
+            .map(|degree| degree as f64)
+            .collect(),
+        AttackStrategy::Rank => Graph::from_fbas(fbas).get_rank_scores(),
+        AttackStrategy::MinimalQuorumMembership => {
+            let mut scores = vec![0.; fbas.number_of_nodes()];
+            for quorum in find_minimal_quorums(fbas).iter() {
+                for node_id in quorum.iter() {
+                    scores[node_id] += 1.;
+                }
+            }
+            scores
+        }
+    }
+}
+
+/// Estimated robustness of an FBAS against a given number of random (crash) failures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RobustnessPoint {
+    pub number_of_failures: usize,
+    pub failure_fraction: f64,
+    /// Fraction of samples in which the remaining nodes still have quorums
+    pub availability_probability: f64,
+    /// Fraction of samples in which the remaining nodes still have quorums, all of which
+    /// intersect
+    pub survival_probability: f64,
+    /// 95% Wilson score interval for `survival_probability`
+    pub survival_confidence_interval: (f64, f64),
+}
+
+/// Lets random sets of 0, 1, ..., `maximum_number_of_failures` nodes fail (crash), sampling
+/// `samples_per_step` failure sets per size, and estimates how likely it is that the remaining
+/// FBAS still has quorums (and that these intersect). As in `simulate_targeted_attack`, failed
+/// nodes are removed without lowering any thresholds.
+pub fn simulate_random_failures(
+    fbas: &Fbas,
+    maximum_number_of_failures: usize,
+    samples_per_step: usize,
+) -> Vec<RobustnessPoint> {
+    assert!(samples_per_step > 0, "Need at least one sample per step!");
+    let n = fbas.number_of_nodes();
+    let all_nodes: Vec<NodeId> = (0..n).collect();
+    let mut rng = thread_rng();
+
+    (0..=cmp::min(maximum_number_of_failures, n))
+        .map(|number_of_failures| {
+            let mut available_samples = 0;
+            let mut surviving_samples = 0;
+            for _ in 0..samples_per_step {
+                let mut remaining_nodes = fbas.all_nodes();
+                for &failed_node in all_nodes.choose_multiple(&mut rng, number_of_failures) {
+                    remaining_nodes.remove(failed_node);
+                }
+                let (remaining_fbas, _, _) = Fbas::shrunken(fbas, remaining_nodes);
+                let has_quorums =
+                    !find_unsatisfiable_nodes(&remaining_fbas.all_nodes(), &remaining_fbas)
+                        .0
+                        .is_empty();
+                if has_quorums {
+                    available_samples += 1;
+                    if find_nonintersecting_quorums(&remaining_fbas).is_none() {
+                        surviving_samples += 1;
+                    }
+                }
+            }
+            let survival_probability = surviving_samples as f64 / samples_per_step as f64;
+            debug!(
+                "{} random failures; survival probability: {}",
+                number_of_failures, survival_probability
+            );
+            RobustnessPoint {
+                number_of_failures,
+                failure_fraction: number_of_failures as f64 / n as f64,
+                availability_probability: available_samples as f64 / samples_per_step as f64,
+                survival_probability,
+                survival_confidence_interval: wilson_score_interval(
+                    survival_probability,
+                    samples_per_step,
+                ),
+            }
+        })
+        .collect()
+}
+
+/// 95% confidence interval for a probability estimated as `p` from `n` samples.
+fn wilson_score_interval(p: f64, n: usize) -> (f64, f64) {
+    let z = 1.96;
+    let n = n as f64;
+    let denominator = 1. + z * z / n;
+    let center = (p + z * z / (2. * n)) / denominator;
+    let half_width = z * (p * (1. - p) / n + z * z / (4. * n * n)).sqrt() / denominator;
+    (
+        f64::max(0., center - half_width),
+        f64::min(1., center + half_width),
+    )
+}
+
+/// Malicious Quorum Set Configurator for Sybil nodes: all nodes with IDs starting from
+/// `first_sybil` trust (only) each other, with a 67% threshold.
+pub struct SybilQsc {
+    first_sybil: NodeId,
+}
+impl SybilQsc {
+    pub fn new(first_sybil: NodeId) -> Self {
+        SybilQsc { first_sybil }
+    }
+}
+impl QuorumSetConfigurator for SybilQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let validators: Vec<NodeId> = (self.first_sybil..fbas.nodes.len()).collect();
+        let threshold = quorum_set_configurators::calculate_67p_threshold(validators.len());
+        let candidate = QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets: vec![],
+        };
+        let existing = &mut fbas.nodes[node_id].quorum_set;
+        if candidate == *existing {
+            NoChange
+        } else {
+            *existing = candidate;
+            Change
+        }
+    }
+}
+
+/// State of the FBAS after one more Sybil node has joined.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SybilAttackStep {
+    pub number_of_sybils: usize,
+    /// The honest nodes can't form a quorum without any Sybils, i.e., the Sybils are blocking
+    pub sybils_can_block: bool,
+    /// Behaving arbitrarily, the Sybils can cause two honest quorums that don't intersect in any
+    /// honest node, i.e., the Sybils are splitting
+    pub sybils_can_split: bool,
+}
+
+/// Lets an adversary inject up to `maximum_number_of_sybils` Sybil nodes into `honest_fbas`,
+/// one after the other. Sybils are configured using `SybilQsc`, honest nodes (old and new
+/// configurations) using `honest_qsc`, which is also used for letting honest nodes react to the
+/// growing FBAS.
+///
+/// After each injected Sybil, records whether the Sybils (as a group) have gained the power to
+/// block the honest nodes or to break quorum intersection among them. The first step in which
+/// either flag is set is the point at which the attack succeeded.
+pub fn simulate_sybil_attack(
+    honest_fbas: Fbas,
+    honest_qsc: Rc<dyn QuorumSetConfigurator>,
+    maximum_number_of_sybils: usize,
+) -> Vec<SybilAttackStep> {
+    let number_of_honest_nodes = honest_fbas.number_of_nodes();
+    assert!(number_of_honest_nodes > 0, "Need at least one honest node!");
+    let honest_nodes: NodeIdSet = (0..number_of_honest_nodes).collect();
+    let qsc = quorum_set_configurators::PerTierQsc::new(
+        vec![number_of_honest_nodes, 1],
+        vec![honest_qsc, Rc::new(SybilQsc::new(number_of_honest_nodes))],
+    );
+    let mut simulator = Simulator::new(honest_fbas, Rc::new(qsc), Rc::new(monitors::DummyMonitor));
+
+    (1..=maximum_number_of_sybils)
+        .map(|number_of_sybils| {
+            simulator.simulate_growth(1);
+            let fbas = &simulator.fbas;
+            let sybils: NodeIdSet = (number_of_honest_nodes..fbas.number_of_nodes()).collect();
+
+            let (fbas_without_sybils, _, _) = Fbas::shrunken(fbas, honest_nodes.clone());
+            let sybils_can_block = find_minimal_quorums(&fbas_without_sybils).is_empty();
+
+            let (fbas_with_sybils_deleted, _, _) = Fbas::shrunken(
+                &with_byzantine_nodes_deleted(fbas, &sybils),
+                honest_nodes.clone(),
+            );
+            let mut analysis = Analysis::new(&fbas_with_sybils_deleted, None);
+            let sybils_can_split =
+                !analysis.minimal_quorums().is_empty() && !analysis.has_quorum_intersection();
+
+            debug!(
+                "{} Sybils injected; Sybils can block: {}, Sybils can split: {}",
+                number_of_sybils, sybils_can_block, sybils_can_split
+            );
+            SybilAttackStep {
+                number_of_sybils,
+                sybils_can_block,
+                sybils_can_split,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    #[test]
+    fn attack_on_ideal_fbas_breaks_liveness_after_f_plus_1_failures() {
+        let fbas = Fbas::new_configured(7, Rc::new(IdealQsc::new()));
+        let steps = simulate_targeted_attack(&fbas, AttackStrategy::Degree, 4);
+        let has_quorums: Vec<bool> = steps.iter().map(|step| step.has_quorums).collect();
+        assert_eq!(vec![true, true, false, false], has_quorums);
+        let has_quorum_intersection: Vec<bool> = steps
+            .iter()
+            .map(|step| step.has_quorum_intersection)
+            .collect();
+        assert_eq!(has_quorums, has_quorum_intersection);
+    }
+
+    #[test]
+    fn attack_targets_most_important_nodes_first() {
+        let fbas = simulate!(TieredQsc::new_67p(vec![4, 8]), 12);
+        for &strategy in [
+            AttackStrategy::Degree,
+            AttackStrategy::Rank,
+            AttackStrategy::MinimalQuorumMembership,
+        ]
+        .iter()
+        {
+            let steps = simulate_targeted_attack(&fbas, strategy, 2);
+            let failed_nodes: Vec<NodeId> = steps.iter().map(|step| step.failed_node).collect();
+            assert_eq!(vec![0, 1], failed_nodes, "strategy: {:?}", strategy);
+            assert!(!steps[1].has_quorums);
+        }
+    }
+
+    #[test]
+    fn attack_stops_after_all_nodes_failed() {
+        let fbas = Fbas::new_configured(3, Rc::new(IdealQsc::new()));
+        let steps = simulate_targeted_attack(&fbas, AttackStrategy::Rank, 10);
+        assert_eq!(steps.len(), 3);
+    }
+
+    #[test]
+    fn sybil_qsc_makes_sybils_trust_each_other() {
+        let fbas = simulate!(SybilQsc::new(2), 5);
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![2, 3, 4],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, fbas.nodes[4].quorum_set);
+    }
+
+    #[test]
+    fn random_failures_in_ideal_fbas() {
+        let fbas = Fbas::new_configured(4, Rc::new(IdealQsc::new()));
+        let points = simulate_random_failures(&fbas, 10, 5);
+        let survival: Vec<f64> = points
+            .iter()
+            .map(|point| point.survival_probability)
+            .collect();
+        assert_eq!(vec![1., 1., 0., 0., 0.], survival);
+        assert_eq!(0.25, points[1].failure_fraction);
+        let (lower, upper) = points[1].survival_confidence_interval;
+        assert!(lower > 0.5 && lower < 1.);
+        assert!((upper - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wilson_score_interval_is_symmetric_around_one_half() {
+        let (lower, upper) = wilson_score_interval(0.5, 100);
+        assert!((0.5 - lower - (upper - 0.5)).abs() < 1e-9);
+        assert!(lower > 0.39 && lower < 0.41);
+    }
+
+    #[test]
+    fn sybils_overwhelm_ideal_fbas_that_trusts_everyone() {
+        let qsc: Rc<dyn QuorumSetConfigurator> = Rc::new(IdealQsc::new());
+        let honest_fbas = Fbas::new_configured(4, Rc::clone(&qsc));
+        let steps = simulate_sybil_attack(honest_fbas, qsc, 4);
+        let can_block: Vec<bool> = steps.iter().map(|step| step.sybils_can_block).collect();
+        let can_split: Vec<bool> = steps.iter().map(|step| step.sybils_can_split).collect();
+        assert_eq!(vec![false, true, true, true], can_block);
+        assert_eq!(vec![false, false, true, true], can_split);
+        assert_eq!(4, steps[3].number_of_sybils);
+    }
+
+    #[test]
+    fn sybils_are_harmless_if_nobody_trusts_them() {
+        let qsc: Rc<dyn QuorumSetConfigurator> =
+            Rc::new(SimpleGraphQsc::new_67p(Graph::new_full_mesh(4)));
+        let honest_fbas = Fbas::new_configured(4, Rc::clone(&qsc));
+        let steps = simulate_sybil_attack(honest_fbas, qsc, 5);
+        assert!(steps
+            .iter()
+            .all(|step| !step.sybils_can_block && !step.sybils_can_split));
+    }
+}
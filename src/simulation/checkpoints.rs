@@ -0,0 +1,129 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Snapshot of a simulation, for resuming it later (e.g., after a crash, in long experiments).
+///
+/// Captures the FBAS, departed and failed nodes, the number of completed steps, the regions new
+/// nodes are placed in and the state of the simulator's random number generator (as a seed -
+/// taking a checkpoint reseeds the simulator so that the original and the resumed simulation
+/// continue identically). Quorum set configurators and monitors are not part of the checkpoint and need
+/// to be passed again when resuming; any internal state they have is lost.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatorCheckpoint {
+    pub fbas: Fbas,
+    pub departed_nodes: Vec<NodeId>,
+    /// Nodes that are currently failed (see `Simulator::failed_nodes`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_nodes: Vec<NodeId>,
+    pub number_of_steps: usize,
+    pub seed: u64,
+    /// Regions new nodes are placed in (see `Simulator::with_regions`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<Region>,
+}
+impl SimulatorCheckpoint {
+    pub fn from_json_str(json: &str) -> Self {
+        serde_json::from_str(json).expect("Error parsing checkpoint JSON")
+    }
+    pub fn from_json_file(path: &Path) -> Self {
+        let json =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
+        Self::from_json_str(&json)
+    }
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(&self).expect("Error converting checkpoint to JSON!")
+    }
+    pub fn to_json_file(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json_string())
+    }
+}
+
+impl Simulator {
+    /// Take a checkpoint of the current simulation state.
+    pub fn checkpoint(&mut self) -> SimulatorCheckpoint {
+        let seed = self.rng.gen();
+        self.rng = StdRng::seed_from_u64(seed);
+        SimulatorCheckpoint {
+            fbas: self.fbas.clone(),
+            departed_nodes: self.departed_nodes.iter().collect(),
+            failed_nodes: self.failed_nodes.iter().collect(),
+            number_of_steps: self.number_of_steps,
+            seed,
+            regions: self.regions.clone(),
+        }
+    }
+    /// Resume a simulation from `checkpoint`.
+    pub fn from_checkpoint(
+        checkpoint: SimulatorCheckpoint,
+        qsc: Rc<dyn QuorumSetConfigurator>,
+        monitor: Rc<dyn SimulationMonitor>,
+    ) -> Self {
+        let mut simulator = Self::new_seeded(checkpoint.fbas, qsc, monitor, checkpoint.seed)
+            .with_regions(checkpoint.regions);
+        simulator.departed_nodes = checkpoint.departed_nodes.into_iter().collect();
+        simulator.failed_nodes = checkpoint.failed_nodes.into_iter().collect();
+        simulator.number_of_steps = checkpoint.number_of_steps;
+        simulator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::monitors::*;
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    fn new_ideal_simulator(seed: u64) -> Simulator {
+        Simulator::new_seeded(
+            Fbas::new(),
+            Rc::new(IdealQsc::new()),
+            Rc::new(DummyMonitor),
+            seed,
+        )
+    }
+
+    #[test]
+    fn seeded_simulations_are_reproducible() {
+        let mut simulator_1 = new_ideal_simulator(42);
+        let mut simulator_2 = new_ideal_simulator(42);
+        simulator_1.simulate_churn(10, 1.5, 0.2);
+        simulator_2.simulate_churn(10, 1.5, 0.2);
+        assert_eq!(simulator_1.departed_nodes(), simulator_2.departed_nodes());
+        assert_eq!(simulator_1.finalize(), simulator_2.finalize());
+    }
+
+    #[test]
+    fn resumed_simulation_continues_like_original() {
+        let mut original = new_ideal_simulator(7);
+        original.simulate_churn(10, 1.5, 0.2);
+        let checkpoint = original.checkpoint();
+        assert_eq!(checkpoint.number_of_steps, 10);
+
+        let checkpoint = SimulatorCheckpoint::from_json_str(&checkpoint.to_json_string());
+        let mut resumed =
+            Simulator::from_checkpoint(checkpoint, Rc::new(IdealQsc::new()), Rc::new(DummyMonitor));
+        original.simulate_churn(10, 1.5, 0.2);
+        resumed.simulate_churn(10, 1.5, 0.2);
+        assert_eq!(original.departed_nodes(), resumed.departed_nodes());
+        assert_eq!(original.number_of_steps(), resumed.number_of_steps());
+        assert_eq!(original.finalize(), resumed.finalize());
+    }
+
+    #[test]
+    fn resumed_simulation_keeps_failed_nodes() {
+        let mut original = new_ideal_simulator(7);
+        original.simulate_growth(4);
+        let model = failures::FailureModel::new(0., 0.).with_node_failure_probability(1, 1.);
+        original.simulate_failures(1, &model);
+        let checkpoint =
+            SimulatorCheckpoint::from_json_str(&original.checkpoint().to_json_string());
+        let resumed =
+            Simulator::from_checkpoint(checkpoint, Rc::new(IdealQsc::new()), Rc::new(DummyMonitor));
+        assert_eq!(vec![1], resumed.failed_nodes());
+    }
+}
@@ -0,0 +1,427 @@
+use super::*;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// Names of the metrics collected per simulation run, in the order used in `RunMetrics` and
+/// `ExperimentResult`.
+pub const METRIC_NAMES: [&str; 5] = [
+    "has_quorum_intersection",
+    "minimal_quorums",
+    "top_tier_size",
+    "smallest_blocking_set_size",
+    "smallest_splitting_set_size",
+];
+
+/// Metrics gathered from analyzing the FBAS resulting from one simulation run; `values[i]`
+/// corresponds to `METRIC_NAMES[i]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunMetrics {
+    pub values: Vec<f64>,
+}
+impl RunMetrics {
+    pub fn from_fbas(fbas: &Fbas) -> Self {
+        let mut analysis = Analysis::new(fbas, None);
+        let has_quorum_intersection = analysis.has_quorum_intersection();
+        let minimal_quorums = analysis.minimal_quorums().len();
+        let top_tier_size = analysis.top_tier().len();
+        let smallest_blocking_set_size = analysis.minimal_blocking_sets().minmaxmean().0;
+        let smallest_splitting_set_size = analysis.minimal_splitting_sets().minmaxmean().0;
+        RunMetrics {
+            values: vec![
+                if has_quorum_intersection { 1. } else { 0. },
+                minimal_quorums as f64,
+                top_tier_size as f64,
+                smallest_blocking_set_size as f64,
+                smallest_splitting_set_size as f64,
+            ],
+        }
+    }
+}
+
+/// Builds a quorum set configurator for a given FBAS size.
+pub type QscFactory = Box<dyn Fn(usize) -> Rc<dyn QuorumSetConfigurator>>;
+
+/// Aggregated metrics of all repetitions for one point in the parameter grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExperimentResult {
+    pub label: String,
+    pub n: usize,
+    pub repetitions: usize,
+    /// `(mean, standard_deviation)` per metric, ordered like `METRIC_NAMES`
+    pub metrics: Vec<(f64, f64)>,
+}
+impl ExperimentResult {
+    fn aggregate(label: &str, n: usize, runs: &[RunMetrics]) -> Self {
+        let metrics = (0..METRIC_NAMES.len())
+            .map(|i| {
+                let values: Vec<f64> = runs.iter().map(|run| run.values[i]).collect();
+                mean_and_standard_deviation(&values)
+            })
+            .collect();
+        ExperimentResult {
+            label: label.to_string(),
+            n,
+            repetitions: runs.len(),
+            metrics,
+        }
+    }
+}
+
+/// Setup of an `ExperimentRunner`, for publishing alongside its results (see also
+/// `manifests::SimulationManifest`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentManifest {
+    pub crate_version: String,
+    pub seed: u64,
+    pub sizes: Vec<usize>,
+    pub repetitions: usize,
+    /// Configuration labels, which should name the QSCs and their parameters
+    pub configs: Vec<String>,
+}
+impl ExperimentManifest {
+    pub fn to_json_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self).expect("Error converting manifest to JSON!")
+    }
+}
+
+/// Runs a grid of simulations - each configuration for each FBAS size, repeatedly - and
+/// aggregates the analysis results.
+///
+/// Configurations are labelled functions that build a quorum set configurator for a given FBAS
+/// size, so any QSC parameters can be varied by adding several configurations. The simulators'
+/// seeds are derived from the runner's seed (random unless set using `with_seed`), which is part
+/// of the runner's `manifest`:
+///
+/// ```
+/// use fbas_analyzer::experiments::ExperimentRunner;
+/// use fbas_analyzer::quorum_set_configurators::{IdealQsc, RandomQsc};
+/// use std::rc::Rc;
+///
+/// let mut runner = ExperimentRunner::new(vec![4, 8], 2);
+/// runner.add_config("ideal", |_| Rc::new(IdealQsc::new()));
+/// for &k in [3, 5].iter() {
+///     runner.add_config(&format!("random(k={})", k), move |_| Rc::new(RandomQsc::new_simple(k)));
+/// }
+/// let results = runner.run();
+/// assert_eq!(results.len(), 3 * 2);
+/// println!("{}", ExperimentRunner::results_to_csv_string(&results));
+/// println!("{}", runner.manifest().to_json_string_pretty());
+/// ```
+pub struct ExperimentRunner {
+    configs: Vec<(String, QscFactory)>,
+    sizes: Vec<usize>,
+    repetitions: usize,
+    seed: u64,
+}
+impl ExperimentRunner {
+    pub fn new(sizes: Vec<usize>, repetitions: usize) -> Self {
+        assert!(repetitions > 0, "Need at least one repetition!");
+        ExperimentRunner {
+            configs: vec![],
+            sizes,
+            repetitions,
+            seed: thread_rng().gen(),
+        }
+    }
+    /// Makes simulator decisions reproducible (see `Simulator::new_seeded`).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    pub fn manifest(&self) -> ExperimentManifest {
+        ExperimentManifest {
+            crate_version: manifests::CRATE_VERSION.to_string(),
+            seed: self.seed,
+            sizes: self.sizes.clone(),
+            repetitions: self.repetitions,
+            configs: self
+                .configs
+                .iter()
+                .map(|(label, _)| label.clone())
+                .collect(),
+        }
+    }
+    pub fn add_config<F>(&mut self, label: &str, make_qsc: F)
+    where
+        F: Fn(usize) -> Rc<dyn QuorumSetConfigurator> + 'static,
+    {
+        self.configs.push((label.to_string(), Box::new(make_qsc)));
+    }
+    /// Runs all simulations; returns one result per configuration and FBAS size.
+    pub fn run(&self) -> Vec<ExperimentResult> {
+        let mut results = vec![];
+        let mut seeds = StdRng::seed_from_u64(self.seed);
+        for (label, make_qsc) in self.configs.iter() {
+            for &n in self.sizes.iter() {
+                info!("Running experiment {} with n={}...", label, n);
+                let runs: Vec<RunMetrics> = (0..self.repetitions)
+                    .map(|_| {
+                        let mut simulator = Simulator::new_seeded(
+                            Fbas::new(),
+                            make_qsc(n),
+                            Rc::new(monitors::DummyMonitor),
+                            seeds.gen(),
+                        );
+                        simulator.simulate_growth(n);
+                        RunMetrics::from_fbas(&simulator.finalize())
+                    })
+                    .collect();
+                results.push(ExperimentResult::aggregate(label, n, &runs));
+            }
+        }
+        results
+    }
+    /// One line per result, with mean and standard deviation columns for each metric.
+    pub fn results_to_csv_string(results: &[ExperimentResult]) -> String {
+        let mut header = vec![
+            "label".to_string(),
+            "n".to_string(),
+            "repetitions".to_string(),
+        ];
+        for name in METRIC_NAMES.iter() {
+            header.push(format!("{}_mean", name));
+            header.push(format!("{}_stddev", name));
+        }
+        let mut lines = vec![header.join(",")];
+        for result in results.iter() {
+            let mut line = vec![
+                csv_field(&result.label),
+                result.n.to_string(),
+                result.repetitions.to_string(),
+            ];
+            for (mean, stddev) in result.metrics.iter() {
+                line.push(mean.to_string());
+                line.push(stddev.to_string());
+            }
+            lines.push(line.join(","));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Quotes `field` (doubling contained quotes) if it contains anything that would otherwise break
+/// the CSV structure.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One independent simulation run, from setting up the simulator to extracting results (e.g.,
+/// from a monitor). Everything that isn't thread-safe (such as `Rc`s of QSCs and monitors) has to
+/// be created inside the function.
+pub type BatchConfig<T> = Box<dyn Fn() -> T + Send + Sync>;
+
+/// Executes each of `configs` `repetitions` times, distributing the runs across up to
+/// `max_threads()` threads. Returns the collected outputs, grouped by configuration and ordered
+/// like `configs`:
+///
+/// ```
+/// use fbas_analyzer::experiments::{run_batch, BatchConfig};
+/// use fbas_analyzer::monitors::TimeSeriesMonitor;
+/// use fbas_analyzer::quorum_set_configurators::IdealQsc;
+/// use fbas_analyzer::{Fbas, SimulationMonitor, Simulator};
+/// use std::rc::Rc;
+///
+/// let configs: Vec<BatchConfig<String>> = vec![Box::new(|| {
+///     let monitor = Rc::new(TimeSeriesMonitor::new());
+///     let mut simulator = Simulator::new(
+///         Fbas::new(),
+///         Rc::new(IdealQsc::new()),
+///         Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+///     );
+///     simulator.simulate_growth(5);
+///     monitor.to_csv_string()
+/// })];
+/// let outputs = run_batch(configs, 4);
+/// assert_eq!(outputs.len(), 1);
+/// assert_eq!(outputs[0].len(), 4);
+/// ```
+pub fn run_batch<T>(configs: Vec<BatchConfig<T>>, repetitions: usize) -> Vec<Vec<T>>
+where
+    T: Send + 'static,
+{
+    let number_of_configs = configs.len();
+    let number_of_jobs = number_of_configs * repetitions;
+    let number_of_threads = cmp::min(number_of_jobs, max_threads());
+    info!(
+        "Running {} simulations in {} threads...",
+        number_of_jobs, number_of_threads
+    );
+    let configs = Arc::new(configs);
+    let next_job = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..number_of_threads)
+        .map(|_| {
+            let configs = Arc::clone(&configs);
+            let next_job = Arc::clone(&next_job);
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let job = next_job.fetch_add(1, Ordering::SeqCst);
+                if job >= number_of_jobs {
+                    break;
+                }
+                let output = configs[job / repetitions]();
+                sender.send((job, output)).unwrap();
+            })
+        })
+        .collect();
+    drop(sender);
+
+    let mut outputs: Vec<Option<T>> = (0..number_of_jobs).map(|_| None).collect();
+    for (job, output) in receiver {
+        outputs[job] = Some(output);
+    }
+    for worker in workers {
+        worker.join().expect("Simulation thread panicked!");
+    }
+    let mut outputs = outputs.into_iter().map(|output| output.unwrap());
+    (0..number_of_configs)
+        .map(|_| outputs.by_ref().take(repetitions).collect())
+        .collect()
+}
+
+/// Returns (mean, sample standard deviation); the standard deviation of a single value is 0.
+fn mean_and_standard_deviation(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let standard_deviation = if values.len() > 1 {
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.)).sqrt()
+    } else {
+        0.
+    };
+    (mean, standard_deviation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    #[test]
+    fn mean_and_standard_deviation_of_values() {
+        let (mean, stddev) = mean_and_standard_deviation(&[2., 4., 4., 4., 5., 5., 7., 9.]);
+        assert_eq!(mean, 5.);
+        assert!((stddev - (32_f64 / 7.).sqrt()).abs() < 1e-9);
+        assert_eq!((3., 0.), mean_and_standard_deviation(&[3.]));
+    }
+
+    #[test]
+    fn run_metrics_of_ideal_fbas() {
+        let fbas = Fbas::new_configured(4, Rc::new(IdealQsc::new()));
+        let expected = RunMetrics {
+            values: vec![1., 4., 4., 2., 2.],
+        };
+        let actual = RunMetrics::from_fbas(&fbas);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn experiment_runner_covers_grid() {
+        let mut runner = ExperimentRunner::new(vec![3, 4], 3);
+        runner.add_config("ideal", |_| Rc::new(IdealQsc::new()));
+        runner.add_config("super_safe", |_| Rc::new(SuperSafeQsc::new()));
+        let results = runner.run();
+        let labels_and_sizes: Vec<(String, usize)> = results
+            .iter()
+            .map(|result| (result.label.clone(), result.n))
+            .collect();
+        assert_eq!(
+            vec![
+                ("ideal".to_string(), 3),
+                ("ideal".to_string(), 4),
+                ("super_safe".to_string(), 3),
+                ("super_safe".to_string(), 4)
+            ],
+            labels_and_sizes
+        );
+        // deterministic QSCs => no variance
+        assert!(results
+            .iter()
+            .all(|result| result.metrics.iter().all(|&(_, stddev)| stddev == 0.)));
+        assert_eq!(results[3].metrics[2], (4., 0.));
+    }
+
+    #[test]
+    fn experiment_manifest_lists_setup() {
+        let mut runner = ExperimentRunner::new(vec![3, 4], 2).with_seed(5);
+        runner.add_config("ideal", |_| Rc::new(IdealQsc::new()));
+        let expected = ExperimentManifest {
+            crate_version: manifests::CRATE_VERSION.to_string(),
+            seed: 5,
+            sizes: vec![3, 4],
+            repetitions: 2,
+            configs: vec!["ideal".to_string()],
+        };
+        assert_eq!(expected, runner.manifest());
+    }
+
+    #[test]
+    fn results_to_csv_string_has_header_and_rows() {
+        let results = vec![ExperimentResult {
+            label: "test".to_string(),
+            n: 5,
+            repetitions: 2,
+            metrics: vec![(1., 0.); METRIC_NAMES.len()],
+        }];
+        let csv = ExperimentRunner::results_to_csv_string(&results);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("label,n,repetitions,has_quorum_intersection_mean,"));
+        assert!(lines[1].starts_with("test,5,2,1,0,"));
+    }
+
+    #[test]
+    fn results_to_csv_string_quotes_labels() {
+        let results = vec![ExperimentResult {
+            label: "top_k(k=3, \"rank\")".to_string(),
+            n: 5,
+            repetitions: 2,
+            metrics: vec![(1., 0.); METRIC_NAMES.len()],
+        }];
+        let csv = ExperimentRunner::results_to_csv_string(&results);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[1].starts_with("\"top_k(k=3, \"\"rank\"\")\",5,2,1,0,"));
+    }
+
+    #[test]
+    fn run_batch_groups_outputs_by_config() {
+        let configs: Vec<BatchConfig<usize>> = (0..3_usize)
+            .map(|i| Box::new(move || i) as BatchConfig<usize>)
+            .collect();
+        let expected = vec![vec![0; 5], vec![1; 5], vec![2; 5]];
+        assert_eq!(expected, run_batch(configs, 5));
+    }
+
+    #[test]
+    fn run_batch_collects_monitor_outputs() {
+        let configs: Vec<BatchConfig<Vec<monitors::StepAnalysis>>> = vec![Box::new(|| {
+            let monitor = Rc::new(monitors::AnalysisMonitor::new());
+            let mut simulator = Simulator::new(
+                Fbas::new(),
+                Rc::new(IdealQsc::new()),
+                Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+            );
+            simulator.simulate_growth(4);
+            monitor.steps_clone()
+        })];
+        let outputs = run_batch(configs, 3);
+        assert_eq!(outputs[0].len(), 3);
+        for steps in outputs[0].iter() {
+            assert_eq!(steps.len(), 4);
+            assert!(steps.iter().all(|step| step.has_quorum_intersection));
+        }
+    }
+
+    #[test]
+    fn run_batch_without_repetitions() {
+        let configs: Vec<BatchConfig<usize>> = vec![Box::new(|| 1)];
+        assert_eq!(vec![Vec::<usize>::new()], run_batch(configs, 0));
+    }
+}
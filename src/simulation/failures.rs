@@ -0,0 +1,315 @@
+//! Temporary node failures, for simulating the availability of an FBAS (rather than only how its
+//! structure evolves). Failed nodes are tracked by the simulator (see
+//! `Simulator::failed_nodes`; node metadata is left untouched), don't reevaluate their quorum
+//! sets, are hidden from quorum set configurators and left out of the quorum sets of
+//! reevaluating nodes (like departed nodes, until they recover) and are ignored by analyses of
+//! `Simulator::available_fbas`.
+
+use super::*;
+
+use std::collections::BTreeMap;
+
+/// Whether nodes fail independently of each other or together with their organization.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FailureCorrelation {
+    Independent,
+    /// Nodes with the same organization ID (see `NodeMetadata`) fail and recover together; nodes
+    /// without organization ID fail independently.
+    ByOrganization,
+}
+
+/// Per-round failure and recovery probabilities, see `Simulator::simulate_failures`.
+///
+/// ```
+/// use fbas_analyzer::failures::FailureModel;
+/// use fbas_analyzer::monitors::AnalysisMonitor;
+/// use fbas_analyzer::quorum_set_configurators::IdealQsc;
+/// use fbas_analyzer::{Fbas, SimulationMonitor, Simulator};
+/// use std::rc::Rc;
+///
+/// let monitor = Rc::new(AnalysisMonitor::new());
+/// let mut simulator = Simulator::new(
+///     Fbas::new(),
+///     Rc::new(IdealQsc::new()),
+///     Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+/// );
+/// simulator.simulate_growth(7);
+///
+/// // nodes fail in 10% of all rounds and stay down for 2 rounds on average
+/// let model = FailureModel::new(0.1, 0.5);
+/// simulator.simulate_failures(100, &model);
+///
+/// let available_rounds = monitor.steps_ref()[7..]
+///     .iter()
+///     .filter(|step| step.has_quorums)
+///     .count();
+/// println!("FBAS was available in {} of 100 rounds", available_rounds);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailureModel {
+    pub failure_probability: f64,
+    /// Overrides `failure_probability` for individual nodes
+    pub node_failure_probabilities: BTreeMap<NodeId, f64>,
+    /// Probability that a failed node recovers in a round; 1 means that failures last for one
+    /// round only.
+    pub recovery_probability: f64,
+    pub correlation: FailureCorrelation,
+}
+impl FailureModel {
+    /// Independent failures, with the same probabilities for all nodes.
+    pub fn new(failure_probability: f64, recovery_probability: f64) -> Self {
+        assert_probability(failure_probability);
+        assert_probability(recovery_probability);
+        FailureModel {
+            failure_probability,
+            node_failure_probabilities: BTreeMap::new(),
+            recovery_probability,
+            correlation: FailureCorrelation::Independent,
+        }
+    }
+    /// Let organizations fail as a whole. An organization fails with the mean failure
+    /// probability of its active nodes.
+    pub fn correlated_by_organization(mut self) -> Self {
+        self.correlation = FailureCorrelation::ByOrganization;
+        self
+    }
+    pub fn with_node_failure_probability(mut self, node_id: NodeId, probability: f64) -> Self {
+        assert_probability(probability);
+        self.node_failure_probabilities.insert(node_id, probability);
+        self
+    }
+    pub fn failure_probability_of(&self, node_id: NodeId) -> f64 {
+        *self
+            .node_failure_probabilities
+            .get(&node_id)
+            .unwrap_or(&self.failure_probability)
+    }
+    fn failure_probability_of_group(&self, group: &[NodeId]) -> f64 {
+        group
+            .iter()
+            .map(|&node_id| self.failure_probability_of(node_id))
+            .sum::<f64>()
+            / group.len() as f64
+    }
+}
+
+fn assert_probability(probability: f64) {
+    assert!(
+        (0. ..=1.).contains(&probability),
+        "Failure and recovery rates must be probabilities!"
+    );
+}
+
+impl Simulator {
+    /// Simulate `rounds` rounds of failures and recoveries according to `model`. In each round,
+    /// failed nodes recover and active nodes fail with the model's probabilities (nodes that
+    /// recover can't fail again in the same round); then all active nodes reevaluate their
+    /// quorum sets, leaving out failed nodes.
+    pub fn simulate_failures(&mut self, rounds: usize, model: &FailureModel) {
+        for _ in 0..rounds {
+            if self.is_aborted() {
+                break;
+            }
+            let recovering_groups = self.failure_groups(self.failed_nodes(), model.correlation);
+            let failing_groups = self.failure_groups(self.active_nodes(), model.correlation);
+
+            let recovering_nodes: Vec<NodeId> = recovering_groups
+                .into_iter()
+                .filter(|_| self.rng.gen_bool(model.recovery_probability))
+                .flatten()
+                .collect();
+            let failing_nodes: Vec<NodeId> = failing_groups
+                .into_iter()
+                .filter(|group| self.rng.gen_bool(model.failure_probability_of_group(group)))
+                .flatten()
+                .collect();
+
+            for node_id in recovering_nodes {
+                self.failed_nodes.remove(node_id);
+                self.monitor.register_event(NodeRecovered(node_id));
+            }
+            for node_id in failing_nodes {
+                self.failed_nodes.insert(node_id);
+                self.monitor.register_event(NodeFailed(node_id));
+            }
+            self.simulate_capped_global_reevaluation();
+        }
+    }
+    /// Nodes that are currently failed, i.e., that haven't left the FBAS but failed in
+    /// `simulate_failures` and haven't recovered yet.
+    pub fn failed_nodes(&self) -> Vec<NodeId> {
+        self.failed_nodes.iter().collect()
+    }
+    /// Current state of the FBAS as seen by analyses: like `fbas`, but with failed nodes removed
+    /// from all quorum sets (without lowering any thresholds). Node IDs are unchanged.
+    pub fn available_fbas(&self) -> Fbas {
+        self.fbas
+            .filtered_by_node_id(|node_id| !self.failed_nodes.contains(node_id))
+    }
+    /// Groups of nodes that fail or recover together, in the order of their first nodes.
+    fn failure_groups(
+        &self,
+        nodes: Vec<NodeId>,
+        correlation: FailureCorrelation,
+    ) -> Vec<Vec<NodeId>> {
+        match correlation {
+            FailureCorrelation::Independent => {
+                nodes.into_iter().map(|node_id| vec![node_id]).collect()
+            }
+            FailureCorrelation::ByOrganization => {
+                let mut groups: Vec<Vec<NodeId>> = vec![];
+                let mut group_indices: HashMap<&str, usize> = HashMap::new();
+                for node_id in nodes {
+                    match self.fbas.nodes[node_id].metadata.organization_id.as_deref() {
+                        Some(organization_id) => {
+                            let index =
+                                *group_indices.entry(organization_id).or_insert_with(|| {
+                                    groups.push(vec![]);
+                                    groups.len() - 1
+                                });
+                            groups[index].push(node_id);
+                        }
+                        None => groups.push(vec![node_id]),
+                    }
+                }
+                groups
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::monitors::*;
+    use super::quorum_set_configurators::*;
+    use super::*;
+    use std::cell::RefCell;
+
+    fn simulate_ideal_growth(n: usize, monitor: Rc<dyn SimulationMonitor>) -> Simulator {
+        let mut simulator = Simulator::new_seeded(Fbas::new(), Rc::new(IdealQsc), monitor, 42);
+        simulator.simulate_growth(n);
+        simulator
+    }
+
+    #[test]
+    fn certain_failures_make_fbas_unavailable() {
+        let monitor = Rc::new(AnalysisMonitor::new());
+        let mut simulator =
+            simulate_ideal_growth(4, Rc::clone(&monitor) as Rc<dyn SimulationMonitor>);
+        let fbas_before = simulator.fbas().clone();
+
+        simulator.simulate_failures(1, &FailureModel::new(1., 0.));
+        assert_eq!(vec![0, 1, 2, 3], simulator.failed_nodes());
+        assert!(simulator.active_nodes().is_empty());
+        let last_step = monitor.steps_clone().pop().unwrap();
+        assert_eq!(0, last_step.number_of_nodes);
+        assert!(!last_step.has_quorums);
+
+        simulator.simulate_failures(1, &FailureModel::new(0., 1.));
+        assert!(simulator.failed_nodes().is_empty());
+        assert!(monitor.steps_clone().pop().unwrap().has_quorum_intersection);
+        // failed nodes don't reevaluate, so nothing has changed
+        for (node, node_before) in simulator.fbas().nodes.iter().zip(fbas_before.nodes.iter()) {
+            assert_eq!(node.quorum_set, node_before.quorum_set);
+        }
+    }
+
+    #[test]
+    fn available_fbas_ignores_failed_nodes() {
+        let mut simulator = simulate_ideal_growth(4, Rc::new(DummyMonitor));
+        let model = FailureModel::new(0., 0.).with_node_failure_probability(2, 1.);
+        simulator.simulate_failures(1, &model);
+        assert_eq!(vec![2], simulator.failed_nodes());
+        let fbas = simulator.available_fbas();
+        assert_eq!(4, fbas.number_of_nodes());
+        assert!(fbas.is_quorum(&bitset![0, 1, 3]));
+        assert!(!fbas.is_quorum(&bitset![0, 1, 2]));
+    }
+
+    #[test]
+    fn reevaluating_nodes_leave_out_failed_nodes_until_they_recover() {
+        let mut simulator = simulate_ideal_growth(4, Rc::new(DummyMonitor));
+        let model = FailureModel::new(0., 0.).with_node_failure_probability(3, 1.);
+        simulator.simulate_failures(1, &model);
+        for node_id in 0..3 {
+            let quorum_set = &simulator.fbas().nodes[node_id].quorum_set;
+            assert_eq!(vec![0, 1, 2], quorum_set.validators);
+        }
+
+        simulator.simulate_failures(1, &FailureModel::new(0., 1.));
+        for node in simulator.fbas().nodes.iter() {
+            assert_eq!(vec![0, 1, 2, 3], node.quorum_set.validators);
+        }
+    }
+
+    #[test]
+    fn nodes_marked_as_inactive_in_metadata_arent_failed() {
+        let mut simulator = simulate_ideal_growth(4, Rc::new(DummyMonitor));
+        // like nodes loaded from stellarbeat snapshots
+        simulator.fbas.nodes[3].metadata.active = Some(false);
+        simulator.simulate_failures(1, &FailureModel::new(0., 0.));
+        assert!(simulator.failed_nodes().is_empty());
+        assert_eq!(vec![0, 1, 2, 3], simulator.active_nodes());
+        assert!(simulator.available_fbas().is_quorum(&bitset![0, 1, 2, 3]));
+        for node in simulator.fbas().nodes.iter() {
+            assert_eq!(vec![0, 1, 2, 3], node.quorum_set.validators);
+        }
+        assert_eq!(Some(false), simulator.fbas().nodes[3].metadata.active);
+    }
+
+    /// Records all nodes that appear in the FBAS (with a non-empty quorum set or as part of
+    /// someone's quorum set) each time it is asked to configure a node.
+    struct RecordingQsc {
+        seen_nodes: RefCell<Vec<NodeIdSet>>,
+    }
+    impl QuorumSetConfigurator for RecordingQsc {
+        fn configure(&self, _: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+            let mut seen_nodes = NodeIdSet::new();
+            for (node_id, node) in fbas.nodes.iter().enumerate() {
+                if node.quorum_set != QuorumSet::new() {
+                    seen_nodes.insert(node_id);
+                }
+                seen_nodes.union_with(&node.quorum_set.contained_nodes());
+            }
+            self.seen_nodes.borrow_mut().push(seen_nodes);
+            NoChange
+        }
+    }
+
+    #[test]
+    fn quorum_set_configurators_dont_see_failed_nodes() {
+        let mut simulator = simulate_ideal_growth(4, Rc::new(DummyMonitor));
+        let qsc = Rc::new(RecordingQsc {
+            seen_nodes: RefCell::new(vec![]),
+        });
+        simulator.qsc = Rc::clone(&qsc) as Rc<dyn QuorumSetConfigurator>;
+        let model = FailureModel::new(0., 0.).with_node_failure_probability(2, 1.);
+        simulator.simulate_failures(1, &model);
+
+        let seen_nodes = qsc.seen_nodes.borrow();
+        assert!(!seen_nodes.is_empty());
+        for nodes in seen_nodes.iter() {
+            assert_eq!(&bitset![0, 1, 3], nodes);
+        }
+    }
+
+    #[test]
+    fn organizations_fail_together() {
+        let mut simulator = simulate_ideal_growth(6, Rc::new(DummyMonitor));
+        for node_id in 0..4 {
+            simulator.fbas.nodes[node_id].metadata.organization_id =
+                Some(format!("org{}", node_id / 2));
+        }
+        let model = FailureModel::new(0., 0.)
+            .with_node_failure_probability(0, 1.)
+            .with_node_failure_probability(1, 1.)
+            .with_node_failure_probability(2, 1.)
+            .correlated_by_organization();
+        // org1 fails with probability 0.5
+        for _ in 0..100 {
+            simulator.simulate_failures(1, &model);
+        }
+        let failed_nodes = simulator.failed_nodes();
+        assert!(failed_nodes == vec![0, 1] || failed_nodes == vec![0, 1, 2, 3]);
+    }
+}
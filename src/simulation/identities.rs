@@ -0,0 +1,182 @@
+use super::*;
+
+impl Fbas {
+    /// Copy of this FBAS in which all nodes have deterministic synthetic identities, so that
+    /// generated FBASs can be processed by tools that expect real-looking networks (e.g.,
+    /// stellarbeat.org-compatible visualizers):
+    ///
+    /// - public keys that are valid Stellar account IDs (derived from node IDs),
+    /// - names like "sim-node-042",
+    /// - organization IDs like "sim-org-007", for groups of `nodes_per_organization` consecutive
+    ///   nodes (see `Organizations::from_organization_ids` for turning these into
+    ///   `Organizations`).
+    ///
+    /// Quorum sets and other metadata are kept as they are.
+    ///
+    /// ```
+    /// use fbas_analyzer::{Fbas, Organizations};
+    ///
+    /// let fbas = Fbas::new_generic_unconfigured(6).with_synthetic_identities(3);
+    /// let reread = Fbas::from_json_str(&fbas.to_json_string());
+    ///
+    /// assert_eq!(fbas, reread);
+    /// assert!(reread.get_node_id("n0").is_none());
+    /// let organizations = Organizations::from_organization_ids(&reread);
+    /// assert_eq!(2, organizations.number_of_organizations());
+    /// ```
+    pub fn with_synthetic_identities(&self, nodes_per_organization: usize) -> Self {
+        assert!(
+            nodes_per_organization > 0,
+            "Organizations need at least one node!"
+        );
+        let max_node_id = self.nodes.len().saturating_sub(1);
+        let node_digits = number_of_digits(max_node_id);
+        let organization_digits = number_of_digits(max_node_id / nodes_per_organization);
+
+        let mut fbas = Fbas::new();
+        for (node_id, node) in self.nodes.iter().enumerate() {
+            let mut metadata = node.metadata.clone();
+            metadata.organization_id = Some(format!(
+                "sim-org-{:0width$}",
+                node_id / nodes_per_organization,
+                width = organization_digits
+            ));
+            fbas.add_node(Node {
+                public_key: synthetic_public_key(node_id),
+                quorum_set: node.quorum_set.clone(),
+                name: Some(format!("sim-node-{:0width$}", node_id, width = node_digits)),
+                metadata,
+            });
+        }
+        fbas
+    }
+}
+
+/// Digits needed for zero-padded numbers up to `max_number`, but at least 3.
+fn number_of_digits(max_number: usize) -> usize {
+    cmp::max(3, max_number.to_string().len())
+}
+
+/// Stellar account ID ("G...") with a pseudorandom but deterministic key derived from `node_id`.
+fn synthetic_public_key(node_id: NodeId) -> PublicKey {
+    let mut state = node_id as u64;
+    let mut key = [0; 32];
+    for chunk in key.chunks_mut(8) {
+        chunk.copy_from_slice(&splitmix64(&mut state).to_be_bytes());
+    }
+    encode_account_id(&key)
+}
+
+/// Stellar "strkey" encoding of an ed25519 public key: version byte, key and CRC16 checksum,
+/// in base32.
+fn encode_account_id(key: &[u8; 32]) -> PublicKey {
+    const ACCOUNT_ID_VERSION_BYTE: u8 = 6 << 3;
+    let mut bytes = vec![ACCOUNT_ID_VERSION_BYTE];
+    bytes.extend_from_slice(key);
+    let checksum = crc16_xmodem(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    base32_encode(&bytes)
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn crc16_xmodem(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// RFC 4648 base32, without padding.
+fn base32_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut result = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits_in_buffer = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits_in_buffer += 8;
+        while bits_in_buffer >= 5 {
+            bits_in_buffer -= 5;
+            result.push(ALPHABET[((buffer >> bits_in_buffer) & 31) as usize] as char);
+        }
+    }
+    if bits_in_buffer > 0 {
+        result.push(ALPHABET[((buffer << (5 - bits_in_buffer)) & 31) as usize] as char);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_account_ids_like_stellar() {
+        // well-known account ID with an all-zero key
+        assert_eq!(
+            "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF",
+            encode_account_id(&[0; 32])
+        );
+    }
+
+    #[test]
+    fn synthetic_identities_are_deterministic_and_unique() {
+        let fbas = Fbas::new_generic_unconfigured(1000).with_synthetic_identities(1);
+        assert_eq!(fbas, fbas.with_synthetic_identities(1));
+        let public_keys: HashSet<&PublicKey> =
+            fbas.nodes.iter().map(|node| &node.public_key).collect();
+        assert_eq!(1000, public_keys.len());
+        assert!(public_keys
+            .iter()
+            .all(|public_key| public_key.len() == 56 && public_key.starts_with('G')));
+        assert_eq!(Some("sim-node-042"), fbas.nodes[42].name.as_deref());
+        assert_eq!(Some("sim-node-999"), fbas.nodes[999].name.as_deref());
+    }
+
+    #[test]
+    fn synthetic_identities_keep_quorum_sets_and_metadata() {
+        let mut fbas = Fbas::new_generic_unconfigured(5);
+        fbas.nodes[1].quorum_set = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1, 4],
+            inner_quorum_sets: vec![],
+        };
+        fbas.set_region(4, &Region::new("Europe", "Germany"));
+        let renamed = fbas.with_synthetic_identities(2);
+
+        for (node, renamed_node) in fbas.nodes.iter().zip(renamed.nodes.iter()) {
+            assert_eq!(node.quorum_set, renamed_node.quorum_set);
+            assert_eq!(node.metadata.country, renamed_node.metadata.country);
+        }
+        let organization_ids: Vec<&str> = renamed
+            .nodes
+            .iter()
+            .map(|node| node.metadata.organization_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                "sim-org-000",
+                "sim-org-000",
+                "sim-org-001",
+                "sim-org-001",
+                "sim-org-002"
+            ],
+            organization_ids
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Version of this crate, as recorded in manifests
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Everything needed to reproduce a simulation run, for publishing alongside its results: crate
+/// version, simulator seed, quorum set configurator and (optionally) the parameters of the
+/// configurator and of the underlying graph.
+///
+/// Parameters can't be read from arbitrary configurators, so they have to be added by whoever
+/// built the configurator (as scenarios do). The seed doesn't cover configurators or random
+/// graphs that use randomness of their own (see `Simulator::new_seeded`).
+///
+/// ```
+/// use fbas_analyzer::manifests::SimulationManifest;
+/// use fbas_analyzer::monitors::DummyMonitor;
+/// use fbas_analyzer::quorum_set_configurators::IdealQsc;
+/// use fbas_analyzer::{Fbas, Simulator};
+/// use std::rc::Rc;
+///
+/// let mut simulator =
+///     Simulator::new_seeded(Fbas::new(), Rc::new(IdealQsc::new()), Rc::new(DummyMonitor), 7);
+/// simulator.simulate_growth(3);
+/// let manifest = simulator.manifest();
+///
+/// assert_eq!(7, manifest.seed);
+/// assert_eq!("IdealQsc", manifest.qsc);
+/// assert_eq!(manifest, SimulationManifest::from_json_str(&manifest.to_json_string()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationManifest {
+    pub crate_version: String,
+    pub seed: u64,
+    pub number_of_steps: usize,
+    /// Name of the quorum set configurator (see `QuorumSetConfigurator::name`)
+    pub qsc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qsc_parameters: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph_parameters: Option<serde_json::Value>,
+    /// Regions new nodes are placed in (see `Simulator::with_regions`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<Region>,
+}
+impl SimulationManifest {
+    pub fn with_qsc_parameters<T: Serialize>(mut self, parameters: &T) -> Self {
+        self.qsc_parameters = Some(to_json_value(parameters));
+        self
+    }
+    pub fn with_graph_parameters<T: Serialize>(mut self, parameters: &T) -> Self {
+        self.graph_parameters = Some(to_json_value(parameters));
+        self
+    }
+    pub fn from_json_str(json: &str) -> Self {
+        serde_json::from_str(json).expect("Error parsing manifest JSON")
+    }
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(&self).expect("Error converting manifest to JSON!")
+    }
+    pub fn to_json_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self).expect("Error converting manifest to JSON!")
+    }
+    pub fn to_json_file(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json_string_pretty())
+    }
+}
+
+impl Simulator {
+    /// Manifest describing the simulation so far; add QSC and graph parameters using
+    /// `SimulationManifest::with_qsc_parameters` and `with_graph_parameters`.
+    pub fn manifest(&self) -> SimulationManifest {
+        SimulationManifest {
+            crate_version: CRATE_VERSION.to_string(),
+            seed: self.seed,
+            number_of_steps: self.number_of_steps,
+            qsc: self.qsc.name(),
+            qsc_parameters: None,
+            graph_parameters: None,
+            regions: self.regions.clone(),
+        }
+    }
+}
+
+fn to_json_value<T: Serialize>(parameters: &T) -> serde_json::Value {
+    serde_json::to_value(parameters).expect("Error converting parameters to JSON!")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::monitors::*;
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    #[test]
+    fn manifest_seed_reproduces_simulation() {
+        let simulate = |seed| {
+            let mut simulator = Simulator::new_seeded(
+                Fbas::new(),
+                Rc::new(RandomQsc::new_simple(3)),
+                Rc::new(DummyMonitor),
+                seed,
+            );
+            simulator.simulate_growth(5);
+            simulator.simulate_shrink(2);
+            simulator
+        };
+        let original = simulate(thread_rng().gen());
+        let manifest = original.manifest();
+        assert_eq!(5 + 2, manifest.number_of_steps);
+        assert_eq!(CRATE_VERSION, manifest.crate_version);
+
+        let reproduced = simulate(manifest.seed);
+        assert_eq!(original.departed_nodes(), reproduced.departed_nodes());
+    }
+
+    #[test]
+    fn configurator_names_omit_paths_and_type_parameters() {
+        let graph = Graph::new_full_mesh(3);
+        assert_eq!("SuperSafeQsc", SuperSafeQsc::new().name());
+        assert_eq!(
+            "QualityAwareGraphQsc",
+            QualityAwareGraphQsc::new(graph).name()
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+impl Simulator {
+    /// Merges the FBASs of two independently run simulations into the FBAS of a new simulator,
+    /// for studying under which conditions two separately grown networks achieve quorum
+    /// intersection. Nodes of `first` keep their IDs; nodes of `second` get their IDs shifted by
+    /// the number of nodes in `first` (departed nodes included) and are renamed if their public
+    /// keys are already taken (e.g., because both simulations use generic names).
+    ///
+    /// The new simulator uses `bridging_qsc`, which is applied to selected nodes only via
+    /// `simulate_bridging`; all other nodes keep their quorum sets unless further `simulate_*`
+    /// methods are called. The simulator's random number generator is taken over from `first`.
+    pub fn merge(
+        first: Simulator,
+        second: Simulator,
+        bridging_qsc: Rc<dyn QuorumSetConfigurator>,
+        monitor: Rc<dyn SimulationMonitor>,
+    ) -> Self {
+        let offset = first.fbas.number_of_nodes();
+        let shift_map: HashMap<NodeId, NodeId> = (0..second.fbas.number_of_nodes())
+            .map(|node_id| (node_id, node_id + offset))
+            .collect();
+
+        let mut fbas = first.fbas;
+        for (node_id, node) in second.fbas.nodes.iter().enumerate() {
+            let mut node = Node::shrunken(node, &shift_map);
+            if fbas.get_node_id(&node.public_key).is_some() {
+                node.public_key = generate_generic_node_name(node_id + offset);
+            }
+            fbas.add_node(node);
+        }
+        let mut departed_nodes = first.departed_nodes;
+        departed_nodes.extend(second.departed_nodes.iter().map(|node_id| node_id + offset));
+        let mut failed_nodes = first.failed_nodes;
+        failed_nodes.extend(second.failed_nodes.iter().map(|node_id| node_id + offset));
+        debug!(
+            "Merged simulated FBASs into one of {} nodes ({} departed).",
+            fbas.number_of_nodes(),
+            departed_nodes.len()
+        );
+        Simulator {
+            fbas,
+            qsc: bridging_qsc,
+            monitor,
+            departed_nodes,
+            failed_nodes,
+            number_of_steps: 0,
+            seed: first.seed,
+            rng: first.rng,
+            regions: first.regions,
+            maximum_number_of_rounds: first.maximum_number_of_rounds,
+            number_of_unconverged_reevaluations: 0,
+        }
+    }
+    /// Make only the `bridging_nodes` reevaluate and update their quorum sets (using the
+    /// simulator's quorum set configurator), in random order, up to `maximum_number_of_rounds`
+    /// or until their configuration has stabilized. Departed nodes are skipped.
+    ///
+    /// Returns the number of reevaluation rounds made; see also `has_converged`.
+    pub fn simulate_bridging(
+        &mut self,
+        bridging_nodes: &[NodeId],
+        maximum_number_of_rounds: usize,
+    ) -> usize {
+        let order: Vec<NodeId> = bridging_nodes
+            .iter()
+            .copied()
+            .filter(|&node_id| !self.departed_nodes.contains(node_id))
+            .collect();
+        self.simulate_reevaluation(order, maximum_number_of_rounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::monitors::*;
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    fn simulate_ideal_growth(n: usize) -> Simulator {
+        let mut simulator =
+            Simulator::new_seeded(Fbas::new(), Rc::new(IdealQsc), Rc::new(DummyMonitor), 42);
+        simulator.simulate_growth(n);
+        simulator
+    }
+
+    #[test]
+    fn merged_simulations_intersect_only_after_bridging() {
+        let mut simulator = Simulator::merge(
+            simulate_ideal_growth(3),
+            simulate_ideal_growth(4),
+            Rc::new(IdealQsc),
+            Rc::new(DummyMonitor),
+        );
+        let fbas = simulator.fbas().clone();
+        assert_eq!(7, fbas.number_of_nodes());
+        assert_eq!(Some(5), fbas.get_node_id("n5"));
+        assert_eq!(
+            bitset![3, 4, 5, 6],
+            fbas.nodes[5].quorum_set.contained_nodes()
+        );
+        assert!(!Analysis::new(&fbas, None).has_quorum_intersection());
+
+        simulator.simulate_bridging(&[0, 1, 2, 3, 4, 5, 6], 10);
+        let fbas = simulator.finalize();
+        assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+    }
+
+    #[test]
+    fn merging_shifts_departed_nodes() {
+        let mut second = simulate_ideal_growth(4);
+        second.simulate_departures(&[1]);
+        let mut simulator = Simulator::merge(
+            simulate_ideal_growth(3),
+            second,
+            Rc::new(IdealQsc),
+            Rc::new(DummyMonitor),
+        );
+        assert_eq!(&bitset![4], simulator.departed_nodes());
+
+        simulator.simulate_bridging(&[0, 4], 10);
+        assert_eq!(QuorumSet::new(), simulator.fbas().nodes[4].quorum_set);
+        assert!(!simulator.fbas().nodes[0].quorum_set.contains_node(4));
+        assert!(simulator.fbas().nodes[0].quorum_set.contains_node(6));
+    }
+}
@@ -1,18 +1,40 @@
 use super::*;
+// takes precedence over the glob-imported `Event::AnalysisResult`, here and in submodules
+use crate::AnalysisResult;
 
+use std::cmp;
 use std::rc::Rc;
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+pub mod attacks;
+pub mod checkpoints;
+pub mod experiments;
+pub mod failures;
+mod identities;
+pub mod manifests;
+mod merging;
 pub mod monitors;
 pub mod quorum_set_configurators;
+pub mod scenarios;
+pub mod synthetic;
 
 #[derive(Clone)]
 pub struct Simulator {
     fbas: Fbas,
     qsc: Rc<dyn QuorumSetConfigurator>,
     monitor: Rc<dyn SimulationMonitor>,
+    departed_nodes: NodeIdSet,
+    failed_nodes: NodeIdSet,
+    number_of_steps: usize,
+    seed: u64,
+    rng: StdRng,
+    regions: Vec<Region>,
+    maximum_number_of_rounds: Option<usize>,
+    number_of_unconverged_reevaluations: usize,
 }
 impl Simulator {
     pub fn new(
@@ -20,76 +42,417 @@ impl Simulator {
         qsc: Rc<dyn QuorumSetConfigurator>,
         monitor: Rc<dyn SimulationMonitor>,
     ) -> Self {
-        Simulator { fbas, qsc, monitor }
+        Self::new_seeded(fbas, qsc, monitor, thread_rng().gen())
     }
-    /// Get the contained FBAS, effectively ending the simulation
+    /// Like `new`, but with reproducible simulator decisions (reevaluation orders, leaving and
+    /// joining nodes). Quorum set configurators that use randomness of their own are not
+    /// affected by `seed`.
+    pub fn new_seeded(
+        fbas: Fbas,
+        qsc: Rc<dyn QuorumSetConfigurator>,
+        monitor: Rc<dyn SimulationMonitor>,
+        seed: u64,
+    ) -> Self {
+        Simulator {
+            fbas,
+            qsc,
+            monitor,
+            departed_nodes: NodeIdSet::new(),
+            failed_nodes: NodeIdSet::new(),
+            number_of_steps: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            regions: vec![],
+            maximum_number_of_rounds: None,
+            number_of_unconverged_reevaluations: 0,
+        }
+    }
+    /// Place new nodes in `regions`, in round-robin order: a node with ID `i` is placed in
+    /// `regions[i % regions.len()]`. Without regions (the default), new nodes have no
+    /// geographic metadata.
+    pub fn with_regions(mut self, regions: Vec<Region>) -> Self {
+        self.regions = regions;
+        self
+    }
+    /// Cap the reevaluation rounds that follow each joining or leaving node (in
+    /// `simulate_growth`, `simulate_departures`, etc.). Without a cap (the default), at most as
+    /// many rounds as there are nodes in the FBAS are made.
+    pub fn with_maximum_number_of_rounds(mut self, maximum_number_of_rounds: usize) -> Self {
+        self.maximum_number_of_rounds = Some(maximum_number_of_rounds);
+        self
+    }
+    /// Get the contained FBAS, effectively ending the simulation.
+    /// Departed nodes remain in the FBAS (so that node IDs stay stable), with empty quorum sets
+    /// and without being referenced by any other node.
     pub fn finalize(self) -> Fbas {
         self.fbas
     }
+    /// Number of completed simulation steps (global reevaluations)
+    pub fn number_of_steps(&self) -> usize {
+        self.number_of_steps
+    }
+    /// Seed the simulator was created with (see `new_seeded`)
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    /// Current state of the FBAS, including departed nodes
+    pub fn fbas(&self) -> &Fbas {
+        &self.fbas
+    }
+    /// Whether all reevaluations so far ended because the configuration had stabilized (no
+    /// quorum set changed in a full round), rather than by reaching their maximum number of
+    /// rounds. `true` before any reevaluation.
+    pub fn has_converged(&self) -> bool {
+        self.number_of_unconverged_reevaluations == 0
+    }
+    /// Number of reevaluations so far that reached their maximum number of rounds before the
+    /// configuration had stabilized.
+    pub fn number_of_unconverged_reevaluations(&self) -> usize {
+        self.number_of_unconverged_reevaluations
+    }
+    /// Whether the monitor has requested to stop the simulation; if so, all further
+    /// `simulate_*` steps are skipped.
+    pub fn is_aborted(&self) -> bool {
+        self.monitor.should_abort()
+    }
+    /// Nodes that have left the FBAS
+    pub fn departed_nodes(&self) -> &NodeIdSet {
+        &self.departed_nodes
+    }
+    /// Nodes that are still part of the FBAS and haven't failed (see `failed_nodes`)
+    pub fn active_nodes(&self) -> Vec<NodeId> {
+        (0..self.fbas.nodes.len())
+            .filter(|&node_id| {
+                !self.departed_nodes.contains(node_id) && !self.failed_nodes.contains(node_id)
+            })
+            .collect()
+    }
     /// Add `nodes_to_spawn` new nodes, setting their quorum sets using `qsc`.
     /// Also lets all nodes reevaluate their quorum sets after each new node is added.
     pub fn simulate_growth(&mut self, nodes_to_spawn: usize) {
         for _ in 0..nodes_to_spawn {
-            let node_id = self.fbas.add_generic_node(QuorumSet::new());
-            self.qsc.configure(node_id, &mut self.fbas);
-            self.monitor.register_event(AddNode(node_id));
-            self.simulate_global_reevaluation(self.fbas.number_of_nodes());
+            if self.is_aborted() {
+                break;
+            }
+            let node_id = self.add_node();
+            self.configure(node_id);
+            self.monitor.register_event(NodeAdded(node_id));
+            self.simulate_capped_global_reevaluation();
+        }
+    }
+    /// Remove `nodes_to_remove` randomly chosen nodes, one after the other.
+    /// Also lets all remaining nodes reevaluate their quorum sets after each removal.
+    pub fn simulate_shrink(&mut self, nodes_to_remove: usize) {
+        let active_nodes = self.active_nodes();
+        let nodes: Vec<NodeId> = active_nodes
+            .choose_multiple(&mut self.rng, nodes_to_remove)
+            .copied()
+            .collect();
+        self.simulate_departures(&nodes);
+    }
+    /// Remove the given nodes, in the given order (e.g., for targeted removals).
+    /// Also lets all remaining nodes reevaluate their quorum sets after each removal.
+    pub fn simulate_departures(&mut self, nodes: &[NodeId]) {
+        for &node_id in nodes {
+            if self.is_aborted() {
+                break;
+            }
+            self.remove_node(node_id);
+            self.simulate_capped_global_reevaluation();
+        }
+    }
+    /// Simulate `rounds` rounds of churn. In each round, on average `join_rate` new nodes join
+    /// the FBAS and each active node leaves with probability `leave_rate`. New nodes are
+    /// configured using `qsc`; at the end of each round, all remaining nodes reevaluate their
+    /// quorum sets.
+    pub fn simulate_churn(&mut self, rounds: usize, join_rate: f64, leave_rate: f64) {
+        assert!(join_rate >= 0., "`join_rate` mustn't be negative!");
+        assert!(
+            (0. ..=1.).contains(&leave_rate),
+            "`leave_rate` must be a probability!"
+        );
+        for _ in 0..rounds {
+            if self.is_aborted() {
+                break;
+            }
+            let leaving_nodes: Vec<NodeId> = self
+                .active_nodes()
+                .into_iter()
+                .filter(|_| self.rng.gen_bool(leave_rate))
+                .collect();
+            let number_of_joining_nodes =
+                join_rate.floor() as usize + self.rng.gen_bool(join_rate.fract()) as usize;
+
+            for _ in 0..number_of_joining_nodes {
+                let node_id = self.add_node();
+                self.configure(node_id);
+                self.monitor.register_event(NodeAdded(node_id));
+            }
+            for node_id in leaving_nodes {
+                self.remove_node(node_id);
+            }
+            self.simulate_capped_global_reevaluation();
+        }
+    }
+    /// Global reevaluation after nodes joined or left, see `with_maximum_number_of_rounds`.
+    fn simulate_capped_global_reevaluation(&mut self) {
+        let maximum_number_of_rounds = self
+            .maximum_number_of_rounds
+            .unwrap_or_else(|| self.fbas.number_of_nodes());
+        self.simulate_global_reevaluation(maximum_number_of_rounds);
+    }
+    /// Adds an unconfigured node, placed in a region if regions are set.
+    fn add_node(&mut self) -> NodeId {
+        let node_id = self.fbas.add_generic_node(QuorumSet::new());
+        if !self.regions.is_empty() {
+            let region = &self.regions[node_id % self.regions.len()];
+            self.fbas.set_region(node_id, region);
+        }
+        node_id
+    }
+    /// Lets `node_id` leave the FBAS: its quorum set is cleared and no other node may use it in
+    /// its quorum set anymore. Node IDs of other nodes remain unchanged.
+    fn remove_node(&mut self, node_id: NodeId) {
+        assert!(
+            !self.departed_nodes.contains(node_id),
+            "Node {} has already left the FBAS!",
+            node_id
+        );
+        self.departed_nodes.insert(node_id);
+        self.fbas.nodes[node_id].quorum_set = QuorumSet::new();
+        for node in self.fbas.nodes.iter_mut() {
+            node.quorum_set = node.quorum_set.without_nodes(&self.departed_nodes);
+        }
+        self.monitor.register_event(NodeRemoved(node_id));
+    }
+    /// Configure `node_id` using `qsc`, removing any departed or failed nodes that `qsc` might
+    /// have (re)added to its quorum set. While nodes are failed, `qsc` configures `node_id` in
+    /// a copy of the FBAS in which they are treated like departed nodes (see
+    /// `available_fbas`).
+    fn configure(&mut self, node_id: NodeId) -> ChangeEffect {
+        let mut unavailable_nodes = self.departed_nodes.clone();
+        unavailable_nodes.union_with(&self.failed_nodes);
+        if unavailable_nodes.is_empty() {
+            self.qsc.configure(node_id, &mut self.fbas)
+        } else {
+            let old_quorum_set = self.fbas.nodes[node_id].quorum_set.clone();
+            let configured_quorum_set = if self.failed_nodes.is_empty() {
+                self.qsc.configure(node_id, &mut self.fbas);
+                self.fbas.nodes[node_id].quorum_set.clone()
+            } else {
+                let mut available_fbas = self.available_fbas();
+                available_fbas.nodes[node_id].quorum_set =
+                    old_quorum_set.without_nodes(&unavailable_nodes);
+                self.qsc.configure(node_id, &mut available_fbas);
+                available_fbas.nodes[node_id].quorum_set.clone()
+            };
+            let new_quorum_set = configured_quorum_set.without_nodes(&unavailable_nodes);
+            let change = if new_quorum_set == old_quorum_set {
+                NoChange
+            } else {
+                Change
+            };
+            self.fbas.nodes[node_id].quorum_set = new_quorum_set;
+            change
         }
     }
     /// Make all nodes reevaluate and update their quorum sets using `qsc`, up to
     /// `maximum_number_of_rounds` or until the global configuration has stabilizied (no more
     /// changes happen).
     ///
-    /// Returns the number of reevaluation rounds made.
+    /// Returns the number of reevaluation rounds made; use `has_converged` to find out whether
+    /// the configuration has stabilized.
     pub fn simulate_global_reevaluation(&mut self, maximum_number_of_rounds: usize) -> usize {
-        let mut stable = false;
+        let order = self.active_nodes();
+        self.simulate_reevaluation(order, maximum_number_of_rounds)
+    }
+    /// Like `simulate_global_reevaluation`, but only the nodes in `order` reevaluate.
+    fn simulate_reevaluation(
+        &mut self,
+        mut order: Vec<NodeId>,
+        maximum_number_of_rounds: usize,
+    ) -> usize {
+        // nothing to reevaluate, e.g., before growing from an empty FBAS
+        let mut stable = order.is_empty();
         let mut next_round_number = 0;
 
-        // Visit nodes in random order each time
-        let mut order: Vec<NodeId> = (0..self.fbas.nodes.len()).collect();
-        let mut rng = thread_rng();
-
         self.monitor.register_event(StartGlobalReevaluation);
 
         while !stable && next_round_number < maximum_number_of_rounds {
-            order.shuffle(&mut rng);
-            stable = !self.simulate_global_reevaluation_round(&order).had_change();
+            // Visit nodes in random order each time
+            order.shuffle(&mut self.rng);
+            stable = !self
+                .simulate_global_reevaluation_round(&order, next_round_number)
+                .had_change();
             next_round_number += 1;
         }
-        let number_of_rounds = next_round_number;
+        self.finish_global_reevaluation(next_round_number, stable)
+    }
+    /// Like `simulate_global_reevaluation`, but in each round only a random subset of nodes
+    /// reevaluates their quorum sets (each active node with probability `participation_rate`),
+    /// modelling node operators that don't all react at the same time. The global configuration
+    /// counts as stable once every node has reevaluated without change since the last change.
+    ///
+    /// Returns the number of reevaluation rounds made.
+    pub fn simulate_partial_reevaluation(
+        &mut self,
+        maximum_number_of_rounds: usize,
+        participation_rate: f64,
+    ) -> usize {
+        assert!(
+            (0. ..=1.).contains(&participation_rate),
+            "`participation_rate` must be a probability!"
+        );
+        let active_nodes = self.active_nodes();
+        // nodes that reevaluated without change since the last change
+        let mut confirmed_nodes = NodeIdSet::new();
+        let mut next_round_number = 0;
+
+        self.monitor.register_event(StartGlobalReevaluation);
+
+        while confirmed_nodes.len() < active_nodes.len()
+            && next_round_number < maximum_number_of_rounds
+        {
+            let mut order: Vec<NodeId> = active_nodes
+                .iter()
+                .copied()
+                .filter(|_| self.rng.gen_bool(participation_rate))
+                .collect();
+            order.shuffle(&mut self.rng);
+
+            self.monitor.register_event(StartGlobalReevaluationRound);
+            let mut any_change = NoChange;
+            for node_id in order {
+                let change = self.reevaluate(node_id);
+                any_change.update(change);
+                if change.had_change() {
+                    confirmed_nodes.clear();
+                } else {
+                    confirmed_nodes.insert(node_id);
+                }
+            }
+            self.monitor.register_event(RoundFinished {
+                round: next_round_number,
+                change: any_change,
+            });
+            next_round_number += 1;
+        }
+        let stable = confirmed_nodes.len() >= active_nodes.len();
+        self.finish_global_reevaluation(next_round_number, stable)
+    }
+    fn finish_global_reevaluation(&mut self, number_of_rounds: usize, stable: bool) -> usize {
+        if !stable {
+            debug!(
+                "Reevaluation stopped after {} rounds without converging.",
+                number_of_rounds
+            );
+            self.number_of_unconverged_reevaluations += 1;
+        }
         self.monitor
             .register_event(FinishGlobalReevaluation(number_of_rounds));
+        self.number_of_steps += 1;
+        self.monitor.register_state(self);
         number_of_rounds
     }
     /// Make *all* nodes reevaluate their quorum sets *once*, using `qsc`.
-    fn simulate_global_reevaluation_round(&mut self, order: &[NodeId]) -> ChangeEffect {
+    fn simulate_global_reevaluation_round(
+        &mut self,
+        order: &[NodeId],
+        round: usize,
+    ) -> ChangeEffect {
         self.monitor.register_event(StartGlobalReevaluationRound);
         let mut any_change = NoChange;
         for &node_id in order {
-            let change = self.qsc.configure(node_id, &mut self.fbas);
+            let change = self.reevaluate(node_id);
             any_change.update(change);
-            self.monitor
-                .register_event(QuorumSetChange(node_id, change));
         }
+        self.monitor.register_event(RoundFinished {
+            round,
+            change: any_change,
+        });
         any_change
     }
+    /// Let `node_id` reevaluate its quorum set, reporting the outcome to the monitor.
+    fn reevaluate(&mut self, node_id: NodeId) -> ChangeEffect {
+        let old = self.fbas.nodes[node_id].quorum_set.clone();
+        let change = self.configure(node_id);
+        if change.had_change() {
+            let new = self.fbas.nodes[node_id].quorum_set.clone();
+            self.monitor.register_event(QsetChanged {
+                node: node_id,
+                old,
+                new,
+            });
+        }
+        change
+    }
+    /// Make the result of an analysis made during the simulation (e.g., between simulation
+    /// steps) known to the monitor, so that it can be recorded alongside other events.
+    pub fn report_analysis_result(&self, analysis: &str, result: String) {
+        self.monitor.register_event(Event::AnalysisResult {
+            analysis: analysis.to_string(),
+            result,
+        });
+    }
 }
 
 pub trait QuorumSetConfigurator {
     fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect;
+    /// Name for manifests and logs; the type name (without module path) by default.
+    fn name(&self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        let without_type_parameters = type_name.split('<').next().unwrap_or(type_name);
+        without_type_parameters
+            .rsplit("::")
+            .next()
+            .unwrap_or(without_type_parameters)
+            .to_string()
+    }
 }
 
 pub trait SimulationMonitor {
     fn register_event(&self, event: Event);
+    /// Called at the end of each global reevaluation (i.e., once per simulation step), with the
+    /// simulator in its resulting state.
+    fn register_state(&self, _simulator: &Simulator) {}
+    /// Monitors can request to stop the simulation, e.g., once some interesting state has been
+    /// reached.
+    fn should_abort(&self) -> bool {
+        false
+    }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// Everything that happens during a simulation, as seen by `SimulationMonitor`s.
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Event {
-    AddNode(NodeId),
+    NodeAdded(NodeId),
+    NodeRemoved(NodeId),
+    /// A node has (temporarily) failed, see `Simulator::simulate_failures`
+    NodeFailed(NodeId),
+    NodeRecovered(NodeId),
     StartGlobalReevaluation,
     StartGlobalReevaluationRound,
+    /// `round` counts from 0 within each global reevaluation; `change` is `Change` if any node
+    /// changed its quorum set in this round.
+    RoundFinished {
+        round: usize,
+        change: ChangeEffect,
+    },
+    /// Contains the total number of rounds made
     FinishGlobalReevaluation(usize),
-    QuorumSetChange(NodeId, ChangeEffect),
+    /// A node has reevaluated its quorum set and changed it from `old` to `new`; reevaluations
+    /// without change aren't reported.
+    QsetChanged {
+        node: NodeId,
+        old: QuorumSet,
+        new: QuorumSet,
+    },
+    /// Reported via `Simulator::report_analysis_result`; within this crate, refer to it as
+    /// `Event::AnalysisResult` (plain `AnalysisResult` is the trait from `crate::io`).
+    AnalysisResult {
+        analysis: String,
+        result: String,
+    },
 }
 use Event::*;
 
@@ -110,6 +473,22 @@ impl ChangeEffect {
 }
 use ChangeEffect::*;
 
+/// Geographic location of a (simulated) node, stored as the node's continent and country
+/// metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Region {
+    pub continent: String,
+    pub country: String,
+}
+impl Region {
+    pub fn new(continent: &str, country: &str) -> Self {
+        Region {
+            continent: continent.to_string(),
+            country: country.to_string(),
+        }
+    }
+}
+
 impl Fbas {
     /// FBAS of `n` nodes with empty quorum sets
     pub fn new_generic_unconfigured(n: usize) -> Self {
@@ -119,15 +498,77 @@ impl Fbas {
         }
         fbas
     }
+    /// FBAS of `n` nodes with quorum sets configured using `qsc`, without simulating growth:
+    /// all nodes are added at once and then reevaluate their quorum sets until the global
+    /// configuration is stable (or `n` reevaluation rounds have been made).
+    pub fn new_configured(n: usize, qsc: Rc<dyn QuorumSetConfigurator>) -> Self {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(n),
+            qsc,
+            Rc::new(monitors::DummyMonitor),
+        );
+        simulator.simulate_global_reevaluation(n);
+        simulator.finalize()
+    }
+    /// FBAS with one node per node in `graph`, configured (as in `new_configured`) by the
+    /// quorum set configurator returned by `make_qsc`, e.g.,
+    /// `Fbas::from_graph(graph, SimpleGraphQsc::new_67p)`.
+    pub fn from_graph<Q, F>(graph: Graph, make_qsc: F) -> Self
+    where
+        Q: QuorumSetConfigurator + 'static,
+        F: FnOnce(Graph) -> Q,
+    {
+        let n = graph.number_of_nodes();
+        Self::new_configured(n, Rc::new(make_qsc(graph)))
+    }
     /// Add a node with generic "`public_key`"
     pub fn add_generic_node(&mut self, quorum_set: QuorumSet) -> NodeId {
         let node_id = self.nodes.len();
         self.add_node(Node {
             public_key: generate_generic_node_name(node_id),
             quorum_set,
+            name: None,
+            metadata: NodeMetadata::default(),
         });
         node_id
     }
+    /// Set the continent and country metadata of `node_id`.
+    pub fn set_region(&mut self, node_id: NodeId, region: &Region) {
+        let metadata = self.metadata_mut(node_id);
+        metadata.continent = Some(region.continent.clone());
+        metadata.country = Some(region.country.clone());
+    }
+}
+impl QuorumSet {
+    /// Copy of this quorum set without `nodes`. Inner quorum sets that become empty are dropped;
+    /// thresholds are lowered proportionally to the number of removed entries.
+    fn without_nodes(&self, nodes: &NodeIdSet) -> Self {
+        let validators: Vec<NodeId> = self
+            .validators
+            .iter()
+            .copied()
+            .filter(|&node_id| !nodes.contains(node_id))
+            .collect();
+        let inner_quorum_sets: Vec<QuorumSet> = self
+            .inner_quorum_sets
+            .iter()
+            .map(|inner_quorum_set| inner_quorum_set.without_nodes(nodes))
+            .filter(|inner_quorum_set| *inner_quorum_set != QuorumSet::new())
+            .collect();
+        let old_size = self.validators.len() + self.inner_quorum_sets.len();
+        let new_size = validators.len() + inner_quorum_sets.len();
+        let threshold = if new_size == old_size {
+            self.threshold
+        } else {
+            let scaled = (self.threshold * new_size) as f64 / old_size as f64;
+            cmp::min(new_size, cmp::max(1, scaled.ceil() as usize))
+        };
+        QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets,
+        }
+    }
 }
 fn generate_generic_node_name(node_id: NodeId) -> String {
     format!("n{}", node_id)
@@ -161,6 +602,67 @@ mod tests {
         assert!(!monitor.events_ref().is_empty());
     }
 
+    #[test]
+    fn quorum_set_change_events_contain_old_and_new_quorum_sets() {
+        let monitor = Rc::new(DebugMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(2),
+            Rc::new(SuperSafeQsc),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_global_reevaluation(10);
+        let expected_new = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![],
+        };
+        let events = monitor.events_clone();
+        assert!(events.contains(&QsetChanged {
+            node: 0,
+            old: QuorumSet::new(),
+            new: expected_new.clone(),
+        }));
+        // no changes in the second round, so nothing to report
+        let reported_changes = events
+            .iter()
+            .filter(|event| matches!(event, QsetChanged { .. }))
+            .count();
+        assert_eq!(2, reported_changes);
+        let finished_rounds: Vec<&Event> = events
+            .iter()
+            .filter(|event| matches!(event, RoundFinished { .. }))
+            .collect();
+        assert_eq!(
+            vec![
+                &RoundFinished {
+                    round: 0,
+                    change: Change
+                },
+                &RoundFinished {
+                    round: 1,
+                    change: NoChange
+                }
+            ],
+            finished_rounds
+        );
+    }
+
+    #[test]
+    fn analysis_results_are_reported_to_monitor() {
+        let monitor = Rc::new(DebugMonitor::new());
+        let simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(DummyQsc),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.report_analysis_result("has_quorum_intersection", "false".to_string());
+        let expected = vec![Event::AnalysisResult {
+            analysis: "has_quorum_intersection".to_string(),
+            result: "false".to_string(),
+        }];
+        assert_eq!(expected, monitor.events_clone());
+    }
+
     #[test]
     fn global_reevaluation_round_can_make_all_nodes_super_safe() {
         let mut simulator = Simulator::new(
@@ -168,7 +670,7 @@ mod tests {
             Rc::new(SuperSafeQsc),
             Rc::new(DummyMonitor),
         );
-        simulator.simulate_global_reevaluation_round(&[0, 1, 2, 3, 4, 5, 6, 7]);
+        simulator.simulate_global_reevaluation_round(&[0, 1, 2, 3, 4, 5, 6, 7], 0);
 
         let expected_quorum_set = QuorumSet {
             threshold: 8,
@@ -199,27 +701,236 @@ mod tests {
         assert_eq!(number_of_rounds, 2);
     }
 
+    #[test]
+    fn reevaluation_reports_convergence() {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(8),
+            Rc::new(SuperSafeQsc),
+            Rc::new(DummyMonitor),
+        );
+        assert!(simulator.has_converged());
+        assert_eq!(simulator.simulate_bridging(&[], 0), 0);
+        assert!(simulator.has_converged());
+        assert_eq!(simulator.simulate_global_reevaluation(1), 1);
+        assert!(!simulator.has_converged());
+        // later convergence doesn't undo an earlier unconverged reevaluation
+        assert_eq!(simulator.simulate_global_reevaluation(1), 1);
+        assert!(!simulator.has_converged());
+        assert_eq!(1, simulator.number_of_unconverged_reevaluations());
+        simulator.simulate_partial_reevaluation(3, 0.);
+        assert_eq!(2, simulator.number_of_unconverged_reevaluations());
+    }
+
+    #[test]
+    fn growth_respects_maximum_number_of_rounds() {
+        let monitor = Rc::new(DebugMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(SuperSafeQsc),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        )
+        .with_maximum_number_of_rounds(1);
+        simulator.simulate_growth(3);
+        assert!(!simulator.has_converged());
+        let rounds: Vec<usize> = monitor
+            .events_ref()
+            .iter()
+            .filter_map(|event| match event {
+                FinishGlobalReevaluation(rounds) => Some(*rounds),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![1, 1, 1], rounds);
+    }
+
+    #[test]
+    fn partial_reevaluation_eventually_reaches_stable_configuration() {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(8),
+            Rc::new(SuperSafeQsc),
+            Rc::new(DummyMonitor),
+        );
+        let number_of_rounds = simulator.simulate_partial_reevaluation(1000000, 0.3);
+        assert!(number_of_rounds < 1000000);
+        assert_eq!(
+            simulator.finalize(),
+            Fbas::new_configured(8, Rc::new(SuperSafeQsc))
+        );
+    }
+
+    #[test]
+    fn partial_reevaluation_with_full_participation_is_global_reevaluation() {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(8),
+            Rc::new(SuperSafeQsc),
+            Rc::new(DummyMonitor),
+        );
+        let number_of_rounds = simulator.simulate_partial_reevaluation(1000000, 1.);
+        assert_eq!(number_of_rounds, 2);
+    }
+
+    #[test]
+    fn partial_reevaluation_without_participation_changes_nothing() {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(8),
+            Rc::new(SuperSafeQsc),
+            Rc::new(DummyMonitor),
+        );
+        assert_eq!(simulator.simulate_partial_reevaluation(5, 0.), 5);
+        assert_eq!(simulator.finalize(), Fbas::new_generic_unconfigured(8));
+    }
+
+    #[test]
+    fn new_configured_is_stable() {
+        let fbas = Fbas::new_configured(8, Rc::new(IdealQsc));
+        let mut simulator = Simulator::new(fbas, Rc::new(IdealQsc), Rc::new(DummyMonitor));
+        assert_eq!(simulator.simulate_global_reevaluation(8), 1);
+        assert!(Analysis::new(&simulator.finalize(), None).has_quorum_intersection());
+    }
+
+    #[test]
+    fn from_graph_makes_one_node_per_graph_node() {
+        let graph = Graph::new_random_small_world(20, 4, 0.05);
+        let expected = simulate!(SimpleGraphQsc::new_67p(graph.clone()), 20);
+        let actual = Fbas::from_graph(graph, SimpleGraphQsc::new_67p);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn shrink_removes_nodes_from_quorum_sets() {
+        let monitor = Rc::new(DebugMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(IdealQsc::new()),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(8);
+        simulator.simulate_shrink(3);
+        let departed_nodes = simulator.departed_nodes().clone();
+        assert_eq!(departed_nodes.len(), 3);
+        assert_eq!(
+            monitor
+                .events_ref()
+                .iter()
+                .filter(|event| matches!(event, NodeRemoved(_)))
+                .count(),
+            3
+        );
+
+        let active_nodes: NodeIdSet = simulator.active_nodes().into_iter().collect();
+        let fbas = simulator.finalize();
+        for node_id in departed_nodes.iter() {
+            assert_eq!(fbas.nodes[node_id].quorum_set, QuorumSet::new());
+        }
+        for node_id in active_nodes.iter() {
+            assert_eq!(
+                fbas.nodes[node_id].quorum_set.contained_nodes(),
+                active_nodes
+            );
+            assert_eq!(fbas.nodes[node_id].quorum_set.threshold, 4);
+        }
+        assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+    }
+
+    #[test]
+    fn targeted_departures_remove_given_nodes() {
+        let mut simulator = Simulator::new(
+            Fbas::new_generic_unconfigured(6),
+            Rc::new(SuperSafeQsc::new()),
+            Rc::new(DummyMonitor),
+        );
+        simulator.simulate_global_reevaluation(6);
+        simulator.simulate_departures(&[4, 1]);
+        assert_eq!(simulator.active_nodes(), vec![0, 2, 3, 5]);
+        let fbas = simulator.finalize();
+        assert!(fbas.is_quorum(&bitset![0, 2, 3, 5]));
+        assert!(!fbas.is_quorum(&bitset![0, 2, 3]));
+    }
+
+    #[test]
+    fn churn_without_departures_is_growth() {
+        let mut simulator =
+            Simulator::new(Fbas::new(), Rc::new(IdealQsc::new()), Rc::new(DummyMonitor));
+        simulator.simulate_churn(5, 2., 0.);
+        assert_eq!(simulator.active_nodes().len(), 10);
+        assert_eq!(simulator.finalize(), simulate!(IdealQsc::new(), 10));
+    }
+
+    #[test]
+    fn churn_without_joins_removes_everyone_eventually() {
+        let mut simulator =
+            Simulator::new(Fbas::new(), Rc::new(IdealQsc::new()), Rc::new(DummyMonitor));
+        simulator.simulate_growth(10);
+        simulator.simulate_churn(1, 0., 1.);
+        assert!(simulator.active_nodes().is_empty());
+        assert_eq!(simulator.departed_nodes().len(), 10);
+    }
+
+    #[test]
+    fn churn_keeps_quorum_sets_free_of_departed_nodes() {
+        let mut simulator =
+            Simulator::new(Fbas::new(), Rc::new(IdealQsc::new()), Rc::new(DummyMonitor));
+        simulator.simulate_growth(10);
+        simulator.simulate_churn(10, 1.5, 0.1);
+        let departed_nodes = simulator.departed_nodes().clone();
+        let fbas = simulator.finalize();
+        for node in fbas.nodes.iter() {
+            assert!(node
+                .quorum_set
+                .contained_nodes()
+                .is_disjoint(&departed_nodes));
+        }
+    }
+
+    #[test]
+    fn quorum_set_without_nodes_drops_empty_inner_sets_and_caps_threshold() {
+        let quorum_set = QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 1,
+                validators: vec![2],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        let expected = QuorumSet {
+            threshold: 1,
+            validators: vec![0],
+            inner_quorum_sets: vec![],
+        };
+        let actual = quorum_set.without_nodes(&bitset![1, 2]);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn global_reevaluation_visits_in_random_order() {
+        // so that every reevaluation is reported as a quorum set change
+        struct AlwaysChangingQsc;
+        impl QuorumSetConfigurator for AlwaysChangingQsc {
+            fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+                fbas.nodes[node_id].quorum_set.threshold += 1;
+                Change
+            }
+        }
         let monitor = Rc::new(DebugMonitor::new());
         let mut simulator = Simulator::new(
             Fbas::new_generic_unconfigured(128),
-            Rc::new(SuperSafeQsc),
+            Rc::new(AlwaysChangingQsc),
             Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
         );
         simulator.simulate_global_reevaluation(2);
 
         let events: Vec<Event> = monitor.events_clone();
         let rounds = events
-            .split(|&event| event == StartGlobalReevaluationRound)
+            .split(|event| *event == StartGlobalReevaluationRound)
             .skip(1);
 
         let orderings: Vec<Vec<NodeId>> = rounds
             .map(|round| {
                 round
                     .into_iter()
-                    .filter_map(|&event| match event {
-                        QuorumSetChange(id, _) => Some(id),
+                    .filter_map(|event| match *event {
+                        QsetChanged { node, .. } => Some(node),
                         _ => None,
                     })
                     .collect()
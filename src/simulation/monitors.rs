@@ -1,5 +1,8 @@
 use super::*;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
+use std::fs;
+use std::io;
+use std::path::Path;
 
 /// Does nothing.
 #[derive(Default)]
@@ -32,3 +35,408 @@ impl SimulationMonitor for DebugMonitor {
         self.recorded_events.borrow_mut().push(event);
     }
 }
+
+/// Metrics recorded by `TimeSeriesMonitor` for one simulation step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepMetrics {
+    /// Number of active (not departed) nodes
+    pub number_of_nodes: usize,
+    /// Mean number of nodes contained in the quorum sets of active nodes
+    pub mean_quorum_set_size: f64,
+    /// Mean (top-level) threshold of the quorum sets of active nodes
+    pub mean_threshold: f64,
+    pub added_nodes: usize,
+    pub removed_nodes: usize,
+    /// Number of quorum set reevaluations that resulted in a change
+    pub quorum_set_changes: usize,
+}
+
+/// Records a time series of FBAS metrics, one entry per simulation step (i.e., per global
+/// reevaluation), for later plotting or analysis.
+#[derive(Default)]
+pub struct TimeSeriesMonitor {
+    steps: RefCell<Vec<StepMetrics>>,
+    added_nodes: Cell<usize>,
+    removed_nodes: Cell<usize>,
+    quorum_set_changes: Cell<usize>,
+}
+impl TimeSeriesMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn steps_clone(&self) -> Vec<StepMetrics> {
+        self.steps_ref().clone()
+    }
+    pub fn steps_ref(&self) -> Ref<Vec<StepMetrics>> {
+        self.steps.borrow()
+    }
+    /// One line per simulation step
+    pub fn to_csv_string(&self) -> String {
+        let mut lines = vec![
+            "step,number_of_nodes,mean_quorum_set_size,mean_threshold,added_nodes,removed_nodes,quorum_set_changes"
+                .to_string(),
+        ];
+        for (i, step) in self.steps_ref().iter().enumerate() {
+            lines.push(format!(
+                "{},{},{},{},{},{},{}",
+                i,
+                step.number_of_nodes,
+                step.mean_quorum_set_size,
+                step.mean_threshold,
+                step.added_nodes,
+                step.removed_nodes,
+                step.quorum_set_changes
+            ));
+        }
+        lines.join("\n")
+    }
+}
+impl SimulationMonitor for TimeSeriesMonitor {
+    fn register_event(&self, event: Event) {
+        match event {
+            Event::NodeAdded(_) => self.added_nodes.set(self.added_nodes.get() + 1),
+            Event::NodeRemoved(_) => self.removed_nodes.set(self.removed_nodes.get() + 1),
+            Event::QsetChanged { .. } => self
+                .quorum_set_changes
+                .set(self.quorum_set_changes.get() + 1),
+            _ => {}
+        }
+    }
+    fn register_state(&self, simulator: &Simulator) {
+        let fbas = simulator.fbas();
+        let active_nodes = simulator.active_nodes();
+        let number_of_nodes = active_nodes.len();
+        let (quorum_set_size_sum, threshold_sum) =
+            active_nodes
+                .iter()
+                .fold((0, 0), |(size_sum, threshold_sum), &node_id| {
+                    let quorum_set = &fbas.nodes[node_id].quorum_set;
+                    (
+                        size_sum + quorum_set.number_of_contained_nodes(),
+                        threshold_sum + quorum_set.threshold,
+                    )
+                });
+        let mean = |sum: usize| {
+            if number_of_nodes == 0 {
+                0.
+            } else {
+                sum as f64 / number_of_nodes as f64
+            }
+        };
+        self.steps.borrow_mut().push(StepMetrics {
+            number_of_nodes,
+            mean_quorum_set_size: mean(quorum_set_size_sum),
+            mean_threshold: mean(threshold_sum),
+            added_nodes: self.added_nodes.replace(0),
+            removed_nodes: self.removed_nodes.replace(0),
+            quorum_set_changes: self.quorum_set_changes.replace(0),
+        });
+    }
+}
+
+/// Analysis results recorded by `AnalysisMonitor` for one simulation step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StepAnalysis {
+    /// Number of active (not departed) nodes
+    pub number_of_nodes: usize,
+    pub has_quorums: bool,
+    /// As in `Analysis::has_quorum_intersection`, `false` if there are no quorums at all
+    pub has_quorum_intersection: bool,
+}
+
+/// Checks for quorums and quorum intersection after each simulation step, ignoring failed nodes
+/// (see `Simulator::available_fbas`). Optionally requests to stop the simulation as soon as
+/// there are quorums that don't intersect.
+///
+/// As this happens very often, FBASs consisting of a single symmetric cluster (e.g., as
+/// created by `IdealQsc`) are checked without enumerating minimal quorums.
+#[derive(Default)]
+pub struct AnalysisMonitor {
+    abort_on_lost_intersection: bool,
+    steps: RefCell<Vec<StepAnalysis>>,
+}
+impl AnalysisMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn new_aborting_on_lost_intersection() -> Self {
+        AnalysisMonitor {
+            abort_on_lost_intersection: true,
+            steps: RefCell::new(vec![]),
+        }
+    }
+    pub fn steps_clone(&self) -> Vec<StepAnalysis> {
+        self.steps_ref().clone()
+    }
+    pub fn steps_ref(&self) -> Ref<Vec<StepAnalysis>> {
+        self.steps.borrow()
+    }
+}
+impl SimulationMonitor for AnalysisMonitor {
+    fn register_event(&self, _: Event) {}
+    fn register_state(&self, simulator: &Simulator) {
+        let (has_quorums, has_quorum_intersection) = if simulator.failed_nodes().is_empty() {
+            check_quorums(simulator.fbas())
+        } else {
+            check_quorums(&simulator.available_fbas())
+        };
+        debug!(
+            "Has quorums: {}, has quorum intersection: {}",
+            has_quorums, has_quorum_intersection
+        );
+        self.steps.borrow_mut().push(StepAnalysis {
+            number_of_nodes: simulator.active_nodes().len(),
+            has_quorums,
+            has_quorum_intersection,
+        });
+    }
+    fn should_abort(&self) -> bool {
+        self.abort_on_lost_intersection
+            && self
+                .steps_ref()
+                .last()
+                .into_iter()
+                .any(|step| step.has_quorums && !step.has_quorum_intersection)
+    }
+}
+
+/// Serialized FBAS recorded by `SnapshotMonitor` after simulation step `step` (counting from 1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FbasSnapshot {
+    pub step: usize,
+    /// As in `Fbas::to_json_string`; read back using `Fbas::from_json_str`
+    pub fbas_json: String,
+}
+
+/// Records a full snapshot of the simulated FBAS every `interval` simulation steps (i.e.,
+/// global reevaluations), so that its evolution can be replayed and analyzed offline. Snapshots
+/// include departed nodes, which have empty quorum sets.
+pub struct SnapshotMonitor {
+    interval: usize,
+    snapshots: RefCell<Vec<FbasSnapshot>>,
+}
+impl SnapshotMonitor {
+    pub fn new(interval: usize) -> Self {
+        assert!(interval > 0, "Snapshot interval must be positive!");
+        SnapshotMonitor {
+            interval,
+            snapshots: RefCell::new(vec![]),
+        }
+    }
+    pub fn snapshots_clone(&self) -> Vec<FbasSnapshot> {
+        self.snapshots_ref().clone()
+    }
+    pub fn snapshots_ref(&self) -> Ref<'_, Vec<FbasSnapshot>> {
+        self.snapshots.borrow()
+    }
+    /// Writes one file per snapshot into `dir` (which must exist), named after the step
+    /// (`step_00005.json`, ...) so that they sort chronologically.
+    pub fn write_to_dir(&self, dir: &Path) -> io::Result<()> {
+        for snapshot in self.snapshots_ref().iter() {
+            let path = dir.join(format!("step_{:05}.json", snapshot.step));
+            fs::write(path, &snapshot.fbas_json)?;
+        }
+        Ok(())
+    }
+}
+impl SimulationMonitor for SnapshotMonitor {
+    fn register_event(&self, _: Event) {}
+    fn register_state(&self, simulator: &Simulator) {
+        let step = simulator.number_of_steps();
+        let steps_since_last_snapshot = step % self.interval;
+        if steps_since_last_snapshot > 0 {
+            return;
+        }
+        debug!("Taking FBAS snapshot after step {}", step);
+        self.snapshots.borrow_mut().push(FbasSnapshot {
+            step,
+            fbas_json: simulator.fbas().to_json_string(),
+        });
+    }
+}
+
+/// Returns `(has_quorums, has_quorum_intersection)`, taking a shortcut if all quorums are
+/// formed by a single symmetric cluster.
+fn check_quorums(fbas: &Fbas) -> (bool, bool) {
+    let clusters = find_symmetric_clusters(fbas);
+    match clusters.len() {
+        0 => {
+            let mut analysis = Analysis::new(fbas, None);
+            let has_quorums = !analysis.minimal_quorums().is_empty();
+            (has_quorums, analysis.has_quorum_intersection())
+        }
+        1 => {
+            let cluster = &clusters[0];
+            let mut remaining_nodes = fbas.all_nodes();
+            remaining_nodes.difference_with(&cluster.contained_nodes());
+            let (satisfiable_remaining_nodes, _) = find_unsatisfiable_nodes(&remaining_nodes, fbas);
+            let has_quorum_intersection =
+                satisfiable_remaining_nodes.is_empty() && all_slices_intersect(cluster);
+            (true, has_quorum_intersection)
+        }
+        _ => (true, false),
+    }
+}
+
+/// Whether any two quorum slices of `quorum_set` intersect. Assumes that no node appears more
+/// than once in `quorum_set`; otherwise the answer may be a false `false`.
+fn all_slices_intersect(quorum_set: &QuorumSet) -> bool {
+    let number_of_entries = quorum_set.validators.len() + quorum_set.inner_quorum_sets.len();
+    let number_of_non_intersecting_inner_quorum_sets = quorum_set
+        .inner_quorum_sets
+        .iter()
+        .filter(|inner| !all_slices_intersect(inner))
+        .count();
+    // two slices share at least 2t - n entries; each shared entry must be able to "force" an
+    // intersection
+    quorum_set.threshold > 0
+        && 2 * quorum_set.threshold
+            > number_of_entries + number_of_non_intersecting_inner_quorum_sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quorum_set_configurators::*;
+    use super::*;
+
+    #[test]
+    fn time_series_monitor_records_one_entry_per_step() {
+        let monitor = Rc::new(TimeSeriesMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(SuperSafeQsc::new()),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(3);
+        simulator.simulate_departures(&[0]);
+        let steps = monitor.steps_clone();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[2].number_of_nodes, 3);
+        assert_eq!(steps[2].mean_quorum_set_size, 3.);
+        assert_eq!(steps[2].mean_threshold, 3.);
+        assert_eq!(steps[2].added_nodes, 1);
+        assert_eq!(steps[3].number_of_nodes, 2);
+        assert_eq!(steps[3].removed_nodes, 1);
+        assert_eq!(steps[3].added_nodes, 0);
+    }
+
+    #[test]
+    fn time_series_monitor_to_csv_string() {
+        let monitor = Rc::new(TimeSeriesMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(IdealQsc::new()),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(2);
+        let csv = monitor.to_csv_string();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("step,number_of_nodes,"));
+        assert_eq!(lines[2], "1,2,2,2,1,0,1");
+    }
+
+    #[test]
+    fn snapshot_monitor_records_every_kth_step() {
+        let monitor = Rc::new(SnapshotMonitor::new(2));
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(IdealQsc::new()),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(5);
+        let snapshots = monitor.snapshots_clone();
+        let steps: Vec<usize> = snapshots.iter().map(|snapshot| snapshot.step).collect();
+        assert_eq!(vec![2, 4], steps);
+        let fbas = Fbas::from_json_str(&snapshots[1].fbas_json);
+        assert_eq!(4, fbas.number_of_nodes());
+        assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+    }
+
+    #[test]
+    fn all_slices_intersect_for_flat_and_nested_quorum_sets() {
+        let flat = |threshold, validators| QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets: vec![],
+        };
+        assert!(all_slices_intersect(&flat(3, vec![0, 1, 2, 3])));
+        assert!(!all_slices_intersect(&flat(2, vec![0, 1, 2, 3])));
+        let nested = |threshold, inner_threshold| QuorumSet {
+            threshold,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                flat(inner_threshold, vec![0, 1, 2]),
+                flat(inner_threshold, vec![3, 4, 5]),
+                flat(inner_threshold, vec![6, 7, 8]),
+            ],
+        };
+        assert!(all_slices_intersect(&nested(2, 2)));
+        assert!(!all_slices_intersect(&nested(2, 1)));
+        assert!(!all_slices_intersect(&nested(3, 1)));
+    }
+
+    #[test]
+    fn check_quorums_agrees_with_analysis() {
+        let fbas_files = [
+            "test_data/correct_trivial.json",
+            "test_data/broken_trivial.json",
+            "test_data/correct.json",
+            "test_data/broken.json",
+        ];
+        for file in fbas_files.iter() {
+            let fbas = Fbas::from_json_file(std::path::Path::new(file));
+            let mut analysis = Analysis::new(&fbas, None);
+            let expected = (
+                !analysis.minimal_quorums().is_empty(),
+                analysis.has_quorum_intersection(),
+            );
+            assert_eq!(expected, check_quorums(&fbas), "file: {}", file);
+        }
+        let fbas = Fbas::new_configured(10, Rc::new(IdealQsc::new()));
+        assert_eq!((true, true), check_quorums(&fbas));
+        assert_eq!(
+            (false, false),
+            check_quorums(&Fbas::new_generic_unconfigured(3))
+        );
+    }
+
+    #[test]
+    fn analysis_monitor_aborts_simulation_once_intersection_is_lost() {
+        let monitor = Rc::new(AnalysisMonitor::new_aborting_on_lost_intersection());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(PerTierQsc::new(
+                vec![2, 1],
+                vec![
+                    Rc::new(GlobalTopKQsc::new(&[1., 1.], 2, None)),
+                    Rc::new(attacks::SybilQsc::new(2)),
+                ],
+            )),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(5);
+        assert!(simulator.is_aborted());
+        let steps = monitor.steps_clone();
+        assert_eq!(steps.len(), 3);
+        assert!(steps[1].has_quorum_intersection);
+        assert!(steps[2].has_quorums && !steps[2].has_quorum_intersection);
+        assert_eq!(simulator.finalize().number_of_nodes(), 3);
+    }
+
+    #[test]
+    fn analysis_monitor_records_every_step() {
+        let monitor = Rc::new(AnalysisMonitor::new());
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(IdealQsc::new()),
+            Rc::clone(&monitor) as Rc<dyn SimulationMonitor>,
+        );
+        simulator.simulate_growth(5);
+        assert!(!simulator.is_aborted());
+        let steps = monitor.steps_clone();
+        assert_eq!(steps.len(), 5);
+        assert!(steps.iter().all(|step| step.has_quorum_intersection));
+        assert_eq!(steps[4].number_of_nodes, 5);
+    }
+}
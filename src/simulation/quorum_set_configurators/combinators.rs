@@ -0,0 +1,204 @@
+use super::*;
+
+use std::cell::RefCell;
+
+/// Combines two Quorum Set Configurators: uses `fallback` for nodes that `primary` doesn't know
+/// how to configure, i.e., if `primary` makes no change and leaves the node with an empty quorum
+/// set. Once handed over to `fallback`, nodes stick to it, so that the two don't take turns
+/// overwriting each other's configurations.
+pub struct FallbackQsc {
+    primary: Rc<dyn QuorumSetConfigurator>,
+    fallback: Rc<dyn QuorumSetConfigurator>,
+    fallback_nodes: RefCell<NodeIdSet>,
+}
+impl FallbackQsc {
+    pub fn new(
+        primary: Rc<dyn QuorumSetConfigurator>,
+        fallback: Rc<dyn QuorumSetConfigurator>,
+    ) -> Self {
+        FallbackQsc {
+            primary,
+            fallback,
+            fallback_nodes: RefCell::new(NodeIdSet::new()),
+        }
+    }
+}
+impl QuorumSetConfigurator for FallbackQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        if self.fallback_nodes.borrow().contains(node_id) {
+            return self.fallback.configure(node_id, fbas);
+        }
+        match self.primary.configure(node_id, fbas) {
+            NoChange if fbas.nodes[node_id].quorum_set == QuorumSet::new() => {
+                self.fallback_nodes.borrow_mut().insert(node_id);
+                self.fallback.configure(node_id, fbas)
+            }
+            change => change,
+        }
+    }
+}
+
+/// Combines several Quorum Set Configurators, modelling a population of heterogeneous node
+/// operators. Each node is assigned one of the `components` at random (with probability
+/// proportional to its weight) the first time it is configured, and sticks to it afterwards.
+pub struct MixtureQsc {
+    components: Vec<(f64, Rc<dyn QuorumSetConfigurator>)>,
+    assignments: RefCell<HashMap<NodeId, usize>>,
+}
+impl MixtureQsc {
+    pub fn new(components: Vec<(f64, Rc<dyn QuorumSetConfigurator>)>) -> Self {
+        assert!(
+            components.iter().any(|&(weight, _)| weight > 0.),
+            "At least one component needs a positive weight!"
+        );
+        MixtureQsc {
+            components,
+            assignments: RefCell::new(HashMap::new()),
+        }
+    }
+    /// Index of the component responsible for `node_id`
+    fn get_component(&self, node_id: NodeId) -> usize {
+        let components = &self.components;
+        *self
+            .assignments
+            .borrow_mut()
+            .entry(node_id)
+            .or_insert_with(|| {
+                let indices: Vec<usize> = (0..components.len()).collect();
+                *indices
+                    .choose_weighted(&mut thread_rng(), |&i| components[i].0)
+                    .unwrap()
+            })
+    }
+}
+impl QuorumSetConfigurator for MixtureQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let component = self.get_component(node_id);
+        self.components[component].1.configure(node_id, fbas)
+    }
+}
+
+/// Uses a different Quorum Set Configurator per tier. Tiers are assigned as in `TieredQsc`,
+/// i.e., in the order of node IDs, with `tier_sizes[i]` nodes in tier `i` and all remaining
+/// nodes in the lowest tier.
+pub struct PerTierQsc {
+    tier_sizes: Vec<usize>,
+    qscs: Vec<Rc<dyn QuorumSetConfigurator>>,
+}
+impl PerTierQsc {
+    pub fn new(tier_sizes: Vec<usize>, qscs: Vec<Rc<dyn QuorumSetConfigurator>>) -> Self {
+        assert_eq!(
+            tier_sizes.len(),
+            qscs.len(),
+            "Need exactly one quorum set configurator per tier!"
+        );
+        assert!(!tier_sizes.is_empty(), "Need at least one tier!");
+        PerTierQsc { tier_sizes, qscs }
+    }
+}
+impl QuorumSetConfigurator for PerTierQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let tier = get_tier(&self.tier_sizes, node_id);
+        self.qscs[tier].configure(node_id, fbas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_qsc_uses_fallback_on_no_change() {
+        let expected = simulate!(SuperSafeQsc::new(), 5);
+        let actual = simulate!(
+            FallbackQsc::new(Rc::new(DummyQsc), Rc::new(SuperSafeQsc::new())),
+            5
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn fallback_qsc_prefers_primary() {
+        let expected = simulate!(IdealQsc::new(), 5);
+        let actual = simulate!(
+            FallbackQsc::new(Rc::new(IdealQsc::new()), Rc::new(SuperSafeQsc::new())),
+            5
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn fallback_qsc_configures_nodes_unknown_to_primary() {
+        let mut graph = Graph::new_full_mesh(4);
+        graph.outlinks.push(vec![]); // isolated node 4
+        let fbas = simulate!(
+            FallbackQsc::new(
+                Rc::new(SimpleGraphQsc::new_67p(graph)),
+                Rc::new(SuperSafeQsc::new())
+            ),
+            5
+        );
+        assert_eq!(vec![0, 1, 2, 3], fbas.nodes[3].quorum_set.validators);
+        assert_eq!(vec![0, 1, 2, 3, 4], fbas.nodes[4].quorum_set.validators);
+    }
+
+    #[test]
+    fn mixture_qsc_ignores_zero_weight_components() {
+        let expected = simulate!(IdealQsc::new(), 7);
+        let actual = simulate!(
+            MixtureQsc::new(vec![
+                (0., Rc::new(SuperSafeQsc::new())),
+                (1., Rc::new(IdealQsc::new())),
+            ]),
+            7
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn mixture_qsc_assignments_are_sticky() {
+        let qsc = MixtureQsc::new(vec![
+            (1., Rc::new(SuperSafeQsc::new())),
+            (1., Rc::new(IdealQsc::new())),
+        ]);
+        let first_assignments: Vec<usize> = (0..20).map(|i| qsc.get_component(i)).collect();
+        let second_assignments: Vec<usize> = (0..20).map(|i| qsc.get_component(i)).collect();
+        assert_eq!(first_assignments, second_assignments);
+    }
+
+    #[test]
+    fn mixture_qsc_mixes() {
+        let fbas = simulate!(
+            MixtureQsc::new(vec![
+                (1., Rc::new(SuperSafeQsc::new())),
+                (1., Rc::new(IdealQsc::new())),
+            ]),
+            30
+        );
+        let thresholds: HashSet<usize> = fbas
+            .nodes
+            .iter()
+            .map(|node| node.quorum_set.threshold)
+            .collect();
+        let expected: HashSet<usize> = vec![30, 21].into_iter().collect();
+        assert_eq!(expected, thresholds);
+    }
+
+    #[test]
+    fn per_tier_qsc_dispatches_by_tier() {
+        let fbas = simulate!(
+            PerTierQsc::new(
+                vec![2, 3],
+                vec![Rc::new(SuperSafeQsc::new()), Rc::new(DummyQsc)]
+            ),
+            6
+        );
+        let expected: Vec<usize> = vec![6, 6, 0, 0, 0, 0];
+        let actual: Vec<usize> = fbas
+            .nodes
+            .iter()
+            .map(|node| node.quorum_set.validators.len())
+            .collect();
+        assert_eq!(expected, actual);
+    }
+}
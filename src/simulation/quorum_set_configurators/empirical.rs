@@ -0,0 +1,199 @@
+use super::*;
+
+use rand::Rng;
+use std::cell::RefCell;
+
+/// Quorum Set Configurator that mimics an existing (real-world) FBAS.
+///
+/// Each node is assigned one of the quorum set "shapes" (sizes, thresholds and nesting) found in
+/// the snapshot FBAS, sampled uniformly at random from all configured nodes in the snapshot, so
+/// that the empirical distribution of shapes is reproduced. Shapes are filled with nodes chosen
+/// uniformly at random from the (growing) simulated FBAS. As long as the FBAS is too small for a
+/// node's shape, a truncated version of it (with proportionally lowered thresholds) is used.
+pub struct EmpiricalQsc {
+    shapes: Vec<QuorumSetShape>,
+    assigned_shapes: RefCell<HashMap<NodeId, usize>>,
+}
+impl EmpiricalQsc {
+    pub fn new(snapshot: &Fbas) -> Self {
+        let shapes: Vec<QuorumSetShape> = snapshot
+            .nodes
+            .iter()
+            .map(|node| QuorumSetShape::from_quorum_set(&node.quorum_set))
+            .filter(|shape| shape.size() > 0)
+            .collect();
+        assert!(
+            !shapes.is_empty(),
+            "Snapshot FBAS contains no configured quorum sets!"
+        );
+        EmpiricalQsc {
+            shapes,
+            assigned_shapes: RefCell::new(HashMap::new()),
+        }
+    }
+    fn get_shape(&self, node_id: NodeId) -> &QuorumSetShape {
+        let mut assigned_shapes = self.assigned_shapes.borrow_mut();
+        let shape_index = *assigned_shapes
+            .entry(node_id)
+            .or_insert_with(|| thread_rng().gen_range(0, self.shapes.len()));
+        &self.shapes[shape_index]
+    }
+}
+impl QuorumSetConfigurator for EmpiricalQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let n = fbas.nodes.len();
+        let shape = self.get_shape(node_id);
+        let target_size = cmp::min(shape.size(), n);
+        let existing_quorum_set = &mut fbas.nodes[node_id].quorum_set;
+
+        if existing_quorum_set.number_of_contained_nodes() == target_size {
+            NoChange
+        } else {
+            let all_nodes: Vec<NodeId> = (0..n).collect();
+            let mut chosen_nodes = all_nodes
+                .choose_multiple(&mut thread_rng(), target_size)
+                .copied();
+            *existing_quorum_set = shape.fill(&mut chosen_nodes);
+            Change
+        }
+    }
+}
+
+/// Quorum set without concrete validators, only sizes and thresholds.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct QuorumSetShape {
+    threshold: usize,
+    number_of_validators: usize,
+    inner_shapes: Vec<QuorumSetShape>,
+}
+impl QuorumSetShape {
+    fn from_quorum_set(quorum_set: &QuorumSet) -> Self {
+        let mut inner_shapes: Vec<QuorumSetShape> = quorum_set
+            .inner_quorum_sets
+            .iter()
+            .map(Self::from_quorum_set)
+            .collect();
+        inner_shapes.sort(); // so that equally shaped quorum sets have equal shapes
+        QuorumSetShape {
+            threshold: quorum_set.threshold,
+            number_of_validators: quorum_set.validators.len(),
+            inner_shapes,
+        }
+    }
+    /// Number of nodes needed for filling this shape
+    fn size(&self) -> usize {
+        self.number_of_validators + self.inner_shapes.iter().map(|s| s.size()).sum::<usize>()
+    }
+    /// Builds a quorum set using nodes from `nodes`. If `nodes` runs out, the quorum set is
+    /// truncated and its threshold lowered proportionally.
+    fn fill(&self, nodes: &mut impl Iterator<Item = NodeId>) -> QuorumSet {
+        let mut validators: Vec<NodeId> = nodes.take(self.number_of_validators).collect();
+        let mut inner_quorum_sets: Vec<QuorumSet> = self
+            .inner_shapes
+            .iter()
+            .map(|inner_shape| inner_shape.fill(nodes))
+            .filter(|inner| !inner.validators.is_empty() || !inner.inner_quorum_sets.is_empty())
+            .collect();
+        validators.sort(); // for easier comparability
+        inner_quorum_sets.sort(); // for easier comparability
+
+        let full_size = self.number_of_validators + self.inner_shapes.len();
+        let actual_size = validators.len() + inner_quorum_sets.len();
+        let threshold = if actual_size == full_size {
+            self.threshold
+        } else {
+            let scaled = (self.threshold * actual_size) as f64 / full_size as f64;
+            cmp::max(1, cmp::min(actual_size, scaled.ceil() as usize))
+        };
+        QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn nested_quorum_set(validators: Vec<NodeId>, inner: Vec<Vec<NodeId>>) -> QuorumSet {
+        QuorumSet {
+            threshold: 2,
+            validators,
+            inner_quorum_sets: inner
+                .into_iter()
+                .map(|validators| QuorumSet {
+                    threshold: 2,
+                    validators,
+                    inner_quorum_sets: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn shape_fill_reproduces_shape() {
+        let quorum_set = nested_quorum_set(vec![0], vec![vec![1, 2, 3]]);
+        let shape = QuorumSetShape::from_quorum_set(&quorum_set);
+        assert_eq!(shape.size(), 4);
+        let actual = shape.fill(&mut vec![3, 0, 2, 1].into_iter());
+        let expected = nested_quorum_set(vec![3], vec![vec![0, 1, 2]]);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn shape_fill_truncates_and_lowers_thresholds() {
+        let quorum_set = nested_quorum_set(vec![0, 1], vec![vec![2, 3, 4]]);
+        let shape = QuorumSetShape::from_quorum_set(&quorum_set);
+        let actual = shape.fill(&mut vec![0, 1].into_iter());
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![0, 1],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, actual);
+        let actual = shape.fill(&mut vec![0].into_iter());
+        let expected = QuorumSet {
+            threshold: 1,
+            validators: vec![0],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn empirical_qsc_reproduces_uniform_snapshot_shape() {
+        let mut snapshot = Fbas::new_generic_unconfigured(4);
+        for node in snapshot.nodes.iter_mut() {
+            node.quorum_set = nested_quorum_set(vec![0], vec![vec![1, 2, 3]]);
+        }
+        let expected_shape = QuorumSetShape::from_quorum_set(&snapshot.nodes[0].quorum_set);
+        let fbas = simulate!(EmpiricalQsc::new(&snapshot), 10);
+        for node in fbas.nodes.iter() {
+            assert_eq!(
+                expected_shape,
+                QuorumSetShape::from_quorum_set(&node.quorum_set)
+            );
+        }
+    }
+
+    #[test]
+    fn empirical_qsc_mimics_stellar_network() {
+        let snapshot = Fbas::from_json_file(Path::new("test_data/correct.json"));
+        let qsc = EmpiricalQsc::new(&snapshot);
+        let shapes = qsc.shapes.clone();
+        let fbas = simulate!(qsc, 30);
+        for node in fbas.nodes.iter() {
+            let shape = QuorumSetShape::from_quorum_set(&node.quorum_set);
+            assert!(shapes.contains(&shape));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn empirical_qsc_panics_for_unconfigured_snapshot() {
+        EmpiricalQsc::new(&Fbas::new_generic_unconfigured(3));
+    }
+}
@@ -0,0 +1,185 @@
+use super::*;
+
+use std::collections::BTreeMap;
+
+/// Granularity at which `GeographicQsc` groups nodes into regions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegionLevel {
+    Continent,
+    Country,
+}
+impl RegionLevel {
+    pub(crate) fn get_region(self, node: &Node) -> Option<&String> {
+        match self {
+            RegionLevel::Continent => node.metadata.continent.as_ref(),
+            RegionLevel::Country => node.metadata.country.as_ref(),
+        }
+    }
+}
+
+/// Makes nested quorum sets spanning multiple regions, based on node metadata (see
+/// `Simulator::with_regions`): each region (continent or country) with at least one node
+/// becomes an inner quorum set containing all of its nodes, with a simple majority threshold
+/// (e.g., 2-of-3). The outer threshold is taken over regions, so that no single region can
+/// make or break quorums on its own (given enough regions).
+///
+/// Nodes without the relevant metadata are not included in any quorum set.
+///
+/// ```
+/// #[macro_use] extern crate fbas_analyzer;
+/// use fbas_analyzer::{Fbas, Analysis, Region, Simulator};
+/// use fbas_analyzer::quorum_set_configurators::{GeographicQsc, RegionLevel};
+/// use fbas_analyzer::monitors::DummyMonitor;
+/// use std::rc::Rc;
+///
+/// let mut simulator = Simulator::new(
+///     Fbas::new(),
+///     Rc::new(GeographicQsc::new_67p(RegionLevel::Country)),
+///     Rc::new(DummyMonitor),
+/// )
+/// .with_regions(vec![
+///     Region::new("Europe", "Germany"),
+///     Region::new("Europe", "France"),
+///     Region::new("North America", "United States"),
+///     Region::new("Asia", "Japan"),
+/// ]);
+/// simulator.simulate_growth(12);
+///
+/// let fbas = simulator.finalize();
+/// // majorities in 3 of 4 countries
+/// assert!(fbas.is_quorum(&bitset![0, 4, 1, 5, 2, 6]));
+/// assert!(!fbas.is_quorum(&bitset![0, 4, 8, 1, 5, 9]));
+/// assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+/// ```
+pub struct GeographicQsc {
+    region_level: RegionLevel,
+    relative_threshold: Option<f64>,
+}
+impl GeographicQsc {
+    pub fn new(region_level: RegionLevel, relative_threshold: Option<f64>) -> Self {
+        GeographicQsc {
+            region_level,
+            relative_threshold,
+        }
+    }
+    pub fn new_67p(region_level: RegionLevel) -> Self {
+        Self::new(region_level, None)
+    }
+    pub fn new_relative(region_level: RegionLevel, relative_threshold: f64) -> Self {
+        Self::new(region_level, Some(relative_threshold))
+    }
+    fn build_new_configuration(&self, fbas: &Fbas) -> QuorumSet {
+        let mut regions: BTreeMap<&String, Vec<NodeId>> = BTreeMap::new();
+        for (node_id, node) in fbas.nodes.iter().enumerate() {
+            if let Some(region) = self.region_level.get_region(node) {
+                regions.entry(region).or_default().push(node_id);
+            }
+        }
+        if regions.is_empty() {
+            return QuorumSet::new();
+        }
+        let inner_quorum_sets: Vec<QuorumSet> = regions
+            .values()
+            .map(|validators| QuorumSet {
+                threshold: validators.len() / 2 + 1,
+                validators: validators.clone(),
+                inner_quorum_sets: vec![],
+            })
+            .collect();
+        let threshold = calculate_threshold(inner_quorum_sets.len(), self.relative_threshold);
+        QuorumSet {
+            threshold,
+            validators: vec![],
+            inner_quorum_sets,
+        }
+    }
+}
+impl QuorumSetConfigurator for GeographicQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let candidate_quorum_set = self.build_new_configuration(fbas);
+        let existing_quorum_set = &mut fbas.nodes[node_id].quorum_set;
+        if *existing_quorum_set == candidate_quorum_set {
+            NoChange
+        } else {
+            *existing_quorum_set = candidate_quorum_set;
+            Change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_regions() -> Vec<Region> {
+        vec![
+            Region::new("Europe", "Germany"),
+            Region::new("Europe", "France"),
+            Region::new("North America", "United States"),
+        ]
+    }
+
+    fn majority_quorum_set(validators: Vec<NodeId>) -> QuorumSet {
+        QuorumSet {
+            threshold: validators.len() / 2 + 1,
+            validators,
+            inner_quorum_sets: vec![],
+        }
+    }
+
+    #[test]
+    fn geographic_qsc_makes_one_inner_quorum_set_per_country() {
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(GeographicQsc::new_67p(RegionLevel::Country)),
+            Rc::new(monitors::DummyMonitor),
+        )
+        .with_regions(test_regions());
+        simulator.simulate_growth(9);
+        let fbas = simulator.finalize();
+
+        // countries in alphabetical order
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                majority_quorum_set(vec![1, 4, 7]),
+                majority_quorum_set(vec![0, 3, 6]),
+                majority_quorum_set(vec![2, 5, 8]),
+            ],
+        };
+        for node in fbas.nodes.iter() {
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn geographic_qsc_makes_one_inner_quorum_set_per_continent() {
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(GeographicQsc::new_67p(RegionLevel::Continent)),
+            Rc::new(monitors::DummyMonitor),
+        )
+        .with_regions(test_regions());
+        simulator.simulate_growth(6);
+        let fbas = simulator.finalize();
+
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                majority_quorum_set(vec![0, 1, 3, 4]),
+                majority_quorum_set(vec![2, 5]),
+            ],
+        };
+        for node in fbas.nodes.iter() {
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn geographic_qsc_ignores_nodes_without_regions() {
+        let fbas = simulate!(GeographicQsc::new_67p(RegionLevel::Country), 5);
+        assert_eq!(Fbas::new_generic_unconfigured(5), fbas);
+    }
+}
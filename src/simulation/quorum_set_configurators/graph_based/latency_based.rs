@@ -0,0 +1,188 @@
+use super::*;
+
+/// Makes non-nested quorum sets of up to `quorum_set_size` nodes (including the node itself),
+/// preferring graph neighbors with low edge weights (e.g., measured latencies), like operators
+/// who pick well-performing validators. Diversity constraint: at most `max_per_region` of the
+/// chosen nodes (again including the node itself) may be in the same region (see
+/// `Simulator::with_regions`); nodes without region metadata aren't constrained. Ties are
+/// broken in favor of lower node IDs.
+///
+/// Quorum sets are rebuilt whenever a node reevaluates, so nodes switch to better neighbors as
+/// these join the FBAS.
+///
+/// ```
+/// use fbas_analyzer::{Fbas, Graph, Region, Simulator};
+/// use fbas_analyzer::quorum_set_configurators::{LatencyAwareGraphQsc, RegionLevel};
+/// use fbas_analyzer::monitors::DummyMonitor;
+/// use std::rc::Rc;
+///
+/// // latency grows with the distance between node IDs
+/// let graph = Graph::new_full_mesh(8).with_edge_weights(|i, j| (i as f64 - j as f64).abs());
+/// let qsc = LatencyAwareGraphQsc::new_67p(graph, 4, RegionLevel::Continent, 2);
+/// let mut simulator = Simulator::new(Fbas::new(), Rc::new(qsc), Rc::new(DummyMonitor))
+///     .with_regions(vec![Region::new("Europe", "Germany"), Region::new("Asia", "Japan")]);
+/// simulator.simulate_growth(8);
+///
+/// // each node picks 3 other nodes, at most one of them on its own continent
+/// let fbas = simulator.finalize();
+/// assert!(Graph::from_fbas(&fbas).get_out_degrees().iter().all(|&d| d == 3));
+/// ```
+pub struct LatencyAwareGraphQsc {
+    graph: Graph,
+    connected_nodes: NodeIdSet,
+    quorum_set_size: usize,
+    region_level: RegionLevel,
+    max_per_region: usize,
+    relative_threshold: Option<f64>,
+}
+impl LatencyAwareGraphQsc {
+    pub fn new(
+        graph: Graph,
+        quorum_set_size: usize,
+        region_level: RegionLevel,
+        max_per_region: usize,
+        relative_threshold: Option<f64>,
+    ) -> Self {
+        assert!(
+            quorum_set_size > 0 && max_per_region > 0,
+            "Quorum sets need room for at least the node itself!"
+        );
+        let connected_nodes = graph.get_connected_nodes();
+        LatencyAwareGraphQsc {
+            graph,
+            connected_nodes,
+            quorum_set_size,
+            region_level,
+            max_per_region,
+            relative_threshold,
+        }
+    }
+    pub fn new_67p(
+        graph: Graph,
+        quorum_set_size: usize,
+        region_level: RegionLevel,
+        max_per_region: usize,
+    ) -> Self {
+        Self::new(graph, quorum_set_size, region_level, max_per_region, None)
+    }
+    fn choose_validators(&self, node_id: NodeId, fbas: &Fbas) -> Vec<NodeId> {
+        assert!(
+            node_id < self.graph.number_of_nodes(),
+            "Graph too small for this FBAS!"
+        );
+        let n = fbas.nodes.len();
+        let mut candidates: Vec<(NodeId, EdgeWeight)> = self
+            .graph
+            .get_weighted_outlinks(node_id)
+            .into_iter()
+            .filter(|&(j, _)| j < n && j != node_id)
+            .collect();
+        // NaN weights (e.g., missing measurements) sort last
+        candidates.sort_by(|(i, wi), (j, wj)| wi.total_cmp(wj).then(i.cmp(j)));
+
+        let mut validators = vec![node_id];
+        let mut nodes_per_region: HashMap<&String, usize> = HashMap::new();
+        if let Some(region) = self.region_level.get_region(&fbas.nodes[node_id]) {
+            nodes_per_region.insert(region, 1);
+        }
+        for (candidate, _) in candidates {
+            if validators.len() >= self.quorum_set_size {
+                break;
+            }
+            if let Some(region) = self.region_level.get_region(&fbas.nodes[candidate]) {
+                let count = nodes_per_region.entry(region).or_insert(0);
+                if *count >= self.max_per_region {
+                    continue;
+                }
+                *count += 1;
+            }
+            validators.push(candidate);
+        }
+        validators.sort_unstable(); // for easier comparability
+        validators
+    }
+}
+impl QuorumSetConfigurator for LatencyAwareGraphQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        if !self.connected_nodes.contains(node_id) {
+            return NoChange;
+        }
+        let validators = self.choose_validators(node_id, fbas);
+        let candidate_quorum_set = QuorumSet {
+            threshold: calculate_threshold(validators.len(), self.relative_threshold),
+            validators,
+            inner_quorum_sets: vec![],
+        };
+        let existing_quorum_set = &mut fbas.nodes[node_id].quorum_set;
+        if *existing_quorum_set == candidate_quorum_set {
+            NoChange
+        } else {
+            *existing_quorum_set = candidate_quorum_set;
+            Change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate_latency_aware(max_per_region: usize) -> Fbas {
+        // nodes in the same region are close to each other; otherwise, latency grows with the
+        // distance between node IDs
+        let graph = Graph::new_full_mesh(6).with_edge_weights(|i, j| {
+            if i % 3 == j % 3 {
+                1.
+            } else {
+                10. + (i as f64 - j as f64).abs()
+            }
+        });
+        let qsc = LatencyAwareGraphQsc::new_67p(graph, 3, RegionLevel::Country, max_per_region);
+        let mut simulator =
+            Simulator::new(Fbas::new(), Rc::new(qsc), Rc::new(monitors::DummyMonitor))
+                .with_regions(vec![
+                    Region::new("Europe", "Germany"),
+                    Region::new("Europe", "France"),
+                    Region::new("North America", "United States"),
+                ]);
+        simulator.simulate_growth(6);
+        simulator.finalize()
+    }
+
+    #[test]
+    fn latency_aware_qsc_prefers_low_latency() {
+        let fbas = simulate_latency_aware(3);
+        assert_eq!(vec![0, 1, 3], fbas.nodes[0].quorum_set.validators);
+        assert_eq!(vec![2, 4, 5], fbas.nodes[5].quorum_set.validators);
+        assert_eq!(3, fbas.nodes[0].quorum_set.threshold);
+    }
+
+    #[test]
+    fn latency_aware_qsc_respects_diversity_constraint() {
+        let fbas = simulate_latency_aware(1);
+        assert_eq!(vec![0, 1, 2], fbas.nodes[0].quorum_set.validators);
+        assert_eq!(vec![3, 4, 5], fbas.nodes[5].quorum_set.validators);
+    }
+
+    #[test]
+    fn latency_aware_qsc_without_regions_is_unconstrained() {
+        let graph = Graph::new_full_mesh(4).with_edge_weights(|i, j| (i + j) as f64);
+        let fbas = simulate!(
+            LatencyAwareGraphQsc::new_67p(graph, 2, RegionLevel::Country, 1),
+            4
+        );
+        assert_eq!(vec![0, 1], fbas.nodes[0].quorum_set.validators);
+        assert_eq!(vec![0, 3], fbas.nodes[3].quorum_set.validators);
+    }
+
+    #[test]
+    fn latency_aware_qsc_prefers_measured_latencies_over_nan() {
+        let graph =
+            Graph::new_full_mesh(3).with_edge_weights(|_, j| if j == 1 { f64::NAN } else { 5. });
+        let fbas = simulate!(
+            LatencyAwareGraphQsc::new_67p(graph, 2, RegionLevel::Country, 1),
+            3
+        );
+        assert_eq!(vec![0, 2], fbas.nodes[0].quorum_set.validators);
+    }
+}
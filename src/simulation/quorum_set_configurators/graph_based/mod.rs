@@ -9,3 +9,7 @@ mod tier_based;
 pub use tier_based::*;
 mod rank_based;
 pub use rank_based::*;
+mod org_based;
+pub use org_based::*;
+mod latency_based;
+pub use latency_based::*;
@@ -0,0 +1,139 @@
+use super::*;
+
+/// Makes nested quorum sets based on immediate graph neighbors, grouped by (simulated)
+/// organizations: each organization that contains the node itself or one of its graph neighbors
+/// becomes an inner quorum set containing all of its nodes, with a simple majority threshold
+/// (e.g., 2-of-3).
+///
+/// Organizations are made up of consecutive node IDs, `org_size` nodes each, i.e., node `i`
+/// belongs to organization `i / org_size`.
+pub struct OrgNestedGraphQsc {
+    graph: Graph,
+    connected_nodes: NodeIdSet,
+    org_size: usize,
+    relative_threshold: Option<f64>,
+}
+impl OrgNestedGraphQsc {
+    pub fn new(graph: Graph, org_size: usize, relative_threshold: Option<f64>) -> Self {
+        assert!(org_size > 0, "Organizations mustn't be empty!");
+        let connected_nodes = graph.get_connected_nodes();
+        OrgNestedGraphQsc {
+            graph,
+            connected_nodes,
+            org_size,
+            relative_threshold,
+        }
+    }
+    pub fn new_67p(graph: Graph, org_size: usize) -> Self {
+        Self::new(graph, org_size, None)
+    }
+    pub fn new_relative(graph: Graph, org_size: usize, relative_threshold: f64) -> Self {
+        Self::new(graph, org_size, Some(relative_threshold))
+    }
+    fn get_org_members(&self, org_id: usize, n: usize) -> Vec<NodeId> {
+        let org_start = org_id * self.org_size;
+        let org_end = cmp::min(org_start + self.org_size, n);
+        (org_start..org_end).collect()
+    }
+}
+impl QuorumSetConfigurator for OrgNestedGraphQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        if !self.connected_nodes.contains(node_id) {
+            return NoChange;
+        }
+        let n = fbas.nodes.len();
+        let neighbors = self
+            .graph
+            .outlinks
+            .get(node_id)
+            .expect("Graph too small for this FBAS!");
+
+        // we include the node's own organization, analogous to other QSCs adding nodes to their
+        // own quorum sets
+        let org_ids: BTreeSet<usize> = neighbors
+            .iter()
+            .chain(Some(&node_id))
+            .filter(|&&id| id < n)
+            .map(|&id| id / self.org_size)
+            .collect();
+
+        let inner_quorum_sets: Vec<QuorumSet> = org_ids
+            .into_iter()
+            .map(|org_id| {
+                let validators = self.get_org_members(org_id, n);
+                QuorumSet {
+                    threshold: validators.len() / 2 + 1,
+                    validators,
+                    inner_quorum_sets: vec![],
+                }
+            })
+            .collect();
+        let threshold = calculate_threshold(inner_quorum_sets.len(), self.relative_threshold);
+
+        let candidate_quorum_set = QuorumSet {
+            threshold,
+            validators: vec![],
+            inner_quorum_sets,
+        };
+        let existing_quorum_set = &mut fbas.nodes[node_id].quorum_set;
+        if *existing_quorum_set == candidate_quorum_set {
+            NoChange
+        } else {
+            *existing_quorum_set = candidate_quorum_set;
+            Change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn org_nested_qsc_makes_one_inner_quorum_set_per_org() {
+        let n = 9;
+        let fbas = simulate!(OrgNestedGraphQsc::new_67p(Graph::new_full_mesh(n), 3), n);
+        let org_quorum_set = |validators: Vec<NodeId>| QuorumSet {
+            threshold: 2,
+            validators,
+            inner_quorum_sets: vec![],
+        };
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![],
+            inner_quorum_sets: vec![
+                org_quorum_set(vec![0, 1, 2]),
+                org_quorum_set(vec![3, 4, 5]),
+                org_quorum_set(vec![6, 7, 8]),
+            ],
+        };
+        for node in fbas.nodes.iter() {
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn org_nested_qsc_only_uses_neighboring_orgs() {
+        let n = 9;
+        let mut graph = Graph::new_full_mesh(n);
+        graph.outlinks[3] = vec![7];
+        let fbas = simulate!(OrgNestedGraphQsc::new_67p(graph, 3), n);
+        let expected = vec![bitset![3, 4, 5], bitset![6, 7, 8]];
+        let actual: Vec<NodeIdSet> = fbas.nodes[3]
+            .quorum_set
+            .inner_quorum_sets
+            .iter()
+            .map(|qset| qset.contained_nodes())
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn org_nested_qsc_survives_org_failure() {
+        let n = 12;
+        let fbas = simulate!(OrgNestedGraphQsc::new_67p(Graph::new_full_mesh(n), 3), n);
+        assert!(fbas.is_quorum(&bitset![0, 1, 3, 4, 6, 7]));
+        assert!(!fbas.is_quorum(&bitset![0, 1, 2, 3, 4, 5]));
+        assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+    }
+}
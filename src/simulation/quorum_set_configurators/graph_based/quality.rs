@@ -1,25 +1,50 @@
 use super::*;
+use crate::graph::RankScore;
+
+/// Quality measure used by `QualityAwareGraphQsc` for telling apart higher and lower quality
+/// nodes. Implement this to plug in custom measures (uptime, centrality, random noise, ...).
+pub trait NodeQuality {
+    fn quality(&self, node_id: NodeId) -> RankScore;
+}
+/// Precomputed quality scores, one per node.
+impl NodeQuality for Vec<RankScore> {
+    fn quality(&self, node_id: NodeId) -> RankScore {
+        self[node_id]
+    }
+}
 
 /// Makes 67% quorum sets based on immediate graph neighbors,
 /// putting lower quality nodes into a 67% inner quorum set.
-/// Node degree used as a proxy for quality.
-pub struct QualityAwareGraphQsc {
+/// Node degree used as a proxy for quality, unless a different `NodeQuality` is provided.
+pub struct QualityAwareGraphQsc<Q: NodeQuality = Vec<RankScore>> {
     graph: Graph,
-    quality_scores: Vec<usize>,
+    quality_scores: Q,
 }
 impl QualityAwareGraphQsc {
     pub fn new(graph: Graph) -> Self {
-        let quality_scores = graph.get_in_degrees();
+        let quality_scores = graph
+            .get_in_degrees()
+            .into_iter()
+            .map(|degree| degree as RankScore)
+            .collect();
+        QualityAwareGraphQsc {
+            graph,
+            quality_scores,
+        }
+    }
+}
+impl<Q: NodeQuality> QualityAwareGraphQsc<Q> {
+    pub fn new_with_quality(graph: Graph, quality_scores: Q) -> Self {
         QualityAwareGraphQsc {
             graph,
             quality_scores,
         }
     }
-    fn quality(&self, node_id: NodeId) -> usize {
-        self.quality_scores[node_id]
+    fn quality(&self, node_id: NodeId) -> RankScore {
+        self.quality_scores.quality(node_id)
     }
 }
-impl QuorumSetConfigurator for QualityAwareGraphQsc {
+impl<Q: NodeQuality> QuorumSetConfigurator for QualityAwareGraphQsc<Q> {
     fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
         let existing_quorum_set = &mut fbas.nodes[node_id].quorum_set;
         let neighbors = self
@@ -29,10 +54,11 @@ impl QuorumSetConfigurator for QualityAwareGraphQsc {
             .expect("Graph too small for this FBAS!")
             .clone();
 
-        let average_quality: usize = (neighbors.iter().map(|&id| self.quality(id)).sum::<usize>()
-            as f64
-            / neighbors.len() as f64)
-            .round() as usize;
+        let average_quality = neighbors
+            .iter()
+            .map(|&id| self.quality(id))
+            .sum::<RankScore>()
+            / neighbors.len() as RankScore;
 
         let mut higher_quality_validators: Vec<NodeId> = vec![];
         let mut lower_quality_validators: Vec<NodeId> = vec![];
@@ -45,7 +71,7 @@ impl QuorumSetConfigurator for QualityAwareGraphQsc {
         }
 
         let mut unprocessed = neighbors;
-        unprocessed.sort_by_key(|&id| self.quality(id));
+        unprocessed.sort_by(|&a, &b| self.quality(a).total_cmp(&self.quality(b)));
 
         while let Some(next) = unprocessed.pop() {
             if higher_quality_validators.len() < 3
@@ -113,19 +139,43 @@ mod tests {
         let (n, m0, m) = (23, 3, 2);
         let graph = Graph::new_random_scale_free(n, m0, m);
         let qsc = QualityAwareGraphQsc::new(graph.clone());
-        let actual = graph.get_in_degrees();
+        let actual: Vec<RankScore> = graph
+            .get_in_degrees()
+            .into_iter()
+            .map(|degree| degree as RankScore)
+            .collect();
         let expected = qsc.quality_scores;
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn quality_aware_qsc_accepts_custom_quality() {
+        struct LowerIdsAreBetter;
+        impl NodeQuality for LowerIdsAreBetter {
+            fn quality(&self, node_id: NodeId) -> RankScore {
+                (100 - node_id) as RankScore
+            }
+        }
+        let n = 8;
+        let graph = Graph::new_full_mesh(n);
+        let qsc = QualityAwareGraphQsc::new_with_quality(graph.clone(), LowerIdsAreBetter);
+        let mut vec_qsc = QualityAwareGraphQsc::new(graph);
+        vec_qsc.quality_scores = (0..n).map(|i| (100 - i) as RankScore).collect();
+
+        let expected = simulate!(vec_qsc, n);
+        let actual = simulate!(qsc, n);
+        assert_eq!(expected, actual);
+        assert!(!actual.nodes[0].quorum_set.inner_quorum_sets.is_empty());
+    }
+
     #[test]
     fn quality_aware_qsc_no_inner_set_if_few_friends() {
         let n = 8;
         let mut graph = Graph::new_full_mesh(n);
         graph.outlinks[0] = vec![1, 2, 3];
         let mut qsc = QualityAwareGraphQsc::new(graph.clone());
-        qsc.quality_scores[1] = 30;
-        qsc.quality_scores[2] = 50;
+        qsc.quality_scores[1] = 30.;
+        qsc.quality_scores[2] = 50.;
 
         let fbas = simulate!(qsc, n);
 
@@ -160,13 +210,13 @@ mod tests {
         let n = 8;
         let graph = Graph::new_full_mesh(n);
         let mut qsc = QualityAwareGraphQsc::new(graph.clone());
-        qsc.quality_scores[1] = 70;
-        qsc.quality_scores[2] = 8;
-        qsc.quality_scores[3] = 7;
-        qsc.quality_scores[4] = 7;
-        qsc.quality_scores[5] = 7;
-        qsc.quality_scores[6] = 7;
-        qsc.quality_scores[7] = 7;
+        qsc.quality_scores[1] = 70.;
+        qsc.quality_scores[2] = 8.;
+        qsc.quality_scores[3] = 7.;
+        qsc.quality_scores[4] = 7.;
+        qsc.quality_scores[5] = 7.;
+        qsc.quality_scores[6] = 7.;
+        qsc.quality_scores[7] = 7.;
 
         let fbas = simulate!(qsc, n);
 
@@ -188,13 +238,13 @@ mod tests {
         let n = 8;
         let graph = Graph::new_full_mesh(n);
         let mut qsc = QualityAwareGraphQsc::new(graph.clone());
-        qsc.quality_scores[1] = 79;
-        qsc.quality_scores[2] = 8;
-        qsc.quality_scores[3] = 8;
-        qsc.quality_scores[4] = 8;
-        qsc.quality_scores[5] = 7;
-        qsc.quality_scores[6] = 7;
-        qsc.quality_scores[7] = 7;
+        qsc.quality_scores[1] = 79.;
+        qsc.quality_scores[2] = 8.;
+        qsc.quality_scores[3] = 8.;
+        qsc.quality_scores[4] = 8.;
+        qsc.quality_scores[5] = 7.;
+        qsc.quality_scores[6] = 7.;
+        qsc.quality_scores[7] = 7.;
 
         let fbas = simulate!(qsc, n);
 
@@ -211,6 +261,30 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn quality_aware_qsc_tells_apart_fractional_qualities() {
+        let n = 8;
+        let graph = Graph::new_full_mesh(n);
+        let qsc = QualityAwareGraphQsc::new_with_quality(
+            graph,
+            vec![0.1, 0.9, 0.8, 0.7, 0.1, 0.1, 0.1, 0.1],
+        );
+
+        let fbas = simulate!(qsc, n);
+
+        let expected = &QuorumSet {
+            validators: vec![0, 1, 2, 3],
+            threshold: 4,
+            inner_quorum_sets: vec![QuorumSet {
+                validators: vec![4, 5, 6, 7],
+                threshold: 3,
+                inner_quorum_sets: vec![],
+            }],
+        };
+        let actual = &fbas.nodes[0].quorum_set;
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn quality_aware_qsc_uses_67p_thresholds() {
         let n = 50;
@@ -4,8 +4,20 @@ use std::cmp;
 
 mod random;
 pub use random::*;
+mod tiered;
+pub use tiered::*;
+mod top_k;
+pub use top_k::*;
+mod empirical;
+pub use empirical::*;
+mod combinators;
+pub use combinators::*;
+mod partial_knowledge;
+pub use partial_knowledge::*;
 mod graph_based;
 pub use graph_based::*;
+mod geographic;
+pub use geographic::*;
 
 /// Dummy Quorum Set Configurator.
 ///
@@ -128,7 +140,7 @@ impl IdealQsc {
     }
 }
 
-fn calculate_threshold(n: usize, relative_threshold: Option<f64>) -> usize {
+pub(crate) fn calculate_threshold(n: usize, relative_threshold: Option<f64>) -> usize {
     if let Some(x) = relative_threshold {
         calculate_x_threshold(n, x)
     } else {
@@ -137,7 +149,7 @@ fn calculate_threshold(n: usize, relative_threshold: Option<f64>) -> usize {
 }
 
 /// t = ceil((2n+1)/3) => n >= 3f+1
-fn calculate_67p_threshold(n: usize) -> usize {
+pub(crate) fn calculate_67p_threshold(n: usize) -> usize {
     // reformed for more robustness against floating point errors
     n - ((n as f64 - 1.) / 3.).floor() as usize
 }
@@ -0,0 +1,199 @@
+use super::*;
+
+use std::cell::RefCell;
+
+/// Wraps another Quorum Set Configurator so that each node only "sees" part of the FBAS when
+/// configuring its quorum set, modelling incomplete knowledge (e.g., from crawling the network).
+///
+/// `inner` is run on a view of the FBAS that contains only the nodes known to the configured
+/// node (and the node itself). As nodes are renumbered in the view, this is only meaningful for
+/// configurators that don't rely on node IDs having a meaning outside of the FBAS (such as
+/// `IdealQsc` or `RandomQsc`, but not graph-based configurators).
+pub struct PartialKnowledgeQsc {
+    inner: Rc<dyn QuorumSetConfigurator>,
+    knowledge: Knowledge,
+}
+enum Knowledge {
+    /// node -> (number of nodes already considered for being known, known nodes)
+    Random {
+        knowledge_rate: f64,
+        known_nodes: RefCell<HashMap<NodeId, (usize, NodeIdSet)>>,
+    },
+    GraphNeighborhood {
+        graph: Graph,
+        hops: usize,
+    },
+}
+impl PartialKnowledgeQsc {
+    /// Each node knows each other node with probability `knowledge_rate`. Nodes stick to what
+    /// they know, but can get to know new nodes as the FBAS grows.
+    pub fn new_random(inner: Rc<dyn QuorumSetConfigurator>, knowledge_rate: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&knowledge_rate),
+            "`knowledge_rate` must be a probability!"
+        );
+        PartialKnowledgeQsc {
+            inner,
+            knowledge: Knowledge::Random {
+                knowledge_rate,
+                known_nodes: RefCell::new(HashMap::new()),
+            },
+        }
+    }
+    /// Each node knows the nodes that are at most `hops` links away from it in `graph`.
+    pub fn new_graph_neighborhood(
+        inner: Rc<dyn QuorumSetConfigurator>,
+        graph: Graph,
+        hops: usize,
+    ) -> Self {
+        PartialKnowledgeQsc {
+            inner,
+            knowledge: Knowledge::GraphNeighborhood { graph, hops },
+        }
+    }
+    /// Nodes visible to `node_id` in an FBAS with `n` nodes, including `node_id` itself
+    fn get_known_nodes(&self, node_id: NodeId, n: usize) -> NodeIdSet {
+        let mut known_nodes: NodeIdSet = match &self.knowledge {
+            Knowledge::Random {
+                knowledge_rate,
+                known_nodes,
+            } => {
+                let mut known_nodes = known_nodes.borrow_mut();
+                let (considered, known) = known_nodes
+                    .entry(node_id)
+                    .or_insert_with(|| (0, NodeIdSet::new()));
+                let mut rng = thread_rng();
+                for other_id in *considered..n {
+                    if rng.gen_bool(*knowledge_rate) {
+                        known.insert(other_id);
+                    }
+                }
+                *considered = cmp::max(*considered, n);
+                known.iter().filter(|&id| id < n).collect()
+            }
+            Knowledge::GraphNeighborhood { graph, hops } => {
+                let mut known = NodeIdSet::new();
+                let mut frontier = vec![node_id];
+                for _ in 0..*hops {
+                    let mut next_frontier = vec![];
+                    for current in frontier.into_iter() {
+                        for &neighbor in graph.outlinks.get(current).into_iter().flatten() {
+                            if known.insert(neighbor) {
+                                next_frontier.push(neighbor);
+                            }
+                        }
+                    }
+                    frontier = next_frontier;
+                }
+                known.iter().filter(|&id| id < n).collect()
+            }
+        };
+        known_nodes.insert(node_id);
+        known_nodes
+    }
+}
+impl QuorumSetConfigurator for PartialKnowledgeQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let known_nodes = self.get_known_nodes(node_id, fbas.nodes.len());
+        let (mut view, unshrink_table, shrink_map) = Fbas::shrunken(fbas, known_nodes);
+        let view_node_id = shrink_map[&node_id];
+        self.inner.configure(view_node_id, &mut view);
+
+        let candidate =
+            unshrunken_quorum_set(&view.nodes[view_node_id].quorum_set, &unshrink_table);
+        let existing = &mut fbas.nodes[node_id].quorum_set;
+        if candidate == *existing {
+            NoChange
+        } else {
+            *existing = candidate;
+            Change
+        }
+    }
+}
+
+fn unshrunken_quorum_set(quorum_set: &QuorumSet, unshrink_table: &[NodeId]) -> QuorumSet {
+    let mut validators: Vec<NodeId> = quorum_set
+        .validators
+        .iter()
+        .map(|&id| unshrink_table[id])
+        .collect();
+    let mut inner_quorum_sets: Vec<QuorumSet> = quorum_set
+        .inner_quorum_sets
+        .iter()
+        .map(|inner| unshrunken_quorum_set(inner, unshrink_table))
+        .collect();
+    validators.sort(); // for easier comparability
+    inner_quorum_sets.sort(); // for easier comparability
+    QuorumSet {
+        threshold: quorum_set.threshold,
+        validators,
+        inner_quorum_sets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_knowledge_changes_nothing() {
+        let expected = simulate!(IdealQsc::new(), 10);
+        let actual = simulate!(
+            PartialKnowledgeQsc::new_random(Rc::new(IdealQsc::new()), 1.),
+            10
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn nodes_without_knowledge_only_know_themselves() {
+        let fbas = simulate!(
+            PartialKnowledgeQsc::new_random(Rc::new(IdealQsc::new()), 0.),
+            5
+        );
+        for (node_id, node) in fbas.nodes.iter().enumerate() {
+            let expected = QuorumSet {
+                threshold: 1,
+                validators: vec![node_id],
+                inner_quorum_sets: vec![],
+            };
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn partially_knowing_nodes_see_fewer_nodes() {
+        let fbas = simulate!(
+            PartialKnowledgeQsc::new_random(Rc::new(IdealQsc::new()), 0.5),
+            30
+        );
+        let mean_quorum_set_size = fbas
+            .nodes
+            .iter()
+            .map(|node| node.quorum_set.validators.len())
+            .sum::<usize>() as f64
+            / 30.;
+        assert!(mean_quorum_set_size > 5. && mean_quorum_set_size < 25.);
+    }
+
+    #[test]
+    fn graph_neighborhood_knowledge() {
+        // ring: i <-> i+1
+        let n = 8;
+        let graph = Graph::new((0..n).map(|i| vec![(i + n - 1) % n, (i + 1) % n]).collect());
+        let fbas = simulate!(
+            PartialKnowledgeQsc::new_graph_neighborhood(
+                Rc::new(SuperSafeQsc::new()),
+                graph.clone(),
+                1
+            ),
+            n
+        );
+        assert_eq!(vec![4, 5, 6], fbas.nodes[5].quorum_set.validators);
+        let fbas = simulate!(
+            PartialKnowledgeQsc::new_graph_neighborhood(Rc::new(SuperSafeQsc::new()), graph, 2),
+            n
+        );
+        assert_eq!(vec![3, 4, 5, 6, 7], fbas.nodes[5].quorum_set.validators);
+    }
+}
@@ -0,0 +1,174 @@
+use super::*;
+
+/// Quorum Set Configurator building a hierarchy of tiers, as sketched in the Stellar whitepaper.
+///
+/// Nodes are assigned to tiers in the order of their node IDs: the first `tier_sizes[0]` nodes
+/// form the top tier, the next `tier_sizes[1]` nodes the second tier, and so on. Nodes beyond the
+/// sum of all tier sizes are assigned to the lowest tier.
+///
+/// Top tier nodes build quorum sets containing all top tier nodes. Nodes in lower tiers require
+/// themselves and a threshold of the nodes in the next higher tier.
+///
+/// ```
+/// #[macro_use] extern crate fbas_analyzer;
+/// use fbas_analyzer::{Fbas, Analysis, Simulator};
+/// use fbas_analyzer::quorum_set_configurators::TieredQsc;
+/// use fbas_analyzer::monitors::DummyMonitor;
+/// use std::rc::Rc;
+///
+/// let mut simulator = Simulator::new(
+///     Fbas::new(),
+///     Rc::new(TieredQsc::new_67p(vec![4, 4])),
+///     Rc::new(DummyMonitor),
+/// );
+/// simulator.simulate_growth(12);
+///
+/// let fbas = simulator.finalize();
+/// assert!(fbas.is_quorum(&bitset![0, 1, 2]));
+/// assert!(!fbas.is_quorum(&bitset![4, 5, 6, 7]));
+/// assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+/// ```
+pub struct TieredQsc {
+    tier_sizes: Vec<usize>,
+    relative_threshold: Option<f64>,
+}
+impl TieredQsc {
+    pub fn new(tier_sizes: Vec<usize>, relative_threshold: Option<f64>) -> Self {
+        assert!(
+            !tier_sizes.is_empty() && tier_sizes[0] > 0,
+            "The top tier mustn't be empty!"
+        );
+        TieredQsc {
+            tier_sizes,
+            relative_threshold,
+        }
+    }
+    pub fn new_67p(tier_sizes: Vec<usize>) -> Self {
+        Self::new(tier_sizes, None)
+    }
+    pub fn new_relative(tier_sizes: Vec<usize>, relative_threshold: f64) -> Self {
+        Self::new(tier_sizes, Some(relative_threshold))
+    }
+    /// Index of the tier `node_id` belongs to; 0 is the top tier.
+    pub fn get_tier(&self, node_id: NodeId) -> usize {
+        get_tier(&self.tier_sizes, node_id)
+    }
+    /// Members of `tier` that are already part of an FBAS with `n` nodes.
+    fn get_tier_members(&self, tier: usize, n: usize) -> Vec<NodeId> {
+        let tier_start: usize = self.tier_sizes[..tier].iter().sum();
+        let tier_end = if tier == self.tier_sizes.len() - 1 {
+            n
+        } else {
+            cmp::min(tier_start + self.tier_sizes[tier], n)
+        };
+        (tier_start..tier_end).collect()
+    }
+    fn build_new_configuration(&self, node_id: NodeId, fbas: &Fbas) -> QuorumSet {
+        let n = fbas.nodes.len();
+        let tier = self.get_tier(node_id);
+        if tier == 0 {
+            let validators = self.get_tier_members(0, n);
+            let threshold = calculate_threshold(validators.len(), self.relative_threshold);
+            QuorumSet {
+                threshold,
+                validators,
+                inner_quorum_sets: vec![],
+            }
+        } else {
+            let higher_tier_validators = self.get_tier_members(tier - 1, n);
+            let higher_tier_threshold =
+                calculate_threshold(higher_tier_validators.len(), self.relative_threshold);
+            QuorumSet {
+                threshold: 2,
+                validators: vec![node_id],
+                inner_quorum_sets: vec![QuorumSet {
+                    threshold: higher_tier_threshold,
+                    validators: higher_tier_validators,
+                    inner_quorum_sets: vec![],
+                }],
+            }
+        }
+    }
+}
+/// Tier of `node_id` if tiers are assigned in the order of node IDs and the lowest tier takes
+/// all nodes that don't fit into `tier_sizes`.
+pub(crate) fn get_tier(tier_sizes: &[usize], node_id: NodeId) -> usize {
+    let mut tier_end = 0;
+    for (tier, &tier_size) in tier_sizes.iter().enumerate() {
+        tier_end += tier_size;
+        if node_id < tier_end {
+            return tier;
+        }
+    }
+    tier_sizes.len() - 1
+}
+
+impl QuorumSetConfigurator for TieredQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let candidate = self.build_new_configuration(node_id, fbas);
+        let existing = &mut fbas.nodes[node_id].quorum_set;
+        if candidate == *existing {
+            NoChange
+        } else {
+            *existing = candidate;
+            Change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_are_assigned_in_order() {
+        let qsc = TieredQsc::new_67p(vec![2, 3]);
+        let expected = vec![0, 0, 1, 1, 1, 1, 1];
+        let actual: Vec<usize> = (0..7).map(|i| qsc.get_tier(i)).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn top_tier_has_67p_threshold() {
+        let fbas = simulate!(TieredQsc::new_67p(vec![4, 8]), 12);
+        for node_id in 0..4 {
+            let quorum_set = &fbas.nodes[node_id].quorum_set;
+            assert_eq!(quorum_set.validators, vec![0, 1, 2, 3]);
+            assert_has_67p_threshold!(quorum_set);
+        }
+    }
+
+    #[test]
+    fn lower_tiers_depend_on_next_higher_tier() {
+        let fbas = simulate!(TieredQsc::new_67p(vec![4, 4, 4]), 12);
+        let expected = QuorumSet {
+            threshold: 2,
+            validators: vec![9],
+            inner_quorum_sets: vec![QuorumSet {
+                threshold: 3,
+                validators: vec![4, 5, 6, 7],
+                inner_quorum_sets: vec![],
+            }],
+        };
+        let actual = fbas.nodes[9].quorum_set.clone();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn tiered_qsc_has_quorum_intersection_and_top_tier_core() {
+        let fbas = simulate!(TieredQsc::new_67p(vec![4, 8, 16]), 40);
+        let mut analysis = Analysis::new(&fbas, None);
+        assert!(analysis.has_quorum_intersection());
+        assert_eq!(
+            analysis.minimal_blocking_sets().unwrap(),
+            vec![
+                bitset![0, 1],
+                bitset![0, 2],
+                bitset![0, 3],
+                bitset![1, 2],
+                bitset![1, 3],
+                bitset![2, 3]
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+
+/// Quorum Set Configurator modelling nodes that all copy the same recommendation: every node
+/// uses the (up to) `k` globally top-scored nodes as its quorum set. Scores can be any
+/// quality measure, e.g., node degrees or rank scores in some graph.
+///
+/// Only nodes that are already part of the FBAS are considered, so quorum sets change while the
+/// FBAS grows. Ties are broken in favor of lower node IDs.
+///
+/// ```
+/// #[macro_use] extern crate fbas_analyzer;
+/// use fbas_analyzer::{Fbas, Analysis, Graph, Simulator};
+/// use fbas_analyzer::quorum_set_configurators::GlobalTopKQsc;
+/// use fbas_analyzer::monitors::DummyMonitor;
+/// use std::rc::Rc;
+///
+/// let graph = Graph::new_tiered_full_mesh(&[3, 5]);
+/// let mut simulator = Simulator::new(
+///     Fbas::new(),
+///     Rc::new(GlobalTopKQsc::from_in_degrees(&graph, 3, None)),
+///     Rc::new(DummyMonitor),
+/// );
+/// simulator.simulate_growth(8);
+///
+/// let fbas = simulator.finalize();
+/// assert!(fbas.is_quorum(&bitset![0, 1, 2]));
+/// assert!(!fbas.is_quorum(&bitset![3, 4, 5, 6, 7]));
+/// assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+/// ```
+pub struct GlobalTopKQsc {
+    ranking: Vec<NodeId>,
+    k: usize,
+    relative_threshold: Option<f64>,
+}
+impl GlobalTopKQsc {
+    pub fn new(scores: &[f64], k: usize, relative_threshold: Option<f64>) -> Self {
+        let mut ranking: Vec<NodeId> = (0..scores.len()).collect();
+        // stable sort, so lower node IDs win ties; NaN scores sort last
+        ranking.sort_by(|&i, &j| {
+            scores[i]
+                .is_nan()
+                .cmp(&scores[j].is_nan())
+                .then(scores[j].total_cmp(&scores[i]))
+        });
+        GlobalTopKQsc {
+            ranking,
+            k,
+            relative_threshold,
+        }
+    }
+    pub fn from_in_degrees(graph: &Graph, k: usize, relative_threshold: Option<f64>) -> Self {
+        let scores: Vec<f64> = graph
+            .get_in_degrees()
+            .into_iter()
+            .map(|d| d as f64)
+            .collect();
+        Self::new(&scores, k, relative_threshold)
+    }
+    pub fn from_rank_scores(graph: &Graph, k: usize, relative_threshold: Option<f64>) -> Self {
+        Self::new(&graph.get_rank_scores(), k, relative_threshold)
+    }
+    fn build_new_configuration(&self, fbas: &Fbas) -> QuorumSet {
+        let n = fbas.nodes.len();
+        let mut validators: Vec<NodeId> = self
+            .ranking
+            .iter()
+            .copied()
+            .filter(|&id| id < n)
+            .take(self.k)
+            .collect();
+        validators.sort(); // for easier comparability
+        let threshold = calculate_threshold(validators.len(), self.relative_threshold);
+        QuorumSet {
+            threshold,
+            validators,
+            inner_quorum_sets: vec![],
+        }
+    }
+}
+impl QuorumSetConfigurator for GlobalTopKQsc {
+    fn configure(&self, node_id: NodeId, fbas: &mut Fbas) -> ChangeEffect {
+        let candidate = self.build_new_configuration(fbas);
+        let existing = &mut fbas.nodes[node_id].quorum_set;
+        if candidate == *existing {
+            NoChange
+        } else {
+            *existing = candidate;
+            Change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_k_qsc_uses_top_scored_nodes() {
+        let qsc = GlobalTopKQsc::new(&[0.1, 0.5, 0.2, 0.5, 0.9], 3, None);
+        let fbas = simulate!(qsc, 5);
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![1, 3, 4],
+            inner_quorum_sets: vec![],
+        };
+        for node in fbas.nodes.iter() {
+            assert_eq!(expected, node.quorum_set);
+        }
+    }
+
+    #[test]
+    fn top_k_qsc_only_uses_existing_nodes() {
+        let qsc = GlobalTopKQsc::new(&[0.1, 0.5, 0.2, 0.5, 0.9], 3, None);
+        let fbas = simulate!(qsc, 3);
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![0, 1, 2],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, fbas.nodes[0].quorum_set);
+    }
+
+    #[test]
+    fn top_k_qsc_ranks_nan_scores_last() {
+        let qsc = GlobalTopKQsc::new(&[f64::NAN, 0.5, 0.2, f64::NAN, 0.9], 3, None);
+        let fbas = simulate!(qsc, 5);
+        let expected = QuorumSet {
+            threshold: 3,
+            validators: vec![1, 2, 4],
+            inner_quorum_sets: vec![],
+        };
+        assert_eq!(expected, fbas.nodes[0].quorum_set);
+    }
+
+    #[test]
+    fn top_k_qsc_can_be_like_ideal() {
+        let n = 10;
+        let top_k_qsc = GlobalTopKQsc::from_rank_scores(&Graph::new_full_mesh(n), n, None);
+        let actual = simulate!(top_k_qsc, n);
+        let expected = simulate!(IdealQsc::new(), n);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn top_k_qsc_by_rank_picks_top_tier() {
+        let graph = Graph::new_tiered_full_mesh(&[2, 3, 1]);
+        let fbas = simulate!(GlobalTopKQsc::from_rank_scores(&graph, 2, None), 6);
+        assert_eq!(vec![0, 1], fbas.nodes[5].quorum_set.validators);
+    }
+
+    #[test]
+    fn top_k_qsc_by_in_degree_picks_most_trusted() {
+        let graph = Graph::new_tiered_full_mesh(&[2, 3, 4]);
+        let fbas = simulate!(GlobalTopKQsc::from_in_degrees(&graph, 3, None), 9);
+        assert_eq!(vec![2, 3, 4], fbas.nodes[8].quorum_set.validators);
+    }
+}
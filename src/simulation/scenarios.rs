@@ -0,0 +1,461 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A complete, self-contained simulation setup that can be shared as a (TOML) file:
+///
+/// ```
+/// use fbas_analyzer::scenarios::Scenario;
+///
+/// let scenario = Scenario::from_toml_str(r#"
+///     seed = 42
+///     analyses = ["has_quorum_intersection", "top_tier"]
+///
+///     [graph]
+///     model = "scale_free"
+///     n = 30
+///     m0 = 2
+///     m = 2
+///
+///     [qsc]
+///     type = "simple_graph"
+///     relative_threshold = 0.5
+///
+///     [[schedule]]
+///     action = "grow"
+///     nodes = 20
+///
+///     [[schedule]]
+///     action = "churn"
+///     rounds = 5
+///     join_rate = 1.0
+///     leave_rate = 0.01
+/// "#);
+/// let result = scenario.run();
+/// assert_eq!(result.analysis_results.len(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Seed for the simulator's decisions; random if omitted
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Number of nodes that exist from the start (configured all at once, before the schedule)
+    #[serde(default)]
+    pub initial_nodes: usize,
+    /// Cap for the reevaluation rounds after each change (see
+    /// `Simulator::with_maximum_number_of_rounds`)
+    #[serde(default)]
+    pub max_rounds: Option<usize>,
+    /// Analyses to run on the resulting FBAS
+    #[serde(default)]
+    pub analyses: Vec<ScenarioAnalysis>,
+    /// Needed by graph-based quorum set configurators
+    #[serde(default)]
+    pub graph: Option<GraphModel>,
+    pub qsc: QscModel,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleStep>,
+}
+
+/// Random graph models are shuffled, because FBAS join order shouldn't be correlated with
+/// importance in the graph.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum GraphModel {
+    FullMesh { n: usize },
+    TieredFullMesh { tier_sizes: Vec<usize> },
+    ScaleFree { n: usize, m0: usize, m: usize },
+    SmallWorld { n: usize, k: usize, beta: f64 },
+    AsRelFile { path: PathBuf },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QscModel {
+    SuperSafe,
+    Ideal,
+    Random {
+        quorum_set_size: usize,
+        threshold: Option<usize>,
+    },
+    Tiered {
+        tier_sizes: Vec<usize>,
+        relative_threshold: Option<f64>,
+    },
+    SimpleGraph {
+        relative_threshold: Option<f64>,
+    },
+    HigherTiersGraph {
+        relative_threshold: Option<f64>,
+        #[serde(default)]
+        make_symmetric_top_tier: bool,
+    },
+    GlobalRankGraph {
+        relative_threshold: Option<f64>,
+    },
+    QualityAwareGraph,
+    GlobalTopKByRank {
+        k: usize,
+        relative_threshold: Option<f64>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScheduleStep {
+    Grow {
+        nodes: usize,
+    },
+    Shrink {
+        nodes: usize,
+    },
+    Churn {
+        rounds: usize,
+        join_rate: f64,
+        leave_rate: f64,
+    },
+    Reevaluate {
+        rounds: usize,
+    },
+    PartialReevaluate {
+        rounds: usize,
+        participation_rate: f64,
+    },
+    /// Temporary node failures, see `Simulator::simulate_failures`
+    Fail {
+        rounds: usize,
+        failure_probability: f64,
+        recovery_probability: f64,
+        #[serde(default)]
+        correlated_by_organization: bool,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioAnalysis {
+    HasQuorumIntersection,
+    MinimalQuorums,
+    MinimalBlockingSets,
+    MinimalSplittingSets,
+    TopTier,
+    SymmetricClusters,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioResult {
+    pub fbas: Fbas,
+    /// Results in "describe" format, in the order given in the scenario
+    pub analysis_results: Vec<(ScenarioAnalysis, String)>,
+    /// Includes the seed actually used, also if the scenario doesn't specify one
+    pub manifest: manifests::SimulationManifest,
+    /// Whether all reevaluations stabilized (see `Simulator::has_converged`)
+    pub converged: bool,
+}
+
+impl Scenario {
+    pub fn from_toml_str(toml: &str) -> Self {
+        toml::from_str(toml).expect("Error parsing scenario TOML")
+    }
+    pub fn from_toml_file(path: &Path) -> Self {
+        let toml =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Error reading file {:?}", path));
+        Self::from_toml_str(&toml)
+    }
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(&self).expect("Error converting scenario to TOML!")
+    }
+    /// Simulate, following the schedule, and analyze the resulting FBAS.
+    pub fn run(&self) -> ScenarioResult {
+        let graph = self.graph.as_ref().map(GraphModel::build);
+        let qsc = self.qsc.build(graph);
+        let fbas = Fbas::new_generic_unconfigured(self.initial_nodes);
+        let monitor = Rc::new(monitors::DummyMonitor);
+        let mut simulator = match self.seed {
+            Some(seed) => Simulator::new_seeded(fbas, qsc, monitor, seed),
+            None => Simulator::new(fbas, qsc, monitor),
+        };
+        if let Some(max_rounds) = self.max_rounds {
+            simulator = simulator.with_maximum_number_of_rounds(max_rounds);
+        }
+        simulator.simulate_global_reevaluation(self.max_rounds.unwrap_or(self.initial_nodes));
+        for step in self.schedule.iter() {
+            info!("Simulating {:?}...", step);
+            match *step {
+                ScheduleStep::Grow { nodes } => simulator.simulate_growth(nodes),
+                ScheduleStep::Shrink { nodes } => simulator.simulate_shrink(nodes),
+                ScheduleStep::Churn {
+                    rounds,
+                    join_rate,
+                    leave_rate,
+                } => simulator.simulate_churn(rounds, join_rate, leave_rate),
+                ScheduleStep::Reevaluate { rounds } => {
+                    simulator.simulate_global_reevaluation(rounds);
+                }
+                ScheduleStep::PartialReevaluate {
+                    rounds,
+                    participation_rate,
+                } => {
+                    simulator.simulate_partial_reevaluation(rounds, participation_rate);
+                }
+                ScheduleStep::Fail {
+                    rounds,
+                    failure_probability,
+                    recovery_probability,
+                    correlated_by_organization,
+                } => {
+                    let mut model =
+                        failures::FailureModel::new(failure_probability, recovery_probability);
+                    if correlated_by_organization {
+                        model = model.correlated_by_organization();
+                    }
+                    simulator.simulate_failures(rounds, &model);
+                }
+            }
+        }
+        let analysis_results: Vec<(ScenarioAnalysis, String)> = {
+            // nodes that are failed at the end of the schedule don't count
+            let available_fbas = simulator.available_fbas();
+            let mut analysis = Analysis::new(&available_fbas, None);
+            self.analyses
+                .iter()
+                .map(|&a| (a, a.run_and_describe(&mut analysis)))
+                .collect()
+        };
+        for (analysis, result) in analysis_results.iter() {
+            simulator.report_analysis_result(&format!("{:?}", analysis), result.clone());
+        }
+        let mut manifest = simulator.manifest().with_qsc_parameters(&self.qsc);
+        if let Some(graph) = self.graph.as_ref() {
+            manifest = manifest.with_graph_parameters(graph);
+        }
+        let converged = simulator.has_converged();
+        let fbas = simulator.finalize();
+        ScenarioResult {
+            fbas,
+            analysis_results,
+            manifest,
+            converged,
+        }
+    }
+}
+
+impl GraphModel {
+    pub fn build(&self) -> Graph {
+        match self {
+            GraphModel::FullMesh { n } => Graph::new_full_mesh(*n),
+            GraphModel::TieredFullMesh { tier_sizes } => Graph::new_tiered_full_mesh(tier_sizes),
+            GraphModel::ScaleFree { n, m0, m } => {
+                Graph::new_random_scale_free(*n, *m0, *m).shuffled()
+            }
+            GraphModel::SmallWorld { n, k, beta } => {
+                Graph::new_random_small_world(*n, *k, *beta).shuffled()
+            }
+            GraphModel::AsRelFile { path } => Graph::from_as_rel_file(path),
+        }
+    }
+}
+
+impl QscModel {
+    pub fn build(&self, graph: Option<Graph>) -> Rc<dyn QuorumSetConfigurator> {
+        use quorum_set_configurators::*;
+        let expect_graph = || graph.expect("This quorum set configurator needs a graph!");
+        match self {
+            QscModel::SuperSafe => Rc::new(SuperSafeQsc::new()),
+            QscModel::Ideal => Rc::new(IdealQsc::new()),
+            QscModel::Random {
+                quorum_set_size,
+                threshold,
+            } => Rc::new(RandomQsc::new(*quorum_set_size, *threshold, None)),
+            QscModel::Tiered {
+                tier_sizes,
+                relative_threshold,
+            } => Rc::new(TieredQsc::new(tier_sizes.clone(), *relative_threshold)),
+            QscModel::SimpleGraph { relative_threshold } => {
+                Rc::new(SimpleGraphQsc::new(expect_graph(), *relative_threshold))
+            }
+            QscModel::HigherTiersGraph {
+                relative_threshold,
+                make_symmetric_top_tier,
+            } => Rc::new(HigherTiersGraphQsc::new(
+                expect_graph(),
+                *relative_threshold,
+                *make_symmetric_top_tier,
+            )),
+            QscModel::GlobalRankGraph { relative_threshold } => {
+                Rc::new(GlobalRankGraphQsc::new(expect_graph(), *relative_threshold))
+            }
+            QscModel::QualityAwareGraph => Rc::new(QualityAwareGraphQsc::new(expect_graph())),
+            QscModel::GlobalTopKByRank {
+                k,
+                relative_threshold,
+            } => Rc::new(GlobalTopKQsc::from_rank_scores(
+                &expect_graph(),
+                *k,
+                *relative_threshold,
+            )),
+        }
+    }
+}
+
+impl ScenarioAnalysis {
+    fn run_and_describe(self, analysis: &mut Analysis) -> String {
+        match self {
+            ScenarioAnalysis::HasQuorumIntersection => {
+                analysis.has_quorum_intersection().into_describe_string()
+            }
+            ScenarioAnalysis::MinimalQuorums => analysis.minimal_quorums().into_describe_string(),
+            ScenarioAnalysis::MinimalBlockingSets => {
+                analysis.minimal_blocking_sets().into_describe_string()
+            }
+            ScenarioAnalysis::MinimalSplittingSets => {
+                analysis.minimal_splitting_sets().into_describe_string()
+            }
+            ScenarioAnalysis::TopTier => analysis.top_tier().into_describe_string(),
+            ScenarioAnalysis::SymmetricClusters => {
+                analysis.symmetric_clusters().into_describe_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scenario_with_defaults() {
+        let scenario = Scenario::from_toml_str(
+            r#"
+            [qsc]
+            type = "ideal"
+
+            [[schedule]]
+            action = "grow"
+            nodes = 4
+            "#,
+        );
+        let expected = Scenario {
+            seed: None,
+            initial_nodes: 0,
+            max_rounds: None,
+            analyses: vec![],
+            graph: None,
+            qsc: QscModel::Ideal,
+            schedule: vec![ScheduleStep::Grow { nodes: 4 }],
+        };
+        assert_eq!(expected, scenario);
+    }
+
+    #[test]
+    fn scenario_toml_roundtrip() {
+        let expected = Scenario {
+            seed: Some(3),
+            initial_nodes: 2,
+            max_rounds: Some(5),
+            analyses: vec![ScenarioAnalysis::MinimalQuorums],
+            graph: Some(GraphModel::SmallWorld {
+                n: 20,
+                k: 4,
+                beta: 0.05,
+            }),
+            qsc: QscModel::HigherTiersGraph {
+                relative_threshold: Some(0.5),
+                make_symmetric_top_tier: true,
+            },
+            schedule: vec![
+                ScheduleStep::Grow { nodes: 10 },
+                ScheduleStep::Shrink { nodes: 2 },
+                ScheduleStep::Reevaluate { rounds: 3 },
+                ScheduleStep::PartialReevaluate {
+                    rounds: 3,
+                    participation_rate: 0.5,
+                },
+                ScheduleStep::Fail {
+                    rounds: 2,
+                    failure_probability: 0.1,
+                    recovery_probability: 0.5,
+                    correlated_by_organization: true,
+                },
+            ],
+        };
+        let actual = Scenario::from_toml_str(&expected.to_toml_string());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn run_ideal_scenario() {
+        let scenario = Scenario::from_toml_str(
+            r#"
+            initial_nodes = 3
+            analyses = ["has_quorum_intersection", "minimal_quorums", "top_tier"]
+
+            [qsc]
+            type = "ideal"
+
+            [[schedule]]
+            action = "grow"
+            nodes = 2
+
+            [[schedule]]
+            action = "shrink"
+            nodes = 1
+            "#,
+        );
+        let result = scenario.run();
+        assert_eq!(result.fbas.number_of_nodes(), 5);
+        let expected = vec![
+            (ScenarioAnalysis::HasQuorumIntersection, "true".to_string()),
+            (
+                ScenarioAnalysis::MinimalQuorums,
+                Analysis::new(&result.fbas, None)
+                    .minimal_quorums()
+                    .into_describe_string(),
+            ),
+            (ScenarioAnalysis::TopTier, "4".to_string()),
+        ];
+        assert_eq!(expected, result.analysis_results);
+        assert!(result.converged);
+        assert_eq!("IdealQsc", result.manifest.qsc);
+        assert_eq!(
+            Some(serde_json::json!({ "type": "ideal" })),
+            result.manifest.qsc_parameters
+        );
+    }
+
+    #[test]
+    fn scenario_reports_capped_reevaluations() {
+        let scenario = Scenario::from_toml_str(
+            r#"
+            max_rounds = 1
+
+            [qsc]
+            type = "super_safe"
+
+            [[schedule]]
+            action = "grow"
+            nodes = 3
+
+            [[schedule]]
+            action = "reevaluate"
+            rounds = 5
+            "#,
+        );
+        // the final reevaluation converges, but the ones during growth didn't
+        assert!(!scenario.run().converged);
+    }
+
+    #[test]
+    #[should_panic]
+    fn graph_based_qsc_without_graph_panics() {
+        let scenario = Scenario::from_toml_str(
+            r#"
+            [qsc]
+            type = "simple_graph"
+            "#,
+        );
+        scenario.run();
+    }
+}
@@ -0,0 +1,238 @@
+//! Direct generation of complete random FBASs, without the round-based `Simulator`. Much faster
+//! when many disposable FBASs are needed (e.g., for benchmarking), at the cost of not modelling
+//! how nodes react to each other's configuration changes.
+
+use super::*;
+use quorum_set_configurators::{calculate_threshold, get_tier};
+
+/// How synthetic nodes pick their quorum sets.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntheticPolicy {
+    /// Like `TieredQsc`: top tier nodes require a threshold of all top tier nodes; nodes in lower
+    /// tiers require themselves and a threshold of the next higher tier.
+    Tiered { relative_threshold: Option<f64> },
+    /// Top tier nodes behave as in `Tiered`; nodes in lower tiers require a threshold of
+    /// themselves and `quorum_set_size - 1` random members of higher tiers.
+    RandomUpstream {
+        quorum_set_size: usize,
+        relative_threshold: Option<f64>,
+    },
+    /// Tiers are ignored; every node requires a threshold of itself and
+    /// `quorum_set_size - 1` random other nodes.
+    Random {
+        quorum_set_size: usize,
+        relative_threshold: Option<f64>,
+    },
+}
+
+/// Blueprint for synthetic FBASs. Tiers are assigned as in `TieredQsc`, i.e., in the order of node
+/// IDs, with `tier_sizes[i]` nodes in tier `i` and all remaining nodes in the lowest tier.
+///
+/// ```
+/// use fbas_analyzer::Analysis;
+/// use fbas_analyzer::synthetic::{SyntheticFbas, SyntheticPolicy};
+///
+/// let blueprint = SyntheticFbas::new(
+///     20,
+///     vec![4, 16],
+///     SyntheticPolicy::RandomUpstream {
+///         quorum_set_size: 4,
+///         relative_threshold: None,
+///     },
+/// );
+/// let fbas = blueprint.generate_seeded(42);
+/// assert_eq!(fbas.number_of_nodes(), 20);
+/// assert!(Analysis::new(&fbas, None).has_quorum_intersection());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntheticFbas {
+    pub n: usize,
+    pub tier_sizes: Vec<usize>,
+    pub policy: SyntheticPolicy,
+}
+impl SyntheticFbas {
+    pub fn new(n: usize, tier_sizes: Vec<usize>, policy: SyntheticPolicy) -> Self {
+        assert!(
+            !tier_sizes.is_empty() && tier_sizes[0] > 0,
+            "The top tier mustn't be empty!"
+        );
+        SyntheticFbas {
+            n,
+            tier_sizes,
+            policy,
+        }
+    }
+    pub fn generate(&self) -> Fbas {
+        self.generate_with_rng(&mut thread_rng())
+    }
+    /// Like `generate`, but reproducible.
+    pub fn generate_seeded(&self, seed: u64) -> Fbas {
+        self.generate_with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+    /// Nodes get generic public keys ("n0", "n1", ...).
+    pub fn generate_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Fbas {
+        let tiers = self.assign_tiers();
+        let mut fbas = Fbas::new_generic_unconfigured(self.n);
+        for node_id in 0..self.n {
+            fbas.nodes[node_id].quorum_set = self.build_quorum_set(node_id, &tiers, rng);
+        }
+        debug!(
+            "Generated synthetic FBAS with {} nodes in {} tiers.",
+            self.n,
+            tiers.len()
+        );
+        fbas
+    }
+    fn assign_tiers(&self) -> Vec<Vec<NodeId>> {
+        let mut tiers = vec![vec![]; self.tier_sizes.len()];
+        for node_id in 0..self.n {
+            tiers[get_tier(&self.tier_sizes, node_id)].push(node_id);
+        }
+        tiers
+    }
+    fn build_quorum_set<R: Rng + ?Sized>(
+        &self,
+        node_id: NodeId,
+        tiers: &[Vec<NodeId>],
+        rng: &mut R,
+    ) -> QuorumSet {
+        let tier = get_tier(&self.tier_sizes, node_id);
+        match self.policy {
+            SyntheticPolicy::Tiered { relative_threshold } => {
+                if tier == 0 {
+                    flat_quorum_set(tiers[0].clone(), relative_threshold)
+                } else {
+                    QuorumSet {
+                        threshold: 2,
+                        validators: vec![node_id],
+                        inner_quorum_sets: vec![flat_quorum_set(
+                            tiers[tier - 1].clone(),
+                            relative_threshold,
+                        )],
+                    }
+                }
+            }
+            SyntheticPolicy::RandomUpstream {
+                quorum_set_size,
+                relative_threshold,
+            } => {
+                if tier == 0 {
+                    flat_quorum_set(tiers[0].clone(), relative_threshold)
+                } else {
+                    let candidates: Vec<NodeId> = tiers[..tier].concat();
+                    random_quorum_set(
+                        node_id,
+                        &candidates,
+                        quorum_set_size,
+                        relative_threshold,
+                        rng,
+                    )
+                }
+            }
+            SyntheticPolicy::Random {
+                quorum_set_size,
+                relative_threshold,
+            } => {
+                let candidates: Vec<NodeId> = (0..self.n).filter(|&x| x != node_id).collect();
+                random_quorum_set(
+                    node_id,
+                    &candidates,
+                    quorum_set_size,
+                    relative_threshold,
+                    rng,
+                )
+            }
+        }
+    }
+}
+
+fn flat_quorum_set(validators: Vec<NodeId>, relative_threshold: Option<f64>) -> QuorumSet {
+    QuorumSet {
+        threshold: calculate_threshold(validators.len(), relative_threshold),
+        validators,
+        inner_quorum_sets: vec![],
+    }
+}
+
+/// `node_id` plus up to `quorum_set_size - 1` nodes chosen from `candidates`.
+fn random_quorum_set<R: Rng + ?Sized>(
+    node_id: NodeId,
+    candidates: &[NodeId],
+    quorum_set_size: usize,
+    relative_threshold: Option<f64>,
+    rng: &mut R,
+) -> QuorumSet {
+    let mut validators: Vec<NodeId> = candidates
+        .choose_multiple(rng, quorum_set_size.saturating_sub(1))
+        .copied()
+        .collect();
+    validators.push(node_id);
+    validators.sort_unstable();
+    flat_quorum_set(validators, relative_threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiered_policy_matches_tiered_qsc() {
+        let mut simulator = Simulator::new(
+            Fbas::new(),
+            Rc::new(quorum_set_configurators::TieredQsc::new_67p(vec![4, 4])),
+            Rc::new(monitors::DummyMonitor),
+        );
+        simulator.simulate_growth(12);
+        let expected = simulator.finalize();
+        let actual = SyntheticFbas::new(
+            12,
+            vec![4, 4],
+            SyntheticPolicy::Tiered {
+                relative_threshold: None,
+            },
+        )
+        .generate();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn random_upstream_policy_only_trusts_higher_tiers() {
+        let fbas = SyntheticFbas::new(
+            30,
+            vec![5, 10, 15],
+            SyntheticPolicy::RandomUpstream {
+                quorum_set_size: 4,
+                relative_threshold: Some(0.5),
+            },
+        )
+        .generate();
+        for node_id in 5..30 {
+            let tier = get_tier(&[5, 10, 15], node_id);
+            let quorum_set = &fbas.nodes[node_id].quorum_set;
+            assert_eq!(quorum_set.validators.len(), 4);
+            assert_eq!(quorum_set.threshold, 2);
+            assert!(quorum_set
+                .validators
+                .iter()
+                .all(|&x| x == node_id || get_tier(&[5, 10, 15], x) < tier));
+        }
+    }
+
+    #[test]
+    fn seeded_generation_is_reproducible() {
+        let blueprint = SyntheticFbas::new(
+            20,
+            vec![20],
+            SyntheticPolicy::Random {
+                quorum_set_size: 5,
+                relative_threshold: None,
+            },
+        );
+        assert_eq!(blueprint.generate_seeded(7), blueprint.generate_seeded(7));
+        assert!(blueprint.generate_seeded(7).nodes.iter().all(|node| node
+            .quorum_set
+            .validators
+            .len()
+            == 5));
+    }
+}